@@ -42,10 +42,12 @@ buildargs=(
     "-p nostr-indexeddb --target wasm32-unknown-unknown"
     "-p nostr-ndb"
     "-p nostr-keyring"
+    "-p nostr-keyring --no-default-features"
     "-p nostr-keyring --features async"
     "-p nostr-relay-pool"
     "-p nostr-relay-builder"
     "-p nostr-connect"
+    "-p nostr-frost"
     "-p nwc"
     "-p nostr-sdk"                                                # No default features
     "-p nostr-sdk --features all-nips"                            # Only NIPs features
@@ -61,6 +63,7 @@ skip_msrv=(
     "-p nostr-mls-sqlite-storage"         # MSRV: 1.74.0
     "-p nostr-mls"                        # MSRV: 1.74.0
     "-p nostr-keyring"                    # MSRV: 1.75.0
+    "-p nostr-keyring --no-default-features"  # MSRV: 1.75.0
     "-p nostr-keyring --features async"   # MSRV: 1.75.0
     "-p nostr-sdk --features tor"         # MSRV: 1.77.0
     "-p nostr-sdk --all-features"         # MSRV: 1.77.0 (since uses lmdb and tor)
@@ -7,6 +7,7 @@ buildargs=(
     "-p nostr-database"
     "-p nostr-relay-pool"
     "-p nostr-connect"
+    "-p nostr-frost"
     "-p nwc"
     "-p nostr-sdk"
 )
@@ -148,7 +148,7 @@ async fn run() -> Result<()> {
             };
 
             // Print bunker URI
-            let uri: NostrConnectURI = signer.bunker_uri();
+            let uri: NostrConnectURI = signer.bunker_uri().await;
             println!("\nBunker URI: {uri}\n");
 
             // Serve signer
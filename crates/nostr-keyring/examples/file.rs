@@ -0,0 +1,25 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2025 Rust Nostr Developers
+// Distributed under the MIT software license
+
+use nostr_keyring::prelude::*;
+
+fn main() -> Result<()> {
+    let keys = Keys::parse("nsec1j4c6269y9w0q2er2xjw8sv2ehyrtfxq3jwgdlxj6qfn8z4gjsq5qfvfk99")?;
+
+    let path = std::env::temp_dir().join("rust-nostr-keystore.json");
+    let keystore = FileKeystore::new(path);
+
+    keystore.unlock("password")?;
+    keystore.set("test", &keys)?;
+
+    println!("Accounts: {:?}", keystore.accounts()?);
+
+    let found_keys = keystore.get("test")?;
+
+    assert_eq!(keys, found_keys);
+
+    keystore.lock();
+
+    Ok(())
+}
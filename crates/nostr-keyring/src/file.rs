@@ -0,0 +1,503 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2025 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Encrypted file keystore
+//!
+//! Store multiple named accounts as NIP-49 `ncryptsec` in a single file.
+//! Useful where an OS keyring is not available (i.e., headless servers).
+//!
+//! <https://github.com/nostr-protocol/nips/blob/master/49.md>
+
+use std::collections::BTreeMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+#[cfg(unix)]
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "async")]
+use async_utility::task;
+use fd_lock::RwLock;
+use nostr::nips::nip49::{EncryptedSecretKey, KeySecurity};
+use nostr::{serde_json, FromBech32, Keys, SecretKey, ToBech32};
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroizing;
+
+use crate::Error;
+
+const KEYSTORE_VERSION: u8 = 1;
+const DEFAULT_LOG_N: u8 = 16;
+const DEFAULT_UNLOCK_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Keystore file content
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+struct KeystoreFile {
+    version: u8,
+    /// Accounts `ncryptsec`, by name
+    accounts: BTreeMap<String, String>,
+}
+
+/// Unlocked password
+#[derive(Debug)]
+struct Unlocked {
+    /// Zeroed on drop
+    password: Zeroizing<String>,
+    expires_at: Instant,
+}
+
+/// Encrypted file keystore
+///
+/// All the accounts are encrypted with the same password.
+/// The keystore must be unlocked with [`FileKeystore::unlock`] before reading or writing the keys:
+/// the password is kept in memory until [`FileKeystore::lock`] is called or the unlock timeout expires.
+///
+/// The changes to the file are serialized, also between different processes,
+/// with an advisory lock on a `.lock` file next to the keystore.
+#[derive(Debug, Clone)]
+pub struct FileKeystore {
+    path: PathBuf,
+    log_n: u8,
+    timeout: Duration,
+    unlocked: Arc<Mutex<Option<Unlocked>>>,
+    /// Serializes the read-modify-write cycles of the clones
+    update_lock: Arc<Mutex<()>>,
+}
+
+impl FileKeystore {
+    /// Construct keystore for file
+    ///
+    /// The file is created at the first [`FileKeystore::set`].
+    pub fn new<P>(path: P) -> Self
+    where
+        P: AsRef<Path>,
+    {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            log_n: DEFAULT_LOG_N,
+            timeout: DEFAULT_UNLOCK_TIMEOUT,
+            unlocked: Arc::new(Mutex::new(None)),
+            update_lock: Arc::new(Mutex::new(())),
+        }
+    }
+
+    /// Scrypt `LOG_N` used to encrypt the keys (default: 16)
+    #[inline]
+    pub fn log_n(mut self, log_n: u8) -> Self {
+        self.log_n = log_n;
+        self
+    }
+
+    /// How long the keystore stays unlocked (default: 5 min)
+    #[inline]
+    pub fn unlock_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Get the keystore file path
+    #[inline]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn read(&self) -> Result<KeystoreFile, Error> {
+        match fs::read(&self.path) {
+            Ok(bytes) => {
+                let file: KeystoreFile = serde_json::from_slice(&bytes)?;
+
+                if file.version != KEYSTORE_VERSION {
+                    return Err(Error::UnknownKeystoreVersion(file.version));
+                }
+
+                Ok(file)
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(KeystoreFile {
+                version: KEYSTORE_VERSION,
+                accounts: BTreeMap::new(),
+            }),
+            Err(e) => Err(Error::Io(e)),
+        }
+    }
+
+    /// Write the file atomically
+    fn write(&self, file: &KeystoreFile) -> Result<(), Error> {
+        let json: Vec<u8> = serde_json::to_vec_pretty(file)?;
+
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let tmp: PathBuf = self.path.with_extension("tmp");
+
+        let mut options: OpenOptions = OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+
+        // Readable only by the owner
+        #[cfg(unix)]
+        options.mode(0o600);
+
+        let mut f = options.open(&tmp)?;
+        f.write_all(&json)?;
+        f.sync_all()?;
+        fs::rename(&tmp, &self.path)?;
+
+        Ok(())
+    }
+
+    fn open_lock_file(&self) -> Result<File, Error> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let mut options: OpenOptions = OpenOptions::new();
+        options.read(true).write(true).create(true).truncate(false);
+
+        #[cfg(unix)]
+        options.mode(0o600);
+
+        Ok(options.open(self.path.with_extension("lock"))?)
+    }
+
+    /// Read, modify and write the file
+    ///
+    /// The in-process lock and the advisory file lock are held for the whole cycle,
+    /// so concurrent changes are not lost.
+    fn update<F, T>(&self, f: F) -> Result<T, Error>
+    where
+        F: FnOnce(&mut KeystoreFile) -> Result<T, Error>,
+    {
+        let _guard = self.update_lock.lock().unwrap_or_else(|e| e.into_inner());
+
+        let mut lock_file: RwLock<File> = RwLock::new(self.open_lock_file()?);
+        let _file_guard = lock_file.write()?;
+
+        let mut file: KeystoreFile = self.read()?;
+        let output: T = f(&mut file)?;
+        self.write(&file)?;
+
+        Ok(output)
+    }
+
+    /// Get the unlocked password, if not expired
+    fn password(&self) -> Result<Zeroizing<String>, Error> {
+        let mut unlocked = self.unlocked.lock().unwrap_or_else(|e| e.into_inner());
+
+        match unlocked.as_ref() {
+            Some(u) if u.expires_at > Instant::now() => Ok(u.password.clone()),
+            Some(..) => {
+                // Expired
+                *unlocked = None;
+                Err(Error::Locked)
+            }
+            None => Err(Error::Locked),
+        }
+    }
+
+    fn encrypt(&self, secret_key: &SecretKey, password: &str) -> Result<String, Error> {
+        let encrypted: EncryptedSecretKey =
+            EncryptedSecretKey::new(secret_key, password, self.log_n, KeySecurity::Medium)?;
+        Ok(encrypted.to_bech32()?)
+    }
+
+    /// The decrypted [`SecretKey`] is erased on drop.
+    fn decrypt(ncryptsec: &str, password: &str) -> Result<SecretKey, Error> {
+        let encrypted: EncryptedSecretKey = EncryptedSecretKey::from_bech32(ncryptsec)?;
+        encrypted
+            .decrypt(password)
+            .map_err(|_| Error::WrongPassword)
+    }
+
+    /// Unlock the keystore
+    ///
+    /// If the keystore already contains some accounts, the password is verified against the first one:
+    /// all the accounts are encrypted with the same password, so decrypting (and running scrypt on) the others isn't needed.
+    pub fn unlock(&self, password: &str) -> Result<(), Error> {
+        let file: KeystoreFile = self.read()?;
+
+        if let Some(ncryptsec) = file.accounts.values().next() {
+            Self::decrypt(ncryptsec, password)?;
+        }
+
+        let mut unlocked = self.unlocked.lock().unwrap_or_else(|e| e.into_inner());
+        *unlocked = Some(Unlocked {
+            password: Zeroizing::new(password.to_string()),
+            expires_at: Instant::now() + self.timeout,
+        });
+
+        Ok(())
+    }
+
+    /// Lock the keystore, removing the password from memory
+    pub fn lock(&self) {
+        let mut unlocked = self.unlocked.lock().unwrap_or_else(|e| e.into_inner());
+        *unlocked = None;
+    }
+
+    /// Check if the keystore is locked
+    #[inline]
+    pub fn is_locked(&self) -> bool {
+        self.password().is_err()
+    }
+
+    /// Get the names of the accounts
+    ///
+    /// Doesn't require to unlock the keystore.
+    pub fn accounts(&self) -> Result<Vec<String>, Error> {
+        let file: KeystoreFile = self.read()?;
+        Ok(file.accounts.into_keys().collect())
+    }
+
+    /// Change the keystore password
+    ///
+    /// All the accounts are encrypted again with the new password.
+    /// If the keystore is unlocked, it remains unlocked with the new password.
+    pub fn change_password(&self, old_password: &str, new_password: &str) -> Result<(), Error> {
+        self.update(|file| {
+            for ncryptsec in file.accounts.values_mut() {
+                let secret_key: SecretKey = Self::decrypt(ncryptsec, old_password)?;
+                *ncryptsec = self.encrypt(&secret_key, new_password)?;
+            }
+
+            Ok(())
+        })?;
+
+        let mut unlocked = self.unlocked.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(u) = unlocked.as_mut() {
+            u.password = Zeroizing::new(new_password.to_string());
+        }
+
+        Ok(())
+    }
+
+    /// Save a [`Keys`] into the keystore
+    pub fn set(&self, name: &str, keys: &Keys) -> Result<(), Error> {
+        let password: Zeroizing<String> = self.password()?;
+        let ncryptsec: String = self.encrypt(keys.secret_key(), &password)?;
+
+        self.update(|file| {
+            file.accounts.insert(name.to_string(), ncryptsec);
+            Ok(())
+        })
+    }
+
+    /// Asynchronously save a [`Keys`] into the keystore
+    #[cfg(feature = "async")]
+    pub async fn set_async(&self, name: &str, keys: &Keys) -> Result<(), Error> {
+        let keystore: Self = self.clone();
+        let name: String = name.to_string();
+        let keys: Keys = keys.clone();
+        task::spawn_blocking(move || keystore.set(&name, &keys)).await?
+    }
+
+    /// Get the [`Keys`] from the keystore
+    pub fn get(&self, name: &str) -> Result<Keys, Error> {
+        let password: Zeroizing<String> = self.password()?;
+        let file: KeystoreFile = self.read()?;
+
+        let ncryptsec: &String = file.accounts.get(name).ok_or(Error::AccountNotFound)?;
+        let secret_key: SecretKey = Self::decrypt(ncryptsec, &password)?;
+
+        Ok(Keys::new(secret_key))
+    }
+
+    /// Asynchronously get the [`Keys`] from the keystore
+    #[cfg(feature = "async")]
+    pub async fn get_async(&self, name: &str) -> Result<Keys, Error> {
+        let keystore: Self = self.clone();
+        let name: String = name.to_string();
+        task::spawn_blocking(move || keystore.get(&name)).await?
+    }
+
+    /// Delete the [`Keys`] from the keystore
+    pub fn delete(&self, name: &str) -> Result<(), Error> {
+        // Require unlock, to avoid deletions without knowing the password
+        self.password()?;
+
+        self.update(|file| match file.accounts.remove(name) {
+            Some(..) => Ok(()),
+            None => Err(Error::AccountNotFound),
+        })
+    }
+
+    /// Asynchronously delete the [`Keys`] from the keystore
+    #[cfg(feature = "async")]
+    pub async fn delete_async(&self, name: &str) -> Result<(), Error> {
+        let keystore: Self = self.clone();
+        let name: String = name.to_string();
+        task::spawn_blocking(move || keystore.delete(&name)).await?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keystore(dir: &tempfile::TempDir) -> FileKeystore {
+        FileKeystore::new(dir.path().join("keystore.json")).log_n(4)
+    }
+
+    #[test]
+    fn test_file_keystore() {
+        let dir = tempfile::tempdir().unwrap();
+        let keystore = keystore(&dir);
+
+        let alice = Keys::generate();
+        let bob = Keys::generate();
+
+        // Locked
+        assert!(keystore.is_locked());
+        assert!(matches!(keystore.set("alice", &alice), Err(Error::Locked)));
+
+        keystore.unlock("password").unwrap();
+        keystore.set("alice", &alice).unwrap();
+        keystore.set("bob", &bob).unwrap();
+
+        assert_eq!(keystore.accounts().unwrap(), vec!["alice", "bob"]);
+        assert_eq!(keystore.get("alice").unwrap(), alice);
+        assert_eq!(keystore.get("bob").unwrap(), bob);
+
+        // Delete
+        keystore.delete("bob").unwrap();
+        assert!(matches!(keystore.get("bob"), Err(Error::AccountNotFound)));
+        assert_eq!(keystore.accounts().unwrap(), vec!["alice"]);
+
+        // Lock
+        keystore.lock();
+        assert!(matches!(keystore.get("alice"), Err(Error::Locked)));
+
+        // Accounts are stored as ncryptsec
+        let file: KeystoreFile =
+            serde_json::from_slice(&fs::read(keystore.path()).unwrap()).unwrap();
+        assert!(file.accounts["alice"].starts_with("ncryptsec1"));
+
+        // Reopen
+        let keystore = FileKeystore::new(keystore.path());
+        assert!(matches!(
+            keystore.unlock("wrong"),
+            Err(Error::WrongPassword)
+        ));
+        keystore.unlock("password").unwrap();
+        assert_eq!(keystore.get("alice").unwrap(), alice);
+    }
+
+    #[test]
+    fn test_change_password() {
+        let dir = tempfile::tempdir().unwrap();
+        let keystore = keystore(&dir);
+        let keys = Keys::generate();
+
+        keystore.unlock("old").unwrap();
+        keystore.set("main", &keys).unwrap();
+
+        assert!(matches!(
+            keystore.change_password("wrong", "new"),
+            Err(Error::WrongPassword)
+        ));
+        keystore.change_password("old", "new").unwrap();
+
+        // Still unlocked
+        assert_eq!(keystore.get("main").unwrap(), keys);
+
+        keystore.lock();
+        assert!(matches!(keystore.unlock("old"), Err(Error::WrongPassword)));
+        keystore.unlock("new").unwrap();
+        assert_eq!(keystore.get("main").unwrap(), keys);
+    }
+
+    #[test]
+    fn test_unlock_checks_one_account() {
+        let dir = tempfile::tempdir().unwrap();
+        let keystore = keystore(&dir);
+
+        keystore.unlock("password").unwrap();
+        keystore.set("alice", &Keys::generate()).unwrap();
+
+        // Account encrypted with another password
+        let mut file: KeystoreFile = keystore.read().unwrap();
+        let ncryptsec: String = keystore
+            .encrypt(Keys::generate().secret_key(), "other")
+            .unwrap();
+        file.accounts.insert(String::from("bob"), ncryptsec);
+        keystore.write(&file).unwrap();
+
+        // Only the first account is decrypted
+        keystore.lock();
+        assert!(matches!(
+            keystore.unlock("other"),
+            Err(Error::WrongPassword)
+        ));
+        assert!(keystore.is_locked());
+        keystore.unlock("password").unwrap();
+        assert!(keystore.get("alice").is_ok());
+        assert!(matches!(keystore.get("bob"), Err(Error::WrongPassword)));
+    }
+
+    #[test]
+    fn test_concurrent_updates() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("keystore.json");
+
+        // Clones share the in-process lock, the other instances only the file lock
+        let shared = FileKeystore::new(&path).log_n(4);
+        shared.unlock("password").unwrap();
+
+        let mut handles = Vec::new();
+        for i in 0..8 {
+            let keystore = if i % 2 == 0 {
+                shared.clone()
+            } else {
+                let keystore = FileKeystore::new(&path).log_n(4);
+                keystore.unlock("password").unwrap();
+                keystore
+            };
+
+            handles.push(std::thread::spawn(move || {
+                for j in 0..5 {
+                    keystore
+                        .set(&format!("account-{i}-{j}"), &Keys::generate())
+                        .unwrap();
+                }
+            }));
+        }
+
+        for handle in handles.into_iter() {
+            handle.join().unwrap();
+        }
+
+        assert_eq!(shared.accounts().unwrap().len(), 40);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_file_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let keystore = keystore(&dir);
+
+        keystore.unlock("password").unwrap();
+        keystore.set("main", &Keys::generate()).unwrap();
+
+        let metadata = fs::metadata(keystore.path()).unwrap();
+        assert_eq!(metadata.permissions().mode() & 0o777, 0o600);
+    }
+
+    #[test]
+    fn test_unlock_timeout() {
+        let dir = tempfile::tempdir().unwrap();
+        let keystore = keystore(&dir).unlock_timeout(Duration::from_millis(100));
+
+        keystore.unlock("password").unwrap();
+        assert!(!keystore.is_locked());
+
+        std::thread::sleep(Duration::from_millis(150));
+        assert!(keystore.is_locked());
+        assert!(matches!(
+            keystore.set("main", &Keys::generate()),
+            Err(Error::Locked)
+        ));
+    }
+}
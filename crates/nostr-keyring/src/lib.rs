@@ -11,15 +11,24 @@
 #![cfg_attr(docsrs, feature(doc_auto_cfg))]
 #![doc = include_str!("../README.md")]
 
-use std::fmt;
+use std::{fmt, io};
 
+#[cfg(all(feature = "async", feature = "keyring"))]
+use async_utility::task;
 #[cfg(feature = "async")]
-use async_utility::{task, tokio};
+use async_utility::tokio;
+#[cfg(feature = "keyring")]
 pub use keyring::{Entry, Error as KeyringError};
-use nostr::{key, Keys, SecretKey};
+use nostr::nips::{nip19, nip49};
+use nostr::{key, serde_json};
+#[cfg(feature = "keyring")]
+use nostr::{Keys, SecretKey};
 
+pub mod file;
 pub mod prelude;
 
+pub use self::file::FileKeystore;
+
 /// Keyring error
 #[derive(Debug)]
 pub enum Error {
@@ -27,9 +36,26 @@ pub enum Error {
     #[cfg(feature = "async")]
     Join(tokio::task::JoinError),
     /// Keyring error
+    #[cfg(feature = "keyring")]
     Keyring(KeyringError),
     /// Nostr keys error
     Keys(key::Error),
+    /// I/O error
+    Io(io::Error),
+    /// Json error
+    Json(serde_json::Error),
+    /// NIP19 error
+    NIP19(nip19::Error),
+    /// NIP49 error
+    NIP49(nip49::Error),
+    /// Keystore is locked
+    Locked,
+    /// Wrong password
+    WrongPassword,
+    /// Account not found
+    AccountNotFound,
+    /// Unknown keystore version
+    UnknownKeystoreVersion(u8),
 }
 
 impl std::error::Error for Error {}
@@ -39,8 +65,17 @@ impl fmt::Display for Error {
         match self {
             #[cfg(feature = "async")]
             Self::Join(e) => write!(f, "{e}"),
+            #[cfg(feature = "keyring")]
             Self::Keyring(e) => write!(f, "{e}"),
             Self::Keys(e) => write!(f, "{e}"),
+            Self::Io(e) => write!(f, "{e}"),
+            Self::Json(e) => write!(f, "{e}"),
+            Self::NIP19(e) => write!(f, "{e}"),
+            Self::NIP49(e) => write!(f, "{e}"),
+            Self::Locked => write!(f, "keystore is locked"),
+            Self::WrongPassword => write!(f, "wrong password"),
+            Self::AccountNotFound => write!(f, "account not found"),
+            Self::UnknownKeystoreVersion(v) => write!(f, "unknown keystore version: {v}"),
         }
     }
 }
@@ -52,6 +87,7 @@ impl From<tokio::task::JoinError> for Error {
     }
 }
 
+#[cfg(feature = "keyring")]
 impl From<KeyringError> for Error {
     fn from(e: KeyringError) -> Self {
         Self::Keyring(e)
@@ -64,12 +100,38 @@ impl From<key::Error> for Error {
     }
 }
 
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Self::Json(e)
+    }
+}
+
+impl From<nip19::Error> for Error {
+    fn from(e: nip19::Error) -> Self {
+        Self::NIP19(e)
+    }
+}
+
+impl From<nip49::Error> for Error {
+    fn from(e: nip49::Error) -> Self {
+        Self::NIP49(e)
+    }
+}
+
 /// Nostr keyring
+#[cfg(feature = "keyring")]
 #[derive(Debug, Clone)]
 pub struct NostrKeyring {
     service: String,
 }
 
+#[cfg(feature = "keyring")]
 impl NostrKeyring {
     /// Construct keyring for service
     pub fn new<S>(service: S) -> Self
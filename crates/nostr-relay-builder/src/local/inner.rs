@@ -27,6 +27,13 @@ use crate::error::Error;
 
 type WsTx<S> = SplitSink<WebSocketStream<S>, Message>;
 
+/// Max number of events to include in a HyperLogLog sketch (NIP45)
+///
+/// If more events match the filter, only the count is returned.
+const MAX_HLL_EVENTS: usize = 100_000;
+/// Number of events queried at once, to build a HyperLogLog sketch (NIP45)
+const HLL_PAGE_SIZE: usize = 500;
+
 #[derive(Debug, Clone)]
 pub(super) struct InnerLocalRelay {
     addr: SocketAddr,
@@ -262,7 +269,8 @@ impl InnerLocalRelay {
                     }
                 }
                 event = new_event.recv() => {
-                    if let Ok(event) = event {
+                    // Skip expired events (NIP-40)
+                    if let Some(event) = event.ok().filter(|event| !event.is_expired()) {
                          // Iter subscriptions
                         for (subscription_id, filter) in session.subscriptions.iter() {
                             if filter.match_event(&event, MatchEventOptions::new()) {
@@ -626,6 +634,13 @@ impl InnerLocalRelay {
                     self.database.query(filter.clone()).await?
                 };
 
+                // Remove expired events (NIP-40), in case the database backend doesn't exclude them
+                let now: Timestamp = Timestamp::now();
+                let events: Vec<Event> = events
+                    .into_iter()
+                    .filter(|event| !event.is_expired_at(&now))
+                    .collect();
+
                 let events_len: usize = events.len();
 
                 tracing::debug!(
@@ -677,8 +692,22 @@ impl InnerLocalRelay {
                 subscription_id,
                 filter,
             } => {
-                let count: usize = self.database.count(filter.into_owned()).await?;
-                send_msg(ws_tx, RelayMessage::Count { subscription_id, count }).await
+                let filter: Filter = filter.into_owned();
+
+                let count: usize = self.database.count(filter.clone()).await?;
+
+                // Compute the HyperLogLog sketch for the eligible filters (NIP45), if not too many events
+                match Hll::offset(&filter) {
+                    Some(offset) if count <= MAX_HLL_EVENTS => {
+                        let hll: Hll = self.compute_hll(filter, offset).await?;
+                        send_msg(
+                            ws_tx,
+                            RelayMessage::count_with_hll(subscription_id.into_owned(), count, hll),
+                        )
+                        .await
+                    }
+                    _ => send_msg(ws_tx, RelayMessage::count(subscription_id.into_owned(), count)).await,
+                }
             }
             ClientMessage::Close(subscription_id) => {
                 session.subscriptions.remove(&subscription_id);
@@ -799,6 +828,86 @@ impl InnerLocalRelay {
             }
         }
     }
+
+    /// Compute the HyperLogLog sketch of the authors of the events matching the filter
+    ///
+    /// The events are queried in pages of [`HLL_PAGE_SIZE`], from the newest to the oldest,
+    /// to not load all of them in memory.
+    async fn compute_hll(&self, filter: Filter, offset: usize) -> Result<Hll, DatabaseError> {
+        let mut hll: Hll = Hll::new();
+        let mut until: Option<Timestamp> = filter.until;
+
+        loop {
+            let mut page: Filter = filter.clone().limit(HLL_PAGE_SIZE);
+            page.until = until;
+
+            let events: Events = self.database.query(page).await?;
+
+            for event in events.iter() {
+                hll.add(offset, &event.pubkey);
+            }
+
+            // Last page
+            if events.len() < HLL_PAGE_SIZE {
+                return Ok(hll);
+            }
+
+            let (newest, oldest) = match (events.first(), events.last()) {
+                (Some(newest), Some(oldest)) => (newest.created_at, oldest.created_at),
+                _ => return Ok(hll),
+            };
+
+            // Request again the events with the same timestamp of the oldest one, since may not be all included
+            // (adding the same public key twice doesn't change the sketch).
+            // If the whole page has the same timestamp, add all the events of that second before moving to the previous one.
+            let next: Timestamp = if newest != oldest {
+                oldest
+            } else {
+                self.add_second_to_hll(&mut hll, &filter, offset, oldest)
+                    .await?;
+
+                if oldest > Timestamp::from_secs(0) {
+                    oldest - 1
+                } else {
+                    return Ok(hll);
+                }
+            };
+
+            until = Some(next);
+        }
+    }
+
+    /// Add to the HyperLogLog sketch the authors of all the events of a second
+    ///
+    /// The database has no cursor within the same timestamp, so the second is queried again with a doubled limit,
+    /// until all its events are returned (adding the same public key twice doesn't change the sketch).
+    async fn add_second_to_hll(
+        &self,
+        hll: &mut Hll,
+        filter: &Filter,
+        offset: usize,
+        timestamp: Timestamp,
+    ) -> Result<(), DatabaseError> {
+        let mut limit: usize = HLL_PAGE_SIZE * 2;
+
+        loop {
+            let mut page: Filter = filter.clone().limit(limit);
+            page.since = Some(timestamp);
+            page.until = Some(timestamp);
+
+            let events: Events = self.database.query(page).await?;
+
+            for event in events.iter() {
+                hll.add(offset, &event.pubkey);
+            }
+
+            if events.len() < limit {
+                return Ok(());
+            }
+
+            limit = limit.saturating_mul(2);
+        }
+    }
 }
 
 #[inline]
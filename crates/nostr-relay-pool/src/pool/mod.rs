@@ -1103,6 +1103,104 @@ impl RelayPool {
         Ok(output)
     }
 
+    /// Count events from relays with [`RelayServiceFlags::READ`] flag.
+    ///
+    /// Check [`RelayPool::count_events_from`] docs to learn more.
+    pub async fn count_events(
+        &self,
+        filter: Filter,
+        timeout: Duration,
+    ) -> Result<Output<usize>, Error> {
+        let urls: Vec<RelayUrl> = self.__read_relay_urls().await;
+        self.count_events_from(urls, filter, timeout).await
+    }
+
+    /// Count events from specific relays
+    ///
+    /// The HyperLogLog sketches returned by the relays (NIP45) are merged,
+    /// so the same public key is counted only once across relays.
+    /// If a relay doesn't provide a sketch, its exact count is used as a lower bound of the total.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/45.md>
+    pub async fn count_events_from<I, U>(
+        &self,
+        urls: I,
+        filter: Filter,
+        timeout: Duration,
+    ) -> Result<Output<usize>, Error>
+    where
+        I: IntoIterator<Item = U>,
+        U: TryIntoUrl,
+        Error: From<<U as TryIntoUrl>::Err>,
+    {
+        // Compose URLs
+        let set: HashSet<RelayUrl> = urls
+            .into_iter()
+            .map(|u| u.try_into_url())
+            .collect::<Result<_, _>>()?;
+
+        // Check if urls set is empty
+        if set.is_empty() {
+            return Err(Error::NoRelaysSpecified);
+        }
+
+        // Lock with read shared access
+        let relays = self.inner.atomic.relays.read().await;
+
+        if relays.is_empty() {
+            return Err(Error::NoRelays);
+        }
+
+        // Check if urls set contains ONLY already added relays
+        if !set.iter().all(|url| relays.contains_key(url)) {
+            return Err(Error::RelayNotFound);
+        }
+
+        let mut urls: Vec<RelayUrl> = Vec::with_capacity(set.len());
+        let mut futures = Vec::with_capacity(set.len());
+        let mut output: Output<usize> = Output::default();
+
+        // Compose futures
+        for url in set.into_iter() {
+            let relay: &Relay = self.internal_relay(&relays, &url)?;
+            urls.push(url);
+            futures.push(relay.count_events_with_hll(filter.clone(), timeout));
+        }
+
+        // Join futures
+        let list = future::join_all(futures).await;
+
+        let mut sketch: Option<Hll> = None;
+        let mut max_count: usize = 0;
+
+        // Iter results and construct output
+        for (url, result) in urls.into_iter().zip(list.into_iter()) {
+            match result {
+                Ok((count, hll)) => {
+                    match hll {
+                        Some(hll) => match &mut sketch {
+                            Some(sketch) => sketch.merge(&hll),
+                            None => sketch = Some(hll),
+                        },
+                        None => max_count = max_count.max(count),
+                    }
+
+                    output.success.insert(url);
+                }
+                Err(e) => {
+                    output.failed.insert(url, e.to_string());
+                }
+            }
+        }
+
+        output.val = match sketch {
+            Some(sketch) => sketch.estimate().max(max_count),
+            None => max_count,
+        };
+
+        Ok(output)
+    }
+
     /// Fetch events from relays with [`RelayServiceFlags::READ`] flag.
     pub async fn fetch_events(
         &self,
@@ -1493,4 +1591,53 @@ mod tests {
             Error::RelayNotFound
         ));
     }
+
+    #[tokio::test]
+    async fn test_count_events_with_hll() {
+        let mock1 = MockRelay::run().await.unwrap();
+        let url1 = RelayUrl::parse(&mock1.url()).unwrap();
+        let mock2 = MockRelay::run().await.unwrap();
+        let url2 = RelayUrl::parse(&mock2.url()).unwrap();
+
+        let pool = RelayPool::default();
+        pool.add_relay(&url1, RelayOptions::default())
+            .await
+            .unwrap();
+        pool.add_relay(&url2, RelayOptions::default())
+            .await
+            .unwrap();
+        pool.connect().await;
+        pool.wait_for_connection(Duration::from_secs(3)).await;
+
+        let author = Keys::generate();
+        let note = EventBuilder::text_note("Test")
+            .sign_with_keys(&author)
+            .unwrap();
+
+        // 10 reactions: 6 to the first relay, 6 to the second one (2 overlapping)
+        let reactors: Vec<Keys> = (0..10).map(|_| Keys::generate()).collect();
+        for (i, keys) in reactors.iter().enumerate() {
+            let reaction = EventBuilder::reaction(ReactionTarget::new(&note, None), "+")
+                .sign_with_keys(keys)
+                .unwrap();
+
+            if i < 6 {
+                pool.send_event_to([&url1], &reaction).await.unwrap();
+            }
+
+            if i >= 4 {
+                pool.send_event_to([&url2], &reaction).await.unwrap();
+            }
+        }
+
+        let filter = Filter::new().kind(Kind::Reaction).event(note.id);
+        let output = pool
+            .count_events(filter, Duration::from_secs(5))
+            .await
+            .unwrap();
+        assert_eq!(output.success.len(), 2);
+
+        // Without merging the sketches, the count would be 12
+        assert!((8..=11).contains(&output.val), "{}", output.val);
+    }
 }
@@ -28,6 +28,12 @@ pub(super) const NEGENTROPY_HIGH_WATER_UP: usize = 100;
 pub(super) const NEGENTROPY_LOW_WATER_UP: usize = 50;
 pub(super) const NEGENTROPY_BATCH_SIZE_DOWN: usize = 100;
 
+/// Max number of ready messages to take from the websocket stream and handle in a single batch
+pub(super) const MAX_MESSAGES_BATCH_SIZE: usize = 512;
+
+/// Max number of event IDs to remember per subscription, to drop the already delivered events before the full parsing
+pub(super) const MAX_SEEN_EVENTS: usize = 10_000;
+
 pub(super) const MIN_ATTEMPTS: usize = 1;
 pub(super) const MIN_SUCCESS_RATE: f64 = 0.90;
 
@@ -5,14 +5,16 @@
 use std::borrow::Cow;
 use std::cmp;
 use std::collections::{HashMap, HashSet};
+use std::num::NonZeroUsize;
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 
 use async_utility::{task, time};
-use async_wsocket::futures_util::{self, SinkExt, StreamExt};
+use async_wsocket::futures_util::{self, FutureExt, SinkExt, StreamExt};
 use async_wsocket::{ConnectionMode, Message};
 use atomic_destructor::AtomicDestroyer;
+use lru::LruCache;
 use negentropy::{Id, Negentropy, NegentropyStorageVector};
 use nostr::secp256k1::rand::{self, Rng};
 use nostr_database::prelude::*;
@@ -20,10 +22,10 @@ use tokio::sync::mpsc::{self, Receiver, Sender};
 use tokio::sync::{broadcast, Mutex, MutexGuard, Notify, RwLock, RwLockWriteGuard};
 
 use super::constants::{
-    DEFAULT_CONNECTION_TIMEOUT, JITTER_RANGE, MAX_RETRY_INTERVAL, MIN_ATTEMPTS, MIN_SUCCESS_RATE,
-    NEGENTROPY_BATCH_SIZE_DOWN, NEGENTROPY_FRAME_SIZE_LIMIT, NEGENTROPY_HIGH_WATER_UP,
-    NEGENTROPY_LOW_WATER_UP, PING_INTERVAL, SLEEP_INTERVAL, WAIT_FOR_OK_TIMEOUT,
-    WEBSOCKET_TX_TIMEOUT,
+    DEFAULT_CONNECTION_TIMEOUT, JITTER_RANGE, MAX_MESSAGES_BATCH_SIZE, MAX_RETRY_INTERVAL,
+    MAX_SEEN_EVENTS, MIN_ATTEMPTS, MIN_SUCCESS_RATE, NEGENTROPY_BATCH_SIZE_DOWN,
+    NEGENTROPY_FRAME_SIZE_LIMIT, NEGENTROPY_HIGH_WATER_UP, NEGENTROPY_LOW_WATER_UP, PING_INTERVAL,
+    SLEEP_INTERVAL, WAIT_FOR_OK_TIMEOUT, WEBSOCKET_TX_TIMEOUT,
 };
 use super::flags::AtomicRelayServiceFlags;
 use super::options::{RelayOptions, ReqExitPolicy, SubscribeAutoCloseOptions, SyncOptions};
@@ -44,8 +46,12 @@ type ClientMessageJson = String;
 // Skip NIP-50 matches since they may create issues and ban non-malicious relays.
 const MATCH_EVENT_OPTS: MatchEventOptions = MatchEventOptions::new().nip50(false);
 
-enum IngesterCommand {
-    Authenticate { challenge: String },
+pub(super) enum IngesterCommand {
+    Authenticate {
+        challenge: String,
+        /// Public key to authenticate as (the default signer if `None`)
+        public_key: Option<PublicKey>,
+    },
 }
 
 enum HandleClosedMsg {
@@ -58,6 +64,28 @@ struct HandleAutoClosing {
     reason: Option<SubscriptionAutoClosedReason>,
 }
 
+pub(super) struct ParsedRelayMessage {
+    message: RelayMessage<'static>,
+    /// Event status, checked before the full parsing
+    ///
+    /// `None` if not an event or if not existent yet (checked again later, since may be saved in the meantime).
+    status: Option<DatabaseEventStatus>,
+    /// The event has been taken from the database, instead of deserializing the message
+    from_database: bool,
+}
+
+/// Relay message that passed the checks (see [`InnerRelay::check_parsed_relay_message`])
+enum CheckedRelayMessage {
+    /// Event to verify and save
+    Event {
+        subscription_id: SubscriptionId,
+        event: Event,
+        status: Option<DatabaseEventStatus>,
+    },
+    /// Other message
+    Other(RelayMessage<'static>),
+}
+
 #[derive(Debug)]
 struct RelayChannels {
     nostr: (
@@ -123,6 +151,10 @@ struct SubscriptionData {
     pub received_events: AtomicUsize,
     /// Subscription closed by relay
     pub closed: bool,
+    /// Public key to use for NIP42 authentication
+    pub auth_as: Option<PublicKey>,
+    /// IDs of the events already delivered to the subscription
+    pub seen_events: LruCache<EventId, ()>,
 }
 
 impl Default for SubscriptionData {
@@ -135,7 +167,22 @@ impl Default for SubscriptionData {
             received_eose: false,
             received_events: AtomicUsize::new(0),
             closed: false,
+            auth_as: None,
+            seen_events: LruCache::new(
+                NonZeroUsize::new(MAX_SEEN_EVENTS).expect("MAX_SEEN_EVENTS must be greater than 0"),
+            ),
+        }
+    }
+}
+
+impl SubscriptionData {
+    /// Update the filter, forgetting the delivered events if it changed
+    fn set_filter(&mut self, filter: Filter) {
+        if self.filter != filter {
+            self.seen_events.clear();
         }
+
+        self.filter = filter;
     }
 }
 
@@ -146,6 +193,8 @@ pub(super) struct AtomicPrivateData {
     status: AtomicRelayStatus,
     channels: RelayChannels,
     subscriptions: RwLock<HashMap<SubscriptionId, SubscriptionData>>,
+    /// Last NIP42 challenge received in the current connection
+    auth_challenge: RwLock<Option<String>>,
     running: AtomicBool,
 }
 
@@ -178,6 +227,7 @@ impl InnerRelay {
                 status: AtomicRelayStatus::default(),
                 channels: RelayChannels::new(),
                 subscriptions: RwLock::new(HashMap::new()),
+                auth_challenge: RwLock::new(None),
                 running: AtomicBool::new(false),
             }),
             flags: AtomicRelayServiceFlags::new(opts.flags),
@@ -310,11 +360,40 @@ impl InnerRelay {
     }
 
     /// Register an auto-closing subscription
-    pub(crate) async fn add_auto_closing_subscription(&self, id: SubscriptionId, filter: Filter) {
+    pub(crate) async fn add_auto_closing_subscription(
+        &self,
+        id: SubscriptionId,
+        filter: Filter,
+        auth_as: Option<PublicKey>,
+    ) {
         let mut subscriptions = self.atomic.subscriptions.write().await;
         let data: &mut SubscriptionData = subscriptions.entry(id).or_default();
-        data.filter = filter;
+        data.set_filter(filter);
         data.is_auto_closing = true;
+        data.auth_as = auth_as;
+    }
+
+    /// Set the public key to use for NIP42 authentication for a subscription
+    pub(crate) async fn set_subscription_auth_as(&self, id: SubscriptionId, public_key: PublicKey) {
+        let mut subscriptions = self.atomic.subscriptions.write().await;
+        let data: &mut SubscriptionData = subscriptions.entry(id).or_default();
+        data.auth_as = Some(public_key);
+    }
+
+    async fn subscription_auth_as(&self, id: &SubscriptionId) -> Option<PublicKey> {
+        let subscriptions = self.atomic.subscriptions.read().await;
+        subscriptions.get(id)?.auth_as
+    }
+
+    /// Get the last NIP42 challenge received in the current connection
+    pub(super) async fn auth_challenge(&self) -> Option<String> {
+        let challenge = self.atomic.auth_challenge.read().await;
+        challenge.clone()
+    }
+
+    async fn set_auth_challenge(&self, challenge: Option<String>) {
+        let mut current = self.atomic.auth_challenge.write().await;
+        *current = challenge;
     }
 
     pub(crate) async fn update_subscription(
@@ -325,7 +404,7 @@ impl InnerRelay {
     ) {
         let mut subscriptions = self.atomic.subscriptions.write().await;
         let data: &mut SubscriptionData = subscriptions.entry(id).or_default();
-        data.filter = filter;
+        data.set_filter(filter);
 
         if update_subscribed_at {
             data.subscribed_at = Timestamp::now();
@@ -340,6 +419,22 @@ impl InnerRelay {
         }
     }
 
+    /// Check if the event was already delivered to the subscription
+    async fn is_event_seen(&self, id: &SubscriptionId, event_id: &EventId) -> bool {
+        let subscriptions = self.atomic.subscriptions.read().await;
+        subscriptions
+            .get(id)
+            .is_some_and(|data| data.seen_events.contains(event_id))
+    }
+
+    /// Mark the event as delivered to the subscription
+    async fn mark_event_as_seen(&self, id: &SubscriptionId, event_id: EventId) {
+        let mut subscriptions = self.atomic.subscriptions.write().await;
+        if let Some(data) = subscriptions.get_mut(id) {
+            data.seen_events.put(event_id, ());
+        }
+    }
+
     /// Received eose for subscription
     async fn received_eose(&self, id: &SubscriptionId) {
         let mut subscriptions = self.atomic.subscriptions.write().await;
@@ -751,6 +846,9 @@ impl InnerRelay {
 
         let ping: PingTracker = PingTracker::default();
 
+        // The challenges of the previous connections are no longer valid
+        self.set_auth_challenge(None).await;
+
         let (ingester_tx, ingester_rx) = mpsc::unbounded_channel();
 
         // Wait that one of the futures terminates/completes
@@ -867,9 +965,45 @@ impl InnerRelay {
         #[cfg(target_arch = "wasm32")]
         let _ping = ping;
 
-        while let Some(msg) = ws_rx.next().await {
+        // Message taken from the stream while collecting a batch, to handle in the next iteration
+        let mut pending: Option<Result<Message, _>> = None;
+
+        loop {
+            let msg = match pending.take() {
+                Some(msg) => msg,
+                None => match ws_rx.next().await {
+                    Some(msg) => msg,
+                    None => break,
+                },
+            };
+
             match msg? {
-                Message::Text(json) => self.handle_relay_message(&json, &ingester_tx).await,
+                Message::Text(json) => {
+                    let mut batch: Vec<String> = vec![json.to_string()];
+                    let mut ended: bool = false;
+
+                    // Collect the text messages already available, without waiting
+                    while batch.len() < MAX_MESSAGES_BATCH_SIZE {
+                        match ws_rx.next().now_or_never() {
+                            Some(Some(Ok(Message::Text(json)))) => batch.push(json.to_string()),
+                            Some(Some(msg)) => {
+                                pending = Some(msg);
+                                break;
+                            }
+                            Some(None) => {
+                                ended = true;
+                                break;
+                            }
+                            None => break,
+                        }
+                    }
+
+                    self.handle_relay_messages(batch, &ingester_tx).await;
+
+                    if ended {
+                        break;
+                    }
+                }
                 Message::Binary(_) => {
                     tracing::warn!(url = %self.url, "Binary messages aren't supported.");
                 }
@@ -927,32 +1061,12 @@ impl InnerRelay {
         while let Some(command) = rx.recv().await {
             match command {
                 // Authenticate to relay
-                IngesterCommand::Authenticate { challenge } => {
-                    match self.auth(challenge).await {
-                        Ok(..) => {
-                            self.send_notification(RelayNotification::Authenticated, false);
-
-                            tracing::info!(url = %self.url, "Authenticated to relay.");
-
-                            // TODO: ?
-                            if let Err(e) = self.resubscribe().await {
-                                tracing::error!(
-                                    url = %self.url,
-                                    error = %e,
-                                    "Impossible to resubscribe."
-                                );
-                            }
-                        }
-                        Err(e) => {
-                            self.send_notification(RelayNotification::AuthenticationFailed, false);
-
-                            tracing::error!(
-                                url = %self.url,
-                                error = %e,
-                                "Can't authenticate to relay."
-                            );
-                        }
-                    }
+                IngesterCommand::Authenticate {
+                    challenge,
+                    public_key,
+                } => {
+                    // The result is already handled
+                    let _ = self.authenticate(challenge, public_key).await;
                 }
             }
         }
@@ -992,12 +1106,79 @@ impl InnerRelay {
         }
     }
 
-    async fn handle_relay_message(
+    /// Handle a batch of relay messages
+    ///
+    /// The cheap checks (limits, subscription, expiration and admission policy) are run first:
+    /// only the new events that pass them are verified in batch, across multiple threads,
+    /// before being handled one by one.
+    pub(super) async fn handle_relay_messages(
+        &self,
+        msgs: Vec<String>,
+        ingester_tx: &mpsc::UnboundedSender<IngesterCommand>,
+    ) {
+        // Parse and check messages
+        let mut checked: Vec<(String, Result<Option<CheckedRelayMessage>, Error>)> =
+            Vec::with_capacity(msgs.len());
+
+        for msg in msgs.into_iter() {
+            let res = match self.parse_relay_message(&msg).await {
+                Ok(Some(message)) => self.check_parsed_relay_message(message).await,
+                // Message dropped before the full parsing
+                Ok(None) => continue,
+                Err(e) => Err(e),
+            };
+
+            checked.push((msg, res));
+        }
+
+        // Verify events in batch
+        //
+        // Skip the events already saved or deleted (not verified again)
+        // and the duplicated ones.
+        let mut ids: HashSet<EventId> = HashSet::new();
+        let events: Vec<Event> = checked
+            .iter()
+            .filter_map(|(_, res)| match res {
+                Ok(Some(CheckedRelayMessage::Event {
+                    event,
+                    status: None,
+                    ..
+                })) if ids.insert(event.id) => Some(event.clone()),
+                _ => None,
+            })
+            .collect();
+
+        if events.len() > 1 {
+            if let Err(e) = self.state.verify_batch(events).await {
+                tracing::error!(url = %self.url, error = %e, "Impossible to verify events in batch.");
+            }
+        }
+
+        // Handle messages
+        for (msg, res) in checked.into_iter() {
+            let res = match res {
+                Ok(Some(CheckedRelayMessage::Event {
+                    subscription_id,
+                    event,
+                    status,
+                })) => self.process_event_msg(subscription_id, event, status).await,
+                Ok(Some(CheckedRelayMessage::Other(message))) => Ok(Some(message)),
+                Ok(None) => Ok(None),
+                Err(e) => Err(e),
+            };
+
+            self.handle_relay_message_result(&msg, res, ingester_tx)
+                .await;
+        }
+    }
+
+    async fn handle_relay_message_result(
         &self,
         msg: &str,
+        res: Result<Option<RelayMessage<'static>>, Error>,
         ingester_tx: &mpsc::UnboundedSender<IngesterCommand>,
     ) {
-        match self.handle_raw_relay_message(msg).await {
+        match res {
             Ok(Some(message)) => {
                 match &message {
                     RelayMessage::Closed {
@@ -1005,7 +1186,9 @@ impl InnerRelay {
                         message,
                     } => {
                         // Check machine-readable prefix
-                        let res: HandleClosedMsg = match MachineReadablePrefix::parse(message) {
+                        let prefix: Option<MachineReadablePrefix> =
+                            MachineReadablePrefix::parse(message);
+                        let res: HandleClosedMsg = match prefix {
                             Some(MachineReadablePrefix::Duplicate) => HandleClosedMsg::Remove,
                             Some(MachineReadablePrefix::Pow) => HandleClosedMsg::Remove,
                             Some(MachineReadablePrefix::Blocked) => HandleClosedMsg::Remove,
@@ -1047,16 +1230,34 @@ impl InnerRelay {
                                 self.remove_subscription(subscription_id).await;
                             }
                         }
+
+                        // Authenticate as the owner of the subscription
+                        if let Some(MachineReadablePrefix::AuthRequired) = prefix {
+                            if self.state.is_auto_authentication_enabled()
+                                && self.state.has_auth_signers().await
+                            {
+                                let public_key: Option<PublicKey> =
+                                    self.subscription_auth_as(subscription_id).await;
+                                self.request_authentication(public_key, ingester_tx).await;
+                            }
+                        }
                     }
                     RelayMessage::EndOfStoredEvents(id) => {
                         self.received_eose(id).await;
                     }
                     RelayMessage::Auth { challenge } => {
-                        // Check if NIP42 auto authentication is enabled
-                        if self.state.is_auto_authentication_enabled() {
+                        self.set_auth_challenge(Some(challenge.to_string())).await;
+
+                        // Check if NIP42 auto authentication is enabled.
+                        // If there are auth signers, authenticate only when required,
+                        // to use the signer of the event author or of the subscription owner.
+                        if self.state.is_auto_authentication_enabled()
+                            && !self.state.has_auth_signers().await
+                        {
                             // Forward action to ingester
                             let _ = ingester_tx.send(IngesterCommand::Authenticate {
                                 challenge: challenge.to_string(),
+                                public_key: None,
                             });
                         }
                     }
@@ -1076,10 +1277,24 @@ impl InnerRelay {
         }
     }
 
+    #[cfg(bench)]
     async fn handle_raw_relay_message(
         &self,
         msg: &str,
     ) -> Result<Option<RelayMessage<'static>>, Error> {
+        match self.parse_relay_message(msg).await? {
+            Some(message) => self.handle_parsed_relay_message(message).await,
+            None => Ok(None),
+        }
+    }
+
+    /// Parse relay message
+    ///
+    /// Return `None` if the message can be dropped without the full parsing.
+    pub(super) async fn parse_relay_message(
+        &self,
+        msg: &str,
+    ) -> Result<Option<ParsedRelayMessage>, Error> {
         // Trim the message (removes leading and trailing whitespaces and line breaks).
         let msg: &str = msg.trim();
 
@@ -1099,24 +1314,121 @@ impl InnerRelay {
             }
         }
 
-        // Handle msg
-        match RelayMessage::from_json(msg)? {
+        // Check the event before the full parsing (without allocating the event),
+        // to immediately drop the events already delivered to the subscription and the deleted ones,
+        // and to skip the verification of the known events.
+        //
+        // Only the `EVENT` messages are partially parsed, so the other messages are parsed once.
+        // The saved events not delivered yet (i.e., received from other relays) must still be delivered to the subscription:
+        // they are taken from the database, without deserializing and checking them again,
+        // unless the full event must be checked (subscription to verify or admission policy).
+        let mut status: Option<DatabaseEventStatus> = None;
+        if is_event_msg(msg) {
+            if let RawRelayMessage::Event {
+                subscription_id,
+                event_id,
+            } = RawRelayMessage::from_json(msg)?
+            {
+                // Drop it if already delivered to the subscription (i.e., re-sent after a reconnection)
+                let subscription_id: SubscriptionId = SubscriptionId::new(subscription_id);
+                if self.is_event_seen(&subscription_id, &event_id).await {
+                    return Ok(None);
+                }
+
+                let must_verify: bool =
+                    self.opts.verify_subscriptions || self.opts.ban_relay_on_mismatch;
+
+                match self.state.database().check_id(&event_id).await? {
+                    // Checked again later, since may be saved in the meantime (i.e., duplicated in the same batch)
+                    DatabaseEventStatus::NotExistent => {}
+                    // Drop it, unless the subscription must be verified (the full event is required)
+                    DatabaseEventStatus::Deleted if !must_verify => return Ok(None),
+                    // Already received from another relay
+                    DatabaseEventStatus::Saved
+                        if !must_verify && self.state.admit_policy.is_none() =>
+                    {
+                        // The database may not store the events (only the IDs)
+                        if let Some(event) = self.state.database().event_by_id(&event_id).await? {
+                            return Ok(Some(ParsedRelayMessage {
+                                message: RelayMessage::event(subscription_id, event),
+                                status: Some(DatabaseEventStatus::Saved),
+                                from_database: true,
+                            }));
+                        }
+
+                        status = Some(DatabaseEventStatus::Saved);
+                    }
+                    s => status = Some(s),
+                }
+            }
+        }
+
+        // Parse msg
+        Ok(Some(ParsedRelayMessage {
+            message: RelayMessage::from_json(msg)?,
+            status,
+            from_database: false,
+        }))
+    }
+
+    #[cfg(bench)]
+    async fn handle_parsed_relay_message(
+        &self,
+        message: ParsedRelayMessage,
+    ) -> Result<Option<RelayMessage<'static>>, Error> {
+        match self.check_parsed_relay_message(message).await? {
+            Some(CheckedRelayMessage::Event {
+                subscription_id,
+                event,
+                status,
+            }) => self.process_event_msg(subscription_id, event, status).await,
+            Some(CheckedRelayMessage::Other(message)) => Ok(Some(message)),
+            None => Ok(None),
+        }
+    }
+
+    /// Run the cheap checks of the event messages
+    ///
+    /// Return `None` if the event is rejected by the admission policy.
+    async fn check_parsed_relay_message(
+        &self,
+        message: ParsedRelayMessage,
+    ) -> Result<Option<CheckedRelayMessage>, Error> {
+        match message.message {
             RelayMessage::Event {
                 subscription_id,
                 event,
             } => {
-                self.handle_event_msg(subscription_id.into_owned(), event.into_owned())
-                    .await
+                let subscription_id: SubscriptionId = subscription_id.into_owned();
+                let event: Event = event.into_owned();
+
+                // Already checked before being saved, except the expiration
+                if message.from_database {
+                    if event.is_expired() {
+                        return Err(Error::EventExpired);
+                    }
+                } else if !self.check_event_msg(&subscription_id, &event).await? {
+                    return Ok(None);
+                }
+
+                Ok(Some(CheckedRelayMessage::Event {
+                    subscription_id,
+                    event,
+                    status: message.status,
+                }))
             }
-            m => Ok(Some(m)),
+            m => Ok(Some(CheckedRelayMessage::Other(m))),
         }
     }
 
-    async fn handle_event_msg(
+    /// Check limits, subscription, expiration and admission policy of the event
+    ///
+    /// Return `false` if the event is rejected by the admission policy.
+    async fn check_event_msg(
         &self,
-        subscription_id: SubscriptionId,
-        event: Event,
-    ) -> Result<Option<RelayMessage<'static>>, Error> {
+        subscription_id: &SubscriptionId,
+        event: &Event,
+    ) -> Result<bool, Error> {
         // Check event size
         if let Some(max_size) = self.opts.limits.events.get_max_size(&event.kind) {
             let size: usize = event.as_json().len();
@@ -1152,7 +1464,7 @@ impl InnerRelay {
                 received_events,
                 ..
             } = subscriptions
-                .get(&subscription_id)
+                .get(subscription_id)
                 .ok_or(Error::SubscriptionNotFound)?;
 
             // EOSE received, not check anymore the limit
@@ -1176,7 +1488,7 @@ impl InnerRelay {
             }
 
             // Check if the filter matches the event
-            if !filter.match_event(&event, MATCH_EVENT_OPTS) {
+            if !filter.match_event(event, MATCH_EVENT_OPTS) {
                 // Ban the relay
                 if self.opts.ban_relay_on_mismatch {
                     self.ban();
@@ -1194,15 +1506,30 @@ impl InnerRelay {
         // Check event admission policy
         if let Some(policy) = &self.state.admit_policy {
             if let AdmitStatus::Rejected { .. } = policy
-                .admit_event(&self.url, &subscription_id, &event)
+                .admit_event(&self.url, subscription_id, event)
                 .await?
             {
-                return Ok(None);
+                return Ok(false);
             }
         }
 
-        // Check the event status
-        match self.state.database().check_id(&event.id).await? {
+        Ok(true)
+    }
+
+    /// Verify, save and deliver the event that passed the checks (see [`InnerRelay::check_event_msg`])
+    async fn process_event_msg(
+        &self,
+        subscription_id: SubscriptionId,
+        event: Event,
+        status: Option<DatabaseEventStatus>,
+    ) -> Result<Option<RelayMessage<'static>>, Error> {
+        // Check the event status, if not already known
+        let status: DatabaseEventStatus = match status {
+            Some(status) => status,
+            None => self.state.database().check_id(&event.id).await?,
+        };
+
+        match status {
             // Already saved, continue with code execution
             DatabaseEventStatus::Saved => {}
             // Deleted, immediately return
@@ -1211,14 +1538,14 @@ impl InnerRelay {
             DatabaseEventStatus::NotExistent => {
                 // Check if the event was already verified.
                 //
-                // This is useful if someone continues to send the same invalid event:
-                // since invalid events aren't stored in the database,
-                // skipping this check would result in the re-verification of the event.
-                // This may also be useful to avoid double verification if the event is received at the exact same time by many different Relay instances.
+                // The event may have been verified in batch (see `InnerRelay::handle_relay_messages`)
+                // or by another Relay instance, if received at the exact same time by many relays.
+                // Only the valid events are cached, so an invalid event is always rejected.
                 //
                 // This is important since event signature verification is a heavy job!
-                if !self.state.verified(&event.id)? {
+                if !self.state.is_verified(&event.id)? {
                     event.verify()?;
+                    self.state.set_verified(&event.id)?;
                 }
 
                 // Save into the database
@@ -1252,6 +1579,8 @@ impl InnerRelay {
             }
         }
 
+        self.mark_event_as_seen(&subscription_id, event.id).await;
+
         Ok(Some(RelayMessage::Event {
             subscription_id: Cow::Owned(subscription_id),
             event: Cow::Owned(event),
@@ -1335,9 +1664,71 @@ impl InnerRelay {
         })
     }
 
-    async fn auth(&self, challenge: String) -> Result<(), Error> {
+    /// Request the authentication as `public_key`, with the last received challenge
+    async fn request_authentication(
+        &self,
+        public_key: Option<PublicKey>,
+        ingester_tx: &mpsc::UnboundedSender<IngesterCommand>,
+    ) {
+        match self.auth_challenge().await {
+            Some(challenge) => {
+                // Forward action to ingester
+                let _ = ingester_tx.send(IngesterCommand::Authenticate {
+                    challenge,
+                    public_key,
+                });
+            }
+            None => {
+                self.send_notification(RelayNotification::AuthenticationFailed, false);
+
+                tracing::warn!(url = %self.url, "Can't authenticate to relay: challenge not received.");
+            }
+        }
+    }
+
+    /// Authenticate as `public_key` (the default signer if `None`) and resubscribe
+    pub(super) async fn authenticate(
+        &self,
+        challenge: String,
+        public_key: Option<PublicKey>,
+    ) -> Result<(), Error> {
+        match self.auth(challenge, public_key).await {
+            Ok(..) => {
+                self.send_notification(RelayNotification::Authenticated, false);
+
+                tracing::info!(url = %self.url, "Authenticated to relay.");
+
+                // TODO: ?
+                if let Err(e) = self.resubscribe().await {
+                    tracing::error!(
+                        url = %self.url,
+                        error = %e,
+                        "Impossible to resubscribe."
+                    );
+                }
+
+                Ok(())
+            }
+            Err(e) => {
+                self.send_notification(RelayNotification::AuthenticationFailed, false);
+
+                tracing::error!(
+                    url = %self.url,
+                    error = %e,
+                    "Can't authenticate to relay."
+                );
+
+                Err(e)
+            }
+        }
+    }
+
+    async fn auth(&self, challenge: String, public_key: Option<PublicKey>) -> Result<(), Error> {
         // Get signer
-        let signer = self.state.signer().await?;
+        let signer = self
+            .state
+            .auth_signer(public_key.as_ref())
+            .await?;
 
         // Construct event
         let event: Event = EventBuilder::auth(challenge, self.url.clone())
@@ -1876,7 +2267,7 @@ impl InnerRelay {
         };
 
         // Register an auto-closing subscription
-        self.add_auto_closing_subscription(down_sub_id.clone(), filter.clone())
+        self.add_auto_closing_subscription(down_sub_id.clone(), filter.clone(), None)
             .await;
 
         // Send msg
@@ -2049,7 +2440,7 @@ impl InnerRelay {
                             if subscription_id.as_ref() == &down_sub_id {
                                 in_flight_down = false;
 
-                                // NOTE: the subscription is removed in the `InnerRelay::handle_relay_message` method,
+                                // NOTE: the subscription is removed in the `InnerRelay::handle_relay_messages` method,
                                 // so there is no need to try to remove it also here.
                             }
                         }
@@ -2109,6 +2500,15 @@ async fn close_ws(tx: &mut BoxSink) -> Result<(), Error> {
     }
 }
 
+/// Check if the message is an `EVENT` message, without parsing it
+///
+/// The message must be already trimmed.
+#[inline]
+fn is_event_msg(msg: &str) -> bool {
+    msg.strip_prefix('[')
+        .is_some_and(|msg| msg.trim_start().starts_with("\"EVENT\""))
+}
+
 #[inline]
 fn neg_id_to_event_id(id: Id) -> EventId {
     EventId::from_byte_array(id.to_bytes())
@@ -2233,4 +2633,49 @@ mod benches {
             });
         });
     }
+
+    const SAVED_EVENT: &str = r#"{"id":"70b10f70c1318967eddf12527799411b1a9780ad9c43858f5e5fcd45486a13a5","pubkey":"379e863e8357163b5bce5d2688dc4f1dcc2d505222fb8d74db600f30535dfdfe","created_at":1612809991,"kind":1,"tags":[],"content":"test","sig":"273a9cd5d11455590f4359500bccb7a89428262b96b3ea87a756b770964472f8c3e87f5d5e64d8d2e859a71462a3f477b554565c4f2f326cb01dd7620db71502"}"#;
+
+    /// Relay with the event already saved and delivered to the `seen` subscription
+    fn relay_with_seen_event() -> InnerRelay {
+        let relay = relay();
+
+        RUNTIME.block_on(async {
+            let id = SubscriptionId::new("seen");
+            relay.update_subscription(id, Filter::new(), false).await;
+
+            let msg = format!(r#"["EVENT", "seen", {SAVED_EVENT}]"#);
+            relay.handle_raw_relay_message(&msg).await.unwrap();
+        });
+
+        relay
+    }
+
+    #[bench]
+    fn bench_handle_relay_msg_saved_event(bh: &mut Bencher) {
+        let relay = relay_with_seen_event();
+
+        // Saved, but not delivered to this subscription yet (i.e., received from another relay)
+        let msg = format!(r#"["EVENT", "random_string", {SAVED_EVENT}]"#);
+
+        bh.iter(|| {
+            RUNTIME.block_on(async {
+                relay.handle_raw_relay_message(&msg).await.unwrap();
+            });
+        });
+    }
+
+    #[bench]
+    fn bench_handle_relay_msg_seen_event(bh: &mut Bencher) {
+        let relay = relay_with_seen_event();
+
+        // Already delivered to this subscription
+        let msg = format!(r#"["EVENT", "seen", {SAVED_EVENT}]"#);
+
+        bh.iter(|| {
+            RUNTIME.block_on(async {
+                relay.handle_raw_relay_message(&msg).await.unwrap();
+            });
+        });
+    }
 }
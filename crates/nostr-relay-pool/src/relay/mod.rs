@@ -401,11 +401,30 @@ impl Relay {
         // If auth required, wait for authentication adn resend it
         if let Some(MachineReadablePrefix::AuthRequired) = MachineReadablePrefix::parse(&message) {
             // Check if NIP42 auth is enabled and signer is set
-            let has_signer: bool = self.inner.state.has_signer().await;
+            let has_signer: bool = self
+                .inner
+                .state
+                .can_authenticate(Some(&event.pubkey))
+                .await;
             if self.inner.state.is_auto_authentication_enabled() && has_signer {
-                // Wait that relay authenticate
-                self.wait_for_authentication(&mut notifications, WAIT_FOR_AUTHENTICATION_TIMEOUT)
+                if self.inner.state.has_auth_signers().await {
+                    // Authenticate as the event author
+                    let challenge: String = self
+                        .inner
+                        .auth_challenge()
+                        .await
+                        .ok_or(Error::AuthenticationFailed)?;
+                    self.inner
+                        .authenticate(challenge, Some(event.pubkey))
+                        .await?;
+                } else {
+                    // Wait that relay authenticate
+                    self.wait_for_authentication(
+                        &mut notifications,
+                        WAIT_FOR_AUTHENTICATION_TIMEOUT,
+                    )
                     .await?;
+                }
 
                 // Try to resend event
                 let (status, message) = self._send_event(&mut notifications, event).await?;
@@ -492,8 +511,11 @@ impl Relay {
     ) -> Result<(), Error> {
         // Check if the auto-close condition is set
         match opts.auto_close {
-            Some(opts) => self.subscribe_auto_closing(id, filter, opts, None).await,
-            None => self.subscribe_long_lived(id, filter).await,
+            Some(auto_close) => {
+                self.subscribe_auto_closing(id, filter, auto_close, opts.auth_as, None)
+                    .await
+            }
+            None => self.subscribe_long_lived(id, filter, opts.auth_as).await,
         }
     }
 
@@ -502,6 +524,7 @@ impl Relay {
         id: SubscriptionId,
         filter: Filter,
         opts: SubscribeAutoCloseOptions,
+        auth_as: Option<PublicKey>,
         activity: Option<mpsc::Sender<SubscriptionActivity>>,
     ) -> Result<(), Error> {
         // Compose REQ message
@@ -515,7 +538,7 @@ impl Relay {
 
         // Register the auto-closing subscription
         self.inner
-            .add_auto_closing_subscription(id.clone(), filter.clone())
+            .add_auto_closing_subscription(id.clone(), filter.clone(), auth_as)
             .await;
 
         // Send REQ message
@@ -535,13 +558,26 @@ impl Relay {
         Ok(())
     }
 
-    async fn subscribe_long_lived(&self, id: SubscriptionId, filter: Filter) -> Result<(), Error> {
+    async fn subscribe_long_lived(
+        &self,
+        id: SubscriptionId,
+        filter: Filter,
+        auth_as: Option<PublicKey>,
+    ) -> Result<(), Error> {
         // Compose REQ message
         let msg: ClientMessage = ClientMessage::Req {
             subscription_id: Cow::Borrowed(&id),
             filter: Cow::Borrowed(&filter),
         };
 
+        // Set the NIP42 auth public key before sending the REQ,
+        // since the relay may immediately require the authentication.
+        if let Some(public_key) = auth_as {
+            self.inner
+                .set_subscription_auth_as(id.clone(), public_key)
+                .await;
+        }
+
         // Send REQ message
         self.inner.send_msg(msg)?;
 
@@ -582,7 +618,7 @@ impl Relay {
 
         // Subscribe
         let id: SubscriptionId = SubscriptionId::generate();
-        self.subscribe_auto_closing(id, filter, opts, Some(tx))
+        self.subscribe_auto_closing(id, filter, opts, None, Some(tx))
             .await?;
 
         // Handle subscription activity
@@ -650,7 +686,20 @@ impl Relay {
     }
 
     /// Count events
+    #[inline]
     pub async fn count_events(&self, filter: Filter, timeout: Duration) -> Result<usize, Error> {
+        let (count, _) = self.count_events_with_hll(filter, timeout).await?;
+        Ok(count)
+    }
+
+    /// Count events, returning also the HyperLogLog sketch, if provided by the relay
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/45.md>
+    pub async fn count_events_with_hll(
+        &self,
+        filter: Filter,
+        timeout: Duration,
+    ) -> Result<(usize, Option<Hll>), Error> {
         let id = SubscriptionId::generate();
         let msg = ClientMessage::Count {
             subscription_id: Cow::Borrowed(&id),
@@ -659,6 +708,7 @@ impl Relay {
         self.inner.send_msg(msg)?;
 
         let mut count = 0;
+        let mut sketch: Option<Hll> = None;
 
         let mut notifications = self.inner.internal_notification_sender.subscribe();
         time::timeout(Some(timeout), async {
@@ -668,11 +718,13 @@ impl Relay {
                         RelayMessage::Count {
                             subscription_id,
                             count: c,
+                            hll,
                         },
                 } = notification
                 {
                     if subscription_id.as_ref() == &id {
                         count = c;
+                        sketch = hll.map(|hll| hll.into_owned());
                         break;
                     }
                 }
@@ -684,7 +736,7 @@ impl Relay {
         // Unsubscribe
         self.inner.send_msg(ClientMessage::close(id))?;
 
-        Ok(count)
+        Ok((count, sketch))
     }
 
     /// Sync events with relays (negentropy reconciliation)
@@ -751,6 +803,177 @@ mod tests {
 
     use super::{Error, *};
     use crate::policy::{AdmitPolicy, PolicyError};
+    use crate::shared::SharedStateError;
+
+    /// Record if the events were already verified when admitted
+    #[derive(Debug)]
+    struct VerifiedOnAdmitTestPolicy {
+        state: SharedState,
+        verified: std::sync::Mutex<HashMap<EventId, bool>>,
+    }
+
+    impl AdmitPolicy for VerifiedOnAdmitTestPolicy {
+        fn admit_event<'a>(
+            &'a self,
+            _relay_url: &'a RelayUrl,
+            _subscription_id: &'a SubscriptionId,
+            event: &'a Event,
+        ) -> BoxedFuture<'a, Result<AdmitStatus, PolicyError>> {
+            Box::pin(async move {
+                let verified: bool = self.state.is_verified(&event.id).unwrap();
+                self.verified.lock().unwrap().insert(event.id, verified);
+                Ok(AdmitStatus::Success)
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_drop_seen_events_before_parsing() {
+        let keys = Keys::generate();
+        let subscription_id = SubscriptionId::new("test");
+
+        let url = RelayUrl::parse("ws://localhost:8080").unwrap();
+        let relay = Relay::new(url, SharedState::default(), RelayOptions::default());
+        relay
+            .inner
+            .update_subscription(subscription_id.clone(), Filter::new(), false)
+            .await;
+
+        let msgs: Vec<String> = (0..10)
+            .map(|i| {
+                let event = EventBuilder::text_note(i.to_string())
+                    .sign_with_keys(&keys)
+                    .unwrap();
+                RelayMessage::event(subscription_id.clone(), event).as_json()
+            })
+            .collect();
+
+        // Not delivered yet: fully parsed
+        for msg in msgs.iter() {
+            assert!(relay
+                .inner
+                .parse_relay_message(msg)
+                .await
+                .unwrap()
+                .is_some());
+        }
+
+        let (ingester_tx, _ingester_rx) = mpsc::unbounded_channel();
+        relay
+            .inner
+            .handle_relay_messages(msgs.clone(), &ingester_tx)
+            .await;
+
+        // Already delivered (i.e., re-sent after a reconnection): dropped before the full parsing
+        for msg in msgs.iter() {
+            assert!(relay
+                .inner
+                .parse_relay_message(msg)
+                .await
+                .unwrap()
+                .is_none());
+        }
+
+        // Saved, but not delivered to another subscription yet: fully parsed
+        let other = SubscriptionId::new("other");
+        relay
+            .inner
+            .update_subscription(other.clone(), Filter::new(), false)
+            .await;
+        let msg = msgs[0].replacen(subscription_id.as_str(), other.as_str(), 1);
+        assert!(relay
+            .inner
+            .parse_relay_message(&msg)
+            .await
+            .unwrap()
+            .is_some());
+
+        // The filter changed: the events must be delivered again
+        relay
+            .inner
+            .update_subscription(subscription_id, Filter::new().kind(Kind::TextNote), false)
+            .await;
+        for msg in msgs.iter() {
+            assert!(relay
+                .inner
+                .parse_relay_message(msg)
+                .await
+                .unwrap()
+                .is_some());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_saved_events_taken_from_database() {
+        let keys = Keys::generate();
+        let subscription_id = SubscriptionId::new("test");
+
+        // Event already received from another relay
+        let database = MemoryDatabase::with_opts(MemoryDatabaseOptions {
+            events: true,
+            max_events: None,
+        });
+        let event = EventBuilder::text_note("Saved")
+            .sign_with_keys(&keys)
+            .unwrap();
+        database.save_event(&event).await.unwrap();
+
+        let url = RelayUrl::parse("ws://localhost:8080").unwrap();
+        let relay = new_relay_with_database(url, Arc::new(database), RelayOptions::default());
+        relay
+            .inner
+            .update_subscription(subscription_id.clone(), Filter::new(), false)
+            .await;
+
+        // Tampered content: the message isn't deserialized and verified, the saved event is delivered
+        let msg: String = RelayMessage::event(subscription_id.clone(), event.clone())
+            .as_json()
+            .replace("Saved", "Tampered");
+
+        let (ingester_tx, _ingester_rx) = mpsc::unbounded_channel();
+        let mut notifications = relay.inner.internal_notification_sender.subscribe();
+        relay
+            .inner
+            .handle_relay_messages(vec![msg.clone()], &ingester_tx)
+            .await;
+
+        match notifications.recv().await.unwrap() {
+            RelayNotification::Message {
+                message: RelayMessage::Event { event: e, .. },
+            } => assert_eq!(e.into_owned(), event),
+            n => panic!("Unexpected notification: {n:?}"),
+        }
+
+        // Delivered: dropped
+        assert!(relay
+            .inner
+            .parse_relay_message(&msg)
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[derive(Debug)]
+    struct RejectContentTestPolicy {
+        content: String,
+    }
+
+    impl AdmitPolicy for RejectContentTestPolicy {
+        fn admit_event<'a>(
+            &'a self,
+            _relay_url: &'a RelayUrl,
+            _subscription_id: &'a SubscriptionId,
+            event: &'a Event,
+        ) -> BoxedFuture<'a, Result<AdmitStatus, PolicyError>> {
+            Box::pin(async move {
+                if event.content == self.content {
+                    Ok(AdmitStatus::rejected("content"))
+                } else {
+                    Ok(AdmitStatus::Success)
+                }
+            })
+        }
+    }
 
     #[derive(Debug)]
     struct CustomTestPolicy {
@@ -1364,6 +1587,128 @@ mod tests {
         assert!(res.is_ok());
     }
 
+    async fn wait_for_auth_result(
+        notifications: &mut broadcast::Receiver<RelayNotification>,
+    ) -> RelayNotification {
+        time::timeout(Some(Duration::from_secs(5)), async {
+            loop {
+                match notifications.recv().await.unwrap() {
+                    n @ RelayNotification::Authenticated => return n,
+                    n @ RelayNotification::AuthenticationFailed => return n,
+                    _ => (),
+                }
+            }
+        })
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_nip42_auth_signers() {
+        // Mock relay
+        let opts = RelayBuilderNip42 {
+            mode: RelayBuilderNip42Mode::Write,
+        };
+        let builder = RelayBuilder::default().nip42(opts);
+        let mock = LocalRelay::run(builder).await.unwrap();
+        let url = RelayUrl::parse(&mock.url()).unwrap();
+
+        let alice = Keys::generate();
+        let bob = Keys::generate();
+        let default = Keys::generate();
+
+        // Account signers and default signer
+        let state = SharedState::default();
+        state.set_signer(default.clone()).await;
+        state
+            .add_auth_signer(alice.public_key(), alice.clone().into_nostr_signer())
+            .await;
+        state
+            .add_auth_signer(bob.public_key(), bob.clone().into_nostr_signer())
+            .await;
+        state.automatic_authentication(true);
+
+        // Protected events are accepted only if authenticated as the author
+        let relay: Relay = Relay::new(url.clone(), state.clone(), RelayOptions::default());
+        relay.connect();
+        let event = EventBuilder::text_note("Test")
+            .tag(Tag::protected())
+            .sign_with_keys(&bob)
+            .unwrap();
+        assert!(relay.send_event(&event).await.is_ok());
+
+        // Fallback to the default signer
+        let relay: Relay = Relay::new(url.clone(), state.clone(), RelayOptions::default());
+        relay.connect();
+        let event = EventBuilder::text_note("Test")
+            .tag(Tag::protected())
+            .sign_with_keys(&default)
+            .unwrap();
+        assert!(relay.send_event(&event).await.is_ok());
+
+        // Remove the auth signer
+        state.remove_auth_signer(&alice.public_key()).await;
+        state.unset_signer().await;
+        assert!(matches!(
+            state.auth_signer(Some(&alice.public_key())).await,
+            Err(SharedStateError::SignerNotConfigured)
+        ));
+        assert!(state.auth_signer(Some(&bob.public_key())).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_nip42_auth_subscription_owner() {
+        // Mock relay
+        let opts = RelayBuilderNip42 {
+            mode: RelayBuilderNip42Mode::Read,
+        };
+        let builder = RelayBuilder::default().nip42(opts);
+        let mock = LocalRelay::run(builder).await.unwrap();
+        let url = RelayUrl::parse(&mock.url()).unwrap();
+
+        let relay: Relay = new_relay(url.clone(), RelayOptions::default());
+        relay.inner.state.automatic_authentication(true);
+
+        // Account signer only for alice, no default signer
+        let alice = Keys::generate();
+        let bob = Keys::generate();
+        relay
+            .inner
+            .state
+            .add_auth_signer(alice.public_key(), alice.clone().into_nostr_signer())
+            .await;
+
+        relay.try_connect(Duration::from_secs(3)).await.unwrap();
+
+        let mut notifications = relay.inner.internal_notification_sender.subscribe();
+
+        // No signer for bob
+        relay
+            .subscribe(
+                Filter::new().kind(Kind::TextNote),
+                SubscribeOptions::default().auth_as(bob.public_key()),
+            )
+            .await
+            .unwrap();
+        assert!(matches!(
+            wait_for_auth_result(&mut notifications).await,
+            RelayNotification::AuthenticationFailed
+        ));
+
+        // Authenticated as alice
+        relay
+            .subscribe(
+                Filter::new().kind(Kind::TextNote),
+                SubscribeOptions::default().auth_as(alice.public_key()),
+            )
+            .await
+            .unwrap();
+        assert!(matches!(
+            wait_for_auth_result(&mut notifications).await,
+            RelayNotification::Authenticated
+        ));
+    }
+
     #[tokio::test]
     async fn test_fetch_events_exit_on_eose() {
         let (relay, _mock) = setup_event_fetching_relay(5).await;
@@ -1391,6 +1736,347 @@ mod tests {
         assert_eq!(events.len(), 3);
     }
 
+    #[tokio::test]
+    async fn test_fetch_events_batch_verification() {
+        let (relay, _mock) = setup_event_fetching_relay(50).await;
+
+        let events = relay
+            .fetch_events(
+                Filter::new().kind(Kind::TextNote),
+                Duration::from_secs(5),
+                ReqExitPolicy::ExitOnEOSE,
+            )
+            .await
+            .unwrap();
+        assert_eq!(events.len(), 50);
+
+        // All the received events must be marked as verified
+        for event in events.iter() {
+            assert!(relay.inner.state.is_verified(&event.id).unwrap());
+        }
+    }
+
+    async fn fetch_events_with_tampered_event(opts: RelayOptions) {
+        let keys = Keys::generate();
+
+        // Valid events
+        let events: Vec<Event> = (0..20)
+            .map(|i| {
+                EventBuilder::text_note(i.to_string())
+                    .sign_with_keys(&keys)
+                    .unwrap()
+            })
+            .collect();
+
+        // Tampered event: valid ID but signature of another event
+        let other = EventBuilder::text_note("Tampered")
+            .sign_with_keys(&keys)
+            .unwrap();
+        let tampered = Event::new(
+            other.id,
+            other.pubkey,
+            other.created_at,
+            other.kind,
+            other.tags.clone(),
+            other.content.clone(),
+            events[0].sig,
+        );
+        assert!(tampered.verify().is_err());
+
+        // Mock relay, serving also the tampered event
+        let relay_database = MemoryDatabase::with_opts(MemoryDatabaseOptions {
+            events: true,
+            max_events: None,
+        });
+        for event in events.iter() {
+            relay_database.save_event(event).await.unwrap();
+        }
+        relay_database.save_event(&tampered).await.unwrap();
+        let builder = RelayBuilder::default().database(relay_database);
+        let mock = LocalRelay::run(builder).await.unwrap();
+        let url = RelayUrl::parse(&mock.url()).unwrap();
+
+        // Already saved event
+        let database = MemoryDatabase::with_opts(MemoryDatabaseOptions {
+            events: true,
+            max_events: None,
+        });
+        database.save_event(&events[1]).await.unwrap();
+
+        let mut state = SharedState::default();
+        state.database = Arc::new(database);
+        let policy = Arc::new(VerifiedOnAdmitTestPolicy {
+            state: state.clone(),
+            verified: std::sync::Mutex::new(HashMap::new()),
+        });
+        state.admit_policy = Some(policy.clone());
+
+        let relay = Relay::new(url, state, opts);
+        relay.connect();
+
+        let received = relay
+            .fetch_events(
+                Filter::new().kind(Kind::TextNote),
+                Duration::from_secs(5),
+                ReqExitPolicy::ExitOnEOSE,
+            )
+            .await
+            .unwrap();
+
+        // The tampered event is rejected
+        assert_eq!(received.len(), events.len());
+        assert!(!received.contains(&tampered));
+        assert!(!relay.inner.state.is_verified(&tampered.id).unwrap());
+        assert_eq!(
+            relay
+                .inner
+                .state
+                .database()
+                .check_id(&tampered.id)
+                .await
+                .unwrap(),
+            DatabaseEventStatus::NotExistent
+        );
+
+        let verified = policy.verified.lock().unwrap();
+
+        // The events are admitted before being verified
+        for event in events.iter() {
+            assert_eq!(verified.get(&event.id), Some(&false));
+        }
+        assert_eq!(verified.get(&tampered.id), Some(&false));
+
+        // The new valid events are verified after being admitted
+        for event in events.iter().skip(2) {
+            assert!(relay.inner.state.is_verified(&event.id).unwrap());
+        }
+
+        // The already saved event was not verified again
+        assert!(!relay.inner.state.is_verified(&events[1].id).unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_batch_verification_after_checks() {
+        let keys = Keys::generate();
+        let subscription_id = SubscriptionId::new("test");
+
+        let mut state = SharedState::default();
+        state.admit_policy = Some(Arc::new(RejectContentTestPolicy {
+            content: String::from("rejected"),
+        }));
+
+        let url = RelayUrl::parse("ws://localhost:8080").unwrap();
+        let relay = Relay::new(
+            url,
+            state,
+            RelayOptions::default().verify_subscriptions(true),
+        );
+        relay
+            .inner
+            .update_subscription(
+                subscription_id.clone(),
+                Filter::new().kind(Kind::TextNote),
+                false,
+            )
+            .await;
+
+        let event_msg = |subscription_id: &SubscriptionId, event: &Event| {
+            RelayMessage::event(subscription_id.clone(), event.clone()).as_json()
+        };
+
+        // Valid events, to verify in batch
+        let valid: Vec<Event> = (0..10)
+            .map(|i| {
+                EventBuilder::text_note(i.to_string())
+                    .sign_with_keys(&keys)
+                    .unwrap()
+            })
+            .collect();
+        let mut msgs: Vec<String> = valid
+            .iter()
+            .map(|event| event_msg(&subscription_id, event))
+            .collect();
+
+        // Events that don't pass the checks
+        let not_matching = EventBuilder::new(Kind::Reaction, "+")
+            .sign_with_keys(&keys)
+            .unwrap();
+        let rejected = EventBuilder::text_note("rejected")
+            .sign_with_keys(&keys)
+            .unwrap();
+        let expired = EventBuilder::text_note("expired")
+            .tag(Tag::expiration(Timestamp::from_secs(1)))
+            .sign_with_keys(&keys)
+            .unwrap();
+        let unknown_subscription = EventBuilder::text_note("unknown subscription")
+            .sign_with_keys(&keys)
+            .unwrap();
+        msgs.push(event_msg(&subscription_id, &not_matching));
+        msgs.push(event_msg(&subscription_id, &rejected));
+        msgs.push(event_msg(&subscription_id, &expired));
+        msgs.push(event_msg(
+            &SubscriptionId::new("other"),
+            &unknown_subscription,
+        ));
+
+        let (ingester_tx, _ingester_rx) = mpsc::unbounded_channel();
+        relay.inner.handle_relay_messages(msgs, &ingester_tx).await;
+
+        // Only the events that passed the checks were verified
+        for event in valid.iter() {
+            assert!(relay.inner.state.is_verified(&event.id).unwrap());
+            assert_eq!(
+                relay
+                    .inner
+                    .state
+                    .database()
+                    .check_id(&event.id)
+                    .await
+                    .unwrap(),
+                DatabaseEventStatus::Saved
+            );
+        }
+
+        for event in [&not_matching, &rejected, &expired, &unknown_subscription] {
+            assert!(!relay.inner.state.is_verified(&event.id).unwrap());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fetch_events_batch_verification_tampered_event() {
+        fetch_events_with_tampered_event(RelayOptions::default()).await;
+    }
+
+    #[tokio::test]
+    async fn test_fetch_events_batch_verification_tampered_event_verify_subscriptions() {
+        fetch_events_with_tampered_event(RelayOptions::default().verify_subscriptions(true)).await;
+    }
+
+    #[tokio::test]
+    async fn test_count_events_with_hll() {
+        let target = EventId::all_zeros();
+
+        // More reactions than a single page of the relay sketch
+        let database = MemoryDatabase::with_opts(MemoryDatabaseOptions {
+            events: true,
+            max_events: None,
+        });
+        let mut expected = Hll::new();
+        for i in 0..1200 {
+            let keys = Keys::generate();
+            let reaction = EventBuilder::new(Kind::Reaction, "+")
+                .tag(Tag::event(target))
+                .custom_created_at(Timestamp::from_secs(1_700_000_000 + i / 10))
+                .sign_with_keys(&keys)
+                .unwrap();
+            database.save_event(&reaction).await.unwrap();
+            expected.add(8, &keys.public_key());
+        }
+
+        let builder = RelayBuilder::default().database(database);
+        let mock = LocalRelay::run(builder).await.unwrap();
+        let url = RelayUrl::parse(&mock.url()).unwrap();
+
+        let relay = new_relay(url, RelayOptions::default());
+        relay.connect();
+
+        let filter = Filter::new().kind(Kind::Reaction).event(target);
+        assert_eq!(Hll::offset(&filter), Some(8));
+        let (count, hll) = relay
+            .count_events_with_hll(filter, Duration::from_secs(5))
+            .await
+            .unwrap();
+        assert_eq!(count, 1200);
+        assert_eq!(hll, Some(expected));
+    }
+
+    #[tokio::test]
+    async fn test_count_events_with_hll_same_timestamp() {
+        let target = EventId::all_zeros();
+
+        // More reactions than a single page of the relay sketch, all in the same second
+        let database = MemoryDatabase::with_opts(MemoryDatabaseOptions {
+            events: true,
+            max_events: None,
+        });
+        let mut expected = Hll::new();
+        for _ in 0..1200 {
+            let keys = Keys::generate();
+            let reaction = EventBuilder::new(Kind::Reaction, "+")
+                .tag(Tag::event(target))
+                .custom_created_at(Timestamp::from_secs(1_700_000_000))
+                .sign_with_keys(&keys)
+                .unwrap();
+            database.save_event(&reaction).await.unwrap();
+            expected.add(8, &keys.public_key());
+        }
+
+        let builder = RelayBuilder::default().database(database);
+        let mock = LocalRelay::run(builder).await.unwrap();
+        let url = RelayUrl::parse(&mock.url()).unwrap();
+
+        let relay = new_relay(url, RelayOptions::default());
+        relay.connect();
+
+        let filter = Filter::new().kind(Kind::Reaction).event(target);
+        let (count, hll) = relay
+            .count_events_with_hll(filter, Duration::from_secs(5))
+            .await
+            .unwrap();
+        assert_eq!(count, 1200);
+        assert_eq!(hll, Some(expected));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_events_skip_deleted() {
+        // Mock relay
+        let mock = MockRelay::run().await.unwrap();
+        let url = RelayUrl::parse(&mock.url()).unwrap();
+
+        let database = MemoryDatabase::with_opts(MemoryDatabaseOptions {
+            events: true,
+            max_events: None,
+        });
+        let relay = new_relay_with_database(url, Arc::new(database), RelayOptions::default());
+        relay.connect();
+
+        let keys = Keys::generate();
+
+        // Send events
+        let event = EventBuilder::text_note("Deleted")
+            .sign_with_keys(&keys)
+            .unwrap();
+        relay.send_event(&event).await.unwrap();
+        let other = EventBuilder::text_note("Other")
+            .sign_with_keys(&keys)
+            .unwrap();
+        relay.send_event(&other).await.unwrap();
+
+        // Delete the first event only locally
+        let deletion = EventBuilder::delete(EventDeletionRequest::new().id(event.id))
+            .sign_with_keys(&keys)
+            .unwrap();
+        let database = relay.inner.state.database();
+        database.save_event(&event).await.unwrap();
+        database.save_event(&deletion).await.unwrap();
+        assert_eq!(
+            database.check_id(&event.id).await.unwrap(),
+            DatabaseEventStatus::Deleted
+        );
+
+        // The deleted event is dropped
+        let events = relay
+            .fetch_events(
+                Filter::new().kind(Kind::TextNote),
+                Duration::from_secs(5),
+                ReqExitPolicy::ExitOnEOSE,
+            )
+            .await
+            .unwrap();
+        assert_eq!(events.len(), 1);
+        assert!(events.contains(&other));
+    }
+
     #[tokio::test]
     async fn test_fetch_events_wait_for_events() {
         let (relay, _mock) = setup_event_fetching_relay(5).await;
@@ -7,6 +7,7 @@
 use std::time::Duration;
 
 use async_wsocket::ConnectionMode;
+use nostr::PublicKey;
 use tokio::sync::watch::{self, Receiver, Sender};
 
 use super::constants::{DEFAULT_NOTIFICATION_CHANNEL_SIZE, DEFAULT_RETRY_INTERVAL};
@@ -198,6 +199,7 @@ impl SubscribeAutoCloseOptions {
 #[derive(Debug, Clone, Copy, Default)]
 pub struct SubscribeOptions {
     pub(super) auto_close: Option<SubscribeAutoCloseOptions>,
+    pub(super) auth_as: Option<PublicKey>,
 }
 
 impl SubscribeOptions {
@@ -207,6 +209,15 @@ impl SubscribeOptions {
         self
     }
 
+    /// Public key to use if the relay requires NIP42 authentication for the subscription
+    ///
+    /// The signer must be added with [`SharedState::add_auth_signer`](crate::SharedState::add_auth_signer),
+    /// otherwise the default signer is used.
+    pub fn auth_as(mut self, public_key: PublicKey) -> Self {
+        self.auth_as = Some(public_key);
+        self
+    }
+
     pub(crate) fn is_auto_closing(&self) -> bool {
         self.auto_close.is_some()
     }
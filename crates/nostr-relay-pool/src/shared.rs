@@ -3,15 +3,18 @@
 // Distributed under the MIT software license
 
 use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
 use std::fmt;
 use std::hash::{Hash, Hasher};
 use std::num::NonZeroUsize;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 
+#[cfg(not(target_arch = "wasm32"))]
+use async_utility::task;
 use lru::LruCache;
 use nostr::prelude::IntoNostrSigner;
-use nostr::{EventId, NostrSigner};
+use nostr::{event, Event, EventId, NostrSigner, PublicKey};
 use nostr_database::{IntoNostrDatabase, MemoryDatabase, NostrDatabase};
 use tokio::sync::RwLock;
 
@@ -27,6 +30,7 @@ const MAX_VERIFICATION_CACHE_SIZE: usize = 128_000;
 pub enum SharedStateError {
     SignerNotConfigured,
     MutexPoisoned,
+    VerificationFailed,
 }
 
 impl std::error::Error for SharedStateError {}
@@ -36,6 +40,7 @@ impl fmt::Display for SharedStateError {
         match self {
             Self::SignerNotConfigured => f.write_str("signer not configured"),
             Self::MutexPoisoned => f.write_str("mutex poisoned"),
+            Self::VerificationFailed => f.write_str("batch verification failed"),
         }
     }
 }
@@ -45,6 +50,7 @@ pub struct SharedState {
     pub(crate) database: Arc<dyn NostrDatabase>,
     pub(crate) transport: Arc<dyn WebSocketTransport>,
     signer: Arc<RwLock<Option<Arc<dyn NostrSigner>>>>,
+    auth_signers: Arc<RwLock<HashMap<PublicKey, Arc<dyn NostrSigner>>>>,
     nip42_auto_authentication: Arc<AtomicBool>,
     verification_cache: Arc<Mutex<LruCache<u64, ()>>>,
     pub(crate) admit_policy: Option<Arc<dyn AdmitPolicy>>,
@@ -81,6 +87,7 @@ impl SharedState {
             database,
             transport,
             signer: Arc::new(RwLock::new(signer)),
+            auth_signers: Arc::new(RwLock::new(HashMap::new())),
             nip42_auto_authentication: Arc::new(AtomicBool::new(nip42_auto_authentication)),
             verification_cache: Arc::new(Mutex::new(LruCache::new(max_verification_cache_size))),
             admit_policy,
@@ -137,7 +144,73 @@ impl SharedState {
         *s = None;
     }
 
-    pub(crate) fn verified(&self, id: &EventId) -> Result<bool, SharedStateError> {
+    /// Add a signer to use for NIP42 authentication
+    ///
+    /// The auth signers are used on demand, on every relay, with the signer of the author of the event
+    /// or of the owner of the subscription that requires the authentication
+    /// (see [`SubscribeOptions::auth_as`](crate::SubscribeOptions::auth_as)).
+    /// The default signer is used for the other events and subscriptions.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/42.md>
+    pub async fn add_auth_signer(&self, public_key: PublicKey, signer: Arc<dyn NostrSigner>) {
+        let mut auth_signers = self.auth_signers.write().await;
+        auth_signers.insert(public_key, signer);
+    }
+
+    /// Remove an auth signer
+    pub async fn remove_auth_signer(&self, public_key: &PublicKey) {
+        let mut auth_signers = self.auth_signers.write().await;
+        auth_signers.remove(public_key);
+    }
+
+    /// Check if there is at least one auth signer
+    pub(crate) async fn has_auth_signers(&self) -> bool {
+        let auth_signers = self.auth_signers.read().await;
+        !auth_signers.is_empty()
+    }
+
+    /// Check if there is a signer that can be used for NIP42 authentication as `public_key`
+    pub(crate) async fn can_authenticate(&self, public_key: Option<&PublicKey>) -> bool {
+        self.find_auth_signer(public_key).await.is_some() || self.has_signer().await
+    }
+
+    /// Get the signer to use for NIP42 authentication as `public_key`
+    ///
+    /// Fallback to the default signer if no auth signer is set for the public key.
+    pub(crate) async fn auth_signer(
+        &self,
+        public_key: Option<&PublicKey>,
+    ) -> Result<Arc<dyn NostrSigner>, SharedStateError> {
+        match self.find_auth_signer(public_key).await {
+            Some(signer) => Ok(signer),
+            None => self.signer().await,
+        }
+    }
+
+    async fn find_auth_signer(
+        &self,
+        public_key: Option<&PublicKey>,
+    ) -> Option<Arc<dyn NostrSigner>> {
+        let public_key: &PublicKey = public_key?;
+        let auth_signers = self.auth_signers.read().await;
+        auth_signers.get(public_key).cloned()
+    }
+
+    /// Check if the event ID has already been successfully verified
+    pub(crate) fn is_verified(&self, id: &EventId) -> Result<bool, SharedStateError> {
+        let mut cache = self
+            .verification_cache
+            .lock()
+            .map_err(|_| SharedStateError::MutexPoisoned)?;
+
+        // Hash event ID
+        let id: u64 = hash(&id);
+
+        Ok(cache.get(&id).is_some())
+    }
+
+    /// Mark the event ID as successfully verified
+    pub(crate) fn set_verified(&self, id: &EventId) -> Result<(), SharedStateError> {
         let mut cache = self
             .verification_cache
             .lock()
@@ -146,8 +219,49 @@ impl SharedState {
         // Hash event ID
         let id: u64 = hash(&id);
 
-        // Returns `Some(T)` if the key already exists
-        Ok(cache.put(id, ()).is_some())
+        cache.put(id, ());
+
+        Ok(())
+    }
+
+    /// Verify the events in batch, across multiple threads, and cache the valid ones
+    ///
+    /// The events already verified are skipped.
+    /// The invalid events are NOT cached: they will be rejected when verified again.
+    ///
+    /// The verification runs on a blocking thread, to not block the async runtime (except on WASM).
+    pub(crate) async fn verify_batch(&self, events: Vec<Event>) -> Result<(), SharedStateError> {
+        // Skip already verified events
+        let mut unverified: Vec<Event> = Vec::with_capacity(events.len());
+        for event in events.into_iter() {
+            if !self.is_verified(&event.id)? {
+                unverified.push(event);
+            }
+        }
+
+        // Nothing to batch
+        if unverified.len() < 2 {
+            return Ok(());
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        let (unverified, results) = task::spawn_blocking(move || {
+            let results: Vec<Result<(), event::Error>> = Event::verify_batch(&unverified);
+            (unverified, results)
+        })
+        .await
+        .map_err(|_| SharedStateError::VerificationFailed)?;
+
+        #[cfg(target_arch = "wasm32")]
+        let results: Vec<Result<(), event::Error>> = Event::verify_batch(&unverified);
+
+        for (event, res) in unverified.iter().zip(results.into_iter()) {
+            if res.is_ok() {
+                self.set_verified(&event.id)?;
+            }
+        }
+
+        Ok(())
     }
 }
 
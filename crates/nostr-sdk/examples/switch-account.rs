@@ -10,43 +10,48 @@ use nostr_sdk::prelude::*;
 async fn main() -> Result<()> {
     tracing_subscriber::fmt::init();
 
-    // Account 1
     let keys1 = Keys::parse("nsec12kcgs78l06p30jz7z7h3n2x2cy99nw2z6zspjdp7qc206887mwvs95lnkx")?;
-    let client = Client::new(keys1.clone());
+    let keys2 = Keys::parse("nsec1ufnus6pju578ste3v90xd5m2decpuzpql2295m3sknqcjzyys9ls0qlc85")?;
+
+    let client = Client::default();
+
+    // Register accounts
+    client.add_account(keys1.clone()).await?;
+    client.add_account(keys2.clone()).await?;
+
+    // Account 1
+    let account1 = client.account(&keys1.public_key).await?;
+    account1.add_relay("wss://relay.damus.io").await?;
+
+    // Account 2
+    let account2 = client.account(&keys2.public_key).await?;
+    account2.add_relay("wss://nostr.oxtr.dev").await?;
 
-    client.add_relay("wss://relay.damus.io").await?;
     client.connect().await;
 
-    // Subscribe
+    // Subscribe with account 1
     let filter = Filter::new()
         .author(keys1.public_key)
         .kind(Kind::TextNote)
         .limit(10);
-    client.subscribe(filter, None).await?;
+    account1.subscribe(filter, None).await?;
 
     // Wait a little
     tokio::time::sleep(Duration::from_secs(20)).await;
 
     println!("Switching account...");
 
-    // Reset client to change account
-    client.reset().await;
-
-    // Account 2
-    let keys2 = Keys::parse("nsec1ufnus6pju578ste3v90xd5m2decpuzpql2295m3sknqcjzyys9ls0qlc85")?;
-    client.set_signer(keys2.clone()).await;
-
-    client.add_relay("wss://nostr.oxtr.dev").await?;
-    client.connect().await;
+    // Switch the default signer: the subscriptions of account 1 are kept
+    client.switch_account(&keys2.public_key).await?;
 
     println!("Account switched");
 
-    // Subscribe
+    // Subscribe with account 2
     let filter = Filter::new()
         .author(keys2.public_key)
         .kind(Kind::TextNote)
         .limit(5);
-    client.subscribe(filter, None).await?;
+    account2.subscribe(filter, None).await?;
 
     client
         .handle_notifications(|notification| async move {
@@ -0,0 +1,443 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2025 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Client accounts
+//!
+//! Register multiple signers in the same [`Client`] and use them without tearing down the subscriptions of the others.
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use nostr::prelude::*;
+use nostr_relay_pool::prelude::*;
+
+use super::{Client, Error};
+
+/// Account state, stored in the client
+#[derive(Debug, Clone)]
+pub(super) struct AccountState {
+    pub(super) signer: Arc<dyn NostrSigner>,
+    /// Relays used by the account
+    pub(super) relays: HashSet<RelayUrl>,
+    /// Subscriptions opened by the account
+    pub(super) subscriptions: HashSet<SubscriptionId>,
+}
+
+impl AccountState {
+    pub(super) fn new(signer: Arc<dyn NostrSigner>) -> Self {
+        Self {
+            signer,
+            relays: HashSet::new(),
+            subscriptions: HashSet::new(),
+        }
+    }
+}
+
+/// Account handle
+///
+/// Scoped access to the client for a registered account: events are signed with the account signer,
+/// and subscriptions are tracked per account.
+///
+/// Construct it with [`Client::account`].
+#[derive(Debug, Clone)]
+pub struct Account {
+    client: Client,
+    public_key: PublicKey,
+    signer: Arc<dyn NostrSigner>,
+}
+
+impl Account {
+    #[inline]
+    pub(super) fn new(client: Client, public_key: PublicKey, signer: Arc<dyn NostrSigner>) -> Self {
+        Self {
+            client,
+            public_key,
+            signer,
+        }
+    }
+
+    /// Get account public key
+    #[inline]
+    pub fn public_key(&self) -> PublicKey {
+        self.public_key
+    }
+
+    /// Get account signer
+    #[inline]
+    pub fn signer(&self) -> Arc<dyn NostrSigner> {
+        self.signer.clone()
+    }
+
+    async fn state<F, R>(&self, func: F) -> Result<R, Error>
+    where
+        F: FnOnce(&mut AccountState) -> R,
+    {
+        let mut accounts = self.client.accounts.write().await;
+        let state: &mut AccountState = accounts
+            .get_mut(&self.public_key)
+            .ok_or(Error::AccountNotFound)?;
+        Ok(func(state))
+    }
+
+    /// Get the relays used by the account
+    pub async fn relays(&self) -> Result<HashSet<RelayUrl>, Error> {
+        self.state(|state| state.relays.clone()).await
+    }
+
+    /// Add a relay to the account
+    ///
+    /// The relay is added to the client, if not already added.
+    ///
+    /// If the account has at least one relay, the events and the subscriptions of the account
+    /// are sent only to its relays, otherwise to the client ones.
+    ///
+    /// Return `false` if the relay was already added to the account.
+    pub async fn add_relay<U>(&self, url: U) -> Result<bool, Error>
+    where
+        U: TryIntoUrl,
+        pool::Error: From<<U as TryIntoUrl>::Err>,
+    {
+        let url: RelayUrl = url.try_into_url().map_err(pool::Error::from)?;
+        self.add_relay_url(url).await
+    }
+
+    async fn add_relay_url(&self, url: RelayUrl) -> Result<bool, Error> {
+        // Add relay to the client
+        self.client.add_relay(&url).await?;
+
+        self.state(|state| state.relays.insert(url)).await
+    }
+
+    /// Remove a relay from the account
+    ///
+    /// The relay is NOT removed from the client.
+    pub async fn remove_relay<U>(&self, url: U) -> Result<(), Error>
+    where
+        U: TryIntoUrl,
+        pool::Error: From<<U as TryIntoUrl>::Err>,
+    {
+        let url: RelayUrl = url.try_into_url().map_err(pool::Error::from)?;
+
+        self.state(|state| {
+            state.relays.remove(&url);
+        })
+        .await
+    }
+
+    /// Build and sign [`Event`] with the account signer
+    #[inline]
+    pub async fn sign_event_builder(&self, builder: EventBuilder) -> Result<Event, Error> {
+        Ok(builder.sign(&self.signer).await?)
+    }
+
+    /// Send event to the account relays
+    ///
+    /// If the account has no relays, check [`Client::send_event`].
+    pub async fn send_event(&self, event: &Event) -> Result<Output<EventId>, Error> {
+        let relays: HashSet<RelayUrl> = self.relays().await?;
+
+        if relays.is_empty() {
+            self.client.send_event(event).await
+        } else {
+            self.client.send_event_to(relays, event).await
+        }
+    }
+
+    /// Take an [`EventBuilder`], sign it with the account signer and send to the account relays
+    ///
+    /// Check [`Account::send_event`] from more details.
+    #[inline]
+    pub async fn send_event_builder(
+        &self,
+        builder: EventBuilder,
+    ) -> Result<Output<EventId>, Error> {
+        let event: Event = self.sign_event_builder(builder).await?;
+        self.send_event(&event).await
+    }
+
+    /// Get the account subscriptions
+    pub async fn subscriptions(&self) -> Result<HashSet<SubscriptionId>, Error> {
+        self.state(|state| state.subscriptions.clone()).await
+    }
+
+    /// Subscribe to filter with the account relays
+    ///
+    /// If the account has no relays, check [`Client::subscribe`].
+    ///
+    /// The subscription is tracked by the account (auto-closing subscriptions excluded):
+    /// use [`Account::unsubscribe_all`] to close only the subscriptions of this account.
+    ///
+    /// If a relay requires the authentication for the subscription, the account signer is used (NIP42).
+    pub async fn subscribe(
+        &self,
+        filter: Filter,
+        opts: Option<SubscribeAutoCloseOptions>,
+    ) -> Result<Output<SubscriptionId>, Error> {
+        let relays: HashSet<RelayUrl> = self.relays().await?;
+        let is_auto_closing: bool = opts.is_some();
+
+        let id: SubscriptionId = SubscriptionId::generate();
+        let opts: SubscribeOptions = SubscribeOptions::default()
+            .close_on(opts)
+            .auth_as(self.public_key);
+
+        let output: Output<()> = if relays.is_empty() {
+            self.client
+                .subscribe_with_id_and_opts(id.clone(), filter, opts)
+                .await?
+        } else {
+            self.client
+                .pool
+                .subscribe_with_id_to(relays, id.clone(), filter, opts)
+                .await?
+        };
+
+        if !is_auto_closing {
+            let id: SubscriptionId = id.clone();
+            self.state(|state| state.subscriptions.insert(id)).await?;
+        }
+
+        Ok(Output {
+            val: id,
+            success: output.success,
+            failed: output.failed,
+        })
+    }
+
+    /// Subscribe to the gift wraps sent to the account (inbox)
+    ///
+    /// Check [`Account::subscribe`] from more details.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/59.md>
+    #[inline]
+    pub async fn subscribe_gift_wraps(
+        &self,
+        opts: Option<SubscribeAutoCloseOptions>,
+    ) -> Result<Output<SubscriptionId>, Error> {
+        let filter: Filter = Filter::new().kind(Kind::GiftWrap).pubkey(self.public_key);
+        self.subscribe(filter, opts).await
+    }
+
+    /// Unsubscribe
+    pub async fn unsubscribe(&self, id: &SubscriptionId) -> Result<(), Error> {
+        self.state(|state| state.subscriptions.remove(id)).await?;
+        self.client.unsubscribe(id).await;
+        Ok(())
+    }
+
+    /// Unsubscribe from all the account subscriptions
+    ///
+    /// The subscriptions of the other accounts are kept.
+    pub async fn unsubscribe_all(&self) -> Result<(), Error> {
+        let ids = self
+            .state(|state| std::mem::take(&mut state.subscriptions))
+            .await?;
+
+        for id in ids.iter() {
+            self.client.unsubscribe(id).await;
+        }
+
+        Ok(())
+    }
+
+    /// Send a private direct message, signed with the account signer
+    ///
+    /// Check [`Client::send_private_msg`] from more details.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/17.md>
+    #[cfg(feature = "nip59")]
+    pub async fn send_private_msg<S, I>(
+        &self,
+        receiver: PublicKey,
+        message: S,
+        rumor_extra_tags: I,
+    ) -> Result<Output<EventId>, Error>
+    where
+        S: Into<String>,
+        I: IntoIterator<Item = Tag>,
+    {
+        let event: Event =
+            EventBuilder::private_msg(&self.signer, receiver, message, rumor_extra_tags).await?;
+
+        // NOT gossip, send to the account relays
+        if !self.client.opts.gossip {
+            return self.send_event(&event).await;
+        }
+
+        self.client.gossip_send_event(&event, true).await
+    }
+
+    /// Unwrap Gift Wrap event with the account signer
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/59.md>
+    #[inline]
+    #[cfg(feature = "nip59")]
+    pub async fn unwrap_gift_wrap(&self, gift_wrap: &Event) -> Result<UnwrappedGift, Error> {
+        Ok(UnwrappedGift::from_gift_wrap(&self.signer, gift_wrap).await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use nostr_relay_builder::prelude::*;
+
+    use super::{Error, *};
+
+    #[tokio::test]
+    async fn test_accounts() {
+        let mock = MockRelay::run().await.unwrap();
+        let url = RelayUrl::parse(&mock.url()).unwrap();
+
+        let alice = Keys::generate();
+        let bob = Keys::generate();
+
+        let client = Client::default();
+        client.add_relay(&url).await.unwrap();
+        client.connect().await;
+
+        assert_eq!(
+            client.add_account(alice.clone()).await.unwrap(),
+            alice.public_key()
+        );
+        client.add_account(bob.clone()).await.unwrap();
+        assert_eq!(client.accounts().await.len(), 2);
+
+        let alice_account = client.account(&alice.public_key()).await.unwrap();
+        let bob_account = client.account(&bob.public_key()).await.unwrap();
+
+        // Per-account subscriptions
+        let alice_sub = alice_account.subscribe_gift_wraps(None).await.unwrap().val;
+        let bob_sub = bob_account
+            .subscribe(Filter::new().kind(Kind::TextNote), None)
+            .await
+            .unwrap()
+            .val;
+        assert_eq!(
+            client.subscription_account(&alice_sub).await,
+            Some(alice.public_key())
+        );
+
+        // Publish with the account signer
+        let event = bob_account
+            .sign_event_builder(EventBuilder::text_note("Hello"))
+            .await
+            .unwrap();
+        assert_eq!(event.pubkey, bob.public_key());
+        let output = bob_account.send_event(&event).await.unwrap();
+        assert!(output.success.contains(&url));
+
+        // Switching account doesn't tear down the subscriptions
+        client.switch_account(&alice.public_key()).await.unwrap();
+        assert_eq!(client.public_key().await.unwrap(), alice.public_key());
+        assert_eq!(client.subscriptions().await.len(), 2);
+
+        // Unsubscribe only the bob subscriptions
+        bob_account.unsubscribe_all().await.unwrap();
+        let subscriptions = client.subscriptions().await;
+        assert!(subscriptions.contains_key(&alice_sub));
+        assert!(!subscriptions.contains_key(&bob_sub));
+
+        // Remove account
+        assert!(client.remove_account(&alice.public_key()).await);
+        assert!(client.subscriptions().await.is_empty());
+        assert!(matches!(
+            client.account(&alice.public_key()).await,
+            Err(Error::AccountNotFound)
+        ));
+        assert!(matches!(
+            alice_account.subscriptions().await,
+            Err(Error::AccountNotFound)
+        ));
+
+        client.shutdown().await;
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+
+    #[tokio::test]
+    async fn test_account_relays_auth() {
+        let opts = RelayBuilderNip42 {
+            mode: RelayBuilderNip42Mode::Write,
+        };
+        let builder = RelayBuilder::default().nip42(opts);
+        let mock = LocalRelay::run(builder).await.unwrap();
+        let url = RelayUrl::parse(&mock.url()).unwrap();
+
+        // No default signer
+        let client = Client::default();
+
+        let keys = Keys::generate();
+        client.add_account(keys.clone()).await.unwrap();
+
+        let account = client.account(&keys.public_key()).await.unwrap();
+        assert!(account.add_relay(&url).await.unwrap());
+        assert!(!account.add_relay(&url).await.unwrap());
+
+        client.connect().await;
+
+        // Authenticated with the account signer
+        let output = account
+            .send_event_builder(EventBuilder::text_note("Test"))
+            .await
+            .unwrap();
+        assert!(output.success.contains(&url));
+    }
+
+    #[tokio::test]
+    async fn test_account_client_relays_auth() {
+        let opts = RelayBuilderNip42 {
+            mode: RelayBuilderNip42Mode::Read,
+        };
+        let builder = RelayBuilder::default().nip42(opts);
+        let mock = LocalRelay::run(builder).await.unwrap();
+        let url = RelayUrl::parse(&mock.url()).unwrap();
+
+        // No default signer and no account relays: the client relays are used
+        let client = Client::default();
+        client.add_relay(&url).await.unwrap();
+        client.connect().await;
+
+        let keys = Keys::generate();
+        client.add_account(keys.clone()).await.unwrap();
+        let account = client.account(&keys.public_key()).await.unwrap();
+        assert!(account.relays().await.unwrap().is_empty());
+
+        let output = account
+            .send_event_builder(EventBuilder::text_note("Test"))
+            .await
+            .unwrap();
+        assert!(output.success.contains(&url));
+
+        let mut notifications = client.notifications();
+
+        // The relay requires the authentication for the subscription: the account signer is used
+        let id = account
+            .subscribe(Filter::new().kind(Kind::TextNote), None)
+            .await
+            .unwrap()
+            .val;
+
+        let event_id = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                if let Ok(RelayPoolNotification::Message {
+                    message:
+                        RelayMessage::Event {
+                            subscription_id,
+                            event,
+                        },
+                    ..
+                }) = notifications.recv().await
+                {
+                    if subscription_id.as_ref() == &id {
+                        return event.id;
+                    }
+                }
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(event_id, output.val);
+    }
+}
@@ -27,6 +27,14 @@ pub enum Error {
     Json(serde_json::Error),
     /// Shared state error
     SharedState(SharedStateError),
+    /// NIP28 error
+    NIP28(nip28::Error),
+    /// NIP75 error
+    NIP75(nip75::Error),
+    /// NIP78 error
+    NIP78(nip78::Error),
+    /// NIP88 error
+    NIP88(nip88::Error),
     /// NIP59
     #[cfg(feature = "nip59")]
     NIP59(nip59::Error),
@@ -34,6 +42,8 @@ pub enum Error {
     GossipFiltersEmpty,
     /// Private message (NIP17) relays not found
     PrivateMsgRelaysNotFound,
+    /// Account not found
+    AccountNotFound,
 }
 
 impl std::error::Error for Error {}
@@ -48,12 +58,17 @@ impl fmt::Display for Error {
             Self::EventBuilder(e) => e.fmt(f),
             Self::Json(e) => e.fmt(f),
             Self::SharedState(e) => e.fmt(f),
+            Self::NIP28(e) => e.fmt(f),
+            Self::NIP75(e) => e.fmt(f),
+            Self::NIP78(e) => e.fmt(f),
+            Self::NIP88(e) => e.fmt(f),
             #[cfg(feature = "nip59")]
             Self::NIP59(e) => e.fmt(f),
             Self::GossipFiltersEmpty => {
                 f.write_str("gossip broken down filters are empty")
             }
             Self::PrivateMsgRelaysNotFound => f.write_str("Private message relays not found. The user is not ready to receive private messages."),
+            Self::AccountNotFound => f.write_str("account not found"),
         }
     }
 }
@@ -100,6 +115,30 @@ impl From<SharedStateError> for Error {
     }
 }
 
+impl From<nip28::Error> for Error {
+    fn from(e: nip28::Error) -> Self {
+        Self::NIP28(e)
+    }
+}
+
+impl From<nip75::Error> for Error {
+    fn from(e: nip75::Error) -> Self {
+        Self::NIP75(e)
+    }
+}
+
+impl From<nip78::Error> for Error {
+    fn from(e: nip78::Error) -> Self {
+        Self::NIP78(e)
+    }
+}
+
+impl From<nip88::Error> for Error {
+    fn from(e: nip88::Error) -> Self {
+        Self::NIP88(e)
+    }
+}
+
 #[cfg(feature = "nip59")]
 impl From<nip59::Error> for Error {
     fn from(e: nip59::Error) -> Self {
@@ -0,0 +1,53 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2025 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Account lookup (NIP-06)
+
+use std::time::Duration;
+
+use nostr::prelude::*;
+use nostr_database::Events;
+
+use super::Client;
+
+/// Account lookup through the relays of the [`Client`]
+///
+/// Used for the NIP-06 account discovery (see [`Seed::discover`]).
+#[derive(Debug, Clone)]
+pub struct ClientAccountLookup {
+    client: Client,
+    timeout: Duration,
+}
+
+impl ClientAccountLookup {
+    /// New account lookup
+    ///
+    /// The `timeout` is applied to every fetch from relays (see [`Client::fetch_events`]).
+    #[inline]
+    pub fn new(client: Client, timeout: Duration) -> Self {
+        Self { client, timeout }
+    }
+}
+
+impl AccountLookup for ClientAccountLookup {
+    /// Fetch the events from relays (see [`Client::fetch_events`])
+    fn has_events<'a>(
+        &'a self,
+        public_key: &'a PublicKey,
+        kinds: &'a [Kind],
+    ) -> BoxedFuture<'a, Result<bool, nip06::Error>> {
+        Box::pin(async move {
+            let filter: Filter = Filter::new()
+                .author(*public_key)
+                .kinds(kinds.iter().copied())
+                .limit(1);
+            let events: Events = self
+                .client
+                .fetch_events(filter, self.timeout)
+                .await
+                .map_err(|e| nip06::Error::Lookup(e.to_string()))?;
+            Ok(!events.is_empty())
+        })
+    }
+}
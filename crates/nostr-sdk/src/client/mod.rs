@@ -4,7 +4,7 @@
 
 //! Client
 
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::future::Future;
 use std::iter;
 use std::sync::Arc;
@@ -13,14 +13,21 @@ use std::time::Duration;
 use nostr::prelude::*;
 use nostr_database::prelude::*;
 use nostr_relay_pool::prelude::*;
-use tokio::sync::broadcast;
+use tokio::sync::{broadcast, RwLock};
 
+pub mod account;
 pub mod builder;
 mod error;
+#[cfg(feature = "nip06")]
+pub mod lookup;
 pub mod options;
 
+pub use self::account::Account;
+use self::account::AccountState;
 pub use self::builder::ClientBuilder;
 pub use self::error::Error;
+#[cfg(feature = "nip06")]
+pub use self::lookup::ClientAccountLookup;
 pub use self::options::{ClientOptions, SleepWhenIdle};
 #[cfg(not(target_arch = "wasm32"))]
 pub use self::options::{Connection, ConnectionTarget};
@@ -31,6 +38,7 @@ use crate::gossip::{BrokenDownFilters, Gossip};
 pub struct Client {
     pool: RelayPool,
     gossip: Gossip,
+    accounts: Arc<RwLock<HashMap<PublicKey, AccountState>>>,
     opts: ClientOptions,
 }
 
@@ -93,6 +101,7 @@ impl Client {
         Self {
             pool: pool_builder.build(),
             gossip: Gossip::new(),
+            accounts: Arc::new(RwLock::new(HashMap::new())),
             opts: builder.opts,
         }
     }
@@ -147,6 +156,110 @@ impl Client {
         Ok(self.signer().await?.get_public_key().await?)
     }
 
+    /// Register an account
+    ///
+    /// The account is NOT set as the default signer: use [`Client::switch_account`] or [`Client::account`].
+    ///
+    /// The account signer is used for the NIP42 authentication on every relay,
+    /// when required for the events of the account or for its subscriptions.
+    ///
+    /// If the account is already registered, the signer is replaced.
+    ///
+    /// Returns the public key of the account.
+    pub async fn add_account<T>(&self, signer: T) -> Result<PublicKey, Error>
+    where
+        T: IntoNostrSigner,
+    {
+        let signer: Arc<dyn NostrSigner> = signer.into_nostr_signer();
+        let public_key: PublicKey = signer.get_public_key().await?;
+
+        let mut accounts = self.accounts.write().await;
+
+        // Use the account signer for authentication, on every relay
+        self.pool
+            .state()
+            .add_auth_signer(public_key, signer.clone())
+            .await;
+
+        match accounts.get_mut(&public_key) {
+            Some(state) => {
+                state.signer = signer;
+            }
+            None => {
+                accounts.insert(public_key, AccountState::new(signer));
+            }
+        }
+
+        Ok(public_key)
+    }
+
+    /// Remove an account
+    ///
+    /// Close the account subscriptions and stop using its signer for NIP42 authentication.
+    /// The default signer is NOT unset, also if it's the one of this account.
+    ///
+    /// Returns `false` if the account wasn't registered.
+    pub async fn remove_account(&self, public_key: &PublicKey) -> bool {
+        let mut accounts = self.accounts.write().await;
+        match accounts.remove(public_key) {
+            Some(state) => {
+                for id in state.subscriptions.iter() {
+                    self.pool.unsubscribe(id).await;
+                }
+
+                self.pool.state().remove_auth_signer(public_key).await;
+
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Get the public keys of the registered accounts
+    pub async fn accounts(&self) -> Vec<PublicKey> {
+        let accounts = self.accounts.read().await;
+        accounts.keys().copied().collect()
+    }
+
+    /// Get a registered account handle
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::AccountNotFound`] if the account isn't registered.
+    pub async fn account(&self, public_key: &PublicKey) -> Result<Account, Error> {
+        let accounts = self.accounts.read().await;
+        let state: &AccountState = accounts.get(public_key).ok_or(Error::AccountNotFound)?;
+        Ok(Account::new(
+            self.clone(),
+            *public_key,
+            state.signer.clone(),
+        ))
+    }
+
+    /// Set a registered account as the default signer
+    ///
+    /// Unlike [`Client::reset`], the subscriptions and the relays are kept.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::AccountNotFound`] if the account isn't registered.
+    pub async fn switch_account(&self, public_key: &PublicKey) -> Result<(), Error> {
+        let account: Account = self.account(public_key).await?;
+        self.set_signer(account.signer()).await;
+        Ok(())
+    }
+
+    /// Get the account that opened a subscription
+    ///
+    /// Useful to route the [`RelayPoolNotification`] to the right account.
+    pub async fn subscription_account(&self, id: &SubscriptionId) -> Option<PublicKey> {
+        let accounts = self.accounts.read().await;
+        accounts
+            .iter()
+            .find(|(_, state)| state.subscriptions.contains(id))
+            .map(|(public_key, _)| *public_key)
+    }
+
     /// Get [`RelayPool`]
     #[inline]
     pub fn pool(&self) -> &RelayPool {
@@ -173,6 +286,7 @@ impl Client {
     /// * unsubscribe from all subscriptions
     /// * disconnect and force remove all relays
     /// * unset the signer
+    /// * remove all the accounts
     ///
     /// This method will NOT:
     /// * reset [`ClientOptions`]
@@ -182,6 +296,10 @@ impl Client {
         self.unsubscribe_all().await;
         self.force_remove_all_relays().await;
         self.unset_signer().await;
+
+        for public_key in self.accounts().await.iter() {
+            self.remove_account(public_key).await;
+        }
     }
 
     /// Completely shutdown client
@@ -582,7 +700,15 @@ impl Client {
         opts: Option<SubscribeAutoCloseOptions>,
     ) -> Result<Output<()>, Error> {
         let opts: SubscribeOptions = SubscribeOptions::default().close_on(opts);
+        self.subscribe_with_id_and_opts(id, filter, opts).await
+    }
 
+    pub(super) async fn subscribe_with_id_and_opts(
+        &self,
+        id: SubscriptionId,
+        filter: Filter,
+        opts: SubscribeOptions,
+    ) -> Result<Output<()>, Error> {
         if self.opts.gossip {
             self.gossip_subscribe(id, filter, opts).await
         } else {
@@ -988,10 +1114,21 @@ impl Client {
 
     /// Build, sign and return [`Event`]
     ///
+    /// If the POW difficulty is set (see [`EventBuilder::pow`]), the event is mined with a [`PowMiner`],
+    /// without blocking the async runtime.
+    ///
     /// This method requires a [`NostrSigner`].
     pub async fn sign_event_builder(&self, builder: EventBuilder) -> Result<Event, Error> {
         let signer = self.signer().await?;
-        Ok(builder.sign(&signer).await?)
+
+        match builder.pow {
+            #[cfg(not(target_arch = "wasm32"))]
+            Some(difficulty) => {
+                let miner: PowMiner = PowMiner::new(difficulty);
+                Ok(builder.sign_with_miner(&signer, &miner).await?)
+            }
+            _ => Ok(builder.sign(&signer).await?),
+        }
     }
 
     /// Take an [`EventBuilder`], sign it by using the [`NostrSigner`] and broadcast to relays.
@@ -1168,6 +1305,328 @@ impl Client {
         Ok(contacts)
     }
 
+    /// Fetch a public chat channel
+    ///
+    /// Fetch the channel creation event (kind `40`) and its metadata updates (kind `41`).
+    /// Only the updates published by the channel creator are applied.
+    ///
+    /// Returns [`None`] if the channel creation event has not been found.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/28.md>
+    pub async fn fetch_channel(
+        &self,
+        channel_id: EventId,
+        timeout: Duration,
+    ) -> Result<Option<Channel>, Error> {
+        let filter: Filter = Filter::new().id(channel_id).kind(Kind::ChannelCreation);
+        let events: Events = self.fetch_events(filter, timeout).await?;
+
+        let creation: Event = match events.first_owned() {
+            Some(event) => event,
+            None => return Ok(None),
+        };
+
+        // Request only the updates published by the creator
+        let filter: Filter = Filter::new()
+            .author(creation.pubkey)
+            .kind(Kind::ChannelMetadata)
+            .event(channel_id);
+        let updates: Events = self.fetch_events(filter, timeout).await?;
+
+        Ok(Some(Channel::from_events(&creation, updates.iter())?))
+    }
+
+    /// Fetch the messages of a public chat channel
+    ///
+    /// Malformed messages, messages hidden by the user (kind `43`) and messages published by users muted by the user (kind `44`)
+    /// are discarded.
+    ///
+    /// This method requires a [`NostrSigner`].
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/28.md>
+    pub async fn fetch_channel_messages(
+        &self,
+        channel_id: EventId,
+        timeout: Duration,
+    ) -> Result<Vec<Event>, Error> {
+        let public_key: PublicKey = self.public_key().await?;
+
+        // Fetch messages
+        let filter: Filter = Filter::new().kind(Kind::ChannelMessage).event(channel_id);
+        let messages: Events = self.fetch_events(filter, timeout).await?;
+
+        // Fetch the user's hide message and mute user events
+        let filter: Filter = Filter::new()
+            .author(public_key)
+            .kinds([Kind::ChannelHideMessage, Kind::ChannelMuteUser]);
+        let events: Events = self.fetch_events(filter, timeout).await?;
+
+        // Skip the malformed messages and the ones of other channels
+        let messages = messages.into_iter().filter(|message| {
+            ChannelMessage::from_event(message).is_ok_and(|m| m.channel_id == channel_id)
+        });
+
+        let moderation: ChannelModeration =
+            ChannelModeration::from_events(&public_key, events.iter());
+        Ok(moderation.filter(messages))
+    }
+
+    async fn fetch_latest_app_data_event(
+        &self,
+        public_key: PublicKey,
+        identifier: &str,
+        timeout: Duration,
+    ) -> Result<Option<Event>, Error> {
+        let filter: Filter = Filter::new()
+            .author(public_key)
+            .kind(Kind::ApplicationSpecificData)
+            .identifier(identifier);
+        let events: Events = self.fetch_combined_events(filter, timeout).await?;
+        Ok(nip78::latest_version(events.iter()).cloned())
+    }
+
+    /// Fetch the latest version of the user's app data
+    ///
+    /// Query both the database and the relays, then decrypt the newest version.
+    ///
+    /// This method requires a [`NostrSigner`].
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/78.md>
+    pub async fn fetch_app_data(
+        &self,
+        identifier: &str,
+        timeout: Duration,
+    ) -> Result<Option<AppDataVersion>, Error> {
+        let signer = self.signer().await?;
+        let public_key: PublicKey = signer.get_public_key().await?;
+
+        match self
+            .fetch_latest_app_data_event(public_key, identifier, timeout)
+            .await?
+        {
+            Some(event) => Ok(Some(AppDataVersion::decrypt(&signer, &event).await?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Save the user's app data
+    ///
+    /// `base` is the event ID of the version the edit is based on ([`None`] if it's new app data).
+    /// If another version has been published in the meantime, the edit is rejected with [`nip78::Error::Conflict`].
+    /// To merge the concurrent edits, check [`Client::update_app_data`].
+    ///
+    /// This method requires a [`NostrSigner`].
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/78.md>
+    pub async fn save_app_data(
+        &self,
+        data: AppData,
+        base: Option<EventId>,
+        timeout: Duration,
+    ) -> Result<Output<EventId>, Error> {
+        let signer = self.signer().await?;
+        let public_key: PublicKey = signer.get_public_key().await?;
+
+        let latest: Option<Event> = self
+            .fetch_latest_app_data_event(public_key, &data.identifier, timeout)
+            .await?;
+
+        nip78::check_version(base.as_ref(), latest.as_ref())?;
+
+        let builder: EventBuilder = EventBuilder::app_data(&signer, data, latest.as_ref()).await?;
+        self.send_event_builder(builder).await
+    }
+
+    /// Update the user's app data
+    ///
+    /// Fetch the latest version and pass its content (if any) to `merge`, that returns the new content.
+    /// The new version is saved using the fetched one as base (see [`Client::save_app_data`]),
+    /// so it's rejected if another edit has been published in the meantime.
+    ///
+    /// This method requires a [`NostrSigner`].
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/78.md>
+    pub async fn update_app_data<F>(
+        &self,
+        identifier: &str,
+        timeout: Duration,
+        merge: F,
+    ) -> Result<Output<EventId>, Error>
+    where
+        F: FnOnce(Option<&str>) -> String,
+    {
+        let current: Option<AppDataVersion> = self.fetch_app_data(identifier, timeout).await?;
+
+        let content: String = merge(current.as_ref().map(|v| v.data.content.as_str()));
+        let base: Option<EventId> = current.map(|v| v.id);
+
+        self.save_app_data(AppData::new(identifier, content), base, timeout)
+            .await
+    }
+
+    /// Fetch the handlers recommended by the user's follows for a kind
+    ///
+    /// Fetch the handler recommendations (kind `31989`) published by the contacts
+    /// and the related handler information events (kind `31990`).
+    /// Handlers that don't support the kind are discarded.
+    ///
+    /// Returns the handler information events, sorted by number of follows recommending them (descending).
+    ///
+    /// This method requires a [`NostrSigner`].
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/89.md>
+    pub async fn fetch_recommended_handlers(
+        &self,
+        kind: Kind,
+        timeout: Duration,
+    ) -> Result<Vec<Event>, Error> {
+        let follows: Vec<PublicKey> = self.get_contact_list_public_keys(timeout).await?;
+
+        if follows.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // Fetch the recommendations of the follows
+        let filter: Filter = Filter::new()
+            .authors(follows)
+            .kind(Kind::HandlerRecommendation)
+            .identifier(kind.to_string());
+        let events: Events = self.fetch_events(filter, timeout).await?;
+
+        // Collect who recommended each handler
+        let mut recommendations: HashMap<Coordinate, HashSet<PublicKey>> = HashMap::new();
+        for event in events.iter() {
+            // Skip invalid recommendations
+            let recommendation: HandlerRecommendation =
+                match HandlerRecommendation::from_event(event) {
+                    Ok(recommendation) => recommendation,
+                    Err(..) => continue,
+                };
+
+            for handler in recommendation.handlers.into_iter() {
+                recommendations
+                    .entry(handler.coordinate)
+                    .or_default()
+                    .insert(event.pubkey);
+            }
+        }
+
+        if recommendations.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // Fetch the handlers information
+        let filter: Filter = Filter::new()
+            .kind(Kind::HandlerInformation)
+            .authors(recommendations.keys().map(|c| c.public_key))
+            .identifiers(recommendations.keys().map(|c| c.identifier.clone()));
+        let events: Events = self.fetch_events(filter, timeout).await?;
+
+        let mut handlers: Vec<(usize, Event)> = events
+            .into_iter()
+            .filter_map(|event| {
+                let info: HandlerInformation = HandlerInformation::from_event(&event).ok()?;
+
+                if !info.supports_kind(&kind) {
+                    return None;
+                }
+
+                // Authors and identifiers are requested separately: check that the handler has been actually recommended
+                let coordinate: Coordinate =
+                    Coordinate::new(Kind::HandlerInformation, event.pubkey)
+                        .identifier(info.identifier);
+                let recommended_by: usize = recommendations.get(&coordinate)?.len();
+
+                Some((recommended_by, event))
+            })
+            .collect();
+
+        handlers.sort_by(|a, b| b.0.cmp(&a.0));
+
+        Ok(handlers.into_iter().map(|(_, event)| event).collect())
+    }
+
+    /// Fetch the progress of a zap goal
+    ///
+    /// Fetch the zap receipts referencing the goal from the relays listed in the goal
+    /// and sum the valid ones (see [`ZapGoalProgress::compute`]).
+    ///
+    /// The goal relays not already in the pool are added with the [`RelayServiceFlags::GOSSIP`] flag,
+    /// so they are NOT used for the other subscriptions and queries.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/75.md>
+    pub async fn fetch_zap_goal_progress(
+        &self,
+        goal: &Event,
+        timeout: Duration,
+    ) -> Result<ZapGoalProgress, Error> {
+        let zap_goal: ZapGoal = ZapGoal::from_event(goal)?;
+
+        // Make sure that the goal relays are in the pool (as gossip relays, since untrusted)
+        for url in zap_goal.relays.iter() {
+            if self.add_gossip_relay(url).await? {
+                self.connect_relay(url).await?;
+            }
+        }
+
+        let filter: Filter = Filter::new().kind(Kind::ZapReceipt).event(goal.id);
+        let receipts: Events = self
+            .fetch_events_from(zap_goal.relays, filter, timeout)
+            .await?;
+
+        Ok(ZapGoalProgress::compute(goal, receipts.iter())?)
+    }
+
+    /// Fetch the results of a poll
+    ///
+    /// Fetch the responses to the poll from the relays listed in the poll (or from the [`RelayServiceFlags::READ`] relays, if none)
+    /// and tally them (see [`PollResults::compute_restricted`]).
+    ///
+    /// If `voters` is set, only the responses of these public keys are counted (e.g., follows or web of trust).
+    ///
+    /// The poll relays not already in the pool are added with the [`RelayServiceFlags::GOSSIP`] flag,
+    /// so they are NOT used for the other subscriptions and queries.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/88.md>
+    pub async fn fetch_poll_results(
+        &self,
+        poll: &Event,
+        voters: Option<&BTreeSet<PublicKey>>,
+        timeout: Duration,
+    ) -> Result<PollResults, Error> {
+        let nip88_poll: Poll = Poll::from_event(poll)?;
+
+        // Make sure that the poll relays are in the pool (as gossip relays, since untrusted)
+        for url in nip88_poll.relays.iter() {
+            if self.add_gossip_relay(url).await? {
+                self.connect_relay(url).await?;
+            }
+        }
+
+        let mut filter: Filter = Filter::new().kind(Kind::PollResponse).event(poll.id);
+
+        // Responses published after the end are not counted
+        if let Some(ends_at) = nip88_poll.ends_at {
+            filter = filter.until(ends_at);
+        }
+
+        let responses: Events = if nip88_poll.relays.is_empty() {
+            self.fetch_events(filter, timeout).await?
+        } else {
+            self.fetch_events_from(nip88_poll.relays, filter, timeout)
+                .await?
+        };
+
+        match voters {
+            Some(voters) => Ok(PollResults::compute_restricted(
+                poll,
+                responses.iter(),
+                voters,
+            )?),
+            None => Ok(PollResults::compute(poll, responses.iter())?),
+        }
+    }
+
     /// Send a private direct message
     ///
     /// If `gossip` is enabled (see [`ClientOptions::gossip`]) the message will be sent to the NIP17 relays (automatically discovered).
@@ -37,5 +37,9 @@ pub use nostr_relay_pool::{
 pub mod client;
 mod gossip;
 pub mod prelude;
+pub mod signer_policy;
+pub mod wot;
 
 pub use self::client::{Client, ClientBuilder, ClientOptions};
+pub use self::signer_policy::{AuditSink, PolicySigner, SignerPolicy};
+pub use self::wot::{WebOfTrust, WebOfTrustOptions};
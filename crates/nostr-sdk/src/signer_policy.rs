@@ -0,0 +1,557 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2025 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Signer policy
+//!
+//! [`NostrSigner`] wrapper that enforces a [`SignerPolicy`] and audits every operation.
+
+use std::collections::{HashSet, VecDeque};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use nostr::prelude::*;
+// `std::time::Instant` panics on `wasm32`
+use nostr::types::time::Instant;
+
+/// Policy violation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PolicyViolation {
+    /// Kind not allowed
+    KindNotAllowed(Kind),
+    /// The `created_at` is too far from the current time
+    CreatedAtSkew {
+        /// Event timestamp
+        created_at: Timestamp,
+        /// Current timestamp
+        now: Timestamp,
+    },
+    /// Content too large
+    ContentTooLarge {
+        /// Content size
+        size: usize,
+        /// Max allowed size
+        max: usize,
+    },
+    /// Rate limit exceeded
+    RateLimited,
+}
+
+impl std::error::Error for PolicyViolation {}
+
+impl fmt::Display for PolicyViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::KindNotAllowed(kind) => write!(f, "kind {kind} not allowed"),
+            Self::CreatedAtSkew { created_at, now } => {
+                write!(
+                    f,
+                    "created_at too far from now: created_at={created_at}, now={now}"
+                )
+            }
+            Self::ContentTooLarge { size, max } => {
+                write!(f, "content too large: size={size}, max={max}")
+            }
+            Self::RateLimited => f.write_str("rate limit exceeded"),
+        }
+    }
+}
+
+/// Rate limit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RateLimit {
+    /// Max number of operations
+    pub max: usize,
+    /// Time window
+    pub period: Duration,
+}
+
+/// Signer policy
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SignerPolicy {
+    allowed_kinds: Option<HashSet<Kind>>,
+    max_created_at_skew: Option<Duration>,
+    max_content_size: Option<usize>,
+    rate_limit: Option<RateLimit>,
+}
+
+impl SignerPolicy {
+    /// New policy (allow everything)
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allow to sign only these kinds (default: all)
+    #[inline]
+    pub fn allowed_kinds<I>(mut self, kinds: I) -> Self
+    where
+        I: IntoIterator<Item = Kind>,
+    {
+        self.allowed_kinds = Some(kinds.into_iter().collect());
+        self
+    }
+
+    /// Max distance of the event `created_at` from the current time (default: none)
+    #[inline]
+    pub fn max_created_at_skew(mut self, skew: Duration) -> Self {
+        self.max_created_at_skew = Some(skew);
+        self
+    }
+
+    /// Max size, in bytes, of the content to sign or encrypt (default: none)
+    #[inline]
+    pub fn max_content_size(mut self, size: usize) -> Self {
+        self.max_content_size = Some(size);
+        self
+    }
+
+    /// Max number of operations per period (default: none)
+    ///
+    /// Every operation, except [`NostrSigner::get_public_key`], is counted.
+    #[inline]
+    pub fn rate_limit(mut self, max: usize, period: Duration) -> Self {
+        self.rate_limit = Some(RateLimit { max, period });
+        self
+    }
+
+    fn check_event(&self, unsigned: &UnsignedEvent) -> Result<(), PolicyViolation> {
+        if let Some(kinds) = &self.allowed_kinds {
+            if !kinds.contains(&unsigned.kind) {
+                return Err(PolicyViolation::KindNotAllowed(unsigned.kind));
+            }
+        }
+
+        if let Some(skew) = self.max_created_at_skew {
+            let now: Timestamp = Timestamp::now();
+            let diff: u64 = now.as_u64().abs_diff(unsigned.created_at.as_u64());
+
+            if diff > skew.as_secs() {
+                return Err(PolicyViolation::CreatedAtSkew {
+                    created_at: unsigned.created_at,
+                    now,
+                });
+            }
+        }
+
+        self.check_content(&unsigned.content)
+    }
+
+    fn check_content(&self, content: &str) -> Result<(), PolicyViolation> {
+        match self.max_content_size {
+            Some(max) if content.len() > max => Err(PolicyViolation::ContentTooLarge {
+                size: content.len(),
+                max,
+            }),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Signer operation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SignerOperation {
+    /// Sign event
+    SignEvent,
+    /// NIP04 encrypt
+    Nip04Encrypt,
+    /// NIP04 decrypt
+    Nip04Decrypt,
+    /// NIP44 encrypt
+    Nip44Encrypt,
+    /// NIP44 decrypt
+    Nip44Decrypt,
+}
+
+/// Audit outcome
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuditOutcome {
+    /// Executed by the inner signer
+    Success,
+    /// Denied by the policy
+    Denied(PolicyViolation),
+    /// Failed in the inner signer
+    Failed(String),
+}
+
+/// Audit record
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditRecord {
+    /// When the operation was requested
+    pub timestamp: Timestamp,
+    /// Operation
+    pub operation: SignerOperation,
+    /// Event kind (only for [`SignerOperation::SignEvent`])
+    pub kind: Option<Kind>,
+    /// Signed event ID (only for successful [`SignerOperation::SignEvent`])
+    pub event_id: Option<EventId>,
+    /// Counterparty public key (only for encryption and decryption)
+    pub public_key: Option<PublicKey>,
+    /// Outcome
+    pub outcome: AuditOutcome,
+}
+
+/// Audit sink
+pub trait AuditSink: fmt::Debug + Send + Sync {
+    /// Record an operation
+    fn record(&self, record: AuditRecord) -> BoxedFuture<()>;
+}
+
+/// Signer that enforces a [`SignerPolicy`] before forwarding the requests to the inner signer
+///
+/// # Example
+/// ```rust,no_run
+/// use std::time::Duration;
+///
+/// use nostr_sdk::prelude::*;
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<()> {
+/// let keys = Keys::generate();
+/// let policy = SignerPolicy::new()
+///     .allowed_kinds([Kind::TextNote, Kind::Reaction])
+///     .max_created_at_skew(Duration::from_secs(60))
+///     .rate_limit(10, Duration::from_secs(60));
+///
+/// let client = Client::new(PolicySigner::new(keys, policy));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct PolicySigner<T> {
+    signer: T,
+    policy: SignerPolicy,
+    audit: Option<Arc<dyn AuditSink>>,
+    /// Timestamps of the operations in the current rate limit window
+    operations: Mutex<VecDeque<Instant>>,
+}
+
+impl<T> PolicySigner<T>
+where
+    T: NostrSigner,
+{
+    /// New policy signer
+    pub fn new(signer: T, policy: SignerPolicy) -> Self {
+        Self {
+            signer,
+            policy,
+            audit: None,
+            operations: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// Set the audit sink
+    #[inline]
+    pub fn audit_sink<S>(mut self, sink: S) -> Self
+    where
+        S: AuditSink + 'static,
+    {
+        self.audit = Some(Arc::new(sink));
+        self
+    }
+
+    /// Get the policy
+    #[inline]
+    pub fn policy(&self) -> &SignerPolicy {
+        &self.policy
+    }
+
+    /// Get the inner signer
+    #[inline]
+    pub fn inner(&self) -> &T {
+        &self.signer
+    }
+
+    fn check_rate_limit(&self) -> Result<(), PolicyViolation> {
+        let limit: RateLimit = match self.policy.rate_limit {
+            Some(limit) => limit,
+            None => return Ok(()),
+        };
+
+        let now: Instant = Instant::now();
+        let mut operations = self.operations.lock().unwrap_or_else(|e| e.into_inner());
+
+        // Remove the operations outside the window
+        while let Some(first) = operations.front() {
+            if now.duration_since(*first) < limit.period {
+                break;
+            }
+
+            operations.pop_front();
+        }
+
+        if operations.len() >= limit.max {
+            return Err(PolicyViolation::RateLimited);
+        }
+
+        operations.push_back(now);
+
+        Ok(())
+    }
+
+    async fn audit(&self, record: AuditRecord) {
+        if let Some(sink) = &self.audit {
+            sink.record(record).await;
+        }
+    }
+
+    async fn _sign_event(&self, unsigned: UnsignedEvent) -> Result<Event, SignerError> {
+        let timestamp: Timestamp = Timestamp::now();
+        let kind: Kind = unsigned.kind;
+
+        let (result, outcome) = match self
+            .policy
+            .check_event(&unsigned)
+            .and_then(|_| self.check_rate_limit())
+        {
+            Ok(()) => match self.signer.sign_event(unsigned).await {
+                Ok(event) => (Ok(event), AuditOutcome::Success),
+                Err(e) => {
+                    let msg: String = e.to_string();
+                    (Err(e), AuditOutcome::Failed(msg))
+                }
+            },
+            Err(violation) => (
+                Err(SignerError::backend(violation.clone())),
+                AuditOutcome::Denied(violation),
+            ),
+        };
+
+        self.audit(AuditRecord {
+            timestamp,
+            operation: SignerOperation::SignEvent,
+            kind: Some(kind),
+            event_id: result.as_ref().ok().map(|event| event.id),
+            public_key: None,
+            outcome,
+        })
+        .await;
+
+        result
+    }
+
+    async fn _crypto<'a, F>(
+        &'a self,
+        operation: SignerOperation,
+        public_key: &'a PublicKey,
+        plaintext: Option<&'a str>,
+        f: F,
+    ) -> Result<String, SignerError>
+    where
+        F: FnOnce() -> BoxedFuture<'a, Result<String, SignerError>>,
+    {
+        let timestamp: Timestamp = Timestamp::now();
+
+        let check = match plaintext {
+            Some(plaintext) => self.policy.check_content(plaintext),
+            None => Ok(()),
+        };
+
+        let (result, outcome) = match check.and_then(|_| self.check_rate_limit()) {
+            Ok(()) => match f().await {
+                Ok(output) => (Ok(output), AuditOutcome::Success),
+                Err(e) => {
+                    let msg: String = e.to_string();
+                    (Err(e), AuditOutcome::Failed(msg))
+                }
+            },
+            Err(violation) => (
+                Err(SignerError::backend(violation.clone())),
+                AuditOutcome::Denied(violation),
+            ),
+        };
+
+        self.audit(AuditRecord {
+            timestamp,
+            operation,
+            kind: None,
+            event_id: None,
+            public_key: Some(*public_key),
+            outcome,
+        })
+        .await;
+
+        result
+    }
+}
+
+impl<T> NostrSigner for PolicySigner<T>
+where
+    T: NostrSigner,
+{
+    fn backend(&self) -> SignerBackend {
+        self.signer.backend()
+    }
+
+    fn get_public_key(&self) -> BoxedFuture<Result<PublicKey, SignerError>> {
+        self.signer.get_public_key()
+    }
+
+    fn sign_event(&self, unsigned: UnsignedEvent) -> BoxedFuture<Result<Event, SignerError>> {
+        Box::pin(self._sign_event(unsigned))
+    }
+
+    fn nip04_encrypt<'a>(
+        &'a self,
+        public_key: &'a PublicKey,
+        content: &'a str,
+    ) -> BoxedFuture<'a, Result<String, SignerError>> {
+        Box::pin(self._crypto(
+            SignerOperation::Nip04Encrypt,
+            public_key,
+            Some(content),
+            || self.signer.nip04_encrypt(public_key, content),
+        ))
+    }
+
+    fn nip04_decrypt<'a>(
+        &'a self,
+        public_key: &'a PublicKey,
+        encrypted_content: &'a str,
+    ) -> BoxedFuture<'a, Result<String, SignerError>> {
+        Box::pin(
+            self._crypto(SignerOperation::Nip04Decrypt, public_key, None, || {
+                self.signer.nip04_decrypt(public_key, encrypted_content)
+            }),
+        )
+    }
+
+    fn nip44_encrypt<'a>(
+        &'a self,
+        public_key: &'a PublicKey,
+        content: &'a str,
+    ) -> BoxedFuture<'a, Result<String, SignerError>> {
+        Box::pin(self._crypto(
+            SignerOperation::Nip44Encrypt,
+            public_key,
+            Some(content),
+            || self.signer.nip44_encrypt(public_key, content),
+        ))
+    }
+
+    fn nip44_decrypt<'a>(
+        &'a self,
+        public_key: &'a PublicKey,
+        payload: &'a str,
+    ) -> BoxedFuture<'a, Result<String, SignerError>> {
+        Box::pin(
+            self._crypto(SignerOperation::Nip44Decrypt, public_key, None, || {
+                self.signer.nip44_decrypt(public_key, payload)
+            }),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Default)]
+    struct MemorySink {
+        records: Mutex<Vec<AuditRecord>>,
+    }
+
+    impl AuditSink for Arc<MemorySink> {
+        fn record(&self, record: AuditRecord) -> BoxedFuture<()> {
+            Box::pin(async move {
+                self.records.lock().unwrap().push(record);
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_policy_signer() {
+        let keys = Keys::generate();
+        let public_key = keys.public_key();
+        let sink = Arc::new(MemorySink::default());
+
+        let policy = SignerPolicy::new()
+            .allowed_kinds([Kind::TextNote])
+            .max_created_at_skew(Duration::from_secs(60))
+            .max_content_size(10);
+        let signer = PolicySigner::new(keys, policy).audit_sink(sink.clone());
+
+        // Allowed
+        let unsigned = EventBuilder::text_note("hello").build(public_key);
+        let event = signer.sign_event(unsigned).await.unwrap();
+
+        // Kind not allowed
+        let unsigned = EventBuilder::new(Kind::Metadata, "{}").build(public_key);
+        let err = signer.sign_event(unsigned).await.unwrap_err();
+        assert_eq!(
+            err,
+            SignerError::backend(PolicyViolation::KindNotAllowed(Kind::Metadata))
+        );
+
+        // Created at skew
+        let unsigned = EventBuilder::text_note("hello")
+            .custom_created_at(Timestamp::now() - Duration::from_secs(3600))
+            .build(public_key);
+        assert!(signer.sign_event(unsigned).await.is_err());
+
+        // Content too large
+        let unsigned = EventBuilder::text_note("hello world!").build(public_key);
+        assert!(signer.sign_event(unsigned).await.is_err());
+        assert!(signer
+            .nip44_encrypt(&public_key, "hello world!")
+            .await
+            .is_err());
+
+        // Decrypt
+        let ciphertext = signer.nip44_encrypt(&public_key, "secret").await.unwrap();
+        assert_eq!(
+            signer
+                .nip44_decrypt(&public_key, &ciphertext)
+                .await
+                .unwrap(),
+            "secret"
+        );
+
+        let records = sink.records.lock().unwrap();
+        assert_eq!(records.len(), 7);
+        assert_eq!(records[0].operation, SignerOperation::SignEvent);
+        assert_eq!(records[0].event_id, Some(event.id));
+        assert_eq!(records[0].outcome, AuditOutcome::Success);
+        assert_eq!(
+            records[1].outcome,
+            AuditOutcome::Denied(PolicyViolation::KindNotAllowed(Kind::Metadata))
+        );
+        assert!(matches!(
+            records[2].outcome,
+            AuditOutcome::Denied(PolicyViolation::CreatedAtSkew { .. })
+        ));
+        assert_eq!(
+            records[3].outcome,
+            AuditOutcome::Denied(PolicyViolation::ContentTooLarge { size: 12, max: 10 })
+        );
+        assert_eq!(records[4].operation, SignerOperation::Nip44Encrypt);
+        assert_eq!(records[6].operation, SignerOperation::Nip44Decrypt);
+        assert_eq!(records[6].public_key, Some(public_key));
+        assert_eq!(records[6].outcome, AuditOutcome::Success);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit() {
+        let keys = Keys::generate();
+        let public_key = keys.public_key();
+
+        let policy = SignerPolicy::new().rate_limit(2, Duration::from_millis(200));
+        let signer = PolicySigner::new(keys, policy);
+
+        for _ in 0..2 {
+            let unsigned = EventBuilder::text_note("hello").build(public_key);
+            assert!(signer.sign_event(unsigned).await.is_ok());
+        }
+
+        let unsigned = EventBuilder::text_note("hello").build(public_key);
+        assert_eq!(
+            signer.sign_event(unsigned.clone()).await.unwrap_err(),
+            SignerError::backend(PolicyViolation::RateLimited)
+        );
+
+        // New window
+        tokio::time::sleep(Duration::from_millis(250)).await;
+        assert!(signer.sign_event(unsigned).await.is_ok());
+    }
+}
@@ -0,0 +1,607 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2025 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Web of Trust
+//!
+//! Crawl the contact lists (NIP02) starting from a seed public key
+//! and compute the trust scores with a personalized PageRank.
+//! Mute lists (NIP51) and reports (NIP56) are counted as negative signals.
+
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+
+use nostr::prelude::*;
+use nostr_database::prelude::*;
+use nostr_relay_pool::policy::{AdmitPolicy, AdmitStatus, PolicyError};
+use tokio::sync::RwLock;
+
+pub mod options;
+
+pub use self::options::WebOfTrustOptions;
+use crate::client::{Client, Error};
+
+/// Max number of authors per filter while crawling
+const MAX_AUTHORS_PER_FILTER: usize = 250;
+/// Max number of reports per filter while crawling
+const MAX_REPORTS_PER_FILTER: usize = 1000;
+
+#[derive(Debug, Clone, Default)]
+struct PublicKeyList {
+    created_at: Timestamp,
+    public_keys: HashSet<PublicKey>,
+}
+
+#[derive(Debug, Default)]
+struct Graph {
+    /// Latest contact list of each public key
+    follows: HashMap<PublicKey, PublicKeyList>,
+    /// Latest (public) mute list of each public key
+    mutes: HashMap<PublicKey, PublicKeyList>,
+    /// Reported public keys, by reporter
+    reports: HashMap<PublicKey, HashSet<PublicKey>>,
+    /// Already processed reports
+    seen_reports: HashSet<EventId>,
+    /// Number of followers
+    followers: HashMap<PublicKey, usize>,
+    /// Trust scores (relative to the seed)
+    scores: HashMap<PublicKey, f64>,
+}
+
+impl Graph {
+    fn process_event(&mut self, event: &Event) {
+        match event.kind {
+            Kind::ContactList => Self::update_list(&mut self.follows, event),
+            Kind::MuteList => Self::update_list(&mut self.mutes, event),
+            Kind::Reporting => {
+                if self.seen_reports.insert(event.id) {
+                    self.reports
+                        .entry(event.pubkey)
+                        .or_default()
+                        .extend(event.tags.public_keys().filter(|p| *p != &event.pubkey));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn update_list(lists: &mut HashMap<PublicKey, PublicKeyList>, event: &Event) {
+        let list = PublicKeyList {
+            created_at: event.created_at,
+            public_keys: event
+                .tags
+                .public_keys()
+                .copied()
+                .filter(|p| p != &event.pubkey)
+                .collect(),
+        };
+
+        match lists.entry(event.pubkey) {
+            Entry::Occupied(mut entry) => {
+                if event.created_at > entry.get().created_at {
+                    entry.insert(list);
+                }
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(list);
+            }
+        }
+    }
+
+    fn compute(&mut self, seed: &PublicKey, opts: &WebOfTrustOptions) {
+        // Followers
+        self.followers.clear();
+        for list in self.follows.values() {
+            for public_key in list.public_keys.iter() {
+                *self.followers.entry(*public_key).or_default() += 1;
+            }
+        }
+
+        // Personalized PageRank: at every step, jump back to the seed with probability `1 - damping`.
+        // The rank of public keys without follows (dangling) is given back to the seed.
+        let mut ranks: HashMap<PublicKey, f64> = HashMap::from([(*seed, 1.0)]);
+
+        for _ in 0..opts.iterations {
+            let mut next: HashMap<PublicKey, f64> = HashMap::with_capacity(ranks.len());
+            let mut to_seed: f64 = 1.0 - opts.damping;
+
+            for (public_key, rank) in ranks.iter() {
+                match self.follows.get(public_key) {
+                    Some(list) if !list.public_keys.is_empty() => {
+                        let share: f64 = opts.damping * rank / list.public_keys.len() as f64;
+                        for followed in list.public_keys.iter() {
+                            *next.entry(*followed).or_default() += share;
+                        }
+                    }
+                    _ => to_seed += opts.damping * rank,
+                }
+            }
+
+            *next.entry(*seed).or_default() += to_seed;
+            ranks = next;
+        }
+
+        // Negative signals: a public key loses part of the rank of who mutes or reports it
+        let mut penalties: HashMap<PublicKey, f64> = HashMap::new();
+
+        for (public_key, list) in self.mutes.iter() {
+            Self::add_penalties(
+                &mut penalties,
+                &ranks,
+                public_key,
+                &list.public_keys,
+                opts.mute_weight,
+            );
+        }
+
+        for (public_key, reported) in self.reports.iter() {
+            Self::add_penalties(
+                &mut penalties,
+                &ranks,
+                public_key,
+                reported,
+                opts.report_weight,
+            );
+        }
+
+        // Normalize: the seed has score `1.0`
+        let seed_rank: f64 = ranks.get(seed).copied().unwrap_or(1.0);
+
+        self.scores = ranks
+            .into_iter()
+            .map(|(public_key, rank)| {
+                let penalty: f64 = penalties.get(&public_key).copied().unwrap_or_default();
+                (public_key, (rank - penalty) / seed_rank)
+            })
+            .collect();
+    }
+
+    fn add_penalties(
+        penalties: &mut HashMap<PublicKey, f64>,
+        ranks: &HashMap<PublicKey, f64>,
+        from: &PublicKey,
+        targets: &HashSet<PublicKey>,
+        weight: f64,
+    ) {
+        // Signals from public keys outside the web of trust are ignored
+        let rank: f64 = match ranks.get(from) {
+            Some(rank) if !targets.is_empty() => *rank,
+            _ => return,
+        };
+
+        let penalty: f64 = weight * rank / targets.len() as f64;
+        for target in targets.iter() {
+            *penalties.entry(*target).or_default() += penalty;
+        }
+    }
+}
+
+/// Web of Trust
+///
+/// Can be used as [`AdmitPolicy`] (see [`ClientBuilder::admit_policy`](crate::ClientBuilder::admit_policy))
+/// to drop the events of untrusted authors.
+/// Contact lists, mute lists and reports are always admitted, since are needed to build the graph.
+/// The events of the other authors (except the seed) are rejected until the scores have been computed
+/// (see [`WebOfTrust::crawl`] and [`WebOfTrust::compute`]).
+///
+/// # Example
+/// ```rust,no_run
+/// use std::time::Duration;
+///
+/// use nostr_sdk::prelude::*;
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<()> {
+/// let seed = PublicKey::parse("npub1drvpzev3syqt0kjrls50050uzf25gehpz9vgdw08hvex7e0vgfeq0eseet")?;
+/// let wot = WebOfTrust::new(seed);
+///
+/// let client = Client::builder().admit_policy(wot.clone()).build();
+/// client.add_relay("wss://relay.damus.io").await?;
+/// client.connect().await;
+///
+/// wot.crawl(&client, Duration::from_secs(10)).await?;
+///
+/// let events = client
+///     .fetch_events(Filter::new().kind(Kind::TextNote).limit(100), Duration::from_secs(10))
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct WebOfTrust {
+    seed: PublicKey,
+    graph: Arc<RwLock<Graph>>,
+    opts: WebOfTrustOptions,
+}
+
+impl WebOfTrust {
+    /// New web of trust with default options
+    #[inline]
+    pub fn new(seed: PublicKey) -> Self {
+        Self::with_opts(seed, WebOfTrustOptions::default())
+    }
+
+    /// New web of trust
+    pub fn with_opts(seed: PublicKey, opts: WebOfTrustOptions) -> Self {
+        Self {
+            seed,
+            graph: Arc::new(RwLock::new(Graph::default())),
+            opts,
+        }
+    }
+
+    /// Get the seed public key
+    #[inline]
+    pub fn seed(&self) -> &PublicKey {
+        &self.seed
+    }
+
+    /// Process a contact list, mute list or report event
+    ///
+    /// Other events are ignored.
+    /// Call [`WebOfTrust::compute`] to update the scores.
+    pub async fn process_event(&self, event: &Event) {
+        let mut graph = self.graph.write().await;
+        graph.process_event(event);
+    }
+
+    /// Crawl the contact lists up to the configured depth (see [`WebOfTrustOptions::depth`]) and compute the scores
+    ///
+    /// The lists are first searched in the database and the missing ones are fetched from the relays.
+    /// The mute lists and the reports of the crawled public keys are fetched as well
+    /// (the reports are bounded to the most recent ones).
+    pub async fn crawl(&self, client: &Client, timeout: Duration) -> Result<(), Error> {
+        let mut visited: HashSet<PublicKey> = HashSet::new();
+        let mut frontier: HashSet<PublicKey> = HashSet::from([self.seed]);
+
+        for _ in 0..self.opts.depth {
+            visited.extend(frontier.iter().copied());
+
+            let authors: Vec<PublicKey> = frontier.drain().collect();
+
+            for chunk in authors.chunks(MAX_AUTHORS_PER_FILTER) {
+                let lists_filter: Filter = Filter::new()
+                    .authors(chunk.iter().copied())
+                    .kinds([Kind::ContactList, Kind::MuteList]);
+                let reports_filter: Filter = Filter::new()
+                    .authors(chunk.iter().copied())
+                    .kind(Kind::Reporting)
+                    .limit(MAX_REPORTS_PER_FILTER);
+
+                // Query database
+                let stored_lists: Events = client.database().query(lists_filter.clone()).await?;
+                let stored_reports: Events =
+                    client.database().query(reports_filter.clone()).await?;
+
+                // Fetch from relays the lists and the reports of the authors not found in the database,
+                // tracking each kind separately
+                let found_contacts: HashSet<PublicKey> =
+                    authors_of(&stored_lists, Kind::ContactList);
+                let found_mutes: HashSet<PublicKey> = authors_of(&stored_lists, Kind::MuteList);
+                let found_reports: HashSet<PublicKey> =
+                    authors_of(&stored_reports, Kind::Reporting);
+
+                let missing_lists: Vec<PublicKey> = chunk
+                    .iter()
+                    .filter(|p| !found_contacts.contains(p) || !found_mutes.contains(p))
+                    .copied()
+                    .collect();
+                let missing_reports: Vec<PublicKey> = chunk
+                    .iter()
+                    .filter(|p| !found_reports.contains(p))
+                    .copied()
+                    .collect();
+
+                let mut fetched: Vec<Events> = Vec::with_capacity(2);
+
+                if !missing_lists.is_empty() {
+                    fetched.push(
+                        client
+                            .fetch_events(lists_filter.authors(missing_lists), timeout)
+                            .await?,
+                    );
+                }
+
+                if !missing_reports.is_empty() {
+                    fetched.push(
+                        client
+                            .fetch_events(reports_filter.authors(missing_reports), timeout)
+                            .await?,
+                    );
+                }
+
+                let mut graph = self.graph.write().await;
+                for event in stored_lists
+                    .iter()
+                    .chain(stored_reports.iter())
+                    .chain(fetched.iter().flat_map(|e| e.iter()))
+                {
+                    graph.process_event(event);
+                }
+
+                // Next level
+                for public_key in chunk.iter() {
+                    if let Some(list) = graph.follows.get(public_key) {
+                        frontier.extend(
+                            list.public_keys
+                                .iter()
+                                .filter(|p| !visited.contains(p))
+                                .copied(),
+                        );
+                    }
+                }
+            }
+        }
+
+        self.compute().await;
+
+        Ok(())
+    }
+
+    /// Compute the follower counts and the trust scores
+    pub async fn compute(&self) {
+        let mut graph = self.graph.write().await;
+        graph.compute(&self.seed, &self.opts);
+    }
+
+    /// Get the trust score of a public key
+    ///
+    /// The score is relative to the seed (that has score `1.0`) and can be negative.
+    /// Returns `None` if the public key is not part of the web of trust.
+    pub async fn score(&self, public_key: &PublicKey) -> Option<f64> {
+        let graph = self.graph.read().await;
+        graph.scores.get(public_key).copied()
+    }
+
+    /// Get the number of followers of a public key, among the crawled contact lists
+    pub async fn followers(&self, public_key: &PublicKey) -> usize {
+        let graph = self.graph.read().await;
+        graph.followers.get(public_key).copied().unwrap_or_default()
+    }
+
+    /// Check if a public key is trusted
+    ///
+    /// A public key is trusted if its score is greater than [`WebOfTrustOptions::min_score`]
+    /// and it's not muted by the seed.
+    pub async fn is_trusted(&self, public_key: &PublicKey) -> bool {
+        let graph = self.graph.read().await;
+        self.is_trusted_in(&graph, public_key)
+    }
+
+    /// Get the trusted public keys
+    pub async fn trusted_public_keys(&self) -> BTreeSet<PublicKey> {
+        let graph = self.graph.read().await;
+        graph
+            .scores
+            .keys()
+            .filter(|p| self.is_trusted_in(&graph, p))
+            .copied()
+            .collect()
+    }
+
+    fn is_trusted_in(&self, graph: &Graph, public_key: &PublicKey) -> bool {
+        if public_key == &self.seed {
+            return true;
+        }
+
+        if let Some(mutes) = graph.mutes.get(&self.seed) {
+            if mutes.public_keys.contains(public_key) {
+                return false;
+            }
+        }
+
+        match graph.scores.get(public_key) {
+            Some(score) => *score > self.opts.min_score,
+            None => false,
+        }
+    }
+}
+
+impl AdmitPolicy for WebOfTrust {
+    fn admit_event<'a>(
+        &'a self,
+        _relay_url: &'a RelayUrl,
+        _subscription_id: &'a SubscriptionId,
+        event: &'a Event,
+    ) -> BoxedFuture<'a, Result<AdmitStatus, PolicyError>> {
+        Box::pin(async move {
+            // Needed to build the graph
+            if matches!(
+                event.kind,
+                Kind::ContactList | Kind::MuteList | Kind::Reporting
+            ) {
+                return Ok(AdmitStatus::success());
+            }
+
+            if self.is_trusted(&event.pubkey).await {
+                return Ok(AdmitStatus::success());
+            }
+
+            Ok(AdmitStatus::rejected("untrusted author"))
+        })
+    }
+}
+
+/// Public keys of the authors of the events of a kind
+fn authors_of(events: &Events, kind: Kind) -> HashSet<PublicKey> {
+    events
+        .iter()
+        .filter(|e| e.kind == kind)
+        .map(|e| e.pubkey)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use nostr_relay_builder::prelude::*;
+
+    use super::*;
+
+    fn contact_list(keys: &Keys, contacts: &[&Keys]) -> Event {
+        EventBuilder::new(Kind::ContactList, "")
+            .tags(contacts.iter().map(|k| Tag::public_key(k.public_key())))
+            .sign_with_keys(keys)
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_web_of_trust() {
+        let seed = Keys::generate();
+        let alice = Keys::generate();
+        let bob = Keys::generate();
+        let carol = Keys::generate();
+        let spammer = Keys::generate();
+        let stranger = Keys::generate();
+
+        let wot = WebOfTrust::new(seed.public_key());
+
+        // seed -> alice, bob
+        // alice -> bob, carol
+        // bob -> carol, spammer
+        wot.process_event(&contact_list(&seed, &[&alice, &bob]))
+            .await;
+        wot.process_event(&contact_list(&alice, &[&bob, &carol]))
+            .await;
+        wot.process_event(&contact_list(&bob, &[&carol, &spammer]))
+            .await;
+
+        // Spammer muted by alice and reported by carol
+        let mute_list = EventBuilder::new(Kind::MuteList, "")
+            .tag(Tag::public_key(spammer.public_key()))
+            .sign_with_keys(&alice)
+            .unwrap();
+        wot.process_event(&mute_list).await;
+
+        let report = EventBuilder::report(
+            [Tag::public_key_report(spammer.public_key(), Report::Spam)],
+            "",
+        )
+        .sign_with_keys(&carol)
+        .unwrap();
+        wot.process_event(&report).await;
+        wot.process_event(&report).await;
+
+        wot.compute().await;
+
+        assert_eq!(wot.followers(&bob.public_key()).await, 2);
+        assert_eq!(wot.followers(&carol.public_key()).await, 2);
+        assert_eq!(wot.followers(&stranger.public_key()).await, 0);
+
+        assert_eq!(wot.score(&seed.public_key()).await, Some(1.0));
+
+        let alice_score = wot.score(&alice.public_key()).await.unwrap();
+        let bob_score = wot.score(&bob.public_key()).await.unwrap();
+        let carol_score = wot.score(&carol.public_key()).await.unwrap();
+        let spammer_score = wot.score(&spammer.public_key()).await.unwrap();
+
+        // Followed by the seed and by alice
+        assert!(bob_score > alice_score);
+        assert!(carol_score > 0.0);
+        assert!(spammer_score < 0.0);
+        assert!(wot.score(&stranger.public_key()).await.is_none());
+
+        let trusted = wot.trusted_public_keys().await;
+        assert_eq!(
+            trusted,
+            BTreeSet::from([
+                seed.public_key(),
+                alice.public_key(),
+                bob.public_key(),
+                carol.public_key()
+            ])
+        );
+
+        // Muted by the seed
+        let mute_list = EventBuilder::new(Kind::MuteList, "")
+            .tag(Tag::public_key(carol.public_key()))
+            .sign_with_keys(&seed)
+            .unwrap();
+        wot.process_event(&mute_list).await;
+        wot.compute().await;
+        assert!(!wot.is_trusted(&carol.public_key()).await);
+    }
+
+    #[tokio::test]
+    async fn test_admit_event() {
+        let seed = Keys::generate();
+        let stranger = Keys::generate();
+
+        let wot = WebOfTrust::new(seed.public_key());
+        wot.compute().await;
+
+        let relay_url = RelayUrl::parse("wss://relay.example.com").unwrap();
+        let subscription_id = SubscriptionId::generate();
+
+        let note = EventBuilder::text_note("spam")
+            .sign_with_keys(&stranger)
+            .unwrap();
+        let status = wot
+            .admit_event(&relay_url, &subscription_id, &note)
+            .await
+            .unwrap();
+        assert_eq!(status, AdmitStatus::rejected("untrusted author"));
+
+        let note = EventBuilder::text_note("hello")
+            .sign_with_keys(&seed)
+            .unwrap();
+        let status = wot
+            .admit_event(&relay_url, &subscription_id, &note)
+            .await
+            .unwrap();
+        assert_eq!(status, AdmitStatus::success());
+
+        // Contact lists are always admitted
+        let contacts = contact_list(&stranger, &[&seed]);
+        let status = wot
+            .admit_event(&relay_url, &subscription_id, &contacts)
+            .await
+            .unwrap();
+        assert_eq!(status, AdmitStatus::success());
+    }
+
+    #[tokio::test]
+    async fn test_crawl_missing_kinds() {
+        let mock = MockRelay::run().await.unwrap();
+        let url = RelayUrl::parse(&mock.url()).unwrap();
+
+        let seed = Keys::generate();
+        let alice = Keys::generate();
+        let spammer = Keys::generate();
+
+        let database = MemoryDatabase::with_opts(MemoryDatabaseOptions {
+            events: true,
+            max_events: None,
+        });
+        let client = Client::builder().database(database).build();
+        client.add_relay(&url).await.unwrap();
+        client.connect().await;
+
+        // Mute list and report only on the relay
+        let mute_list = EventBuilder::new(Kind::MuteList, "")
+            .tag(Tag::public_key(alice.public_key()))
+            .sign_with_keys(&seed)
+            .unwrap();
+        client.send_event(&mute_list).await.unwrap();
+        let report = EventBuilder::report(
+            [Tag::public_key_report(spammer.public_key(), Report::Spam)],
+            "",
+        )
+        .sign_with_keys(&seed)
+        .unwrap();
+        client.send_event(&report).await.unwrap();
+        client.database().wipe().await.unwrap();
+
+        // Contact list already in the database
+        let contacts = contact_list(&seed, &[&alice, &spammer]);
+        client.database().save_event(&contacts).await.unwrap();
+
+        let wot = WebOfTrust::with_opts(seed.public_key(), WebOfTrustOptions::new().depth(1));
+        wot.crawl(&client, Duration::from_secs(5)).await.unwrap();
+
+        // The mute list and the report have been fetched
+        assert!(!wot.is_trusted(&alice.public_key()).await);
+        assert!(wot.score(&spammer.public_key()).await.unwrap() < 0.0);
+    }
+}
@@ -0,0 +1,84 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2025 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Web of Trust options
+
+/// Web of Trust options
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WebOfTrustOptions {
+    pub(super) depth: u8,
+    pub(super) damping: f64,
+    pub(super) iterations: usize,
+    pub(super) mute_weight: f64,
+    pub(super) report_weight: f64,
+    pub(super) min_score: f64,
+}
+
+impl Default for WebOfTrustOptions {
+    fn default() -> Self {
+        Self {
+            depth: 2,
+            damping: 0.85,
+            iterations: 20,
+            mute_weight: 1.0,
+            report_weight: 0.5,
+            min_score: 0.0,
+        }
+    }
+}
+
+impl WebOfTrustOptions {
+    /// Create new default options
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Max depth of the contact lists to crawl, starting from the seed (default: 2)
+    ///
+    /// With `1` only the contact list of the seed is crawled,
+    /// with `2` also the contact lists of the seed follows, and so on.
+    #[inline]
+    pub fn depth(mut self, depth: u8) -> Self {
+        self.depth = depth;
+        self
+    }
+
+    /// PageRank damping factor (default: 0.85)
+    ///
+    /// The probability of following a contact instead of jumping back to the seed.
+    #[inline]
+    pub fn damping(mut self, damping: f64) -> Self {
+        self.damping = damping.clamp(0.0, 1.0);
+        self
+    }
+
+    /// PageRank iterations (default: 20)
+    #[inline]
+    pub fn iterations(mut self, iterations: usize) -> Self {
+        self.iterations = iterations;
+        self
+    }
+
+    /// Weight of the mutes (default: 1.0)
+    #[inline]
+    pub fn mute_weight(mut self, weight: f64) -> Self {
+        self.mute_weight = weight;
+        self
+    }
+
+    /// Weight of the reports (default: 0.5)
+    #[inline]
+    pub fn report_weight(mut self, weight: f64) -> Self {
+        self.report_weight = weight;
+        self
+    }
+
+    /// Min score to consider a public key trusted (default: 0.0, exclusive)
+    #[inline]
+    pub fn min_score(mut self, score: f64) -> Self {
+        self.min_score = score;
+        self
+    }
+}
@@ -2,9 +2,9 @@
 // Copyright (c) 2023-2025 Rust Nostr Developers
 // Distributed under the MIT software license
 
-use nostr::nips::nip06::FromMnemonic;
+use nostr::nips::nip06::{AccountSet, FromMnemonic, Seed};
 use nostr::nips::nip19::ToBech32;
-use nostr::{Keys, Result};
+use nostr::{JsonUtil, Keys, Result};
 
 const MNEMONIC_PHRASE: &str = "equal dragon fabric refuse stable cherry smoke allow alley easy never medal attend together lumber movie what sad siege weather matrix buffalo state shoot";
 
@@ -12,5 +12,25 @@ fn main() -> Result<()> {
     let keys = Keys::from_mnemonic(MNEMONIC_PHRASE, Some("mypassphrase"))?;
     println!("{}", keys.secret_key().to_bech32()?);
 
+    // Derive multiple accounts
+    let seed = Seed::from_mnemonic(MNEMONIC_PHRASE, Some("mypassphrase"))?;
+    let accounts = seed.derive_accounts(0, 3)?;
+
+    for account in accounts.iter() {
+        println!(
+            "{}: {}",
+            account.path,
+            account.keys.public_key().to_bech32()?
+        );
+    }
+
+    // Export the account set (no secrets) and import it again
+    let json: String = AccountSet::new(&accounts).as_json();
+    println!("{json}");
+
+    let set = AccountSet::from_json(json)?;
+    let imported = seed.import_accounts(&set)?;
+    assert_eq!(imported, accounts);
+
     Ok(())
 }
@@ -2,9 +2,12 @@
 // Copyright (c) 2023-2025 Rust Nostr Developers
 // Distributed under the MIT software license
 
+use std::time::Duration;
+
 use nostr::prelude::*;
 
-fn main() -> Result<()> {
+#[tokio::main]
+async fn main() -> Result<()> {
     let keys = Keys::parse("6b911fd37cdf5c81d4c0adb1ab7fa822ed253ab0ad9aa18d77257c88b29b718e")?;
 
     let difficulty = 20; // leading zero bits
@@ -16,5 +19,20 @@ fn main() -> Result<()> {
 
     println!("{}", event.as_json());
 
+    // Mine across all cores, with a best effort timeout
+    let miner = PowMiner::new(24).timeout(Duration::from_secs(10));
+
+    let event: Event = EventBuilder::text_note(msg_content)
+        .sign_with_miner(&keys, &miner)
+        .await?;
+
+    let progress = miner.progress();
+    println!(
+        "Mined difficulty {} ({:.0} H/s)",
+        progress.best_difficulty,
+        progress.hashrate()
+    );
+    println!("{}", event.as_json());
+
     Ok(())
 }
@@ -4,6 +4,7 @@
 
 //! Event builder
 
+use alloc::borrow::Cow;
 use alloc::string::{String, ToString};
 use alloc::vec::Vec;
 use core::fmt;
@@ -49,6 +50,8 @@ pub enum Error {
     /// NIP04 error
     #[cfg(feature = "nip04")]
     NIP04(nip04::Error),
+    /// NIP13 error
+    NIP13(nip13::Error),
     /// NIP44 error
     #[cfg(all(feature = "std", feature = "nip44"))]
     NIP44(nip44::Error),
@@ -57,6 +60,18 @@ pub enum Error {
     /// NIP59 error
     #[cfg(all(feature = "std", feature = "nip59"))]
     NIP59(nip59::Error),
+    /// NIP68 error
+    NIP68(nip68::Error),
+    /// NIP71 error
+    NIP71(nip71::Error),
+    /// NIP72 error
+    NIP72(nip72::Error),
+    /// NIP75 error
+    NIP75(nip75::Error),
+    /// NIP78 error
+    NIP78(nip78::Error),
+    /// NIP89 error
+    NIP89(nip89::Error),
     /// Wrong kind
     WrongKind {
         /// The received wrong kind
@@ -82,9 +97,16 @@ impl fmt::Display for Error {
             Self::NIP04(e) => e.fmt(f),
             #[cfg(all(feature = "std", feature = "nip44"))]
             Self::NIP44(e) => e.fmt(f),
+            Self::NIP13(e) => e.fmt(f),
             Self::NIP58(e) => e.fmt(f),
             #[cfg(all(feature = "std", feature = "nip59"))]
             Self::NIP59(e) => e.fmt(f),
+            Self::NIP68(e) => e.fmt(f),
+            Self::NIP71(e) => e.fmt(f),
+            Self::NIP72(e) => e.fmt(f),
+            Self::NIP75(e) => e.fmt(f),
+            Self::NIP78(e) => e.fmt(f),
+            Self::NIP89(e) => e.fmt(f),
             Self::WrongKind { received, expected } => {
                 write!(f, "Wrong kind: received={received}, expected={expected}")
             }
@@ -132,6 +154,12 @@ impl From<nip44::Error> for Error {
     }
 }
 
+impl From<nip13::Error> for Error {
+    fn from(e: nip13::Error) -> Self {
+        Self::NIP13(e)
+    }
+}
+
 impl From<nip58::Error> for Error {
     fn from(e: nip58::Error) -> Self {
         Self::NIP58(e)
@@ -145,6 +173,42 @@ impl From<nip59::Error> for Error {
     }
 }
 
+impl From<nip68::Error> for Error {
+    fn from(e: nip68::Error) -> Self {
+        Self::NIP68(e)
+    }
+}
+
+impl From<nip71::Error> for Error {
+    fn from(e: nip71::Error) -> Self {
+        Self::NIP71(e)
+    }
+}
+
+impl From<nip72::Error> for Error {
+    fn from(e: nip72::Error) -> Self {
+        Self::NIP72(e)
+    }
+}
+
+impl From<nip75::Error> for Error {
+    fn from(e: nip75::Error) -> Self {
+        Self::NIP75(e)
+    }
+}
+
+impl From<nip78::Error> for Error {
+    fn from(e: nip78::Error) -> Self {
+        Self::NIP78(e)
+    }
+}
+
+impl From<nip89::Error> for Error {
+    fn from(e: nip89::Error) -> Self {
+        Self::NIP89(e)
+    }
+}
+
 /// Event builder
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub struct EventBuilder {
@@ -346,6 +410,24 @@ impl EventBuilder {
         Ok(self.build(public_key).sign(signer).await?)
     }
 
+    /// Build, mine the POW with the [`PowMiner`] and sign the [`Event`]
+    ///
+    /// The mining runs on dedicated threads, without blocking the async runtime,
+    /// and the signer is called only once, after the mining (useful for remote signers).
+    ///
+    /// The difficulty set with [`EventBuilder::pow`] is ignored: the one of the [`PowMiner`] is used.
+    #[cfg(all(feature = "std", not(target_arch = "wasm32")))]
+    pub async fn sign_with_miner<T>(mut self, signer: &T, miner: &PowMiner) -> Result<Event, Error>
+    where
+        T: NostrSigner,
+    {
+        self.pow = None;
+
+        let public_key: PublicKey = signer.get_public_key().await?;
+        let unsigned: UnsignedEvent = miner.mine(self.build(public_key)).await?;
+        Ok(unsigned.sign(signer).await?)
+    }
+
     /// Build, sign and return [`Event`] using [`Keys`] signer
     ///
     /// Check [`EventBuilder::sign_with_ctx`] to learn more.
@@ -1706,6 +1788,148 @@ impl EventBuilder {
     pub fn poll_response(response: PollResponse) -> Self {
         response.to_event_builder()
     }
+
+    /// Picture
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/68.md>
+    #[inline]
+    pub fn picture(picture: Picture) -> Result<Self, Error> {
+        Ok(picture.to_event_builder()?)
+    }
+
+    /// Video (normal or short-form)
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/71.md>
+    #[inline]
+    pub fn video(video: Video) -> Result<Self, Error> {
+        Ok(video.to_event_builder()?)
+    }
+
+    /// Community definition
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/72.md>
+    #[inline]
+    pub fn community_definition(definition: CommunityDefinition) -> Result<Self, Error> {
+        Ok(definition.to_event_builder()?)
+    }
+
+    /// Post to a community
+    ///
+    /// Top-level post: both the root and the parent are the community.
+    /// To reply to a community post use [`EventBuilder::comment`].
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/72.md>
+    pub fn community_post<S>(
+        community: &Coordinate,
+        relay_hint: Option<&RelayUrl>,
+        content: S,
+    ) -> Result<Self, Error>
+    where
+        S: Into<String>,
+    {
+        if community.kind != Kind::CommunityDefinition {
+            return Err(Error::WrongKind {
+                received: community.kind,
+                expected: WrongKindError::Single(Kind::CommunityDefinition),
+            });
+        }
+
+        let root =
+            CommentTarget::coordinate(Cow::Borrowed(community), relay_hint.map(Cow::Borrowed));
+        let parent =
+            CommentTarget::coordinate(Cow::Borrowed(community), relay_hint.map(Cow::Borrowed));
+
+        Ok(Self::comment(content, parent, Some(root)))
+    }
+
+    /// Community post approval
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/72.md>
+    #[inline]
+    pub fn community_post_approval(approval: CommunityPostApproval) -> Result<Self, Error> {
+        Ok(approval.to_event_builder()?)
+    }
+
+    /// Application-specific data, encrypted to self
+    ///
+    /// Pass the `latest` known version to make sure that the new one replaces it.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/78.md>
+    #[inline]
+    pub async fn app_data<T>(
+        signer: &T,
+        data: AppData,
+        latest: Option<&Event>,
+    ) -> Result<Self, Error>
+    where
+        T: NostrSigner,
+    {
+        Ok(nip78::make_app_data(signer, data, latest).await?)
+    }
+
+    /// Highlight
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/84.md>
+    #[inline]
+    pub fn highlight(highlight: Highlight) -> Self {
+        highlight.to_event_builder()
+    }
+
+    /// Handler information
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/89.md>
+    #[inline]
+    pub fn handler_information(info: HandlerInformation) -> Result<Self, Error> {
+        Ok(info.to_event_builder()?)
+    }
+
+    /// Handler recommendation
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/89.md>
+    #[inline]
+    pub fn handler_recommendation(recommendation: HandlerRecommendation) -> Self {
+        recommendation.to_event_builder()
+    }
+
+    /// Wiki article
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/54.md>
+    #[inline]
+    pub fn wiki_article(article: WikiArticle) -> Self {
+        article.to_event_builder()
+    }
+
+    /// Wiki merge request
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/54.md>
+    #[inline]
+    pub fn wiki_merge_request(request: WikiMergeRequest) -> Self {
+        request.to_event_builder()
+    }
+
+    /// Wiki redirect
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/54.md>
+    #[inline]
+    pub fn wiki_redirect(redirect: WikiRedirect) -> Self {
+        redirect.to_event_builder()
+    }
+
+    /// Peer-to-peer order
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/69.md>
+    #[inline]
+    pub fn p2p_order(order: PeerToPeerOrder) -> Self {
+        order.to_event_builder()
+    }
+
+    /// Zap goal
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/75.md>
+    #[inline]
+    pub fn zap_goal(goal: ZapGoal) -> Result<Self, Error> {
+        Ok(goal.to_event_builder()?)
+    }
 }
 
 #[cfg(test)]
@@ -163,6 +163,18 @@ kind_variants! {
     CodeSnippet => 1337, "Code Snippets", "<https://github.com/nostr-protocol/nips/blob/master/C0.md>",
     Poll => 1068, "Poll", "<https://github.com/nostr-protocol/nips/blob/master/88.md>",
     PollResponse => 1018, "Poll response", "<https://github.com/nostr-protocol/nips/blob/master/88.md>",
+    CommunityDefinition => 34550, "Community Definition", "<https://github.com/nostr-protocol/nips/blob/master/72.md>",
+    CommunityPostApproval => 4550, "Community Post Approval", "<https://github.com/nostr-protocol/nips/blob/master/72.md>",
+    HandlerRecommendation => 31989, "Handler Recommendation", "<https://github.com/nostr-protocol/nips/blob/master/89.md>",
+    HandlerInformation => 31990, "Handler Information", "<https://github.com/nostr-protocol/nips/blob/master/89.md>",
+    Highlight => 9802, "Highlight", "<https://github.com/nostr-protocol/nips/blob/master/84.md>",
+    Picture => 20, "Picture", "<https://github.com/nostr-protocol/nips/blob/master/68.md>",
+    Video => 21, "Video", "<https://github.com/nostr-protocol/nips/blob/master/71.md>",
+    ShortVideo => 22, "Short-form Portrait Video", "<https://github.com/nostr-protocol/nips/blob/master/71.md>",
+    ZapGoal => 9041, "Zap Goal", "<https://github.com/nostr-protocol/nips/blob/master/75.md>",
+    WikiArticle => 30818, "Wiki Article", "<https://github.com/nostr-protocol/nips/blob/master/54.md>",
+    WikiMergeRequest => 818, "Wiki Merge Request", "<https://github.com/nostr-protocol/nips/blob/master/54.md>",
+    WikiRedirect => 30819, "Wiki Redirect", "<https://github.com/nostr-protocol/nips/blob/master/54.md>",
 }
 
 impl PartialEq for Kind {
@@ -7,6 +7,10 @@
 
 use alloc::borrow::Cow;
 use alloc::string::{String, ToString};
+#[cfg(feature = "std")]
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use core::borrow::Borrow;
 use core::cmp::Ordering;
 use core::fmt;
 use core::hash::{Hash, Hasher};
@@ -46,6 +50,10 @@ const TAGS: &str = "tags";
 const CONTENT: &str = "content";
 const SIG: &str = "sig";
 
+/// Min number of events verified by each thread in [`Event::verify_batch`]
+#[cfg(feature = "std")]
+const MIN_BATCH_SIZE_PER_THREAD: usize = 64;
+
 /// Nostr event
 #[derive(Clone)]
 #[non_exhaustive] // In the future private fields may be added, so force event construction with `Event::new`.
@@ -159,6 +167,53 @@ impl Event {
         self.verify_with_ctx(SECP256K1)
     }
 
+    /// Verify multiple events (both [`EventId`] and [`Signature`])
+    ///
+    /// Large batches are verified across all the available cores.
+    ///
+    /// Accepts both events and references to events (i.e., to verify the events without cloning them).
+    ///
+    /// Returns the results in the same order as the events.
+    #[cfg(feature = "std")]
+    pub fn verify_batch<T>(events: &[T]) -> Vec<Result<(), Error>>
+    where
+        T: Borrow<Self> + Sync,
+    {
+        let threads: usize = std::thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1)
+            .min(events.len() / MIN_BATCH_SIZE_PER_THREAD);
+
+        // Not worth spawning threads
+        if threads <= 1 {
+            return events.iter().map(|event| event.borrow().verify()).collect();
+        }
+
+        let chunk_size: usize = (events.len() + threads - 1) / threads;
+
+        std::thread::scope(|s| {
+            let handles: Vec<_> = events
+                .chunks(chunk_size)
+                .map(|chunk| {
+                    s.spawn(move || {
+                        chunk
+                            .iter()
+                            .map(|event| event.borrow().verify())
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+
+            handles
+                .into_iter()
+                .flat_map(|handle| match handle.join() {
+                    Ok(results) => results,
+                    Err(e) => std::panic::resume_unwind(e),
+                })
+                .collect()
+        })
+    }
+
     /// Verify both [`EventId`] and [`Signature`]
     pub fn verify_with_ctx<C>(&self, secp: &Secp256k1<C>) -> Result<(), Error>
     where
@@ -399,6 +454,41 @@ mod tests {
         assert_eq!(ev_ser.as_json(), sample_event);
     }
 
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_verify_batch() {
+        let keys = Keys::generate();
+        let mut events: Vec<Event> = (0..500)
+            .map(|i| {
+                EventBuilder::text_note(i.to_string())
+                    .sign_with_keys(&keys)
+                    .unwrap()
+            })
+            .collect();
+
+        // Tamper some events
+        events[3].content = String::from("tampered");
+        events[321].sig = events[322].sig;
+
+        let results = Event::verify_batch(&events);
+        assert_eq!(results.len(), events.len());
+
+        for (i, res) in results.into_iter().enumerate() {
+            match i {
+                3 => assert_eq!(res, Err(Error::InvalidId)),
+                321 => assert_eq!(res, Err(Error::InvalidSignature)),
+                _ => assert!(res.is_ok()),
+            }
+        }
+
+        assert!(Event::verify_batch::<Event>(&[]).is_empty());
+        assert_eq!(Event::verify_batch(&events[..1]), vec![Ok(())]);
+
+        // References
+        let refs: Vec<&Event> = events.iter().skip(4).take(300).collect();
+        assert!(Event::verify_batch(&refs).iter().all(|res| res.is_ok()));
+    }
+
     #[test]
     #[cfg(feature = "std")]
     fn test_custom_kind() {
@@ -80,7 +80,7 @@ impl UnsignedEvent {
     }
 
     #[inline]
-    fn compute_id(&self) -> EventId {
+    pub(crate) fn compute_id(&self) -> EventId {
         EventId::new(
             &self.pubkey,
             &self.created_at,
@@ -18,6 +18,8 @@ use serde::{Deserialize, Serialize};
 
 use crate::event::tag::list::TagsIndexes;
 use crate::nips::nip01::Coordinate;
+use crate::nips::nip50::SearchQuery;
+use crate::nips::nip69::{OrderStatus, OrderType};
 use crate::{Event, EventId, JsonUtil, Kind, PublicKey, Timestamp};
 
 type GenericTags = BTreeMap<SingleLetterTag, BTreeSet<String>>;
@@ -337,12 +339,12 @@ pub struct Filter {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(default)]
     pub kinds: Option<BTreeSet<Kind>>,
-    /// It's a string describing a query in a human-readable form, i.e. "best nostr apps"
+    /// It's a query in a human-readable form, i.e. "best nostr apps", parsed with the NIP-50 extensions
     ///
     /// <https://github.com/nostr-protocol/nips/blob/master/50.md>
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(default)]
-    pub search: Option<String>,
+    pub search: Option<SearchQuery>,
     /// An integer unix timestamp, events must be newer than this to pass
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(default)]
@@ -381,7 +383,7 @@ pub struct MatchEventOptions {
     /// Check if event timestamp is before filter's `until`.
     pub until: bool,
     /// Check if event content contains filter's search terms
-    /// (basic NIP-50 implementation without extensions).
+    /// (basic NIP-50 implementation, the extensions are ignored).
     pub nip50: bool,
 }
 
@@ -463,7 +465,7 @@ impl MatchEventOptions {
     }
 
     /// Check if event content contains filter's search terms
-    /// (basic NIP-50 implementation without extensions).
+    /// (basic NIP-50 implementation, the extensions are ignored).
     #[inline]
     pub const fn nip50(mut self, enable: bool) -> Self {
         self.nip50 = enable;
@@ -750,13 +752,77 @@ impl Filter {
         )
     }
 
+    /// Add peer-to-peer order type
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/69.md>
+    #[inline]
+    pub fn p2p_order_type(self, order_type: OrderType) -> Self {
+        self.custom_tag(SingleLetterTag::lowercase(Alphabet::K), order_type.as_str())
+    }
+
+    /// Add peer-to-peer order fiat currency (ISO 4217)
+    ///
+    /// The currency code is converted to uppercase.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/69.md>
+    pub fn p2p_currency<S>(self, currency: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.custom_tag(
+            SingleLetterTag::lowercase(Alphabet::F),
+            currency.into().to_uppercase(),
+        )
+    }
+
+    /// Add peer-to-peer order fiat currencies (ISO 4217)
+    ///
+    /// The currency codes are converted to uppercase.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/69.md>
+    pub fn p2p_currencies<I, S>(self, currencies: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.custom_tags(
+            SingleLetterTag::lowercase(Alphabet::F),
+            currencies.into_iter().map(|c| c.into().to_uppercase()),
+        )
+    }
+
+    /// Add peer-to-peer order status
+    ///
+    /// Use [`OrderStatus::Pending`] to query the active orders.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/69.md>
+    #[inline]
+    pub fn p2p_order_status(self, status: OrderStatus) -> Self {
+        self.custom_tag(SingleLetterTag::lowercase(Alphabet::S), status.as_str())
+    }
+
+    /// Add peer-to-peer order platform
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/69.md>
+    #[inline]
+    pub fn p2p_platform<S>(self, platform: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.custom_tag(SingleLetterTag::lowercase(Alphabet::Y), platform)
+    }
+
     /// Add search field
+    ///
+    /// The query is parsed, including the NIP-50 extensions (see [`SearchQuery::parse`]).
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/50.md>
     #[inline]
     pub fn search<S>(mut self, value: S) -> Self
     where
-        S: Into<String>,
+        S: AsRef<str>,
     {
-        self.search = Some(value.into());
+        self.search = Some(SearchQuery::parse(value.as_ref()));
         self
     }
 
@@ -920,11 +986,7 @@ impl Filter {
     #[inline]
     fn search_match(&self, event: &Event) -> bool {
         match &self.search {
-            Some(query) => event
-                .content
-                .as_bytes()
-                .windows(query.len())
-                .any(|window| window.eq_ignore_ascii_case(query.as_bytes())),
+            Some(query) => query.match_text(&event.content),
             None => true,
         }
     }
@@ -1141,7 +1203,7 @@ mod tests {
 
         // Check IDs
         assert!(filter.ids.unwrap().contains(&event_id));
-        assert_eq!(filter.search, Some(String::from("test")));
+        assert_eq!(filter.search, Some(SearchQuery::parse("test")));
 
         // Check #e tag
         let set = filter
@@ -1327,6 +1389,14 @@ mod tests {
 
         let filter = Filter::new().search("yuki kishimoto");
         assert!(filter.match_event(&event, MatchEventOptions::new()));
+
+        // Extensions are ignored
+        let filter = Filter::new().search("kishimoto include:spam language:en");
+        assert!(filter.match_event(&event, MatchEventOptions::new()));
+        assert_eq!(filter.search.as_ref().unwrap().language(), Some("en"));
+
+        let filter = Filter::new().search("yuki satoshi");
+        assert!(!filter.match_event(&event, MatchEventOptions::new()));
     }
 }
 
@@ -33,7 +33,7 @@ pub extern crate bitcoin_hashes as hashes;
 pub extern crate secp256k1;
 
 #[doc(hidden)]
-#[cfg(any(feature = "nip04", feature = "nip44"))]
+#[cfg(any(feature = "nip03", feature = "nip04", feature = "nip44"))]
 pub use base64;
 #[doc(hidden)]
 #[cfg(feature = "nip06")]
@@ -15,9 +15,11 @@ use secp256k1::rand::RngCore;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 pub mod client;
+pub mod raw;
 pub mod relay;
 
 pub use self::client::ClientMessage;
+pub use self::raw::{RawClientMessage, RawRelayMessage};
 pub use self::relay::{MachineReadablePrefix, RelayMessage};
 
 /// Messages error
@@ -0,0 +1,516 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2025 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Raw messages
+//!
+//! Partially parse the messages, borrowing from the JSON input, to extract the message type,
+//! the subscription ID and the event ID without deserializing the events.
+//!
+//! Useful to route, filter or deduplicate the messages before the full parsing
+//! of [`RelayMessage`](super::RelayMessage) or [`ClientMessage`](super::ClientMessage)
+//! (see [`JsonUtil::from_json`](crate::JsonUtil::from_json)).
+
+use alloc::borrow::Cow;
+use core::fmt;
+
+use serde::de::{self, Deserializer, IgnoredAny, SeqAccess, Visitor};
+use serde::Deserialize;
+
+use super::MessageHandleError;
+use crate::EventId;
+
+/// Raw relay message
+///
+/// The strings are borrowed from the JSON input (allocated only if contain escape sequences)
+/// and the events are NOT deserialized: only the event ID is extracted.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum RawRelayMessage<'a> {
+    /// Event
+    Event {
+        /// Subscription ID
+        subscription_id: Cow<'a, str>,
+        /// Event ID
+        event_id: EventId,
+    },
+    /// Ok
+    Ok {
+        /// Event ID
+        event_id: EventId,
+        /// Status
+        status: bool,
+        /// Message
+        message: Cow<'a, str>,
+    },
+    /// End of stored events
+    EndOfStoredEvents(Cow<'a, str>),
+    /// Notice
+    Notice(Cow<'a, str>),
+    /// Closed
+    Closed {
+        /// Subscription ID
+        subscription_id: Cow<'a, str>,
+        /// Message
+        message: Cow<'a, str>,
+    },
+    /// Auth
+    Auth {
+        /// Challenge
+        challenge: Cow<'a, str>,
+    },
+    /// Count
+    Count {
+        /// Subscription ID
+        subscription_id: Cow<'a, str>,
+    },
+    /// Negentropy Message
+    NegMsg {
+        /// Subscription ID
+        subscription_id: Cow<'a, str>,
+        /// Message
+        message: Cow<'a, str>,
+    },
+    /// Negentropy Error
+    NegErr {
+        /// Subscription ID
+        subscription_id: Cow<'a, str>,
+        /// Message
+        message: Cow<'a, str>,
+    },
+}
+
+impl<'a> RawRelayMessage<'a> {
+    /// Partially parse relay message from JSON
+    ///
+    /// **This method doesn't verify the event ID or signature!**
+    pub fn from_json<T>(json: &'a T) -> Result<Self, MessageHandleError>
+    where
+        T: AsRef<[u8]> + ?Sized,
+    {
+        from_json(json.as_ref())
+    }
+
+    /// Get subscription ID
+    pub fn subscription_id(&self) -> Option<&str> {
+        match self {
+            Self::Event {
+                subscription_id, ..
+            } => Some(subscription_id),
+            Self::EndOfStoredEvents(subscription_id) => Some(subscription_id),
+            Self::Closed {
+                subscription_id, ..
+            } => Some(subscription_id),
+            Self::Count { subscription_id } => Some(subscription_id),
+            Self::NegMsg {
+                subscription_id, ..
+            } => Some(subscription_id),
+            Self::NegErr {
+                subscription_id, ..
+            } => Some(subscription_id),
+            Self::Ok { .. } | Self::Notice(..) | Self::Auth { .. } => None,
+        }
+    }
+
+    /// Get event ID
+    pub fn event_id(&self) -> Option<&EventId> {
+        match self {
+            Self::Event { event_id, .. } => Some(event_id),
+            Self::Ok { event_id, .. } => Some(event_id),
+            _ => None,
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for RawRelayMessage<'de> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_seq(RawRelayMessageVisitor)
+    }
+}
+
+struct RawRelayMessageVisitor;
+
+impl<'de> Visitor<'de> for RawRelayMessageVisitor {
+    type Value = RawRelayMessage<'de>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a relay message")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        // Index 0
+        let v_type: Str<'de> = next(&mut seq)?;
+
+        let msg: RawRelayMessage<'de> = match v_type.0.as_ref() {
+            // ["EVENT", <subscription id>, <event JSON>]
+            "EVENT" => RawRelayMessage::Event {
+                subscription_id: next::<_, Str>(&mut seq)?.0, // Index 1
+                event_id: next::<_, PartialEvent>(&mut seq)?.event_id()?, // Index 2
+            },
+            // ["OK", <event_id>, <true|false>, <message>]
+            "OK" => RawRelayMessage::Ok {
+                event_id: parse_event_id(&next::<_, Str>(&mut seq)?.0)?, // Index 1
+                status: next(&mut seq)?,                                 // Index 2
+                message: next::<_, Str>(&mut seq)?.0,                    // Index 3
+            },
+            // ["EOSE", <subscription_id>]
+            "EOSE" => RawRelayMessage::EndOfStoredEvents(next::<_, Str>(&mut seq)?.0), // Index 1
+            // ["NOTICE", <message>]
+            "NOTICE" => RawRelayMessage::Notice(next::<_, Str>(&mut seq)?.0), // Index 1
+            // ["CLOSED", <subscription_id>, <message>]
+            "CLOSED" => RawRelayMessage::Closed {
+                subscription_id: next::<_, Str>(&mut seq)?.0, // Index 1
+                message: next::<_, Str>(&mut seq)?.0,         // Index 2
+            },
+            // ["AUTH", <challenge>]
+            "AUTH" => RawRelayMessage::Auth {
+                challenge: next::<_, Str>(&mut seq)?.0, // Index 1
+            },
+            // ["COUNT", <subscription id>, {"count": num, "hll": <hex>}]
+            "COUNT" => RawRelayMessage::Count {
+                subscription_id: next::<_, Str>(&mut seq)?.0, // Index 1
+            },
+            // ["NEG-MSG", <subscription ID string>, <message, lowercase hex-encoded>]
+            "NEG-MSG" => RawRelayMessage::NegMsg {
+                subscription_id: next::<_, Str>(&mut seq)?.0, // Index 1
+                message: next::<_, Str>(&mut seq)?.0,         // Index 2
+            },
+            // ["NEG-ERR", <subscription ID string>, <reason-code>]
+            "NEG-ERR" => RawRelayMessage::NegErr {
+                subscription_id: next::<_, Str>(&mut seq)?.0, // Index 1
+                message: next::<_, Str>(&mut seq)?.0,         // Index 2
+            },
+            t => return Err(de::Error::custom(format_args!("unknown message type: {t}"))),
+        };
+
+        skip_remaining(&mut seq)?;
+
+        Ok(msg)
+    }
+}
+
+/// Raw client message
+///
+/// The strings are borrowed from the JSON input (allocated only if contain escape sequences),
+/// the events and the filters are NOT deserialized: only the event ID is extracted.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum RawClientMessage<'a> {
+    /// Event
+    Event(EventId),
+    /// Req (single or multi-filter)
+    Req {
+        /// Subscription ID
+        subscription_id: Cow<'a, str>,
+    },
+    /// Count
+    Count {
+        /// Subscription ID
+        subscription_id: Cow<'a, str>,
+    },
+    /// Close
+    Close(Cow<'a, str>),
+    /// Auth
+    Auth(EventId),
+    /// Negentropy Open
+    NegOpen {
+        /// Subscription ID
+        subscription_id: Cow<'a, str>,
+    },
+    /// Negentropy Message
+    NegMsg {
+        /// Subscription ID
+        subscription_id: Cow<'a, str>,
+        /// Message
+        message: Cow<'a, str>,
+    },
+    /// Negentropy Close
+    NegClose {
+        /// Subscription ID
+        subscription_id: Cow<'a, str>,
+    },
+}
+
+impl<'a> RawClientMessage<'a> {
+    /// Partially parse client message from JSON
+    ///
+    /// **This method doesn't verify the event ID or signature!**
+    pub fn from_json<T>(json: &'a T) -> Result<Self, MessageHandleError>
+    where
+        T: AsRef<[u8]> + ?Sized,
+    {
+        from_json(json.as_ref())
+    }
+
+    /// Get subscription ID
+    pub fn subscription_id(&self) -> Option<&str> {
+        match self {
+            Self::Req { subscription_id } => Some(subscription_id),
+            Self::Count { subscription_id } => Some(subscription_id),
+            Self::Close(subscription_id) => Some(subscription_id),
+            Self::NegOpen { subscription_id } => Some(subscription_id),
+            Self::NegMsg {
+                subscription_id, ..
+            } => Some(subscription_id),
+            Self::NegClose { subscription_id } => Some(subscription_id),
+            Self::Event(..) | Self::Auth(..) => None,
+        }
+    }
+
+    /// Get event ID
+    pub fn event_id(&self) -> Option<&EventId> {
+        match self {
+            Self::Event(event_id) => Some(event_id),
+            Self::Auth(event_id) => Some(event_id),
+            _ => None,
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for RawClientMessage<'de> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_seq(RawClientMessageVisitor)
+    }
+}
+
+struct RawClientMessageVisitor;
+
+impl<'de> Visitor<'de> for RawClientMessageVisitor {
+    type Value = RawClientMessage<'de>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a client message")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        // Index 0
+        let v_type: Str<'de> = next(&mut seq)?;
+
+        let msg: RawClientMessage<'de> = match v_type.0.as_ref() {
+            // ["EVENT", <event JSON>]
+            "EVENT" => RawClientMessage::Event(next::<_, PartialEvent>(&mut seq)?.event_id()?), // Index 1
+            // ["REQ", <subscription_id>, <filter JSON>, <filter JSON>...]
+            "REQ" => RawClientMessage::Req {
+                subscription_id: next::<_, Str>(&mut seq)?.0, // Index 1
+            },
+            // ["COUNT", <subscription_id>, <filter JSON>]
+            "COUNT" => RawClientMessage::Count {
+                subscription_id: next::<_, Str>(&mut seq)?.0, // Index 1
+            },
+            // ["CLOSE", <subscription_id>]
+            "CLOSE" => RawClientMessage::Close(next::<_, Str>(&mut seq)?.0), // Index 1
+            // ["AUTH", <event JSON>]
+            "AUTH" => RawClientMessage::Auth(next::<_, PartialEvent>(&mut seq)?.event_id()?), // Index 1
+            // ["NEG-OPEN", <subscription ID string>, <filter>, <initialMessage, lowercase hex-encoded>]
+            "NEG-OPEN" => RawClientMessage::NegOpen {
+                subscription_id: next::<_, Str>(&mut seq)?.0, // Index 1
+            },
+            // ["NEG-MSG", <subscription ID string>, <message, lowercase hex-encoded>]
+            "NEG-MSG" => RawClientMessage::NegMsg {
+                subscription_id: next::<_, Str>(&mut seq)?.0, // Index 1
+                message: next::<_, Str>(&mut seq)?.0,         // Index 2
+            },
+            // ["NEG-CLOSE", <subscription ID string>]
+            "NEG-CLOSE" => RawClientMessage::NegClose {
+                subscription_id: next::<_, Str>(&mut seq)?.0, // Index 1
+            },
+            t => return Err(de::Error::custom(format_args!("unknown message type: {t}"))),
+        };
+
+        skip_remaining(&mut seq)?;
+
+        Ok(msg)
+    }
+}
+
+/// Borrowed string (allocated only if contains escape sequences)
+#[derive(Deserialize)]
+#[serde(transparent)]
+struct Str<'a>(#[serde(borrow)] Cow<'a, str>);
+
+/// Event with only the ID deserialized (the other fields are skipped)
+#[derive(Deserialize)]
+struct PartialEvent<'a> {
+    #[serde(borrow)]
+    id: Cow<'a, str>,
+}
+
+impl PartialEvent<'_> {
+    #[inline]
+    fn event_id<E>(&self) -> Result<EventId, E>
+    where
+        E: de::Error,
+    {
+        parse_event_id(&self.id)
+    }
+}
+
+#[inline]
+fn parse_event_id<E>(id: &str) -> Result<EventId, E>
+where
+    E: de::Error,
+{
+    EventId::from_hex(id).map_err(de::Error::custom)
+}
+
+fn from_json<'a, T>(json: &'a [u8]) -> Result<T, MessageHandleError>
+where
+    T: Deserialize<'a>,
+{
+    if json.is_empty() {
+        return Err(MessageHandleError::InvalidMessageFormat);
+    }
+
+    Ok(serde_json::from_slice(json)?)
+}
+
+#[inline]
+fn next<'de, A, T>(seq: &mut A) -> Result<T, A::Error>
+where
+    A: SeqAccess<'de>,
+    T: Deserialize<'de>,
+{
+    seq.next_element()?
+        .ok_or_else(|| de::Error::custom("invalid message format"))
+}
+
+/// Skip the remaining elements, without deserializing them
+#[inline]
+fn skip_remaining<'de, A>(seq: &mut A) -> Result<(), A::Error>
+where
+    A: SeqAccess<'de>,
+{
+    while seq.next_element::<IgnoredAny>()?.is_some() {}
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    const EVENT_JSON: &str = r#"{"content":"hello","created_at":1612809991,"id":"2be17aa3031bdcb006f0fce80c146dea9c1c0268b0af2398bb673365c6444d45","kind":1,"pubkey":"f86c44a2de95d9149b51c6a29afeabba264c18e2fa7c49de93424a0c56947785","sig":"a5d9290ef9659083c490b303eb7ee41356d8778ff19f2f91776c8dc4443388a64ffcf336e61af4c25c05ac3ae952d1ced889ed655b67790891222aaa15b99fdd","tags":[["e","1"]]}"#;
+
+    #[test]
+    fn test_raw_relay_message_event() {
+        let json = format!(r#"["EVENT", "random_string", {EVENT_JSON}]"#);
+        let msg = RawRelayMessage::from_json(&json).unwrap();
+
+        let event_id =
+            EventId::from_hex("2be17aa3031bdcb006f0fce80c146dea9c1c0268b0af2398bb673365c6444d45")
+                .unwrap();
+        assert_eq!(
+            msg,
+            RawRelayMessage::Event {
+                subscription_id: Cow::Borrowed("random_string"),
+                event_id,
+            }
+        );
+        assert_eq!(msg.subscription_id(), Some("random_string"));
+        assert_eq!(msg.event_id(), Some(&event_id));
+
+        // Borrowed from input
+        if let RawRelayMessage::Event {
+            subscription_id, ..
+        } = msg
+        {
+            assert!(matches!(subscription_id, Cow::Borrowed(..)));
+        }
+
+        // Same as full parsing
+        let full = RelayMessage::from_json(&json).unwrap();
+        match full {
+            RelayMessage::Event {
+                subscription_id,
+                event,
+            } => {
+                assert_eq!(subscription_id.as_str(), "random_string");
+                assert_eq!(event.id, event_id);
+            }
+            _ => panic!("Expected event"),
+        }
+    }
+
+    #[test]
+    fn test_raw_relay_message_others() {
+        assert_eq!(
+            RawRelayMessage::from_json(
+                r#"["OK","b1a649ebe8b435ec71d3784793f3bbf4b93e64e17568a741aecd4c7ddeafce30",true,""]"#
+            )
+            .unwrap(),
+            RawRelayMessage::Ok {
+                event_id: EventId::from_hex(
+                    "b1a649ebe8b435ec71d3784793f3bbf4b93e64e17568a741aecd4c7ddeafce30"
+                )
+                .unwrap(),
+                status: true,
+                message: Cow::Borrowed(""),
+            }
+        );
+        assert_eq!(
+            RawRelayMessage::from_json(r#"["EOSE","sub\"1"]"#).unwrap(),
+            RawRelayMessage::EndOfStoredEvents(Cow::Owned(String::from("sub\"1")))
+        );
+        assert_eq!(
+            RawRelayMessage::from_json(r#"["COUNT","sub1",{"count":5,"hll":"00"}]"#)
+                .unwrap()
+                .subscription_id(),
+            Some("sub1")
+        );
+        assert_eq!(
+            RawRelayMessage::from_json(r#"["NOTICE","Invalid event format!"]"#).unwrap(),
+            RawRelayMessage::Notice(Cow::Borrowed("Invalid event format!"))
+        );
+    }
+
+    #[test]
+    fn test_raw_relay_message_invalid() {
+        assert!(matches!(
+            RawRelayMessage::from_json(""),
+            Err(MessageHandleError::InvalidMessageFormat)
+        ));
+        assert!(RawRelayMessage::from_json(r#"["EVENT","sub1"]"#).is_err());
+        assert!(RawRelayMessage::from_json(r#"["EVENT","sub1",{"kind":1}]"#).is_err());
+        assert!(RawRelayMessage::from_json(r#"["EVENT","sub1",{"id":"abc"}]"#).is_err());
+        assert!(RawRelayMessage::from_json(r#"["UNKNOWN","sub1"]"#).is_err());
+        assert!(RawRelayMessage::from_json(r#"{"type":"EOSE"}"#).is_err());
+    }
+
+    #[test]
+    fn test_raw_client_message() {
+        let json = format!(r#"["EVENT",{EVENT_JSON}]"#);
+        let msg = RawClientMessage::from_json(&json).unwrap();
+        assert_eq!(
+            msg.event_id(),
+            Some(
+                &EventId::from_hex(
+                    "2be17aa3031bdcb006f0fce80c146dea9c1c0268b0af2398bb673365c6444d45"
+                )
+                .unwrap()
+            )
+        );
+
+        let msg =
+            RawClientMessage::from_json(r#"["REQ","sub1",{"kinds":[1]},{"kinds":[0]}]"#).unwrap();
+        assert_eq!(
+            msg,
+            RawClientMessage::Req {
+                subscription_id: Cow::Borrowed("sub1")
+            }
+        );
+
+        let msg = RawClientMessage::from_json(r#"["CLOSE","sub1"]"#).unwrap();
+        assert_eq!(msg, RawClientMessage::Close(Cow::Borrowed("sub1")));
+        assert_eq!(msg.subscription_id(), Some("sub1"));
+    }
+}
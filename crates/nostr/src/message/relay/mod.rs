@@ -15,6 +15,7 @@ use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde_json::{json, Value};
 
 use super::MessageHandleError;
+use crate::nips::nip45::Hll;
 use crate::{Event, EventId, JsonUtil, SubscriptionId};
 
 /// Machine-readable prefixes for `OK` and `CLOSED` relay messages
@@ -168,7 +169,7 @@ pub enum RelayMessage<'a> {
     },
     /// Count
     ///
-    /// `["COUNT", <subscription_id>, {"count": <integer>}]`
+    /// `["COUNT", <subscription_id>, {"count": <integer>, "hll": <hex>}]`
     ///
     /// <https://github.com/nostr-protocol/nips/blob/master/45.md>
     Count {
@@ -176,6 +177,8 @@ pub enum RelayMessage<'a> {
         subscription_id: Cow<'a, SubscriptionId>,
         /// Events count
         count: usize,
+        /// HyperLogLog sketch
+        hll: Option<Cow<'a, Hll>>,
     },
     /// Negentropy Message
     NegMsg {
@@ -254,12 +257,25 @@ impl RelayMessage<'_> {
         }
     }
 
-    /// Create  `EVENT` message
+    /// Create `COUNT` message
     #[inline]
     pub fn count(subscription_id: SubscriptionId, count: usize) -> Self {
         Self::Count {
             subscription_id: Cow::Owned(subscription_id),
             count,
+            hll: None,
+        }
+    }
+
+    /// Create `COUNT` message with HyperLogLog sketch
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/45.md>
+    #[inline]
+    pub fn count_with_hll(subscription_id: SubscriptionId, count: usize, hll: Hll) -> Self {
+        Self::Count {
+            subscription_id: Cow::Owned(subscription_id),
+            count,
+            hll: Some(Cow::Owned(hll)),
         }
     }
 
@@ -318,13 +334,14 @@ impl RelayMessage<'_> {
                 })
             }
             "COUNT" => {
-                // ["COUNT", <subscription id>, {"count": num}]
+                // ["COUNT", <subscription id>, {"count": num, "hll": <hex>}]
                 let subscription_id: SubscriptionId = next_and_deser(&mut v_iter)?; // Index 1
-                let Count { count } = next_and_deser(&mut v_iter)?; // Index 2
+                let Count { count, hll } = next_and_deser(&mut v_iter)?; // Index 2
 
                 Ok(Self::Count {
                     subscription_id: Cow::Owned(subscription_id),
                     count,
+                    hll: hll.map(Cow::Owned),
                 })
             }
             "NEG-MSG" => {
@@ -368,7 +385,13 @@ impl RelayMessage<'_> {
             Self::Count {
                 subscription_id,
                 count,
+                hll: None,
             } => json!(["COUNT", subscription_id, { "count": count }]),
+            Self::Count {
+                subscription_id,
+                count,
+                hll: Some(hll),
+            } => json!(["COUNT", subscription_id, { "count": count, "hll": hll }]),
             Self::NegMsg {
                 subscription_id,
                 message,
@@ -436,6 +459,8 @@ where
 #[derive(Deserialize)]
 struct Count {
     count: usize,
+    #[serde(default)]
+    hll: Option<Hll>,
 }
 
 #[cfg(feature = "std")]
@@ -595,6 +620,28 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_handle_count() {
+        let msg = r#"["COUNT","sub",{"count":42}]"#;
+        let count = RelayMessage::from_json(msg).unwrap();
+        assert_eq!(count, RelayMessage::count(SubscriptionId::new("sub"), 42));
+        assert_eq!(count.as_json(), msg);
+
+        let mut hll = Hll::new();
+        hll.add(
+            8,
+            &PublicKey::from_str(
+                "379e863e8357163b5bce5d2688dc4f1dcc2d505222fb8d74db600f30535dfdfe",
+            )
+            .unwrap(),
+        );
+        let count = RelayMessage::count_with_hll(SubscriptionId::new("sub"), 1, hll);
+        assert_eq!(RelayMessage::from_json(count.as_json()).unwrap(), count);
+
+        // Invalid HLL
+        assert!(RelayMessage::from_json(r#"["COUNT","sub",{"count":1,"hll":"00"}]"#).is_err());
+    }
+
     #[test]
     fn parse_message() {
         // Got this fresh off the wire
@@ -8,6 +8,8 @@
 
 pub mod nip01;
 pub mod nip02;
+#[cfg(feature = "nip03")]
+pub mod nip03;
 #[cfg(feature = "nip04")]
 pub mod nip04;
 pub mod nip05;
@@ -23,6 +25,7 @@ pub mod nip19;
 pub mod nip21;
 pub mod nip22;
 pub mod nip25;
+pub mod nip28;
 pub mod nip34;
 pub mod nip35;
 pub mod nip38;
@@ -30,6 +33,7 @@ pub mod nip39;
 pub mod nip42;
 #[cfg(feature = "nip44")]
 pub mod nip44;
+pub mod nip45;
 #[cfg(all(feature = "std", feature = "nip46"))]
 pub mod nip46;
 #[cfg(feature = "nip47")]
@@ -37,8 +41,10 @@ pub mod nip47;
 pub mod nip48;
 #[cfg(feature = "nip49")]
 pub mod nip49;
+pub mod nip50;
 pub mod nip51;
 pub mod nip53;
+pub mod nip54;
 pub mod nip56;
 #[cfg(feature = "nip57")]
 pub mod nip57;
@@ -47,9 +53,18 @@ pub mod nip58;
 pub mod nip59;
 pub mod nip62;
 pub mod nip65;
+pub mod nip68;
+pub mod nip69;
+pub mod nip71;
+pub mod nip72;
 pub mod nip73;
+pub mod nip75;
+pub mod nip78;
+pub mod nip84;
 pub mod nip88;
+pub mod nip89;
 pub mod nip90;
+pub mod nip92;
 pub mod nip94;
 #[cfg(all(feature = "std", feature = "nip96"))]
 pub mod nip96;
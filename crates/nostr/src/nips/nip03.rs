@@ -0,0 +1,659 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2025 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! NIP03: OpenTimestamps Attestations for Events
+//!
+//! <https://github.com/nostr-protocol/nips/blob/master/03.md>
+
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+
+use base64::engine::{general_purpose, Engine};
+use hashes::ripemd160::Hash as Ripemd160Hash;
+use hashes::sha1::Hash as Sha1Hash;
+use hashes::sha256::Hash as Sha256Hash;
+use hashes::Hash;
+
+use crate::types::RelayUrl;
+use crate::util::BoxedFuture;
+use crate::{Event, EventId, Kind, TagStandard, Timestamp};
+
+const HEADER_MAGIC: &[u8] = b"\x00OpenTimestamps\x00\x00Proof\x00\xbf\x89\xe2\xe8\x84\xe8\x92\x94";
+const VERSION: u64 = 1;
+const MAX_RECURSION_DEPTH: usize = 256;
+const MAX_OP_LENGTH: usize = 4096;
+
+const TAG_ATTESTATION: u8 = 0x00;
+const TAG_FORK: u8 = 0xff;
+const TAG_SHA1: u8 = 0x02;
+const TAG_RIPEMD160: u8 = 0x03;
+const TAG_SHA256: u8 = 0x08;
+const TAG_APPEND: u8 = 0xf0;
+const TAG_PREPEND: u8 = 0xf1;
+const TAG_REVERSE: u8 = 0xf2;
+const TAG_HEXLIFY: u8 = 0xf3;
+
+const BITCOIN_ATTESTATION: [u8; 8] = [0x05, 0x88, 0x96, 0x0d, 0x73, 0xd7, 0x19, 0x01];
+const PENDING_ATTESTATION: [u8; 8] = [0x83, 0xdf, 0xe3, 0x0d, 0x2e, 0xf9, 0x0c, 0x8e];
+
+/// NIP03 error
+#[derive(Debug, PartialEq, Eq)]
+pub enum Error {
+    /// Wrong event kind
+    WrongKind {
+        /// The received kind
+        received: Kind,
+        /// The expected kind
+        expected: Kind,
+    },
+    /// Event tag not found
+    EventTagNotFound,
+    /// Base64 decode error
+    Base64(base64::DecodeError),
+    /// Malformed proof
+    MalformedProof(&'static str),
+    /// Unsupported proof version
+    UnsupportedVersion(u64),
+    /// Unsupported operation
+    UnsupportedOperation(u8),
+    /// The proof doesn't commit to the referenced event ID
+    DigestMismatch,
+    /// The proof has no Bitcoin attestations (i.e. still pending)
+    NotAttested,
+    /// Block header not found
+    BlockHeaderNotFound(u32),
+    /// The proof doesn't match the block merkle root
+    MerkleRootMismatch(u32),
+    /// Block header source error
+    HeaderSource(String),
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::WrongKind { received, expected } => {
+                write!(f, "wrong kind: received={received}, expected={expected}")
+            }
+            Self::EventTagNotFound => f.write_str("event tag not found"),
+            Self::Base64(e) => e.fmt(f),
+            Self::MalformedProof(reason) => write!(f, "malformed proof: {reason}"),
+            Self::UnsupportedVersion(v) => write!(f, "unsupported proof version: {v}"),
+            Self::UnsupportedOperation(tag) => write!(f, "unsupported operation: {tag:#04x}"),
+            Self::DigestMismatch => f.write_str("the proof doesn't commit to the event ID"),
+            Self::NotAttested => f.write_str("the proof has no bitcoin attestations"),
+            Self::BlockHeaderNotFound(height) => write!(f, "block header not found: {height}"),
+            Self::MerkleRootMismatch(height) => {
+                write!(f, "merkle root mismatch at height {height}")
+            }
+            Self::HeaderSource(e) => write!(f, "block header source: {e}"),
+        }
+    }
+}
+
+impl From<base64::DecodeError> for Error {
+    fn from(e: base64::DecodeError) -> Self {
+        Self::Base64(e)
+    }
+}
+
+impl Error {
+    /// New block header source error
+    #[inline]
+    pub fn header_source<E>(error: E) -> Self
+    where
+        E: fmt::Display,
+    {
+        Self::HeaderSource(error.to_string())
+    }
+}
+
+/// Timestamp attestation
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Attestation {
+    /// Committed in the merkle root of a Bitcoin block
+    Bitcoin {
+        /// Block height
+        height: u32,
+    },
+    /// Pending, to be upgraded from the calendar server
+    Pending {
+        /// Calendar URI
+        uri: String,
+    },
+    /// Unknown attestation
+    Unknown {
+        /// Attestation tag
+        tag: [u8; 8],
+        /// Payload
+        payload: Vec<u8>,
+    },
+}
+
+/// Bitcoin block header
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct BlockHeader {
+    /// Merkle root, in the same byte order of the serialized header
+    pub merkle_root: [u8; 32],
+    /// Block timestamp
+    pub time: Timestamp,
+}
+
+/// Source of Bitcoin block headers
+///
+/// Can be backed by a node, a block explorer or (e.g. for testing) a fixed set of headers.
+pub trait BlockHeaderSource: fmt::Debug {
+    /// Get the header of the block at `height`
+    ///
+    /// Returns [`None`] if the block doesn't exist (yet).
+    fn block_header(&self, height: u32) -> BoxedFuture<'_, Result<Option<BlockHeader>, Error>>;
+}
+
+impl BlockHeaderSource for BTreeMap<u32, BlockHeader> {
+    fn block_header(&self, height: u32) -> BoxedFuture<'_, Result<Option<BlockHeader>, Error>> {
+        let header: Option<BlockHeader> = self.get(&height).copied();
+        Box::pin(async move { Ok(header) })
+    }
+}
+
+/// Parsed OpenTimestamps proof (detached timestamp file)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OpenTimestampsProof {
+    /// The timestamped SHA256 digest
+    pub digest: Sha256Hash,
+    /// Attestations and the commitments they attest
+    attestations: Vec<(Attestation, Vec<u8>)>,
+}
+
+impl OpenTimestampsProof {
+    /// Parse detached timestamp file
+    pub fn from_slice(data: &[u8]) -> Result<Self, Error> {
+        let mut reader: Reader = Reader { data, pos: 0 };
+
+        if reader.read_bytes(HEADER_MAGIC.len())? != HEADER_MAGIC {
+            return Err(Error::MalformedProof("invalid header magic"));
+        }
+
+        let version: u64 = reader.read_varuint()?;
+        if version != VERSION {
+            return Err(Error::UnsupportedVersion(version));
+        }
+
+        // NIP03 timestamps the event ID, so only SHA256 is allowed
+        let op: u8 = reader.read_u8()?;
+        if op != TAG_SHA256 {
+            return Err(Error::UnsupportedOperation(op));
+        }
+
+        let digest: &[u8] = reader.read_bytes(32)?;
+        let digest: Sha256Hash =
+            Sha256Hash::from_slice(digest).map_err(|_| Error::MalformedProof("invalid digest"))?;
+
+        let mut attestations: Vec<(Attestation, Vec<u8>)> = Vec::new();
+        reader.read_timestamp(digest.as_byte_array(), 0, &mut attestations)?;
+
+        if !reader.is_empty() {
+            return Err(Error::MalformedProof("trailing bytes"));
+        }
+
+        Ok(Self {
+            digest,
+            attestations,
+        })
+    }
+
+    /// Get the attestations, with the commitment attested by each of them
+    ///
+    /// For [`Attestation::Bitcoin`], the commitment is the merkle root of the block.
+    #[inline]
+    pub fn attestations(&self) -> impl Iterator<Item = (&Attestation, &[u8])> {
+        self.attestations.iter().map(|(a, c)| (a, c.as_slice()))
+    }
+
+    /// Check if there are only pending (or unknown) attestations
+    #[inline]
+    pub fn is_pending(&self) -> bool {
+        !self
+            .attestations
+            .iter()
+            .any(|(a, ..)| matches!(a, Attestation::Bitcoin { .. }))
+    }
+
+    /// Verify the Bitcoin attestations against the block headers
+    ///
+    /// Returns the earliest verified block.
+    pub async fn verify<S>(&self, source: &S) -> Result<VerifiedAttestation, Error>
+    where
+        S: BlockHeaderSource + ?Sized,
+    {
+        let mut bitcoin: Vec<(u32, &[u8])> = self
+            .attestations
+            .iter()
+            .filter_map(|(attestation, commitment)| match attestation {
+                Attestation::Bitcoin { height } => Some((*height, commitment.as_slice())),
+                _ => None,
+            })
+            .collect();
+        bitcoin.sort_by_key(|(height, ..)| *height);
+
+        let mut error: Error = Error::NotAttested;
+
+        for (height, commitment) in bitcoin.into_iter() {
+            match source.block_header(height).await? {
+                Some(header) if header.merkle_root.as_slice() == commitment => {
+                    return Ok(VerifiedAttestation {
+                        height,
+                        time: header.time,
+                    });
+                }
+                Some(..) => error = Error::MerkleRootMismatch(height),
+                None => error = Error::BlockHeaderNotFound(height),
+            }
+        }
+
+        Err(error)
+    }
+}
+
+/// Verified Bitcoin attestation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct VerifiedAttestation {
+    /// Block height
+    pub height: u32,
+    /// Block timestamp
+    ///
+    /// The event existed before this time.
+    pub time: Timestamp,
+}
+
+/// OpenTimestamps attestation for an event (kind `1040`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OpenTimestamps {
+    /// Timestamped event
+    pub event_id: EventId,
+    /// Relay hint
+    pub relay_url: Option<RelayUrl>,
+    /// Kind of the timestamped event
+    pub kind: Option<Kind>,
+    /// Proof
+    pub proof: OpenTimestampsProof,
+}
+
+impl OpenTimestamps {
+    /// Parse attestation from an [`Event`] (kind `1040`)
+    ///
+    /// Check that the proof commits to the referenced event ID.
+    pub fn from_event(event: &Event) -> Result<Self, Error> {
+        if event.kind != Kind::OpenTimestamps {
+            return Err(Error::WrongKind {
+                received: event.kind,
+                expected: Kind::OpenTimestamps,
+            });
+        }
+
+        let mut target: Option<(EventId, Option<RelayUrl>)> = None;
+        let mut kind: Option<Kind> = None;
+
+        for tag in event.tags.iter() {
+            match tag.as_standardized() {
+                Some(TagStandard::Event {
+                    event_id,
+                    relay_url,
+                    uppercase: false,
+                    ..
+                }) => target = Some((*event_id, relay_url.clone())),
+                Some(TagStandard::Kind {
+                    kind: k,
+                    uppercase: false,
+                }) => kind = Some(*k),
+                _ => {}
+            }
+        }
+
+        let (event_id, relay_url) = target.ok_or(Error::EventTagNotFound)?;
+
+        let data: Vec<u8> = general_purpose::STANDARD.decode(event.content.trim())?;
+        let proof: OpenTimestampsProof = OpenTimestampsProof::from_slice(&data)?;
+
+        if proof.digest.as_byte_array() != event_id.as_bytes() {
+            return Err(Error::DigestMismatch);
+        }
+
+        Ok(Self {
+            event_id,
+            relay_url,
+            kind,
+            proof,
+        })
+    }
+
+    /// Verify the proof against the block headers
+    ///
+    /// Check [`OpenTimestampsProof::verify`] for more details.
+    #[inline]
+    pub async fn verify<S>(&self, source: &S) -> Result<VerifiedAttestation, Error>
+    where
+        S: BlockHeaderSource + ?Sized,
+    {
+        self.proof.verify(source).await
+    }
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.pos >= self.data.len()
+    }
+
+    fn read_u8(&mut self) -> Result<u8, Error> {
+        let byte: u8 = *self
+            .data
+            .get(self.pos)
+            .ok_or(Error::MalformedProof("unexpected end"))?;
+        self.pos += 1;
+        Ok(byte)
+    }
+
+    fn read_bytes(&mut self, len: usize) -> Result<&'a [u8], Error> {
+        let end: usize = self
+            .pos
+            .checked_add(len)
+            .ok_or(Error::MalformedProof("unexpected end"))?;
+        let bytes: &[u8] = self
+            .data
+            .get(self.pos..end)
+            .ok_or(Error::MalformedProof("unexpected end"))?;
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    /// Read LEB128 unsigned integer
+    fn read_varuint(&mut self) -> Result<u64, Error> {
+        let mut value: u64 = 0;
+        let mut shift: u32 = 0;
+
+        loop {
+            let byte: u8 = self.read_u8()?;
+
+            if shift > 63 {
+                return Err(Error::MalformedProof("varuint overflow"));
+            }
+
+            value |= u64::from(byte & 0x7f) << shift;
+
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+
+            shift += 7;
+        }
+    }
+
+    fn read_varbytes(&mut self, max: usize) -> Result<&'a [u8], Error> {
+        let len: usize =
+            usize::try_from(self.read_varuint()?).map_err(|_| Error::MalformedProof("too long"))?;
+
+        if len > max {
+            return Err(Error::MalformedProof("too long"));
+        }
+
+        self.read_bytes(len)
+    }
+
+    /// Read a timestamp, executing the operations on `msg`
+    fn read_timestamp(
+        &mut self,
+        msg: &[u8],
+        depth: usize,
+        attestations: &mut Vec<(Attestation, Vec<u8>)>,
+    ) -> Result<(), Error> {
+        if depth > MAX_RECURSION_DEPTH {
+            return Err(Error::MalformedProof("recursion limit exceeded"));
+        }
+
+        let mut tag: u8 = self.read_u8()?;
+
+        // Forks: all the branches but the last are prefixed
+        while tag == TAG_FORK {
+            let branch: u8 = self.read_u8()?;
+            self.read_branch(branch, msg, depth, attestations)?;
+            tag = self.read_u8()?;
+        }
+
+        self.read_branch(tag, msg, depth, attestations)
+    }
+
+    fn read_branch(
+        &mut self,
+        tag: u8,
+        msg: &[u8],
+        depth: usize,
+        attestations: &mut Vec<(Attestation, Vec<u8>)>,
+    ) -> Result<(), Error> {
+        if tag == TAG_ATTESTATION {
+            let attestation: Attestation = self.read_attestation()?;
+            attestations.push((attestation, msg.to_vec()));
+            return Ok(());
+        }
+
+        let result: Vec<u8> = match tag {
+            TAG_SHA1 => Sha1Hash::hash(msg).to_byte_array().to_vec(),
+            TAG_RIPEMD160 => Ripemd160Hash::hash(msg).to_byte_array().to_vec(),
+            TAG_SHA256 => Sha256Hash::hash(msg).to_byte_array().to_vec(),
+            TAG_APPEND => {
+                let arg: &[u8] = self.read_varbytes(MAX_OP_LENGTH)?;
+                [msg, arg].concat()
+            }
+            TAG_PREPEND => {
+                let arg: &[u8] = self.read_varbytes(MAX_OP_LENGTH)?;
+                [arg, msg].concat()
+            }
+            TAG_REVERSE => msg.iter().rev().copied().collect(),
+            TAG_HEXLIFY => hex::encode(msg).into_bytes(),
+            // Keccak256 (Ethereum attestations) isn't supported
+            tag => return Err(Error::UnsupportedOperation(tag)),
+        };
+
+        if result.len() > MAX_OP_LENGTH {
+            return Err(Error::MalformedProof("message too long"));
+        }
+
+        self.read_timestamp(&result, depth + 1, attestations)
+    }
+
+    fn read_attestation(&mut self) -> Result<Attestation, Error> {
+        let mut tag: [u8; 8] = [0u8; 8];
+        tag.copy_from_slice(self.read_bytes(8)?);
+
+        let payload: &[u8] = self.read_varbytes(8192)?;
+
+        match tag {
+            BITCOIN_ATTESTATION => {
+                let mut payload: Reader = Reader {
+                    data: payload,
+                    pos: 0,
+                };
+                let height: u64 = payload.read_varuint()?;
+                let height: u32 = u32::try_from(height)
+                    .map_err(|_| Error::MalformedProof("invalid block height"))?;
+                Ok(Attestation::Bitcoin { height })
+            }
+            PENDING_ATTESTATION => {
+                let mut payload: Reader = Reader {
+                    data: payload,
+                    pos: 0,
+                };
+                let uri: &[u8] = payload.read_varbytes(1000)?;
+                let uri: String = String::from_utf8(uri.to_vec())
+                    .map_err(|_| Error::MalformedProof("invalid calendar uri"))?;
+                Ok(Attestation::Pending { uri })
+            }
+            tag => Ok(Attestation::Unknown {
+                tag,
+                payload: payload.to_vec(),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EventBuilder, Keys, Tag};
+
+    fn varuint(mut value: u64) -> Vec<u8> {
+        let mut out = Vec::new();
+        loop {
+            let byte = (value & 0x7f) as u8;
+            value >>= 7;
+            if value == 0 {
+                out.push(byte);
+                return out;
+            }
+            out.push(byte | 0x80);
+        }
+    }
+
+    fn varbytes(bytes: &[u8]) -> Vec<u8> {
+        [varuint(bytes.len() as u64), bytes.to_vec()].concat()
+    }
+
+    /// Build a proof: `append(nonce) -> sha256 -> fork[pending, prepend(x) -> sha256 -> bitcoin]`
+    ///
+    /// Returns the proof and the commitment of the bitcoin attestation.
+    fn build_proof(digest: &[u8; 32], height: u32) -> (Vec<u8>, [u8; 32]) {
+        let nonce = [0xaa; 16];
+        let prefix = [0xbb; 32];
+
+        let mut proof = HEADER_MAGIC.to_vec();
+        proof.extend(varuint(VERSION));
+        proof.push(TAG_SHA256);
+        proof.extend(digest);
+
+        proof.push(TAG_APPEND);
+        proof.extend(varbytes(&nonce));
+        proof.push(TAG_SHA256);
+
+        // Pending branch
+        proof.push(TAG_FORK);
+        proof.push(TAG_ATTESTATION);
+        proof.extend(PENDING_ATTESTATION);
+        proof.extend(varbytes(&varbytes(
+            b"https://alice.btc.calendar.opentimestamps.org",
+        )));
+
+        // Bitcoin branch
+        proof.push(TAG_PREPEND);
+        proof.extend(varbytes(&prefix));
+        proof.push(TAG_SHA256);
+        proof.push(TAG_ATTESTATION);
+        proof.extend(BITCOIN_ATTESTATION);
+        proof.extend(varbytes(&varuint(height as u64)));
+
+        let msg = Sha256Hash::hash(&[digest.as_slice(), &nonce].concat());
+        let commitment = Sha256Hash::hash(&[prefix.as_slice(), msg.as_byte_array()].concat());
+
+        (proof, commitment.to_byte_array())
+    }
+
+    fn attestation_event(keys: &Keys, event_id: EventId, proof: &[u8]) -> Event {
+        EventBuilder::new(
+            Kind::OpenTimestamps,
+            general_purpose::STANDARD.encode(proof),
+        )
+        .tags([
+            Tag::event(event_id),
+            Tag::from_standardized_without_cell(TagStandard::Kind {
+                kind: Kind::TextNote,
+                uppercase: false,
+            }),
+        ])
+        .sign_with_keys(keys)
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_verify_attestation() {
+        let keys = Keys::generate();
+        let note = EventBuilder::text_note("Hello")
+            .sign_with_keys(&keys)
+            .unwrap();
+
+        let (proof, merkle_root) = build_proof(note.id.as_bytes(), 800_000);
+        let event = attestation_event(&keys, note.id, &proof);
+
+        let ots = OpenTimestamps::from_event(&event).unwrap();
+        assert_eq!(ots.event_id, note.id);
+        assert_eq!(ots.kind, Some(Kind::TextNote));
+        assert!(!ots.proof.is_pending());
+        assert_eq!(ots.proof.attestations().count(), 2);
+
+        // Valid header
+        let time = Timestamp::from(1_689_000_000);
+        let mut headers: BTreeMap<u32, BlockHeader> = BTreeMap::new();
+        headers.insert(800_000, BlockHeader { merkle_root, time });
+        assert_eq!(
+            ots.verify(&headers).await.unwrap(),
+            VerifiedAttestation {
+                height: 800_000,
+                time
+            }
+        );
+
+        // Wrong merkle root
+        headers.insert(
+            800_000,
+            BlockHeader {
+                merkle_root: [0u8; 32],
+                time,
+            },
+        );
+        assert_eq!(
+            ots.verify(&headers).await.unwrap_err(),
+            Error::MerkleRootMismatch(800_000)
+        );
+
+        // Missing header
+        assert_eq!(
+            ots.verify(&BTreeMap::new()).await.unwrap_err(),
+            Error::BlockHeaderNotFound(800_000)
+        );
+    }
+
+    #[test]
+    fn test_digest_mismatch() {
+        let keys = Keys::generate();
+        let note = EventBuilder::text_note("Hello")
+            .sign_with_keys(&keys)
+            .unwrap();
+        let other = EventBuilder::text_note("World")
+            .sign_with_keys(&keys)
+            .unwrap();
+
+        let (proof, ..) = build_proof(other.id.as_bytes(), 800_000);
+        let event = attestation_event(&keys, note.id, &proof);
+        assert_eq!(
+            OpenTimestamps::from_event(&event).unwrap_err(),
+            Error::DigestMismatch
+        );
+
+        // Truncated proof
+        let (proof, ..) = build_proof(note.id.as_bytes(), 800_000);
+        let event = attestation_event(&keys, note.id, &proof[..proof.len() - 2]);
+        assert!(matches!(
+            OpenTimestamps::from_event(&event).unwrap_err(),
+            Error::MalformedProof(..)
+        ));
+    }
+}
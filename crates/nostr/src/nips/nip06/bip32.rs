@@ -111,6 +111,7 @@ impl ChildNumber {
 }
 
 /// Extended private key
+#[derive(Clone)]
 pub struct Xpriv {
     /// How many derivations this key is from the master (which is 0)
     pub depth: u8,
@@ -6,23 +6,31 @@
 //!
 //! <https://github.com/nostr-protocol/nips/blob/master/06.md>
 
+use alloc::string::{String, ToString};
 use alloc::vec;
 use alloc::vec::Vec;
 use core::fmt;
+use core::str::FromStr;
 
 use bip39::Mnemonic;
 use secp256k1::{Secp256k1, Signing};
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 mod bip32;
 
 use self::bip32::{ChildNumber, Xpriv};
+use crate::util::BoxedFuture;
 #[cfg(feature = "std")]
 use crate::SECP256K1;
-use crate::{Keys, SecretKey};
+use crate::{JsonUtil, Keys, Kind, PublicKey, SecretKey};
 
 const PURPOSE: u32 = 44;
 const COIN: u32 = 1237;
 
+/// Kinds checked during the account discovery
+pub const DISCOVERY_KINDS: [Kind; 3] = [Kind::Metadata, Kind::ContactList, Kind::RelayList];
+
 /// `NIP06` error
 #[derive(Debug, Eq, PartialEq)]
 pub enum Error {
@@ -30,6 +38,14 @@ pub enum Error {
     BIP32(bip32::Error),
     /// BIP39 error
     BIP39(bip39::Error),
+    /// Json error
+    Json(String),
+    /// Invalid derivation path
+    InvalidDerivationPath,
+    /// The public key derived at the path doesn't match the expected one (i.e., wrong mnemonic or passphrase)
+    PublicKeyMismatch(DerivationPath),
+    /// Account lookup error
+    Lookup(String),
 }
 
 #[cfg(feature = "std")]
@@ -40,6 +56,10 @@ impl fmt::Display for Error {
         match self {
             Self::BIP32(e) => e.fmt(f),
             Self::BIP39(e) => e.fmt(f),
+            Self::Json(e) => e.fmt(f),
+            Self::InvalidDerivationPath => f.write_str("invalid derivation path"),
+            Self::PublicKeyMismatch(path) => write!(f, "public key mismatch at {path}"),
+            Self::Lookup(e) => write!(f, "account lookup: {e}"),
         }
     }
 }
@@ -56,6 +76,319 @@ impl From<bip39::Error> for Error {
     }
 }
 
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Self::Json(e.to_string())
+    }
+}
+
+/// NIP06 derivation path
+///
+/// `m/44'/1237'/<account>'/<type>/<index>`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DerivationPath {
+    /// Account
+    pub account: u32,
+    /// Type
+    pub r#type: u32,
+    /// Index
+    pub index: u32,
+}
+
+impl DerivationPath {
+    /// Construct derivation path for `account`, with `type` and `index` set to `0`
+    #[inline]
+    pub fn new(account: u32) -> Self {
+        Self {
+            account,
+            r#type: 0,
+            index: 0,
+        }
+    }
+
+    fn child_numbers(&self) -> Result<Vec<ChildNumber>, Error> {
+        Ok(vec![
+            ChildNumber::from_hardened_idx(PURPOSE)?,
+            ChildNumber::from_hardened_idx(COIN)?,
+            ChildNumber::from_hardened_idx(self.account)?,
+            ChildNumber::from_normal_idx(self.r#type)?,
+            ChildNumber::from_normal_idx(self.index)?,
+        ])
+    }
+}
+
+impl fmt::Display for DerivationPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "m/{PURPOSE}'/{COIN}'/{}'/{}/{}",
+            self.account, self.r#type, self.index
+        )
+    }
+}
+
+impl FromStr for DerivationPath {
+    type Err = Error;
+
+    fn from_str(path: &str) -> Result<Self, Self::Err> {
+        let mut iter = path.split('/');
+
+        if iter.next() != Some("m") {
+            return Err(Error::InvalidDerivationPath);
+        }
+
+        let purpose: &str = iter.next().ok_or(Error::InvalidDerivationPath)?;
+        let coin: &str = iter.next().ok_or(Error::InvalidDerivationPath)?;
+
+        if purpose != "44'" || coin != "1237'" {
+            return Err(Error::InvalidDerivationPath);
+        }
+
+        let account: &str = iter
+            .next()
+            .and_then(|a| a.strip_suffix('\''))
+            .ok_or(Error::InvalidDerivationPath)?;
+        let r#type: &str = iter.next().ok_or(Error::InvalidDerivationPath)?;
+        let index: &str = iter.next().ok_or(Error::InvalidDerivationPath)?;
+
+        if iter.next().is_some() {
+            return Err(Error::InvalidDerivationPath);
+        }
+
+        let path: Self = Self {
+            account: account.parse().map_err(|_| Error::InvalidDerivationPath)?,
+            r#type: r#type.parse().map_err(|_| Error::InvalidDerivationPath)?,
+            index: index.parse().map_err(|_| Error::InvalidDerivationPath)?,
+        };
+
+        // Check indexes range
+        path.child_numbers()?;
+
+        Ok(path)
+    }
+}
+
+impl Serialize for DerivationPath {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for DerivationPath {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let path: String = String::deserialize(deserializer)?;
+        Self::from_str(&path).map_err(D::Error::custom)
+    }
+}
+
+/// Account derived from a [`Seed`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DerivedAccount {
+    /// Derivation path
+    pub path: DerivationPath,
+    /// Keys
+    pub keys: Keys,
+}
+
+/// Seed-derived account set entry
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccountSetEntry {
+    /// Derivation path
+    pub path: DerivationPath,
+    /// Public key
+    pub public_key: PublicKey,
+}
+
+/// Seed-derived account set
+///
+/// Contains only the derivation paths and the public keys (no secrets): the keys are derived again
+/// from the [`Seed`] with [`Seed::import_accounts`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccountSet {
+    /// Accounts
+    pub accounts: Vec<AccountSetEntry>,
+}
+
+impl AccountSet {
+    /// Construct from derived accounts
+    pub fn new<'a, I>(accounts: I) -> Self
+    where
+        I: IntoIterator<Item = &'a DerivedAccount>,
+    {
+        Self {
+            accounts: accounts
+                .into_iter()
+                .map(|account| AccountSetEntry {
+                    path: account.path,
+                    public_key: account.keys.public_key(),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl JsonUtil for AccountSet {
+    type Err = Error;
+}
+
+/// Account lookup, used for account discovery
+///
+/// See [`Seed::discover`].
+pub trait AccountLookup: fmt::Debug + Send + Sync {
+    /// Check if there is at least one event of the `kinds` authored by the public key
+    fn has_events<'a>(
+        &'a self,
+        public_key: &'a PublicKey,
+        kinds: &'a [Kind],
+    ) -> BoxedFuture<'a, Result<bool, Error>>;
+}
+
+/// Master key derived from a BIP-39 mnemonic (ENGLISH wordlist) and an optional passphrase
+///
+/// Derive multiple accounts without computing the seed again.
+/// With a passphrase, a completely different set of accounts is derived from the same mnemonic.
+///
+/// <https://github.com/nostr-protocol/nips/blob/master/06.md>
+#[derive(Clone)]
+pub struct Seed {
+    root_key: Xpriv,
+}
+
+impl fmt::Debug for Seed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Seed").finish_non_exhaustive()
+    }
+}
+
+impl Seed {
+    /// Construct from BIP-39 mnemonic and optional passphrase
+    pub fn from_mnemonic<S>(mnemonic: S, passphrase: Option<S>) -> Result<Self, Error>
+    where
+        S: AsRef<str>,
+    {
+        // Parse mnemonic
+        let mnemonic: Mnemonic = Mnemonic::parse_normalized(mnemonic.as_ref())?;
+
+        // Convert mnemonic to seed
+        let seed: [u8; 64] = mnemonic
+            .to_seed_normalized(passphrase.as_ref().map(|s| s.as_ref()).unwrap_or_default());
+
+        // Derive BIP32 root key
+        Ok(Self {
+            root_key: Xpriv::new_master(&seed)?,
+        })
+    }
+
+    /// Derive [`Keys`] at the derivation path
+    pub fn derive_with_ctx<C>(
+        &self,
+        secp: &Secp256k1<C>,
+        path: &DerivationPath,
+    ) -> Result<Keys, Error>
+    where
+        C: Signing,
+    {
+        let child_xprv: Xpriv = self
+            .root_key
+            .clone()
+            .derive_xpriv(secp, path.child_numbers()?);
+        let secret_key: SecretKey = SecretKey::from(child_xprv.private_key);
+        Ok(Keys::new_with_ctx(secp, secret_key))
+    }
+
+    /// Derive [`Keys`] at the derivation path
+    #[inline]
+    #[cfg(feature = "std")]
+    pub fn derive(&self, path: &DerivationPath) -> Result<Keys, Error> {
+        self.derive_with_ctx(SECP256K1, path)
+    }
+
+    /// Derive consecutive accounts, starting from `first` account
+    #[cfg(feature = "std")]
+    pub fn derive_accounts(&self, first: u32, count: u32) -> Result<Vec<DerivedAccount>, Error> {
+        (first..first.saturating_add(count))
+            .map(|account| {
+                let path: DerivationPath = DerivationPath::new(account);
+                Ok(DerivedAccount {
+                    keys: self.derive(&path)?,
+                    path,
+                })
+            })
+            .collect()
+    }
+
+    /// Discover the used accounts
+    ///
+    /// Derive consecutive accounts, starting from account `0`, and check each for existing
+    /// [`DISCOVERY_KINDS`] events through the [`AccountLookup`].
+    /// The discovery stops after `gap_limit` consecutive unused accounts.
+    ///
+    /// Returns only the used accounts.
+    #[cfg(feature = "std")]
+    pub async fn discover<T>(
+        &self,
+        lookup: &T,
+        gap_limit: u32,
+    ) -> Result<Vec<DerivedAccount>, Error>
+    where
+        T: AccountLookup + ?Sized,
+    {
+        let mut accounts: Vec<DerivedAccount> = Vec::new();
+        let mut gap: u32 = 0;
+        let mut account: u32 = 0;
+
+        while gap < gap_limit {
+            let path: DerivationPath = DerivationPath::new(account);
+            let keys: Keys = self.derive(&path)?;
+
+            if lookup
+                .has_events(&keys.public_key(), &DISCOVERY_KINDS)
+                .await?
+            {
+                accounts.push(DerivedAccount { path, keys });
+                gap = 0;
+            } else {
+                gap += 1;
+            }
+
+            account = account.checked_add(1).ok_or(Error::InvalidDerivationPath)?;
+        }
+
+        Ok(accounts)
+    }
+
+    /// Import an [`AccountSet`], deriving the keys again from this seed
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::PublicKeyMismatch`] if a derived public key doesn't match the one in the set
+    /// (i.e., wrong mnemonic or passphrase).
+    #[cfg(feature = "std")]
+    pub fn import_accounts(&self, set: &AccountSet) -> Result<Vec<DerivedAccount>, Error> {
+        set.accounts
+            .iter()
+            .map(|entry| {
+                let keys: Keys = self.derive(&entry.path)?;
+
+                if keys.public_key() != entry.public_key {
+                    return Err(Error::PublicKeyMismatch(entry.path));
+                }
+
+                Ok(DerivedAccount {
+                    path: entry.path,
+                    keys,
+                })
+            })
+            .collect()
+    }
+}
+
 /// NIP06 utils
 ///
 /// <https://github.com/nostr-protocol/nips/blob/master/06.md>
@@ -142,36 +475,16 @@ impl FromMnemonic for Keys {
         C: Signing,
         S: AsRef<str>,
     {
-        // Parse mnemonic
-        let mnemonic: Mnemonic = Mnemonic::parse_normalized(mnemonic.as_ref())?;
-
-        // Convert mnemonic to seed
-        let seed: [u8; 64] = mnemonic
-            .to_seed_normalized(passphrase.as_ref().map(|s| s.as_ref()).unwrap_or_default());
-
-        // Derive BIP32 root key
-        let root_key: Xpriv = Xpriv::new_master(&seed)?;
-
-        // Unwrap idx
-        let account: u32 = account.unwrap_or_default();
-        let _type: u32 = r#type.unwrap_or_default();
-        let index: u32 = index.unwrap_or_default();
+        let seed: Seed = Seed::from_mnemonic(mnemonic, passphrase)?;
 
         // Compose derivation path
-        let path: Vec<ChildNumber> = vec![
-            ChildNumber::from_hardened_idx(PURPOSE)?,
-            ChildNumber::from_hardened_idx(COIN)?,
-            ChildNumber::from_hardened_idx(account)?,
-            ChildNumber::from_normal_idx(_type)?,
-            ChildNumber::from_normal_idx(index)?,
-        ];
-
-        // Derive secret key
-        let child_xprv = root_key.derive_xpriv(secp, path);
-        let secret_key = SecretKey::from(child_xprv.private_key);
+        let path: DerivationPath = DerivationPath {
+            account: account.unwrap_or_default(),
+            r#type: r#type.unwrap_or_default(),
+            index: index.unwrap_or_default(),
+        };
 
-        // Compose keys
-        Ok(Self::new_with_ctx(secp, secret_key))
+        seed.derive_with_ctx(secp, &path)
     }
 }
 
@@ -200,4 +513,120 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_derivation_path() {
+        let path = DerivationPath {
+            account: 1,
+            r#type: 0,
+            index: 2,
+        };
+        assert_eq!(path.to_string(), "m/44'/1237'/1'/0/2");
+        assert_eq!(
+            DerivationPath::from_str("m/44'/1237'/1'/0/2").unwrap(),
+            path
+        );
+
+        assert!(DerivationPath::from_str("m/44'/0'/1'/0/2").is_err());
+        assert!(DerivationPath::from_str("m/44'/1237'/1/0/2").is_err());
+        assert!(DerivationPath::from_str("m/44'/1237'/1'/0").is_err());
+        assert!(DerivationPath::from_str("m/44'/1237'/1'/0/2/3").is_err());
+        assert!(DerivationPath::from_str("m/44'/1237'/2147483648'/0/0").is_err());
+    }
+
+    #[test]
+    fn test_seed() {
+        let mnemonic =
+            "leader monkey parrot ring guide accident before fence cannon height naive bean";
+
+        let seed = Seed::from_mnemonic(mnemonic, None).unwrap();
+        let accounts = seed.derive_accounts(0, 3).unwrap();
+        assert_eq!(accounts.len(), 3);
+
+        for account in accounts.iter() {
+            let keys = Keys::from_mnemonic_with_account(mnemonic, None, Some(account.path.account))
+                .unwrap();
+            assert_eq!(account.keys, keys);
+        }
+
+        // Passphrase-protected wallet
+        let protected = Seed::from_mnemonic(mnemonic, Some("passphrase")).unwrap();
+        let keys = protected.derive(&DerivationPath::new(0)).unwrap();
+        assert_ne!(keys, accounts[0].keys);
+        assert_eq!(
+            keys,
+            Keys::from_mnemonic(mnemonic, Some("passphrase")).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_account_set() {
+        let mnemonic =
+            "leader monkey parrot ring guide accident before fence cannon height naive bean";
+        let seed = Seed::from_mnemonic(mnemonic, Some("passphrase")).unwrap();
+        let accounts = seed.derive_accounts(0, 2).unwrap();
+
+        let set = AccountSet::new(&accounts);
+        let json = set.as_json();
+        assert!(json.contains("m/44'/1237'/1'/0/0"));
+
+        let set = AccountSet::from_json(json).unwrap();
+        assert_eq!(seed.import_accounts(&set).unwrap(), accounts);
+
+        // Wrong passphrase
+        let seed = Seed::from_mnemonic(mnemonic, None).unwrap();
+        assert_eq!(
+            seed.import_accounts(&set).unwrap_err(),
+            Error::PublicKeyMismatch(DerivationPath::new(0))
+        );
+    }
+
+    #[derive(Debug)]
+    struct MockLookup {
+        used: Vec<PublicKey>,
+    }
+
+    impl AccountLookup for MockLookup {
+        fn has_events<'a>(
+            &'a self,
+            public_key: &'a PublicKey,
+            kinds: &'a [Kind],
+        ) -> BoxedFuture<'a, Result<bool, Error>> {
+            Box::pin(async move {
+                assert_eq!(kinds, DISCOVERY_KINDS);
+                Ok(self.used.contains(public_key))
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_discover() {
+        let mnemonic =
+            "leader monkey parrot ring guide accident before fence cannon height naive bean";
+        let seed = Seed::from_mnemonic(mnemonic, None).unwrap();
+        let accounts = seed.derive_accounts(0, 10).unwrap();
+
+        // Accounts 0, 2 and 5 are used
+        let lookup = MockLookup {
+            used: vec![
+                accounts[0].keys.public_key(),
+                accounts[2].keys.public_key(),
+                accounts[5].keys.public_key(),
+            ],
+        };
+
+        let discovered = seed.discover(&lookup, 3).await.unwrap();
+        assert_eq!(
+            discovered,
+            vec![
+                accounts[0].clone(),
+                accounts[2].clone(),
+                accounts[5].clone()
+            ]
+        );
+
+        // Account 5 is after a gap of 2
+        let discovered = seed.discover(&lookup, 2).await.unwrap();
+        assert_eq!(discovered, vec![accounts[0].clone(), accounts[2].clone()]);
+    }
 }
@@ -0,0 +1,625 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2025 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Parallel POW miner
+
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::future::Future;
+use core::num::NonZeroUsize;
+use core::pin::Pin;
+use core::sync::atomic::{AtomicBool, AtomicU64, AtomicU8, Ordering};
+use core::task::{Context, Poll, Waker};
+use core::time::Duration;
+use std::sync::Mutex;
+use std::thread;
+use std::time::Instant;
+
+use hashes::sha256::Hash as Sha256Hash;
+use hashes::{Hash, HashEngine};
+
+use super::{get_leading_zero_bits, Error};
+use crate::{EventId, Tag, TagKind, UnsignedEvent};
+
+/// Number of hashes computed by a thread before checking the stop conditions
+const BATCH_SIZE: u64 = 1024;
+
+/// Mining progress
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PowProgress {
+    /// Computed hashes
+    pub hashes: u64,
+    /// Elapsed time since the mining started
+    pub elapsed: Duration,
+    /// Best difficulty found so far
+    pub best_difficulty: u8,
+}
+
+impl PowProgress {
+    /// Hashes per second
+    pub fn hashrate(&self) -> f64 {
+        let secs: f64 = self.elapsed.as_secs_f64();
+
+        if secs > 0.0 {
+            self.hashes as f64 / secs
+        } else {
+            0.0
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct MinerState {
+    hashes: AtomicU64,
+    best_difficulty: AtomicU8,
+    cancelled: AtomicBool,
+    /// A mining is in progress
+    running: AtomicBool,
+    started_at: Mutex<Option<Instant>>,
+}
+
+/// Release the miner when the mining ends
+struct RunningGuard<'a> {
+    running: &'a AtomicBool,
+}
+
+impl Drop for RunningGuard<'_> {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::SeqCst);
+    }
+}
+
+/// Best nonce found, shared between the mining threads
+#[derive(Debug, Default)]
+struct Best {
+    /// Committed target difficulty (reached by the nonce)
+    target: u8,
+    nonce: u128,
+}
+
+/// Parallel POW miner
+///
+/// Mine the ID of an [`UnsignedEvent`] across multiple threads, so it can be signed later with a single
+/// call to any signer (including the remote ones).
+///
+/// The miner can be cloned to check the progress or to cancel the mining from another thread/task.
+/// A cancelled miner can't be used again.
+///
+/// The clones share the same progress, so a miner (and its clones) mines one event at a time:
+/// starting another mining while one is in progress returns [`Error::AlreadyMining`].
+///
+/// <https://github.com/nostr-protocol/nips/blob/master/13.md>
+#[derive(Debug, Clone)]
+pub struct PowMiner {
+    difficulty: u8,
+    threads: NonZeroUsize,
+    timeout: Option<Duration>,
+    state: Arc<MinerState>,
+}
+
+impl PowMiner {
+    /// Construct new miner for difficulty
+    ///
+    /// By default, all the available cores are used.
+    pub fn new(difficulty: u8) -> Self {
+        Self {
+            difficulty,
+            threads: thread::available_parallelism().unwrap_or(NonZeroUsize::MIN),
+            timeout: None,
+            state: Arc::new(MinerState::default()),
+        }
+    }
+
+    /// Number of threads
+    #[inline]
+    pub fn threads(mut self, threads: NonZeroUsize) -> Self {
+        self.threads = threads;
+        self
+    }
+
+    /// Best effort mining
+    ///
+    /// If the difficulty isn't reached before the timeout, the event with the best difficulty found is returned:
+    /// its `nonce` tag commits the difficulty actually reached, not the target one.
+    ///
+    /// Since the committed difficulty is part of the event ID, it's raised one bit at a time during the mining,
+    /// so reaching the target may take up to about twice the time of a mining without timeout.
+    #[inline]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Target difficulty
+    #[inline]
+    pub fn difficulty(&self) -> u8 {
+        self.difficulty
+    }
+
+    /// Cancel the mining
+    #[inline]
+    pub fn cancel(&self) {
+        self.state.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    /// Check if the mining has been cancelled
+    #[inline]
+    pub fn is_cancelled(&self) -> bool {
+        self.state.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Get the mining progress
+    pub fn progress(&self) -> PowProgress {
+        let started_at = self
+            .state
+            .started_at
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+
+        PowProgress {
+            hashes: self.state.hashes.load(Ordering::SeqCst),
+            elapsed: started_at.map(|s| s.elapsed()).unwrap_or_default(),
+            best_difficulty: self.state.best_difficulty.load(Ordering::SeqCst),
+        }
+    }
+
+    /// Mine the unsigned event, blocking the current thread
+    ///
+    /// A `nonce` tag is added to the event and the event ID is set.
+    /// The `created_at` is NOT changed.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Cancelled`] if the mining is cancelled
+    /// and [`Error::AlreadyMining`] if the miner (or a clone) is already mining.
+    #[inline]
+    pub fn mine_blocking(&self, unsigned: UnsignedEvent) -> Result<UnsignedEvent, Error> {
+        self.run(unsigned, &AtomicBool::new(false))
+    }
+
+    /// Mine the event until found, timeout, miner cancelled or `stop` set
+    fn run(&self, mut unsigned: UnsignedEvent, stop: &AtomicBool) -> Result<UnsignedEvent, Error> {
+        // Only one mining at a time
+        if self
+            .state
+            .running
+            .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+            .is_err()
+        {
+            return Err(Error::AlreadyMining);
+        }
+        let _guard = RunningGuard {
+            running: &self.state.running,
+        };
+
+        // Remove previous nonce tag, if any
+        unsigned.tags.retain(|t| t.kind() != TagKind::Nonce);
+        unsigned.id = None;
+
+        let template: Template = Template::new(&unsigned)?;
+
+        // Reset progress
+        self.state.hashes.store(0, Ordering::SeqCst);
+        self.state.best_difficulty.store(0, Ordering::SeqCst);
+        {
+            let mut started_at = self
+                .state
+                .started_at
+                .lock()
+                .unwrap_or_else(|e| e.into_inner());
+            *started_at = Some(Instant::now());
+        }
+
+        let deadline: Option<Instant> = self.timeout.map(|t| Instant::now() + t);
+
+        // Committed target difficulty: with a timeout, start from 0 (any ID reaches it)
+        // and raise it every time it's reached, so the best nonce found always commits the reached difficulty.
+        let level: AtomicU8 = AtomicU8::new(match deadline {
+            Some(..) => 0,
+            None => self.difficulty,
+        });
+        let found: AtomicBool = AtomicBool::new(false);
+        let best: Mutex<Best> = Mutex::new(Best::default());
+        let threads: u128 = self.threads.get() as u128;
+
+        let worker = |first: u128| {
+            self.work(
+                &template, first, threads, deadline, stop, &level, &found, &best,
+            );
+        };
+
+        if threads == 1 {
+            worker(1);
+        } else {
+            thread::scope(|s| {
+                for first in 1..=threads {
+                    s.spawn(move || worker(first));
+                }
+            });
+        }
+
+        if (self.is_cancelled() || stop.load(Ordering::SeqCst)) && !found.load(Ordering::SeqCst) {
+            return Err(Error::Cancelled);
+        }
+
+        let best: Best = best.into_inner().unwrap_or_else(|e| e.into_inner());
+
+        unsigned.tags.push(Tag::pow(best.nonce, best.target));
+        let id: EventId = unsigned.compute_id();
+
+        debug_assert!(get_leading_zero_bits(id.as_bytes()) >= best.target);
+
+        unsigned.id = Some(id);
+
+        Ok(unsigned)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn work(
+        &self,
+        template: &Template,
+        first: u128,
+        step: u128,
+        deadline: Option<Instant>,
+        stop: &AtomicBool,
+        level: &AtomicU8,
+        found: &AtomicBool,
+        best: &Mutex<Best>,
+    ) {
+        let mut nonce: u128 = first;
+        let mut local_best: Option<u8> = None;
+        let mut buf: [u8; 39] = [0u8; 39];
+        let mut target: u8 = level.load(Ordering::SeqCst);
+
+        loop {
+            for _ in 0..BATCH_SIZE {
+                let difficulty: u8 = template.difficulty(nonce, target, &mut buf);
+
+                if local_best.map_or(true, |b| difficulty > b) {
+                    local_best = Some(difficulty);
+                    self.state
+                        .best_difficulty
+                        .fetch_max(difficulty, Ordering::SeqCst);
+                }
+
+                if difficulty >= target {
+                    // The nonces start from 1: 0 means not set yet
+                    let mut best = best.lock().unwrap_or_else(|e| e.into_inner());
+                    if best.nonce == 0 || target > best.target {
+                        best.target = target;
+                        best.nonce = nonce;
+                    }
+
+                    if target >= self.difficulty {
+                        found.store(true, Ordering::SeqCst);
+                        break;
+                    }
+
+                    // Commit a higher target from now on
+                    level.fetch_max(target + 1, Ordering::SeqCst);
+                    target = level.load(Ordering::SeqCst);
+                }
+
+                nonce += step;
+            }
+
+            self.state.hashes.fetch_add(BATCH_SIZE, Ordering::Relaxed);
+
+            if found.load(Ordering::SeqCst) || self.is_cancelled() || stop.load(Ordering::SeqCst) {
+                break;
+            }
+
+            if let Some(deadline) = deadline {
+                if Instant::now() >= deadline {
+                    break;
+                }
+            }
+
+            // Raised by another thread
+            target = level.load(Ordering::SeqCst);
+        }
+    }
+
+    /// Mine the unsigned event
+    ///
+    /// Same as [`PowMiner::mine_blocking`] but the mining runs on dedicated threads,
+    /// without blocking the async runtime.
+    ///
+    /// If the returned future is dropped, the mining is stopped,
+    /// but the miner is NOT cancelled: it can be used again.
+    pub async fn mine(&self, unsigned: UnsignedEvent) -> Result<UnsignedEvent, Error> {
+        let slot: Arc<Mutex<Slot>> = Arc::new(Mutex::new(Slot::default()));
+        let stop: Arc<AtomicBool> = Arc::new(AtomicBool::new(false));
+
+        let miner: Self = self.clone();
+        let thread_slot: Arc<Mutex<Slot>> = slot.clone();
+        let thread_stop: Arc<AtomicBool> = stop.clone();
+        thread::spawn(move || {
+            let res = miner.run(unsigned, &thread_stop);
+
+            let mut slot = thread_slot.lock().unwrap_or_else(|e| e.into_inner());
+            slot.result = Some(res);
+
+            if let Some(waker) = slot.waker.take() {
+                waker.wake();
+            }
+        });
+
+        MineFuture {
+            stop,
+            slot,
+            done: false,
+        }
+        .await
+    }
+}
+
+/// Pre-serialized event, to avoid serializing the whole event for every nonce
+struct Template {
+    /// Hash engine after the input of the serialized event until the nonce
+    engine: <Sha256Hash as Hash>::Engine,
+    /// Serialized event after the `nonce` tag
+    suffix: Vec<u8>,
+}
+
+impl Template {
+    fn new(unsigned: &UnsignedEvent) -> Result<Self, Error> {
+        // Serialized tags without the closing bracket
+        let tags: String = serde_json::to_string(&unsigned.tags)?;
+        let tags: &str = &tags[..tags.len() - 1];
+
+        let prefix: String = format!(
+            "[0,{},{},{},{tags}{}[\"nonce\",\"",
+            serde_json::to_string(&unsigned.pubkey)?,
+            serde_json::to_string(&unsigned.created_at)?,
+            serde_json::to_string(&unsigned.kind)?,
+            if unsigned.tags.is_empty() { "" } else { "," },
+        );
+        let suffix: String = format!(",{}]", serde_json::to_string(&unsigned.content)?);
+
+        let mut engine = Sha256Hash::engine();
+        engine.input(prefix.as_bytes());
+
+        Ok(Self {
+            engine,
+            suffix: suffix.into_bytes(),
+        })
+    }
+
+    /// Leading zero bits of the event ID with the nonce and the committed target difficulty
+    #[inline]
+    fn difficulty(&self, nonce: u128, target: u8, buf: &mut [u8; 39]) -> u8 {
+        let mut engine = self.engine.clone();
+        engine.input(format_u128(nonce, buf));
+        engine.input(b"\",\"");
+        engine.input(format_u128(target as u128, buf));
+        engine.input(b"\"]]");
+        engine.input(&self.suffix);
+        let hash: Sha256Hash = Sha256Hash::from_engine(engine);
+        get_leading_zero_bits(hash.as_byte_array())
+    }
+}
+
+/// Format `u128` as decimal, without allocations
+fn format_u128(mut n: u128, buf: &mut [u8; 39]) -> &[u8] {
+    let mut i: usize = buf.len();
+
+    loop {
+        i -= 1;
+        buf[i] = b'0' + (n % 10) as u8;
+        n /= 10;
+
+        if n == 0 {
+            break;
+        }
+    }
+
+    &buf[i..]
+}
+
+#[derive(Default)]
+struct Slot {
+    result: Option<Result<UnsignedEvent, Error>>,
+    waker: Option<Waker>,
+}
+
+struct MineFuture {
+    stop: Arc<AtomicBool>,
+    slot: Arc<Mutex<Slot>>,
+    done: bool,
+}
+
+impl Future for MineFuture {
+    type Output = Result<UnsignedEvent, Error>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let res = {
+            let mut slot = self.slot.lock().unwrap_or_else(|e| e.into_inner());
+            match slot.result.take() {
+                Some(res) => res,
+                None => {
+                    slot.waker = Some(cx.waker().clone());
+                    return Poll::Pending;
+                }
+            }
+        };
+
+        self.done = true;
+        Poll::Ready(res)
+    }
+}
+
+impl Drop for MineFuture {
+    fn drop(&mut self) {
+        // Stop the mining threads of this run
+        if !self.done {
+            self.stop.store(true, Ordering::SeqCst);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EventBuilder, Keys, TagStandard};
+
+    fn committed_difficulty(unsigned: &UnsignedEvent) -> u8 {
+        unsigned
+            .tags
+            .iter()
+            .find_map(|tag| match tag.as_standardized() {
+                Some(TagStandard::POW { difficulty, .. }) => Some(*difficulty),
+                _ => None,
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn test_format_u128() {
+        let mut buf = [0u8; 39];
+        assert_eq!(format_u128(0, &mut buf), b"0");
+        assert_eq!(format_u128(1234567890, &mut buf), b"1234567890");
+        assert_eq!(
+            format_u128(u128::MAX, &mut buf),
+            u128::MAX.to_string().as_bytes()
+        );
+    }
+
+    #[test]
+    fn test_template() {
+        let keys = Keys::generate();
+        let unsigned = EventBuilder::text_note("Hello \"world\"\n")
+            .tag(Tag::hashtag("nostr"))
+            .build(keys.public_key());
+
+        for unsigned in [
+            unsigned.clone(),
+            EventBuilder::text_note("").build(keys.public_key()),
+        ] {
+            let template = Template::new(&unsigned).unwrap();
+
+            for target in [0, 12, 255] {
+                let mut expected = unsigned.clone();
+                expected.tags.push(Tag::pow(42, target));
+                let id = expected.compute_id();
+
+                let mut buf = [0u8; 39];
+                assert_eq!(
+                    template.difficulty(42, target, &mut buf),
+                    get_leading_zero_bits(id.as_bytes())
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_mine_blocking() {
+        let keys = Keys::generate();
+        let unsigned = EventBuilder::text_note("PoW").build(keys.public_key());
+
+        let miner = PowMiner::new(12);
+        let mined = miner.mine_blocking(unsigned).unwrap();
+
+        let id = mined.id.unwrap();
+        assert!(id.check_pow(12));
+        assert_eq!(id, mined.compute_id());
+        assert!(miner.progress().hashes > 0);
+        assert!(miner.progress().best_difficulty >= 12);
+
+        let event = mined.sign_with_keys(&keys).unwrap();
+        assert!(event.verify().is_ok());
+    }
+
+    #[test]
+    fn test_mine_timeout() {
+        let keys = Keys::generate();
+        let unsigned = EventBuilder::text_note("PoW").build(keys.public_key());
+
+        // Unreachable difficulty
+        let miner = PowMiner::new(255).timeout(Duration::from_millis(200));
+        let mined = miner.mine_blocking(unsigned).unwrap();
+
+        let id = mined.id.unwrap();
+        assert_eq!(id, mined.compute_id());
+
+        // The committed difficulty is the reached one
+        let committed: u8 = committed_difficulty(&mined);
+        assert!(committed > 0);
+        assert!(committed < 255);
+        assert!(id.check_pow(committed));
+
+        // The target is still reached, if there is enough time
+        let unsigned = EventBuilder::text_note("PoW").build(keys.public_key());
+        let miner = PowMiner::new(8).timeout(Duration::from_secs(60));
+        let mined = miner.mine_blocking(unsigned).unwrap();
+        assert_eq!(committed_difficulty(&mined), 8);
+        assert!(mined.id.unwrap().check_pow(8));
+    }
+
+    #[tokio::test]
+    async fn test_mine_cancel() {
+        let keys = Keys::generate();
+        let unsigned = EventBuilder::text_note("PoW").build(keys.public_key());
+
+        let miner = PowMiner::new(255);
+
+        let handle = miner.clone();
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(200));
+            handle.cancel();
+        });
+
+        assert!(matches!(miner.mine(unsigned).await, Err(Error::Cancelled)));
+    }
+
+    #[tokio::test]
+    async fn test_mine_one_at_a_time() {
+        let keys = Keys::generate();
+        let unsigned = EventBuilder::text_note("PoW").build(keys.public_key());
+
+        let miner = PowMiner::new(255).threads(NonZeroUsize::MIN);
+
+        // Dropping the future stops the mining, without cancelling the miner
+        let handle = miner.clone();
+        let first = unsigned.clone();
+        let task = tokio::spawn(async move { handle.mine(first).await });
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        // Another mining while the first is in progress
+        assert!(matches!(
+            miner.clone().mine_blocking(unsigned.clone()),
+            Err(Error::AlreadyMining)
+        ));
+
+        task.abort();
+        let _ = task.await;
+        assert!(!miner.is_cancelled());
+
+        // The miner is released when the mining threads stop
+        let miner = miner.timeout(Duration::from_millis(100));
+        let mut res = miner.mine_blocking(unsigned.clone());
+        for _ in 0..50 {
+            if !matches!(res, Err(Error::AlreadyMining)) {
+                break;
+            }
+            thread::sleep(Duration::from_millis(20));
+            res = miner.mine_blocking(unsigned.clone());
+        }
+        assert!(res.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_sign_with_miner() {
+        let keys = Keys::generate();
+        let miner = PowMiner::new(10);
+
+        let event = EventBuilder::text_note("PoW")
+            .sign_with_miner(&keys, &miner)
+            .await
+            .unwrap();
+        assert!(event.id.check_pow(10));
+        assert!(event.verify().is_ok());
+    }
+}
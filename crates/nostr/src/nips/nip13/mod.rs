@@ -7,8 +7,45 @@
 //!
 //! <https://github.com/nostr-protocol/nips/blob/master/13.md>
 
-use alloc::string::String;
+use alloc::string::{String, ToString};
 use alloc::vec::Vec;
+use core::fmt;
+
+#[cfg(all(feature = "std", not(target_arch = "wasm32")))]
+mod miner;
+
+#[cfg(all(feature = "std", not(target_arch = "wasm32")))]
+pub use self::miner::{PowMiner, PowProgress};
+
+/// `NIP13` error
+#[derive(Debug, PartialEq, Eq)]
+pub enum Error {
+    /// Json error
+    Json(String),
+    /// Mining cancelled
+    Cancelled,
+    /// The miner is already mining another event
+    AlreadyMining,
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Json(e) => e.fmt(f),
+            Self::Cancelled => f.write_str("mining cancelled"),
+            Self::AlreadyMining => f.write_str("already mining another event"),
+        }
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Self::Json(e.to_string())
+    }
+}
 
 /// Gets the number of leading zero bits. Result is between 0 and 255.
 #[inline]
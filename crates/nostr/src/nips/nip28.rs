@@ -0,0 +1,506 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2025 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! NIP28: Public Chat
+//!
+//! <https://github.com/nostr-protocol/nips/blob/master/28.md>
+
+use alloc::collections::BTreeSet;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+use serde::Deserialize;
+
+use crate::nips::nip01::Metadata;
+use crate::nips::nip10::Marker;
+use crate::{Event, EventId, JsonUtil, Kind, PublicKey, RelayUrl, TagKind, TagStandard, Timestamp};
+
+/// NIP28 error
+#[derive(Debug)]
+pub enum Error {
+    /// JSON error
+    Json(serde_json::Error),
+    /// Wrong event kind
+    WrongKind {
+        /// The received kind
+        received: Kind,
+        /// The expected kind
+        expected: Kind,
+    },
+    /// Channel ID not found
+    ChannelIdNotFound,
+    /// Message ID not found
+    MessageIdNotFound,
+    /// Public key not found
+    PublicKeyNotFound,
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Json(e) => e.fmt(f),
+            Self::WrongKind { received, expected } => {
+                write!(f, "wrong kind: received={received}, expected={expected}")
+            }
+            Self::ChannelIdNotFound => f.write_str("channel ID not found"),
+            Self::MessageIdNotFound => f.write_str("message ID not found"),
+            Self::PublicKeyNotFound => f.write_str("public key not found"),
+        }
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Self::Json(e)
+    }
+}
+
+#[inline]
+fn check_kind(event: &Event, expected: Kind) -> Result<(), Error> {
+    if event.kind != expected {
+        return Err(Error::WrongKind {
+            received: event.kind,
+            expected,
+        });
+    }
+
+    Ok(())
+}
+
+/// Find the channel referenced by a kind `41` or `42` event.
+///
+/// The `e` tag with the `root` marker is preferred, falling back to the first unmarked `e` tag.
+fn find_channel_id(event: &Event) -> Option<(EventId, Option<RelayUrl>)> {
+    let mut unmarked: Option<(EventId, Option<RelayUrl>)> = None;
+
+    for tag in event.tags.filter_standardized(TagKind::e()) {
+        if let TagStandard::Event {
+            event_id,
+            relay_url,
+            marker,
+            uppercase: false,
+            ..
+        } = tag
+        {
+            match marker {
+                Some(Marker::Root) => return Some((*event_id, relay_url.clone())),
+                Some(Marker::Reply) => {}
+                None => {
+                    if unmarked.is_none() {
+                        unmarked = Some((*event_id, relay_url.clone()));
+                    }
+                }
+            }
+        }
+    }
+
+    unmarked
+}
+
+/// Extract the `reason` field from the content of a kind `43` or `44` event.
+fn extract_reason(content: &str) -> Option<String> {
+    #[derive(Deserialize)]
+    struct Content {
+        #[serde(default)]
+        reason: Option<String>,
+    }
+
+    // The content is optional and may be not valid JSON: ignore errors.
+    let content: Content = serde_json::from_str(content).ok()?;
+    content.reason.filter(|r| !r.is_empty())
+}
+
+/// Public chat channel
+///
+/// Built from a channel creation event (kind `40`) and updated with [`Channel::apply_metadata`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Channel {
+    /// Channel ID (the ID of the kind `40` event)
+    pub id: EventId,
+    /// Channel creator
+    pub creator: PublicKey,
+    /// Current metadata
+    pub metadata: Metadata,
+    /// Timestamp of the event that produced the current metadata
+    pub updated_at: Timestamp,
+    /// ID of the event that produced the current metadata
+    metadata_event_id: EventId,
+}
+
+impl Channel {
+    /// Parse a channel creation event (kind `40`)
+    pub fn from_event(event: &Event) -> Result<Self, Error> {
+        check_kind(event, Kind::ChannelCreation)?;
+
+        Ok(Self {
+            id: event.id,
+            creator: event.pubkey,
+            metadata: Metadata::from_json(&event.content)?,
+            updated_at: event.created_at,
+            metadata_event_id: event.id,
+        })
+    }
+
+    /// Parse a channel creation event (kind `40`) and reduce the metadata updates (kind `41`).
+    ///
+    /// Updates that are not valid for this channel are ignored (see [`Channel::apply_metadata`]).
+    pub fn from_events<'a, I>(creation: &Event, updates: I) -> Result<Self, Error>
+    where
+        I: IntoIterator<Item = &'a Event>,
+    {
+        let mut channel: Self = Self::from_event(creation)?;
+
+        for update in updates.into_iter() {
+            // Skip invalid updates
+            let _ = channel.apply_metadata(update);
+        }
+
+        Ok(channel)
+    }
+
+    /// Apply a channel metadata event (kind `41`)
+    ///
+    /// The update is accepted only if it's signed by the channel creator, references this channel
+    /// and is newer than the current metadata.
+    ///
+    /// Returns `true` if the metadata has been updated.
+    pub fn apply_metadata(&mut self, event: &Event) -> Result<bool, Error> {
+        let update: ChannelMetadataUpdate = ChannelMetadataUpdate::from_event(event)?;
+
+        // Only the creator can update the channel
+        if update.channel_id != self.id || event.pubkey != self.creator {
+            return Ok(false);
+        }
+
+        // Keep the newest one. In case of same timestamp, keep the one with the lowest ID (NIP01).
+        let is_newer: bool = event.created_at > self.updated_at
+            || (event.created_at == self.updated_at && event.id < self.metadata_event_id);
+
+        if !is_newer {
+            return Ok(false);
+        }
+
+        self.metadata = update.metadata;
+        self.updated_at = event.created_at;
+        self.metadata_event_id = event.id;
+
+        Ok(true)
+    }
+}
+
+/// Channel metadata update (kind `41`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChannelMetadataUpdate {
+    /// Channel ID
+    pub channel_id: EventId,
+    /// Relay hint
+    pub relay_url: Option<RelayUrl>,
+    /// New metadata
+    pub metadata: Metadata,
+}
+
+impl ChannelMetadataUpdate {
+    /// Parse a channel metadata event (kind `41`)
+    pub fn from_event(event: &Event) -> Result<Self, Error> {
+        check_kind(event, Kind::ChannelMetadata)?;
+
+        let (channel_id, relay_url) = find_channel_id(event).ok_or(Error::ChannelIdNotFound)?;
+
+        Ok(Self {
+            channel_id,
+            relay_url,
+            metadata: Metadata::from_json(&event.content)?,
+        })
+    }
+}
+
+/// Channel message (kind `42`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChannelMessage {
+    /// Channel ID
+    pub channel_id: EventId,
+    /// Relay hint
+    pub relay_url: Option<RelayUrl>,
+    /// The message this is replying to, if any
+    pub reply_to: Option<EventId>,
+    /// Message content
+    pub content: String,
+}
+
+impl ChannelMessage {
+    /// Parse a channel message event (kind `42`)
+    pub fn from_event(event: &Event) -> Result<Self, Error> {
+        check_kind(event, Kind::ChannelMessage)?;
+
+        let (channel_id, relay_url) = find_channel_id(event).ok_or(Error::ChannelIdNotFound)?;
+
+        let reply_to: Option<EventId> =
+            event
+                .tags
+                .filter_standardized(TagKind::e())
+                .find_map(|tag| match tag {
+                    TagStandard::Event {
+                        event_id,
+                        marker: Some(Marker::Reply),
+                        uppercase: false,
+                        ..
+                    } => Some(*event_id),
+                    _ => None,
+                });
+
+        Ok(Self {
+            channel_id,
+            relay_url,
+            reply_to,
+            content: event.content.clone(),
+        })
+    }
+}
+
+/// Hide channel message (kind `43`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HideMessage {
+    /// ID of the hidden message
+    pub message_id: EventId,
+    /// Optional reason
+    pub reason: Option<String>,
+}
+
+impl HideMessage {
+    /// Parse a hide message event (kind `43`)
+    pub fn from_event(event: &Event) -> Result<Self, Error> {
+        check_kind(event, Kind::ChannelHideMessage)?;
+
+        let message_id: EventId = event
+            .tags
+            .event_ids()
+            .next()
+            .copied()
+            .ok_or(Error::MessageIdNotFound)?;
+
+        Ok(Self {
+            message_id,
+            reason: extract_reason(&event.content),
+        })
+    }
+}
+
+/// Mute channel user (kind `44`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MuteUser {
+    /// Muted public key
+    pub public_key: PublicKey,
+    /// Optional reason
+    pub reason: Option<String>,
+}
+
+impl MuteUser {
+    /// Parse a mute user event (kind `44`)
+    pub fn from_event(event: &Event) -> Result<Self, Error> {
+        check_kind(event, Kind::ChannelMuteUser)?;
+
+        let public_key: PublicKey = event
+            .tags
+            .public_keys()
+            .next()
+            .copied()
+            .ok_or(Error::PublicKeyNotFound)?;
+
+        Ok(Self {
+            public_key,
+            reason: extract_reason(&event.content),
+        })
+    }
+}
+
+/// Channel moderation
+///
+/// Collects the hidden messages (kind `43`) and the muted users (kind `44`) of a user,
+/// to filter the channel messages (kind `42`) shown to them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChannelModeration {
+    /// Hidden messages
+    pub hidden_messages: BTreeSet<EventId>,
+    /// Muted users
+    pub muted_users: BTreeSet<PublicKey>,
+}
+
+impl ChannelModeration {
+    /// New empty moderation
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Collect hide message and mute user events authored by `public_key`
+    ///
+    /// Events of other kinds or authored by other users are ignored.
+    pub fn from_events<'a, I>(public_key: &PublicKey, events: I) -> Self
+    where
+        I: IntoIterator<Item = &'a Event>,
+    {
+        let mut moderation: Self = Self::new();
+
+        for event in events.into_iter() {
+            if &event.pubkey != public_key {
+                continue;
+            }
+
+            match event.kind {
+                Kind::ChannelHideMessage => {
+                    if let Ok(hide) = HideMessage::from_event(event) {
+                        moderation.hidden_messages.insert(hide.message_id);
+                    }
+                }
+                Kind::ChannelMuteUser => {
+                    if let Ok(mute) = MuteUser::from_event(event) {
+                        moderation.muted_users.insert(mute.public_key);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        moderation
+    }
+
+    /// Check if a channel message must be shown
+    #[inline]
+    pub fn is_visible(&self, message: &Event) -> bool {
+        !self.hidden_messages.contains(&message.id) && !self.muted_users.contains(&message.pubkey)
+    }
+
+    /// Filter the visible channel messages
+    pub fn filter<I>(&self, messages: I) -> Vec<Event>
+    where
+        I: IntoIterator<Item = Event>,
+    {
+        messages
+            .into_iter()
+            .filter(|message| self.is_visible(message))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EventBuilder, Keys};
+
+    #[test]
+    fn test_channel_metadata_reducer() {
+        let creator = Keys::generate();
+        let other = Keys::generate();
+
+        let creation = EventBuilder::channel(&Metadata::new().name("rust-nostr"))
+            .custom_created_at(Timestamp::from_secs(1000))
+            .sign_with_keys(&creator)
+            .unwrap();
+
+        let update_by_creator =
+            EventBuilder::channel_metadata(creation.id, None, &Metadata::new().name("Rust Nostr"))
+                .custom_created_at(Timestamp::from_secs(2000))
+                .sign_with_keys(&creator)
+                .unwrap();
+        let older_update_by_creator =
+            EventBuilder::channel_metadata(creation.id, None, &Metadata::new().name("Old"))
+                .custom_created_at(Timestamp::from_secs(1500))
+                .sign_with_keys(&creator)
+                .unwrap();
+        let update_by_other =
+            EventBuilder::channel_metadata(creation.id, None, &Metadata::new().name("Hijacked"))
+                .custom_created_at(Timestamp::from_secs(3000))
+                .sign_with_keys(&other)
+                .unwrap();
+
+        let channel = Channel::from_events(
+            &creation,
+            [
+                &update_by_creator,
+                &update_by_other,
+                &older_update_by_creator,
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(channel.id, creation.id);
+        assert_eq!(channel.creator, creator.public_key());
+        assert_eq!(channel.metadata.name.as_deref(), Some("Rust Nostr"));
+        assert_eq!(channel.updated_at, Timestamp::from_secs(2000));
+    }
+
+    #[test]
+    fn test_channel_message() {
+        let keys = Keys::generate();
+        let channel_id =
+            EventId::from_hex("25e5c82273a271cb1a840d0060391a0bf4965cafeb029d5ab55350b418953fbb")
+                .unwrap();
+        let relay_url = RelayUrl::parse("wss://relay.damus.io").unwrap();
+
+        let event = EventBuilder::channel_msg(channel_id, relay_url.clone(), "Hello")
+            .sign_with_keys(&keys)
+            .unwrap();
+
+        let msg = ChannelMessage::from_event(&event).unwrap();
+        assert_eq!(msg.channel_id, channel_id);
+        assert_eq!(msg.relay_url, Some(relay_url));
+        assert_eq!(msg.reply_to, None);
+        assert_eq!(msg.content, "Hello");
+
+        let creation = EventBuilder::channel(&Metadata::new())
+            .sign_with_keys(&keys)
+            .unwrap();
+        assert!(matches!(
+            ChannelMessage::from_event(&creation),
+            Err(Error::WrongKind { .. })
+        ));
+    }
+
+    #[test]
+    fn test_channel_moderation() {
+        let user = Keys::generate();
+        let spammer = Keys::generate();
+        let friend = Keys::generate();
+        let channel_id =
+            EventId::from_hex("25e5c82273a271cb1a840d0060391a0bf4965cafeb029d5ab55350b418953fbb")
+                .unwrap();
+        let relay_url = RelayUrl::parse("wss://relay.damus.io").unwrap();
+
+        let spam = EventBuilder::channel_msg(channel_id, relay_url.clone(), "Buy now")
+            .sign_with_keys(&spammer)
+            .unwrap();
+        let rude = EventBuilder::channel_msg(channel_id, relay_url.clone(), "Rude")
+            .sign_with_keys(&friend)
+            .unwrap();
+        let nice = EventBuilder::channel_msg(channel_id, relay_url, "Nice")
+            .sign_with_keys(&friend)
+            .unwrap();
+
+        let hide = EventBuilder::hide_channel_msg(rude.id, Some("rude"))
+            .sign_with_keys(&user)
+            .unwrap();
+        let mute = EventBuilder::mute_channel_user::<String>(spammer.public_key(), None)
+            .sign_with_keys(&user)
+            .unwrap();
+        // Hide by another user: must be ignored
+        let other_hide = EventBuilder::hide_channel_msg::<String>(nice.id, None)
+            .sign_with_keys(&spammer)
+            .unwrap();
+
+        let hide_parsed = HideMessage::from_event(&hide).unwrap();
+        assert_eq!(hide_parsed.reason.as_deref(), Some("rude"));
+        let mute_parsed = MuteUser::from_event(&mute).unwrap();
+        assert_eq!(mute_parsed.public_key, spammer.public_key());
+        assert_eq!(mute_parsed.reason, None);
+
+        let moderation =
+            ChannelModeration::from_events(&user.public_key(), [&hide, &mute, &other_hide]);
+        let visible = moderation.filter([spam, rude, nice.clone()]);
+        assert_eq!(visible, vec![nice]);
+    }
+}
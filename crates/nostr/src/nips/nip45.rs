@@ -0,0 +1,306 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2025 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! NIP45: Event Counts
+//!
+//! <https://github.com/nostr-protocol/nips/blob/master/45.md>
+
+use alloc::boxed::Box;
+use alloc::string::String;
+use core::fmt;
+
+use serde::de::Error as DeserializerError;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::filter::{Alphabet, SingleLetterTag};
+use crate::{Filter, Kind, PublicKey};
+
+/// Number of HyperLogLog registers
+pub const HLL_REGISTERS: usize = 256;
+
+/// NIP45 error
+#[derive(Debug, PartialEq)]
+pub enum Error {
+    /// Hex decode error
+    Hex(hex::FromHexError),
+    /// Invalid number of registers
+    InvalidLength,
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Hex(e) => e.fmt(f),
+            Self::InvalidLength => write!(f, "invalid length: expected {HLL_REGISTERS} registers"),
+        }
+    }
+}
+
+impl From<hex::FromHexError> for Error {
+    fn from(e: hex::FromHexError) -> Self {
+        Self::Hex(e)
+    }
+}
+
+/// HyperLogLog sketch (256 registers)
+///
+/// Used by relays to return approximate counts that can be merged with the ones of other relays,
+/// without counting twice the same public key.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Hll {
+    // Boxed to keep the `RelayMessage` size small
+    registers: Box<[u8; HLL_REGISTERS]>,
+}
+
+impl Default for Hll {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Hll {
+    /// New empty sketch
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            registers: Box::new([0; HLL_REGISTERS]),
+        }
+    }
+
+    /// Get the offset for a filter, if it's eligible for HyperLogLog
+    ///
+    /// The eligible filters are:
+    /// * `{"#e": ["<id>"], "kinds": [7]}`: reactions to an event;
+    /// * `{"#p": ["<pubkey>"], "kinds": [3]}`: followers of a public key.
+    ///
+    /// The offset is the value of the 32nd hex char of the tag value, plus `8`.
+    pub fn offset(filter: &Filter) -> Option<usize> {
+        if filter.ids.is_some() || filter.authors.is_some() || filter.search.is_some() {
+            return None;
+        }
+
+        let kinds = filter.kinds.as_ref()?;
+        if kinds.len() != 1 || filter.generic_tags.len() != 1 {
+            return None;
+        }
+
+        let (tag, values) = filter.generic_tags.iter().next()?;
+        let expected: SingleLetterTag = match *kinds.iter().next()? {
+            Kind::Reaction => SingleLetterTag::lowercase(Alphabet::E),
+            Kind::ContactList => SingleLetterTag::lowercase(Alphabet::P),
+            _ => return None,
+        };
+
+        if tag != &expected || values.len() != 1 {
+            return None;
+        }
+
+        let value: &String = values.iter().next()?;
+
+        // Must be a valid 32-byte hex value
+        if value.len() != 64 || !value.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return None;
+        }
+
+        let c: char = value.chars().nth(32)?;
+        Some(c.to_digit(16)? as usize + 8)
+    }
+
+    /// Add a public key to the sketch
+    ///
+    /// The `offset` must be the one of the filter (see [`Hll::offset`]).
+    pub fn add(&mut self, offset: usize, public_key: &PublicKey) {
+        let bytes: &[u8; 32] = public_key.as_bytes();
+
+        let (index, rest) = match bytes.get(offset..) {
+            Some([index, rest @ ..]) => (*index as usize, rest),
+            _ => return,
+        };
+
+        // Count the leading zero bits, after the register index
+        let mut zeros: u8 = 0;
+        for byte in rest.iter() {
+            zeros += byte.leading_zeros() as u8;
+
+            if *byte != 0 {
+                break;
+            }
+        }
+
+        let value: u8 = zeros + 1;
+        if value > self.registers[index] {
+            self.registers[index] = value;
+        }
+    }
+
+    /// Merge with another sketch
+    ///
+    /// The result is the same of a sketch of the union of both sets.
+    pub fn merge(&mut self, other: &Self) {
+        for (register, other) in self.registers.iter_mut().zip(other.registers.iter()) {
+            if *other > *register {
+                *register = *other;
+            }
+        }
+    }
+
+    /// Estimate the number of distinct public keys
+    #[cfg(feature = "std")]
+    pub fn estimate(&self) -> usize {
+        let m: f64 = HLL_REGISTERS as f64;
+        let alpha: f64 = 0.7213 / (1.0 + 1.079 / m);
+
+        let mut sum: f64 = 0.0;
+        let mut zeros: usize = 0;
+
+        for register in self.registers.iter() {
+            sum += 2f64.powi(-(*register as i32));
+
+            if *register == 0 {
+                zeros += 1;
+            }
+        }
+
+        let estimate: f64 = alpha * m * m / sum;
+
+        // Small range correction
+        if estimate <= 2.5 * m && zeros > 0 {
+            return (m * (m / zeros as f64).ln()).round() as usize;
+        }
+
+        estimate.round() as usize
+    }
+
+    /// Get the registers
+    #[inline]
+    pub fn registers(&self) -> &[u8; HLL_REGISTERS] {
+        &self.registers
+    }
+
+    /// Parse from hex
+    pub fn from_hex(hll: &str) -> Result<Self, Error> {
+        if hll.len() != HLL_REGISTERS * 2 {
+            return Err(Error::InvalidLength);
+        }
+
+        let mut registers: Box<[u8; HLL_REGISTERS]> = Box::new([0; HLL_REGISTERS]);
+        hex::decode_to_slice(hll, registers.as_mut())?;
+
+        Ok(Self { registers })
+    }
+
+    /// Serialize to hex
+    #[inline]
+    pub fn to_hex(&self) -> String {
+        hex::encode(self.registers.as_ref())
+    }
+}
+
+impl Serialize for Hll {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.to_hex())
+    }
+}
+
+impl<'de> Deserialize<'de> for Hll {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let hll: String = String::deserialize(deserializer)?;
+        Self::from_hex(&hll).map_err(DeserializerError::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EventId, Keys};
+
+    #[test]
+    fn test_offset() {
+        let id =
+            EventId::from_hex("70b10f70c1318967eddf12527799411b1a9780ad9c43858f5e5fcd45486a13a5")
+                .unwrap();
+
+        // The 32nd char is `1`
+        let filter = Filter::new().kind(Kind::Reaction).event(id);
+        assert_eq!(Hll::offset(&filter), Some(1 + 8));
+
+        let filter = Filter::new().kind(Kind::ContactList).pubkey(
+            PublicKey::from_hex("79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798")
+                .unwrap(),
+        );
+        assert_eq!(Hll::offset(&filter), Some(8));
+
+        // Not eligible
+        let filter = Filter::new().kind(Kind::TextNote).event(id);
+        assert_eq!(Hll::offset(&filter), None);
+
+        let filter = Filter::new().kind(Kind::Reaction).event(id).author(
+            PublicKey::from_hex("79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798")
+                .unwrap(),
+        );
+        assert_eq!(Hll::offset(&filter), None);
+    }
+
+    #[test]
+    fn test_add_and_hex() {
+        let public_key =
+            PublicKey::from_hex("79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798")
+                .unwrap();
+
+        let mut hll = Hll::new();
+
+        // Register index 0x55, followed by 0xa0 (no leading zeros)
+        hll.add(8, &public_key);
+        assert_eq!(hll.registers()[0x55], 1);
+
+        // Register index 0xa0, followed by 0x62 (one leading zero)
+        hll.add(9, &public_key);
+        assert_eq!(hll.registers()[0xa0], 2);
+
+        let hex = hll.to_hex();
+        assert_eq!(hex.len(), 512);
+        assert_eq!(Hll::from_hex(&hex).unwrap(), hll);
+        assert_eq!(Hll::from_hex("00").unwrap_err(), Error::InvalidLength);
+    }
+
+    #[test]
+    fn test_merge_and_estimate() {
+        let offset: usize = 12;
+        let keys: Vec<PublicKey> = (0..2000).map(|_| Keys::generate().public_key()).collect();
+
+        // Two relays with overlapping sets
+        let mut relay1 = Hll::new();
+        for public_key in keys[..1500].iter() {
+            relay1.add(offset, public_key);
+        }
+
+        let mut relay2 = Hll::new();
+        for public_key in keys[500..].iter() {
+            relay2.add(offset, public_key);
+        }
+
+        relay1.merge(&relay2);
+
+        // Standard error with 256 registers is ~6.5%
+        let estimate = relay1.estimate() as f64;
+        assert!((estimate - 2000.0).abs() / 2000.0 < 0.25, "{estimate}");
+
+        // Small counts
+        let mut small = Hll::new();
+        for public_key in keys[..10].iter() {
+            small.add(offset, public_key);
+        }
+        assert!((8..=12).contains(&small.estimate()));
+        assert_eq!(Hll::new().estimate(), 0);
+    }
+}
@@ -20,7 +20,7 @@ use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 use crate::event::unsigned::UnsignedEvent;
 use crate::types::url::{self, ParseError, RelayUrl, Url};
-use crate::{event, key, Event, JsonUtil, PublicKey};
+use crate::{event, key, Event, JsonUtil, Kind, PublicKey};
 
 /// NIP46 URI Scheme
 pub const NOSTR_CONNECT_URI_SCHEME: &str = "nostrconnect";
@@ -52,6 +52,8 @@ pub enum Error {
     NotRequest,
     /// Unexpected result
     UnexpectedResult,
+    /// Invalid permission
+    InvalidPermission,
 }
 
 #[cfg(feature = "std")]
@@ -71,6 +73,7 @@ impl fmt::Display for Error {
             Self::InvalidURI => f.write_str("Invalid uri"),
             Self::NotRequest => f.write_str("Not a request"),
             Self::UnexpectedResult => f.write_str("Unexpected result"),
+            Self::InvalidPermission => f.write_str("Invalid permission"),
         }
     }
 }
@@ -124,6 +127,8 @@ pub enum NostrConnectMethod {
     Nip44Decrypt,
     /// Ping
     Ping,
+    /// Switch relays
+    SwitchRelays,
 }
 
 impl fmt::Display for NostrConnectMethod {
@@ -137,6 +142,7 @@ impl fmt::Display for NostrConnectMethod {
             Self::Nip44Encrypt => write!(f, "nip44_encrypt"),
             Self::Nip44Decrypt => write!(f, "nip44_decrypt"),
             Self::Ping => write!(f, "ping"),
+            Self::SwitchRelays => write!(f, "switch_relays"),
         }
     }
 }
@@ -154,6 +160,7 @@ impl FromStr for NostrConnectMethod {
             "nip44_encrypt" => Ok(Self::Nip44Encrypt),
             "nip44_decrypt" => Ok(Self::Nip44Decrypt),
             "ping" => Ok(Self::Ping),
+            "switch_relays" => Ok(Self::SwitchRelays),
             other => Err(Error::UnsupportedMethod(other.to_string())),
         }
     }
@@ -178,6 +185,88 @@ impl<'de> Deserialize<'de> for NostrConnectMethod {
     }
 }
 
+/// Nostr Connect permission
+///
+/// Requested by the client in the `connect` request, as a comma-separated list (i.e., `nip44_encrypt,sign_event:1`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NostrConnectPermission {
+    /// Method
+    pub method: NostrConnectMethod,
+    /// Event kind (only for `sign_event`)
+    ///
+    /// If `None`, any kind is allowed.
+    pub kind: Option<Kind>,
+}
+
+impl NostrConnectPermission {
+    /// New permission
+    #[inline]
+    pub fn new(method: NostrConnectMethod) -> Self {
+        Self { method, kind: None }
+    }
+
+    /// New `sign_event` permission for a specific kind
+    #[inline]
+    pub fn sign_event(kind: Kind) -> Self {
+        Self {
+            method: NostrConnectMethod::SignEvent,
+            kind: Some(kind),
+        }
+    }
+
+    /// Parse comma-separated permissions
+    ///
+    /// Unknown or invalid permissions are skipped.
+    pub fn parse_list(perms: &str) -> Vec<Self> {
+        perms
+            .split(',')
+            .filter_map(|perm| Self::from_str(perm.trim()).ok())
+            .collect()
+    }
+
+    /// Check if the permission allows the request
+    pub fn allows(&self, req: &NostrConnectRequest) -> bool {
+        if self.method != req.method() {
+            return false;
+        }
+
+        match (self.kind, req) {
+            (Some(kind), NostrConnectRequest::SignEvent(unsigned)) => unsigned.kind == kind,
+            _ => true,
+        }
+    }
+}
+
+impl fmt::Display for NostrConnectPermission {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            Some(kind) => write!(f, "{}:{}", self.method, kind.as_u16()),
+            None => write!(f, "{}", self.method),
+        }
+    }
+}
+
+impl FromStr for NostrConnectPermission {
+    type Err = Error;
+
+    fn from_str(perm: &str) -> Result<Self, Self::Err> {
+        match perm.split_once(':') {
+            Some((method, kind)) => {
+                let method: NostrConnectMethod = NostrConnectMethod::from_str(method)?;
+
+                if method != NostrConnectMethod::SignEvent {
+                    return Err(Error::InvalidPermission);
+                }
+
+                let kind: u16 = kind.parse().map_err(|_| Error::InvalidPermission)?;
+
+                Ok(Self::sign_event(Kind::from_u16(kind)))
+            }
+            None => Ok(Self::new(NostrConnectMethod::from_str(perm)?)),
+        }
+    }
+}
+
 /// Nostr Connect Request
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum NostrConnectRequest {
@@ -187,6 +276,8 @@ pub enum NostrConnectRequest {
         public_key: PublicKey,
         /// Optional secret
         secret: Option<String>,
+        /// Requested permissions
+        perms: Vec<NostrConnectPermission>,
     },
     /// Get public key
     GetPublicKey,
@@ -222,6 +313,8 @@ pub enum NostrConnectRequest {
     },
     /// Ping
     Ping,
+    /// Switch relays
+    SwitchRelays,
 }
 
 impl NostrConnectRequest {
@@ -231,8 +324,17 @@ impl NostrConnectRequest {
             NostrConnectMethod::Connect => {
                 let public_key = params.first().ok_or(Error::InvalidRequest)?;
                 let public_key: PublicKey = PublicKey::from_hex(public_key)?;
-                let secret: Option<String> = params.get(1).cloned();
-                Ok(Self::Connect { public_key, secret })
+                // The secret may be empty if only the permissions are set
+                let secret: Option<String> = params.get(1).filter(|s| !s.is_empty()).cloned();
+                let perms: Vec<NostrConnectPermission> = params
+                    .get(2)
+                    .map(|perms| NostrConnectPermission::parse_list(perms))
+                    .unwrap_or_default();
+                Ok(Self::Connect {
+                    public_key,
+                    secret,
+                    perms,
+                })
             }
             NostrConnectMethod::GetPublicKey => Ok(Self::GetPublicKey),
             NostrConnectMethod::SignEvent => {
@@ -281,6 +383,7 @@ impl NostrConnectRequest {
                 })
             }
             NostrConnectMethod::Ping => Ok(Self::Ping),
+            NostrConnectMethod::SwitchRelays => Ok(Self::SwitchRelays),
         }
     }
 
@@ -295,17 +398,29 @@ impl NostrConnectRequest {
             Self::Nip44Encrypt { .. } => NostrConnectMethod::Nip44Encrypt,
             Self::Nip44Decrypt { .. } => NostrConnectMethod::Nip44Decrypt,
             Self::Ping => NostrConnectMethod::Ping,
+            Self::SwitchRelays => NostrConnectMethod::SwitchRelays,
         }
     }
 
     /// Get req params
     pub fn params(&self) -> Vec<String> {
         match self {
-            Self::Connect { public_key, secret } => {
+            Self::Connect {
+                public_key,
+                secret,
+                perms,
+            } => {
                 let mut params = vec![public_key.to_hex()];
-                if let Some(secret) = secret {
-                    params.push(secret.to_owned());
+
+                if secret.is_some() || !perms.is_empty() {
+                    params.push(secret.clone().unwrap_or_default());
+                }
+
+                if !perms.is_empty() {
+                    let perms: Vec<String> = perms.iter().map(|p| p.to_string()).collect();
+                    params.push(perms.join(","));
                 }
+
                 params
             }
             Self::GetPublicKey => Vec::new(),
@@ -322,6 +437,7 @@ impl NostrConnectRequest {
                 ciphertext,
             } => vec![public_key.to_hex(), ciphertext.to_owned()],
             Self::Ping => Vec::new(),
+            Self::SwitchRelays => Vec::new(),
         }
     }
 }
@@ -357,6 +473,10 @@ pub enum ResponseResult {
     },
     /// Pong
     Pong,
+    /// Switch relays
+    ///
+    /// The new relays or `None` if there is nothing to change.
+    SwitchRelays(Option<Vec<RelayUrl>>),
     /// Auth Challenges
     AuthUrl,
     /// Error
@@ -409,6 +529,10 @@ impl NostrConnectResponse {
         Ok(Self {
             result: match result {
                 Some(result) => Some(ResponseResult::parse(method, result)?),
+                // A `null` result of `switch_relays` means no changes
+                None if method == NostrConnectMethod::SwitchRelays && error.is_none() => {
+                    Some(ResponseResult::SwitchRelays(None))
+                }
                 None => None,
             },
             error,
@@ -438,6 +562,15 @@ impl fmt::Display for ResponseResult {
                 write!(f, "{plaintext}")
             }
             Self::Pong => write!(f, "pong"),
+            Self::SwitchRelays(Some(relays)) => {
+                let relays: Vec<&str> = relays
+                    .iter()
+                    .map(|url| url.as_str_without_trailing_slash())
+                    .collect();
+                let relays: String = serde_json::to_string(&relays).map_err(|_| fmt::Error)?;
+                write!(f, "{relays}")
+            }
+            Self::SwitchRelays(None) => write!(f, "null"),
             Self::AuthUrl => write!(f, "auth_url"),
             Self::Error => write!(f, "error"),
         }
@@ -493,6 +626,19 @@ impl ResponseResult {
                     Err(Error::UnexpectedResult)
                 }
             }
+            NostrConnectMethod::SwitchRelays => {
+                let relays: Option<Vec<String>> = serde_json::from_str(&response)?;
+                match relays {
+                    Some(relays) => {
+                        let relays: Vec<RelayUrl> = relays
+                            .iter()
+                            .map(|url| RelayUrl::parse(url))
+                            .collect::<Result<_, _>>()?;
+                        Ok(Self::SwitchRelays(Some(relays)))
+                    }
+                    None => Ok(Self::SwitchRelays(None)),
+                }
+            }
         }
     }
 
@@ -577,6 +723,15 @@ impl ResponseResult {
             Err(Error::UnexpectedResult)
         }
     }
+
+    #[inline]
+    pub fn to_switch_relays(self) -> Result<Option<Vec<RelayUrl>>, Error> {
+        if let Self::SwitchRelays(relays) = self {
+            Ok(relays)
+        } else {
+            Err(Error::UnexpectedResult)
+        }
+    }
 }
 
 /// Nostr Connect Message
@@ -1085,6 +1240,38 @@ mod test {
         assert_eq!(res, ResponseResult::Pong);
     }
 
+    #[test]
+    fn test_switch_relays() {
+        let req = NostrConnectRequest::from_message(NostrConnectMethod::SwitchRelays, Vec::new())
+            .unwrap();
+        assert_eq!(req, NostrConnectRequest::SwitchRelays);
+        assert_eq!(req.method().to_string(), "switch_relays");
+
+        let relays = vec![
+            RelayUrl::parse("wss://relay.nsec.app").unwrap(),
+            RelayUrl::parse("wss://relay.example.com").unwrap(),
+        ];
+        let res = ResponseResult::SwitchRelays(Some(relays.clone()));
+        assert_eq!(
+            res.to_string(),
+            r#"["wss://relay.nsec.app","wss://relay.example.com"]"#
+        );
+        assert_eq!(
+            ResponseResult::parse(NostrConnectMethod::SwitchRelays, res.to_string()).unwrap(),
+            res
+        );
+
+        // No changes
+        let msg =
+            NostrConnectMessage::from_json(r#"{"id":"1","result":null,"error":null}"#).unwrap();
+        let res = msg.to_response(NostrConnectMethod::SwitchRelays).unwrap();
+        assert_eq!(res.result, Some(ResponseResult::SwitchRelays(None)));
+        assert_eq!(
+            ResponseResult::parse(NostrConnectMethod::SwitchRelays, "null").unwrap(),
+            ResponseResult::SwitchRelays(None)
+        );
+    }
+
     #[test]
     fn test_message_serialization() {
         // Error
@@ -1112,6 +1299,45 @@ mod test {
         assert_eq!(req, NostrConnectRequest::SignEvent(unsigned));
     }
 
+    #[test]
+    fn test_permissions() {
+        let perms = NostrConnectPermission::parse_list(
+            "nip44_encrypt, sign_event:1,sign_event:x,get_relays",
+        );
+        assert_eq!(
+            perms,
+            vec![
+                NostrConnectPermission::new(NostrConnectMethod::Nip44Encrypt),
+                NostrConnectPermission::sign_event(Kind::TextNote),
+            ]
+        );
+        assert_eq!(perms[1].to_string(), "sign_event:1");
+
+        let public_key =
+            PublicKey::from_hex("79dff8f82963424e0bb02708a22e44b4980893e3a4be0fa3cb60a43b946764e3")
+                .unwrap();
+        let unsigned = UnsignedEvent::from_json(r#"{"created_at":1710854115,"content":"Testing rust-nostr NIP46 signer [bunker]","tags":[],"kind":1,"pubkey":"79dff8f82963424e0bb02708a22e44b4980893e3a4be0fa3cb60a43b946764e3"}"#).unwrap();
+        let req = NostrConnectRequest::SignEvent(unsigned);
+        assert!(perms[1].allows(&req));
+        assert!(!perms[0].allows(&req));
+        assert!(!NostrConnectPermission::sign_event(Kind::Metadata).allows(&req));
+        assert!(NostrConnectPermission::new(NostrConnectMethod::SignEvent).allows(&req));
+
+        // Connect with permissions and without secret
+        let req = NostrConnectRequest::Connect {
+            public_key,
+            secret: None,
+            perms,
+        };
+        let params = req.params();
+        assert_eq!(params[1], "");
+        assert_eq!(params[2], "nip44_encrypt,sign_event:1");
+        assert_eq!(
+            NostrConnectRequest::from_message(NostrConnectMethod::Connect, params).unwrap(),
+            req
+        );
+    }
+
     #[test]
     fn test_message_deserialization() {
         // Connect
@@ -1134,6 +1360,7 @@ mod test {
                 )
                 .unwrap(),
                 secret: None,
+                perms: Vec::new(),
             }
         );
 
@@ -0,0 +1,335 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2025 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! NIP50: Search Capability
+//!
+//! <https://github.com/nostr-protocol/nips/blob/master/50.md>
+
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::convert::Infallible;
+use core::fmt;
+use core::str::FromStr;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+const INCLUDE: &str = "include";
+const DOMAIN: &str = "domain";
+const LANGUAGE: &str = "language";
+const SENTIMENT: &str = "sentiment";
+const NSFW: &str = "nsfw";
+
+/// Min number of chars of a search term to match it also as prefix of longer words
+///
+/// The shorter terms match only the equal words, to not match a large part of the events.
+pub const MIN_PREFIX_CHARS: usize = 3;
+
+/// Sentiment
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Sentiment {
+    /// Negative
+    Negative,
+    /// Neutral
+    Neutral,
+    /// Positive
+    Positive,
+}
+
+impl fmt::Display for Sentiment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Negative => write!(f, "negative"),
+            Self::Neutral => write!(f, "neutral"),
+            Self::Positive => write!(f, "positive"),
+        }
+    }
+}
+
+impl Sentiment {
+    fn parse(sentiment: &str) -> Option<Self> {
+        match sentiment {
+            "negative" => Some(Self::Negative),
+            "neutral" => Some(Self::Neutral),
+            "positive" => Some(Self::Positive),
+            _ => None,
+        }
+    }
+}
+
+/// Search query
+///
+/// Structured form of the `search` field of a filter: the free text terms and the `key:value` extensions.
+///
+/// The original query is kept, so it's serialized back as received.
+/// Only the NIP-50 keys (`include`, `domain`, `language`, `sentiment` and `nsfw`) are parsed as extensions:
+/// any other `key:value` token is a search term.
+/// Extensions with an invalid value are kept in [`SearchQuery::extensions`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SearchQuery {
+    query: String,
+    terms: Vec<String>,
+    include_spam: bool,
+    domain: Option<String>,
+    language: Option<String>,
+    sentiment: Option<Sentiment>,
+    nsfw: Option<bool>,
+    extensions: BTreeMap<String, String>,
+}
+
+impl SearchQuery {
+    /// Parse search query
+    ///
+    /// Tokens in the `key:value` form with a NIP-50 key are parsed as extensions, everything else as search terms.
+    pub fn parse(query: &str) -> Self {
+        let mut search: Self = Self {
+            query: query.to_string(),
+            ..Default::default()
+        };
+
+        for word in query.split_whitespace() {
+            match word.split_once(':') {
+                Some((key, value)) if is_extension(key, value) => search.add_extension(key, value),
+                _ => search.terms.extend(tokenize(word)),
+            }
+        }
+
+        search
+    }
+
+    fn add_extension(&mut self, key: &str, value: &str) {
+        match (key, value) {
+            (INCLUDE, "spam") => self.include_spam = true,
+            (DOMAIN, domain) => self.domain = Some(domain.to_lowercase()),
+            (LANGUAGE, language) => self.language = Some(language.to_lowercase()),
+            (SENTIMENT, sentiment) => match Sentiment::parse(sentiment) {
+                Some(sentiment) => self.sentiment = Some(sentiment),
+                None => {
+                    self.extensions.insert(key.to_string(), value.to_string());
+                }
+            },
+            (NSFW, "true") => self.nsfw = Some(true),
+            (NSFW, "false") => self.nsfw = Some(false),
+            _ => {
+                self.extensions.insert(key.to_string(), value.to_string());
+            }
+        }
+    }
+
+    /// Get the original query
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        &self.query
+    }
+
+    /// Lowercase search terms (see [`tokenize`])
+    #[inline]
+    pub fn terms(&self) -> &[String] {
+        &self.terms
+    }
+
+    /// Check if the query has search terms
+    #[inline]
+    pub fn has_terms(&self) -> bool {
+        !self.terms.is_empty()
+    }
+
+    /// Include spam (`include:spam`)
+    #[inline]
+    pub fn include_spam(&self) -> bool {
+        self.include_spam
+    }
+
+    /// Only events from users with a NIP-05 of this domain (`domain:<domain>`)
+    #[inline]
+    pub fn domain(&self) -> Option<&str> {
+        self.domain.as_deref()
+    }
+
+    /// ISO language code (`language:<two letter ISO 639-1 language code>`)
+    #[inline]
+    pub fn language(&self) -> Option<&str> {
+        self.language.as_deref()
+    }
+
+    /// Sentiment (`sentiment:<negative/neutral/positive>`)
+    #[inline]
+    pub fn sentiment(&self) -> Option<Sentiment> {
+        self.sentiment
+    }
+
+    /// Include or exclude NSFW events (`nsfw:<true/false>`)
+    #[inline]
+    pub fn nsfw(&self) -> Option<bool> {
+        self.nsfw
+    }
+
+    /// Extensions with an invalid value (i.e., `sentiment:angry`)
+    #[inline]
+    pub fn extensions(&self) -> &BTreeMap<String, String> {
+        &self.extensions
+    }
+
+    /// Check if the text contains all the search terms (case-insensitive)
+    ///
+    /// The text is split into words (see [`tokenize`]) and each term must match at least one word (see [`match_word`]).
+    /// The extensions are ignored.
+    pub fn match_text(&self, text: &str) -> bool {
+        if self.terms.is_empty() {
+            return true;
+        }
+
+        let words: Vec<String> = tokenize(text).collect();
+        self.terms
+            .iter()
+            .all(|term| words.iter().any(|word| match_word(word, term)))
+    }
+}
+
+impl FromStr for SearchQuery {
+    type Err = Infallible;
+
+    #[inline]
+    fn from_str(query: &str) -> Result<Self, Self::Err> {
+        Ok(Self::parse(query))
+    }
+}
+
+impl fmt::Display for SearchQuery {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.query)
+    }
+}
+
+impl From<SearchQuery> for String {
+    #[inline]
+    fn from(query: SearchQuery) -> Self {
+        query.query
+    }
+}
+
+impl Serialize for SearchQuery {
+    #[inline]
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.query)
+    }
+}
+
+impl<'de> Deserialize<'de> for SearchQuery {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let query: String = String::deserialize(deserializer)?;
+        Ok(Self::parse(&query))
+    }
+}
+
+/// Check if a `key:value` token is a NIP-50 extension
+///
+/// Any other `key:value` token (i.e., `nostr:npub1...` or an URL) is a search term.
+fn is_extension(key: &str, value: &str) -> bool {
+    !value.is_empty() && matches!(key, INCLUDE | DOMAIN | LANGUAGE | SENTIMENT | NSFW)
+}
+
+/// Check if the search `term` matches the `word` (both lowercase, see [`tokenize`])
+///
+/// The terms match the words that start with them,
+/// unless shorter than [`MIN_PREFIX_CHARS`] (only equal words).
+pub fn match_word(word: &str, term: &str) -> bool {
+    if term.chars().count() < MIN_PREFIX_CHARS {
+        word == term
+    } else {
+        word.starts_with(term)
+    }
+}
+
+/// Split text into lowercase search terms
+///
+/// Words are separated by any non-alphanumeric char.
+pub fn tokenize(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| word.to_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokenize() {
+        let terms: Vec<String> = tokenize("Best #nostr apps, 2025 (Ünïcode)!").collect();
+        assert_eq!(terms, vec!["best", "nostr", "apps", "2025", "ünïcode"]);
+    }
+
+    #[test]
+    fn test_parse_search_query() {
+        let query = SearchQuery::parse(
+            "Best nostr apps include:spam domain:Example.com language:en sentiment:positive nsfw:false foo:bar",
+        );
+        assert_eq!(query.terms(), ["best", "nostr", "apps", "foo", "bar"]);
+        assert!(query.include_spam());
+        assert_eq!(query.domain(), Some("example.com"));
+        assert_eq!(query.language(), Some("en"));
+        assert_eq!(query.sentiment(), Some(Sentiment::Positive));
+        assert_eq!(query.nsfw(), Some(false));
+        assert!(query.extensions().is_empty());
+
+        // The original query is kept
+        assert_eq!(
+            query.to_string(),
+            "Best nostr apps include:spam domain:Example.com language:en sentiment:positive nsfw:false foo:bar"
+        );
+        assert_eq!(SearchQuery::parse(&query.to_string()), query);
+        assert_eq!(
+            serde_json::to_string(&query).unwrap(),
+            format!("\"{query}\"")
+        );
+
+        // Invalid extensions are kept
+        let query = SearchQuery::parse("sentiment:angry");
+        assert!(!query.has_terms());
+        assert_eq!(query.sentiment(), None);
+        assert_eq!(
+            query.extensions().get("sentiment").map(|v| v.as_str()),
+            Some("angry")
+        );
+
+        // Not an extension
+        let query = SearchQuery::parse("nostr: https://example.com");
+        assert_eq!(query.terms(), ["nostr", "https", "example", "com"]);
+        assert!(query.extensions().is_empty());
+
+        // Unknown keys are search terms
+        let query = SearchQuery::parse("nostr:npub1abc hello:world");
+        assert_eq!(query.terms(), ["nostr", "npub1abc", "hello", "world"]);
+        assert!(query.extensions().is_empty());
+        assert!(!query.match_text("unrelated"));
+    }
+
+    #[test]
+    fn test_match_text() {
+        let query = SearchQuery::parse("Yuki kishi include:spam");
+        assert!(query.match_text("Yuki Kishimoto"));
+        assert!(!query.match_text("Yuki"));
+        assert!(SearchQuery::parse("include:spam").match_text("anything"));
+
+        // Word prefixes only
+        assert!(!SearchQuery::parse("imoto").match_text("Yuki Kishimoto"));
+
+        // Short terms match only the equal words
+        assert!(!SearchQuery::parse("ki").match_text("Yuki Kishimoto"));
+        assert!(SearchQuery::parse("ki").match_text("Ki Kishimoto"));
+
+        // Non-ASCII text
+        let query = SearchQuery::parse("ÜNÏCODE kishi");
+        assert!(query.match_text("Ünïcode by Yuki Kishimoto"));
+        assert!(!query.match_text("Unicode by Yuki Kishimoto"));
+    }
+}
@@ -0,0 +1,788 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2025 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! NIP54: Wiki
+//!
+//! <https://github.com/nostr-protocol/nips/blob/master/54.md>
+
+#![allow(clippy::wrong_self_convention)]
+
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::cmp::Reverse;
+use core::fmt;
+
+use super::nip01::Coordinate;
+use crate::types::RelayUrl;
+use crate::{Event, EventBuilder, EventId, Kind, PublicKey, Tag, TagKind, TagStandard};
+
+const FORK: &str = "fork";
+const DEFER: &str = "defer";
+const SOURCE: &str = "source";
+
+/// NIP54 error
+#[derive(Debug, PartialEq, Eq)]
+pub enum Error {
+    /// Wrong event kind
+    WrongKind {
+        /// The received kind
+        received: Kind,
+        /// The expected kind
+        expected: Kind,
+    },
+    /// Identifier tag not found
+    IdentifierTagNotFound,
+    /// Target article not found
+    TargetNotFound,
+    /// Source event not found
+    SourceNotFound,
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::WrongKind { received, expected } => {
+                write!(f, "wrong kind: received={received}, expected={expected}")
+            }
+            Self::IdentifierTagNotFound => f.write_str("identifier tag not found"),
+            Self::TargetNotFound => f.write_str("target article not found"),
+            Self::SourceNotFound => f.write_str("source event not found"),
+        }
+    }
+}
+
+/// Normalize a wiki topic, to be used as `d` tag
+///
+/// * letters are converted to lowercase;
+/// * whitespaces are converted to `-`;
+/// * punctuation and symbols are removed;
+/// * consecutive `-` are collapsed and leading/trailing ones removed.
+///
+/// Non-ASCII letters and numbers are preserved.
+pub fn normalize_topic(topic: &str) -> String {
+    let mut normalized: String = String::with_capacity(topic.len());
+
+    for c in topic.chars() {
+        if c.is_alphanumeric() {
+            normalized.extend(c.to_lowercase());
+        } else if (c.is_whitespace() || c == '-')
+            && !normalized.is_empty()
+            && !normalized.ends_with('-')
+        {
+            normalized.push('-');
+        }
+    }
+
+    while normalized.ends_with('-') {
+        normalized.pop();
+    }
+
+    normalized
+}
+
+/// Reference to another version of a wiki article
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct WikiReference {
+    /// Article coordinate
+    pub coordinate: Coordinate,
+    /// Article version
+    pub event_id: Option<EventId>,
+    /// Relay hint
+    pub relay_url: Option<RelayUrl>,
+}
+
+impl WikiReference {
+    /// New reference to an article
+    #[inline]
+    pub fn new(coordinate: Coordinate) -> Self {
+        Self {
+            coordinate,
+            event_id: None,
+            relay_url: None,
+        }
+    }
+
+    /// Reference an article version
+    #[inline]
+    pub fn from_event(event: &Event) -> Self {
+        Self {
+            coordinate: Coordinate::new(event.kind, event.pubkey)
+                .identifier(event.tags.identifier().unwrap_or_default()),
+            event_id: Some(event.id),
+            relay_url: None,
+        }
+    }
+
+    fn to_tags(self, marker: &str) -> impl Iterator<Item = Tag> {
+        let relay_url: String = self.relay_url.map(|r| r.to_string()).unwrap_or_default();
+
+        // The author of the version is added to the `e` tag, to pair it with the `a` one
+        let public_key: PublicKey = self.coordinate.public_key;
+        let event: Option<Tag> = self.event_id.map(|id| {
+            Tag::custom(
+                TagKind::e(),
+                [
+                    id.to_hex(),
+                    relay_url.clone(),
+                    marker.to_string(),
+                    public_key.to_hex(),
+                ],
+            )
+        });
+        let coordinate: Tag = Tag::custom(
+            TagKind::a(),
+            [self.coordinate.to_string(), relay_url, marker.to_string()],
+        );
+
+        core::iter::once(coordinate).chain(event)
+    }
+}
+
+/// Parse an `a` or `e` tag with marker
+fn parse_marked_tag(values: &[String]) -> Option<(&str, &str, Option<RelayUrl>)> {
+    let kind: &str = values.first()?;
+    let value: &str = values.get(1)?;
+    let marker: &str = values.get(3)?;
+    let relay_url: Option<RelayUrl> = values.get(2).and_then(|r| RelayUrl::parse(r).ok());
+
+    match kind {
+        "a" | "e" => Some((marker, value, relay_url)),
+        _ => None,
+    }
+}
+
+/// Collect the references with the given marker
+///
+/// The versions (`e` tags) are paired with the articles (`a` tags) by author (the public key in the `e` tag).
+/// A version without author is paired only if there is a single article and a single version.
+fn extract_references(event: &Event, marker: &str) -> Vec<WikiReference> {
+    let mut references: Vec<WikiReference> = Vec::new();
+    let mut event_ids: Vec<(EventId, Option<RelayUrl>, Option<PublicKey>)> = Vec::new();
+
+    for tag in event.tags.iter() {
+        let values: &[String] = tag.as_slice();
+
+        if let Some((m, value, relay_url)) = parse_marked_tag(values) {
+            if m != marker {
+                continue;
+            }
+
+            match values[0].as_str() {
+                "a" => {
+                    if let Ok(coordinate) = Coordinate::parse(value) {
+                        references.push(WikiReference {
+                            coordinate,
+                            event_id: None,
+                            relay_url,
+                        });
+                    }
+                }
+                _ => {
+                    if let Ok(id) = EventId::from_hex(value) {
+                        let public_key: Option<PublicKey> =
+                            values.get(4).and_then(|p| PublicKey::from_hex(p).ok());
+                        event_ids.push((id, relay_url, public_key));
+                    }
+                }
+            }
+        }
+    }
+
+    // Pair the versions with the articles
+    let is_unambiguous: bool = references.len() == 1 && event_ids.len() == 1;
+    for (id, relay_url, public_key) in event_ids.into_iter() {
+        let reference: Option<&mut WikiReference> = match public_key {
+            Some(public_key) => references
+                .iter_mut()
+                .find(|r| r.event_id.is_none() && r.coordinate.public_key == public_key),
+            None if is_unambiguous => references.first_mut(),
+            None => None,
+        };
+
+        if let Some(reference) = reference {
+            reference.event_id = Some(id);
+            if reference.relay_url.is_none() {
+                reference.relay_url = relay_url;
+            }
+        }
+    }
+
+    references
+}
+
+/// Wiki article
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WikiArticle {
+    /// Normalized topic
+    pub topic: String,
+    /// Title
+    pub title: Option<String>,
+    /// Summary
+    pub summary: Option<String>,
+    /// Content (asciidoc)
+    pub content: String,
+    /// The article this one has been forked from
+    pub fork: Option<WikiReference>,
+    /// The articles the author defers to, as better versions of the topic
+    pub defer: Vec<WikiReference>,
+}
+
+impl WikiArticle {
+    /// New wiki article
+    ///
+    /// The topic is normalized (see [`normalize_topic`]).
+    pub fn new<S>(topic: &str, content: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self {
+            topic: normalize_topic(topic),
+            title: None,
+            summary: None,
+            content: content.into(),
+            fork: None,
+            defer: Vec::new(),
+        }
+    }
+
+    /// Parse wiki article from an [`Event`] (kind `30818`)
+    pub fn from_event(event: &Event) -> Result<Self, Error> {
+        if event.kind != Kind::WikiArticle {
+            return Err(Error::WrongKind {
+                received: event.kind,
+                expected: Kind::WikiArticle,
+            });
+        }
+
+        let topic: &str = event
+            .tags
+            .identifier()
+            .ok_or(Error::IdentifierTagNotFound)?;
+
+        let mut article: Self = Self::new(topic, event.content.clone());
+        article.fork = extract_references(event, FORK).into_iter().next();
+        article.defer = extract_references(event, DEFER);
+
+        for tag in event.tags.iter() {
+            match tag.as_standardized() {
+                Some(TagStandard::Title(title)) => article.title = Some(title.clone()),
+                Some(TagStandard::Summary(summary)) => article.summary = Some(summary.clone()),
+                _ => {}
+            }
+        }
+
+        Ok(article)
+    }
+
+    /// Fork an article
+    ///
+    /// Returns a copy of the article referencing the original version.
+    pub fn fork(event: &Event) -> Result<Self, Error> {
+        let mut article: Self = Self::from_event(event)?;
+        article.fork = Some(WikiReference::from_event(event));
+        article.defer.clear();
+        Ok(article)
+    }
+
+    pub(crate) fn to_event_builder(self) -> EventBuilder {
+        let mut tags: Vec<Tag> = Vec::with_capacity(3 + (self.defer.len() + 1) * 2);
+
+        // Add topic
+        tags.push(Tag::identifier(normalize_topic(&self.topic)));
+
+        // Add title
+        if let Some(title) = self.title {
+            tags.push(Tag::from_standardized_without_cell(TagStandard::Title(
+                title,
+            )));
+        }
+
+        // Add summary
+        if let Some(summary) = self.summary {
+            tags.push(Tag::from_standardized_without_cell(TagStandard::Summary(
+                summary,
+            )));
+        }
+
+        // Add fork
+        if let Some(fork) = self.fork {
+            tags.extend(fork.to_tags(FORK));
+        }
+
+        // Add defers
+        for defer in self.defer.into_iter() {
+            tags.extend(defer.to_tags(DEFER));
+        }
+
+        EventBuilder::new(Kind::WikiArticle, self.content).tags(tags)
+    }
+}
+
+/// Wiki merge request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WikiMergeRequest {
+    /// Article to merge into
+    pub target: WikiReference,
+    /// Version of the forked article to merge
+    pub source: EventId,
+    /// Relay hint of the source
+    pub source_relay_url: Option<RelayUrl>,
+    /// Message
+    pub message: String,
+}
+
+impl WikiMergeRequest {
+    /// New merge request
+    ///
+    /// The `target` event ID is the version against which the modification is based.
+    #[inline]
+    pub fn new<S>(target: WikiReference, source: EventId, message: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self {
+            target,
+            source,
+            source_relay_url: None,
+            message: message.into(),
+        }
+    }
+
+    /// Parse merge request from an [`Event`] (kind `818`)
+    pub fn from_event(event: &Event) -> Result<Self, Error> {
+        if event.kind != Kind::WikiMergeRequest {
+            return Err(Error::WrongKind {
+                received: event.kind,
+                expected: Kind::WikiMergeRequest,
+            });
+        }
+
+        let mut target: Option<WikiReference> = None;
+        let mut base: Option<EventId> = None;
+        let mut source: Option<(EventId, Option<RelayUrl>)> = None;
+
+        for tag in event.tags.iter() {
+            let values: &[String] = tag.as_slice();
+
+            // The source is marked, so parse it before the standardized tags
+            if let Some((SOURCE, value, relay_url)) = parse_marked_tag(values) {
+                if let Ok(id) = EventId::from_hex(value) {
+                    source = Some((id, relay_url));
+                }
+                continue;
+            }
+
+            match tag.as_standardized() {
+                Some(TagStandard::Coordinate {
+                    coordinate,
+                    relay_url,
+                    uppercase: false,
+                }) if coordinate.kind == Kind::WikiArticle => {
+                    target = Some(WikiReference {
+                        coordinate: coordinate.clone(),
+                        event_id: None,
+                        relay_url: relay_url.clone(),
+                    })
+                }
+                Some(TagStandard::Event {
+                    event_id,
+                    uppercase: false,
+                    ..
+                }) => base = Some(*event_id),
+                _ => {}
+            }
+        }
+
+        let mut target: WikiReference = target.ok_or(Error::TargetNotFound)?;
+        target.event_id = base;
+
+        let (source, source_relay_url) = source.ok_or(Error::SourceNotFound)?;
+
+        Ok(Self {
+            target,
+            source,
+            source_relay_url,
+            message: event.content.clone(),
+        })
+    }
+
+    pub(crate) fn to_event_builder(self) -> EventBuilder {
+        let mut tags: Vec<Tag> = Vec::with_capacity(4);
+
+        // Add target article
+        let public_key: PublicKey = self.target.coordinate.public_key;
+        tags.push(Tag::coordinate(
+            self.target.coordinate,
+            self.target.relay_url.clone(),
+        ));
+
+        // Add base version
+        if let Some(event_id) = self.target.event_id {
+            tags.push(Tag::from_standardized_without_cell(TagStandard::Event {
+                event_id,
+                relay_url: self.target.relay_url,
+                marker: None,
+                public_key: None,
+                uppercase: false,
+            }));
+        }
+
+        // Add destination author
+        tags.push(Tag::public_key(public_key));
+
+        // Add source
+        tags.push(Tag::custom(
+            TagKind::e(),
+            [
+                self.source.to_hex(),
+                self.source_relay_url
+                    .map(|r| r.to_string())
+                    .unwrap_or_default(),
+                SOURCE.to_string(),
+            ],
+        ));
+
+        EventBuilder::new(Kind::WikiMergeRequest, self.message).tags(tags)
+    }
+}
+
+/// Wiki redirect
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WikiRedirect {
+    /// Normalized topic to redirect from
+    pub topic: String,
+    /// Article to redirect to
+    pub target: Coordinate,
+    /// Relay hint
+    pub relay_url: Option<RelayUrl>,
+}
+
+impl WikiRedirect {
+    /// New wiki redirect
+    ///
+    /// The topic is normalized (see [`normalize_topic`]).
+    #[inline]
+    pub fn new(topic: &str, target: Coordinate) -> Self {
+        Self {
+            topic: normalize_topic(topic),
+            target,
+            relay_url: None,
+        }
+    }
+
+    /// Parse wiki redirect from an [`Event`] (kind `30819`)
+    pub fn from_event(event: &Event) -> Result<Self, Error> {
+        if event.kind != Kind::WikiRedirect {
+            return Err(Error::WrongKind {
+                received: event.kind,
+                expected: Kind::WikiRedirect,
+            });
+        }
+
+        let topic: &str = event
+            .tags
+            .identifier()
+            .ok_or(Error::IdentifierTagNotFound)?;
+
+        let (target, relay_url) = event
+            .tags
+            .iter()
+            .find_map(|tag| match tag.as_standardized() {
+                Some(TagStandard::Coordinate {
+                    coordinate,
+                    relay_url,
+                    uppercase: false,
+                }) => Some((coordinate.clone(), relay_url.clone())),
+                _ => None,
+            })
+            .ok_or(Error::TargetNotFound)?;
+
+        Ok(Self {
+            topic: normalize_topic(topic),
+            target,
+            relay_url,
+        })
+    }
+
+    pub(crate) fn to_event_builder(self) -> EventBuilder {
+        EventBuilder::new(Kind::WikiRedirect, "").tags([
+            Tag::identifier(normalize_topic(&self.topic)),
+            Tag::coordinate(self.target, self.relay_url),
+        ])
+    }
+}
+
+/// Rank the competing articles on the same topic
+///
+/// Only the latest version of each author is kept. The articles are sorted by:
+/// 1. articles written by the `contacts`;
+/// 2. positive reactions from the `contacts`, minus the negative ones;
+/// 3. positive reactions from everyone else, minus the negative ones;
+/// 4. newest first.
+///
+/// Each public key counts once per article and reactions not referencing an article
+/// (by event ID or by coordinate) are ignored.
+pub fn rank_articles<'a, I, R>(
+    articles: I,
+    contacts: &BTreeSet<PublicKey>,
+    reactions: R,
+) -> Vec<&'a Event>
+where
+    I: IntoIterator<Item = &'a Event>,
+    R: IntoIterator<Item = &'a Event>,
+{
+    // Keep the latest version of each author
+    let mut latest: BTreeMap<PublicKey, &Event> = BTreeMap::new();
+    for article in articles.into_iter() {
+        if article.kind != Kind::WikiArticle {
+            continue;
+        }
+
+        latest
+            .entry(article.pubkey)
+            .and_modify(|current| {
+                if article.created_at > current.created_at {
+                    *current = article;
+                }
+            })
+            .or_insert(article);
+    }
+
+    // Collect the reactions of each public key (the latest one wins)
+    let ids: BTreeMap<EventId, PublicKey> = latest.values().map(|e| (e.id, e.pubkey)).collect();
+    let identifiers: BTreeMap<PublicKey, &str> = latest
+        .values()
+        .filter_map(|e| Some((e.pubkey, e.tags.identifier()?)))
+        .collect();
+    let mut votes: BTreeMap<(PublicKey, PublicKey), &Event> = BTreeMap::new();
+    for reaction in reactions.into_iter() {
+        if reaction.kind != Kind::Reaction {
+            continue;
+        }
+
+        // The last `e` tag is the reacted event, otherwise the article is referenced by coordinate
+        let author: PublicKey = match reaction.tags.event_ids().last().and_then(|id| ids.get(id)) {
+            Some(author) => *author,
+            None => match reaction.tags.coordinates().find(|c| {
+                c.kind == Kind::WikiArticle
+                    && identifiers.get(&c.public_key) == Some(&c.identifier.as_str())
+            }) {
+                Some(coordinate) => coordinate.public_key,
+                None => continue,
+            },
+        };
+
+        votes
+            .entry((author, reaction.pubkey))
+            .and_modify(|current| {
+                if reaction.created_at > current.created_at {
+                    *current = reaction;
+                }
+            })
+            .or_insert(reaction);
+    }
+
+    let mut scores: BTreeMap<PublicKey, (i64, i64)> = BTreeMap::new();
+    for ((author, voter), reaction) in votes.into_iter() {
+        let vote: i64 = if reaction.content == "-" { -1 } else { 1 };
+        let score: &mut (i64, i64) = scores.entry(author).or_default();
+
+        if contacts.contains(&voter) {
+            score.0 += vote;
+        } else {
+            score.1 += vote;
+        }
+    }
+
+    let mut articles: Vec<&Event> = latest.into_values().collect();
+    articles.sort_by_key(|article| {
+        let (by_contacts, by_others) = scores.get(&article.pubkey).copied().unwrap_or_default();
+        (
+            Reverse(contacts.contains(&article.pubkey)),
+            Reverse(by_contacts),
+            Reverse(by_others),
+            Reverse(article.created_at),
+        )
+    });
+    articles
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Keys, Timestamp};
+
+    #[test]
+    fn test_normalize_topic() {
+        assert_eq!(normalize_topic("Wiki Article"), "wiki-article");
+        assert_eq!(normalize_topic("  What's   up?  "), "whats-up");
+        assert_eq!(
+            normalize_topic("Bitcoin -- Whitepaper"),
+            "bitcoin-whitepaper"
+        );
+        assert_eq!(normalize_topic("Olá Mundo"), "olá-mundo");
+        assert_eq!(normalize_topic("日本語 テスト"), "日本語-テスト");
+        assert_eq!(normalize_topic("C++ & Rust!"), "c-rust");
+    }
+
+    #[test]
+    fn test_fork_and_merge_request() {
+        let alice = Keys::generate();
+        let bob = Keys::generate();
+
+        let mut article = WikiArticle::new("Hot Ice Creams", "= Hot ice creams");
+        article.title = Some("Hot Ice Creams".into());
+        let original = EventBuilder::wiki_article(article.clone())
+            .sign_with_keys(&alice)
+            .unwrap();
+        assert_eq!(original.tags.identifier(), Some("hot-ice-creams"));
+        assert_eq!(WikiArticle::from_event(&original).unwrap(), article);
+
+        // Fork
+        let mut fork = WikiArticle::fork(&original).unwrap();
+        fork.content.push_str("\n\nHow to make them.");
+        let forked = EventBuilder::wiki_article(fork.clone())
+            .sign_with_keys(&bob)
+            .unwrap();
+        let parsed = WikiArticle::from_event(&forked).unwrap();
+        assert_eq!(parsed, fork);
+        assert_eq!(parsed.fork, Some(WikiReference::from_event(&original)));
+
+        // Merge request
+        let request = WikiMergeRequest::new(
+            WikiReference::from_event(&original),
+            forked.id,
+            "Added the recipe",
+        );
+        let event = EventBuilder::wiki_merge_request(request.clone())
+            .sign_with_keys(&bob)
+            .unwrap();
+        assert_eq!(event.tags.public_keys().next(), Some(&alice.public_key()));
+        assert_eq!(WikiMergeRequest::from_event(&event).unwrap(), request);
+
+        // Redirect
+        let redirect = WikiRedirect::new(
+            "Hot Ice-Cream",
+            WikiReference::from_event(&original).coordinate,
+        );
+        let event = EventBuilder::wiki_redirect(redirect.clone())
+            .sign_with_keys(&alice)
+            .unwrap();
+        assert_eq!(WikiRedirect::from_event(&event).unwrap(), redirect);
+    }
+
+    #[test]
+    fn test_rank_articles() {
+        let reader = Keys::generate();
+        let contact = Keys::generate();
+        let stranger1 = Keys::generate();
+        let stranger2 = Keys::generate();
+        let voter = Keys::generate();
+
+        let article = |keys: &Keys, created_at: u64| {
+            EventBuilder::wiki_article(WikiArticle::new("nostr", "Nostr"))
+                .custom_created_at(Timestamp::from(created_at))
+                .sign_with_keys(keys)
+                .unwrap()
+        };
+        let reaction = |keys: &Keys, article: &Event, content: &str| {
+            EventBuilder::new(Kind::Reaction, content)
+                .tag(Tag::event(article.id))
+                .sign_with_keys(keys)
+                .unwrap()
+        };
+
+        let by_contact = article(&contact, 10);
+        let by_stranger1_old = article(&stranger1, 10);
+        let by_stranger1 = article(&stranger1, 20);
+        let by_stranger2 = article(&stranger2, 30);
+
+        let contacts: BTreeSet<PublicKey> = [reader.public_key(), contact.public_key()].into();
+        let reactions = [
+            reaction(&reader, &by_stranger1, "+"),
+            reaction(&voter, &by_stranger2, "+"),
+            reaction(&contact, &by_stranger2, "-"),
+            // Reaction to an outdated version
+            reaction(&voter, &by_stranger1_old, "+"),
+        ];
+
+        let ranked = rank_articles(
+            [&by_contact, &by_stranger1_old, &by_stranger1, &by_stranger2],
+            &contacts,
+            reactions.iter(),
+        );
+        assert_eq!(ranked, vec![&by_contact, &by_stranger1, &by_stranger2]);
+
+        // Reactions referencing the article by coordinate (the newest article is downvoted)
+        let coordinate =
+            Coordinate::new(Kind::WikiArticle, stranger2.public_key()).identifier("nostr");
+        let reactions = [
+            EventBuilder::new(Kind::Reaction, "-")
+                .tag(Tag::coordinate(coordinate.clone(), None))
+                .sign_with_keys(&reader)
+                .unwrap(),
+            EventBuilder::new(Kind::Reaction, "-")
+                .tag(Tag::coordinate(coordinate, None))
+                .sign_with_keys(&voter)
+                .unwrap(),
+        ];
+        let ranked = rank_articles([&by_stranger1, &by_stranger2], &contacts, reactions.iter());
+        assert_eq!(ranked, vec![&by_stranger1, &by_stranger2]);
+    }
+
+    #[test]
+    fn test_extract_references() {
+        let alice = Keys::generate();
+        let bob = Keys::generate();
+
+        let alice_article = EventBuilder::wiki_article(WikiArticle::new("nostr", "Nostr"))
+            .sign_with_keys(&alice)
+            .unwrap();
+        let bob_article = EventBuilder::wiki_article(WikiArticle::new("nostr", "Nostr!"))
+            .sign_with_keys(&bob)
+            .unwrap();
+
+        // Versions not in the same order of the articles and an article without version
+        let mut article = WikiArticle::new("nostr", "Nostr?");
+        article.defer = vec![
+            WikiReference::new(WikiReference::from_event(&alice_article).coordinate),
+            WikiReference::from_event(&bob_article),
+        ];
+        let event = EventBuilder::wiki_article(article.clone())
+            .sign_with_keys(&Keys::generate())
+            .unwrap();
+        assert_eq!(WikiArticle::from_event(&event).unwrap(), article);
+
+        // Version without author
+        let event = EventBuilder::new(Kind::WikiArticle, "")
+            .tags([
+                Tag::identifier("nostr"),
+                Tag::custom(
+                    TagKind::a(),
+                    [
+                        WikiReference::from_event(&alice_article)
+                            .coordinate
+                            .to_string(),
+                        String::new(),
+                        DEFER.to_string(),
+                    ],
+                ),
+                Tag::custom(
+                    TagKind::e(),
+                    [alice_article.id.to_hex(), String::new(), DEFER.to_string()],
+                ),
+            ])
+            .sign_with_keys(&bob)
+            .unwrap();
+        let parsed = WikiArticle::from_event(&event).unwrap();
+        assert_eq!(
+            parsed.defer,
+            vec![WikiReference::from_event(&alice_article)]
+        );
+    }
+}
@@ -0,0 +1,224 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2025 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! NIP68: Picture-first feeds
+//!
+//! <https://github.com/nostr-protocol/nips/blob/master/68.md>
+
+#![allow(clippy::wrong_self_convention)]
+
+use alloc::collections::BTreeSet;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+use hashes::sha256::Hash as Sha256Hash;
+
+use super::nip92::{self, MediaAttachment};
+use crate::{Event, EventBuilder, Kind, PublicKey, Tag, TagStandard};
+
+/// NIP68 error
+#[derive(Debug, PartialEq, Eq)]
+pub enum Error {
+    /// Wrong event kind
+    WrongKind {
+        /// The received kind
+        received: Kind,
+        /// The expected kind
+        expected: Kind,
+    },
+    /// No valid `imeta` tag found
+    MediaNotFound,
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::WrongKind { received, expected } => {
+                write!(f, "wrong kind: received={received}, expected={expected}")
+            }
+            Self::MediaNotFound => f.write_str("media not found"),
+        }
+    }
+}
+
+/// Picture
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Picture {
+    /// Title
+    pub title: Option<String>,
+    /// Description
+    pub description: String,
+    /// Images
+    pub images: Vec<MediaAttachment>,
+    /// Content warning
+    ///
+    /// An empty reason marks the content as sensitive without specifying why.
+    pub content_warning: Option<String>,
+    /// Hashtags
+    pub hashtags: Vec<String>,
+    /// Tagged users
+    pub tagged_users: Vec<PublicKey>,
+}
+
+impl Picture {
+    /// New picture
+    pub fn new<S>(description: S, images: Vec<MediaAttachment>) -> Self
+    where
+        S: Into<String>,
+    {
+        Self {
+            title: None,
+            description: description.into(),
+            images,
+            content_warning: None,
+            hashtags: Vec::new(),
+            tagged_users: Vec::new(),
+        }
+    }
+
+    /// Parse picture from an [`Event`] (kind `20`)
+    pub fn from_event(event: &Event) -> Result<Self, Error> {
+        if event.kind != Kind::Picture {
+            return Err(Error::WrongKind {
+                received: event.kind,
+                expected: Kind::Picture,
+            });
+        }
+
+        let images: Vec<MediaAttachment> = nip92::extract_media_attachments(&event.tags).collect();
+
+        if images.is_empty() {
+            return Err(Error::MediaNotFound);
+        }
+
+        let mut picture: Self = Self::new(event.content.clone(), images);
+
+        for tag in event.tags.iter() {
+            match tag.as_standardized() {
+                Some(TagStandard::Title(title)) => picture.title = Some(title.clone()),
+                Some(TagStandard::ContentWarning { reason }) => {
+                    picture.content_warning = Some(reason.clone().unwrap_or_default())
+                }
+                Some(TagStandard::Hashtag(hashtag)) => picture.hashtags.push(hashtag.clone()),
+                Some(TagStandard::PublicKey {
+                    public_key,
+                    uppercase: false,
+                    ..
+                }) => picture.tagged_users.push(*public_key),
+                _ => {}
+            }
+        }
+
+        Ok(picture)
+    }
+
+    pub(crate) fn to_event_builder(self) -> Result<EventBuilder, Error> {
+        if self.images.is_empty() {
+            return Err(Error::MediaNotFound);
+        }
+
+        let mut tags: Vec<Tag> = Vec::with_capacity(self.images.len() * 3 + 2);
+
+        // Add title
+        if let Some(title) = self.title {
+            tags.push(Tag::from_standardized_without_cell(TagStandard::Title(
+                title,
+            )));
+        }
+
+        // Add images
+        tags.extend(self.images.iter().map(MediaAttachment::to_tag));
+
+        // Add MIME types and hashes, for filtering
+        let mime_types: BTreeSet<&String> = self
+            .images
+            .iter()
+            .filter_map(|i| i.mime_type.as_ref())
+            .collect();
+        for mime_type in mime_types.into_iter() {
+            tags.push(Tag::from_standardized_without_cell(TagStandard::MimeType(
+                mime_type.clone(),
+            )));
+        }
+
+        let hashes: BTreeSet<&Sha256Hash> =
+            self.images.iter().filter_map(|i| i.hash.as_ref()).collect();
+        for hash in hashes.into_iter() {
+            tags.push(Tag::from_standardized_without_cell(TagStandard::Sha256(
+                *hash,
+            )));
+        }
+
+        // Add content warning
+        if let Some(reason) = self.content_warning {
+            tags.push(Tag::from_standardized_without_cell(
+                TagStandard::ContentWarning {
+                    reason: (!reason.is_empty()).then_some(reason),
+                },
+            ));
+        }
+
+        // Add hashtags
+        tags.extend(self.hashtags.into_iter().map(Tag::hashtag));
+
+        // Add tagged users
+        tags.extend(self.tagged_users.into_iter().map(Tag::public_key));
+
+        Ok(EventBuilder::new(Kind::Picture, self.description).tags(tags))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::str::FromStr;
+
+    use super::*;
+    use crate::{Alphabet, ImageDimensions, Keys, TagKind, Url};
+
+    #[test]
+    fn test_picture() {
+        let keys = Keys::generate();
+        let tagged = Keys::generate().public_key();
+
+        let image = MediaAttachment::new(Url::parse("https://example.com/image.jpg").unwrap())
+            .mime_type("image/jpeg")
+            .hash(
+                Sha256Hash::from_str(
+                    "1aea8e98e0e5d969b7124f553b88dfae47d1f00472ea8c0dbf4ac4577d39ef02",
+                )
+                .unwrap(),
+            )
+            .dimensions(ImageDimensions::new(3024, 4032))
+            .alt("A scenic photo");
+
+        let mut picture = Picture::new("Costa Rica", vec![image]);
+        picture.title = Some("Sunset".into());
+        picture.content_warning = Some(String::new());
+        picture.hashtags = vec!["photography".into()];
+        picture.tagged_users = vec![tagged];
+
+        let event = EventBuilder::picture(picture.clone())
+            .unwrap()
+            .sign_with_keys(&keys)
+            .unwrap();
+        assert_eq!(event.kind, Kind::Picture);
+        assert_eq!(
+            event
+                .tags
+                .find_standardized(TagKind::single_letter(Alphabet::M, false)),
+            Some(&TagStandard::MimeType("image/jpeg".into()))
+        );
+        assert_eq!(Picture::from_event(&event).unwrap(), picture);
+
+        let picture = Picture::new("No images", Vec::new());
+        assert_eq!(
+            EventBuilder::picture(picture).unwrap_err(),
+            crate::event::builder::Error::NIP68(Error::MediaNotFound)
+        );
+    }
+}
@@ -0,0 +1,666 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2025 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! NIP69: Peer-to-peer Order events
+//!
+//! <https://github.com/nostr-protocol/nips/blob/master/69.md>
+
+#![allow(clippy::wrong_self_convention)]
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+use core::str::FromStr;
+
+use crate::types::Url;
+use crate::{Event, EventBuilder, Kind, Tag, TagKind, TagStandard, Timestamp};
+
+const ORDER_TYPE: &str = "k";
+const CURRENCY: &str = "f";
+const STATUS: &str = "s";
+const AMOUNT: &str = "amt";
+const FIAT_AMOUNT: &str = "fa";
+const PAYMENT_METHODS: &str = "pm";
+const PREMIUM: &str = "premium";
+const NETWORK: &str = "network";
+const LAYER: &str = "layer";
+const EXPIRES_AT: &str = "expires_at";
+const PLATFORM: &str = "y";
+const DOCUMENT: &str = "z";
+const NAME: &str = "name";
+const SOURCE: &str = "source";
+const RATING: &str = "rating";
+const GEOHASH: &str = "g";
+const BOND: &str = "bond";
+
+/// NIP69 error
+#[derive(Debug, PartialEq, Eq)]
+pub enum Error {
+    /// Wrong event kind
+    WrongKind {
+        /// The received kind
+        received: Kind,
+        /// The expected kind
+        expected: Kind,
+    },
+    /// Unknown order type
+    UnknownOrderType,
+    /// Unknown order status
+    UnknownStatus,
+    /// Unknown network
+    UnknownNetwork,
+    /// Unknown layer
+    UnknownLayer,
+    /// Invalid value of a tag
+    InvalidTag(&'static str),
+    /// Missing tag
+    MissingTag(&'static str),
+    /// Invalid status transition
+    InvalidTransition {
+        /// Current status
+        from: OrderStatus,
+        /// New status
+        to: OrderStatus,
+    },
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::WrongKind { received, expected } => {
+                write!(f, "wrong kind: received={received}, expected={expected}")
+            }
+            Self::UnknownOrderType => f.write_str("unknown order type"),
+            Self::UnknownStatus => f.write_str("unknown order status"),
+            Self::UnknownNetwork => f.write_str("unknown network"),
+            Self::UnknownLayer => f.write_str("unknown layer"),
+            Self::InvalidTag(tag) => write!(f, "invalid '{tag}' tag"),
+            Self::MissingTag(tag) => write!(f, "missing '{tag}' tag"),
+            Self::InvalidTransition { from, to } => {
+                write!(f, "invalid status transition: from={from}, to={to}")
+            }
+        }
+    }
+}
+
+/// Order type
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum OrderType {
+    /// Buy bitcoin
+    Buy,
+    /// Sell bitcoin
+    Sell,
+}
+
+impl fmt::Display for OrderType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl OrderType {
+    /// Get as `&str`
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Buy => "buy",
+            Self::Sell => "sell",
+        }
+    }
+}
+
+impl FromStr for OrderType {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "buy" => Ok(Self::Buy),
+            "sell" => Ok(Self::Sell),
+            _ => Err(Error::UnknownOrderType),
+        }
+    }
+}
+
+/// Order status
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum OrderStatus {
+    /// Waiting for a taker
+    Pending,
+    /// Canceled by the maker
+    Canceled,
+    /// Taken, the trade is in progress
+    InProgress,
+    /// Trade completed
+    Success,
+    /// Expired without being taken
+    Expired,
+}
+
+impl fmt::Display for OrderStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl OrderStatus {
+    /// Get as `&str`
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Pending => "pending",
+            Self::Canceled => "canceled",
+            Self::InProgress => "in-progress",
+            Self::Success => "success",
+            Self::Expired => "expired",
+        }
+    }
+
+    /// Check if the status is final (no more transitions allowed)
+    #[inline]
+    pub fn is_final(&self) -> bool {
+        matches!(self, Self::Canceled | Self::Success | Self::Expired)
+    }
+
+    /// Check if the order can move from this status to the `next` one
+    ///
+    /// * `pending` can become `in-progress`, `canceled` or `expired`;
+    /// * `in-progress` can become `success` or `canceled`, or go back to `pending` if the taker gives up.
+    pub fn can_transition_to(&self, next: &Self) -> bool {
+        matches!(
+            (self, next),
+            (
+                Self::Pending,
+                Self::InProgress | Self::Canceled | Self::Expired
+            ) | (
+                Self::InProgress,
+                Self::Success | Self::Canceled | Self::Pending
+            )
+        )
+    }
+}
+
+impl FromStr for OrderStatus {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pending" => Ok(Self::Pending),
+            "canceled" => Ok(Self::Canceled),
+            "in-progress" => Ok(Self::InProgress),
+            "success" => Ok(Self::Success),
+            "expired" => Ok(Self::Expired),
+            _ => Err(Error::UnknownStatus),
+        }
+    }
+}
+
+/// Bitcoin network
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Network {
+    /// Mainnet
+    Mainnet,
+    /// Testnet
+    Testnet,
+    /// Signet
+    Signet,
+    /// Regtest
+    Regtest,
+}
+
+impl fmt::Display for Network {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Network {
+    /// Get as `&str`
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Mainnet => "mainnet",
+            Self::Testnet => "testnet",
+            Self::Signet => "signet",
+            Self::Regtest => "regtest",
+        }
+    }
+}
+
+impl FromStr for Network {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "mainnet" => Ok(Self::Mainnet),
+            "testnet" => Ok(Self::Testnet),
+            "signet" => Ok(Self::Signet),
+            "regtest" => Ok(Self::Regtest),
+            _ => Err(Error::UnknownNetwork),
+        }
+    }
+}
+
+/// Settlement layer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Layer {
+    /// Onchain
+    Onchain,
+    /// Lightning
+    Lightning,
+    /// Liquid
+    Liquid,
+}
+
+impl fmt::Display for Layer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Layer {
+    /// Get as `&str`
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Onchain => "onchain",
+            Self::Lightning => "lightning",
+            Self::Liquid => "liquid",
+        }
+    }
+}
+
+impl FromStr for Layer {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "onchain" => Ok(Self::Onchain),
+            "lightning" => Ok(Self::Lightning),
+            "liquid" => Ok(Self::Liquid),
+            _ => Err(Error::UnknownLayer),
+        }
+    }
+}
+
+/// Fiat amount
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum FiatAmount {
+    /// Fixed amount
+    Fixed(u64),
+    /// Range, chosen by the taker
+    Range {
+        /// Minimum amount
+        min: u64,
+        /// Maximum amount
+        max: u64,
+    },
+}
+
+impl FiatAmount {
+    /// Check if the amount is accepted
+    pub fn contains(&self, amount: u64) -> bool {
+        match self {
+            Self::Fixed(fixed) => *fixed == amount,
+            Self::Range { min, max } => (*min..=*max).contains(&amount),
+        }
+    }
+
+    fn parse(values: &[String]) -> Result<Self, Error> {
+        let parse = |value: &String| {
+            value
+                .parse::<u64>()
+                .map_err(|_| Error::InvalidTag(FIAT_AMOUNT))
+        };
+
+        match values {
+            [amount] => Ok(Self::Fixed(parse(amount)?)),
+            [min, max] => {
+                let (min, max) = (parse(min)?, parse(max)?);
+
+                if min > max {
+                    return Err(Error::InvalidTag(FIAT_AMOUNT));
+                }
+
+                Ok(Self::Range { min, max })
+            }
+            _ => Err(Error::InvalidTag(FIAT_AMOUNT)),
+        }
+    }
+
+    fn to_values(self) -> Vec<String> {
+        match self {
+            Self::Fixed(amount) => vec![amount.to_string()],
+            Self::Range { min, max } => vec![min.to_string(), max.to_string()],
+        }
+    }
+}
+
+/// Peer-to-peer order
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PeerToPeerOrder {
+    /// Order ID
+    pub id: String,
+    /// Order type
+    pub order_type: OrderType,
+    /// Fiat currency (ISO 4217)
+    pub currency: String,
+    /// Status
+    pub status: OrderStatus,
+    /// Amount of sats (`0` means that it's determined by the market price)
+    pub amount: u64,
+    /// Fiat amount
+    pub fiat_amount: FiatAmount,
+    /// Payment methods
+    pub payment_methods: Vec<String>,
+    /// Premium over the market price (percentage)
+    pub premium: i64,
+    /// Bitcoin network
+    pub network: Network,
+    /// Settlement layer
+    pub layer: Layer,
+    /// When the order expires (it can't be taken anymore)
+    pub expires_at: Timestamp,
+    /// When the event can be deleted by relays
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/40.md>
+    pub expiration: Option<Timestamp>,
+    /// Platform
+    pub platform: String,
+    /// Name or nickname of the maker
+    pub name: Option<String>,
+    /// URL of the order on the platform
+    pub source: Option<Url>,
+    /// Reputation data of the maker (JSON)
+    pub rating: Option<String>,
+    /// Geohash, for face to face trades
+    pub geohash: Option<String>,
+    /// Bond (sats)
+    pub bond: Option<u64>,
+}
+
+impl PeerToPeerOrder {
+    /// New pending order
+    #[allow(clippy::too_many_arguments)]
+    pub fn new<ID, C, P, I, S>(
+        id: ID,
+        order_type: OrderType,
+        currency: C,
+        amount: u64,
+        fiat_amount: FiatAmount,
+        payment_methods: I,
+        expires_at: Timestamp,
+        platform: P,
+    ) -> Self
+    where
+        ID: Into<String>,
+        C: Into<String>,
+        P: Into<String>,
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            id: id.into(),
+            order_type,
+            currency: currency.into(),
+            status: OrderStatus::Pending,
+            amount,
+            fiat_amount,
+            payment_methods: payment_methods.into_iter().map(|m| m.into()).collect(),
+            premium: 0,
+            network: Network::Mainnet,
+            layer: Layer::Lightning,
+            expires_at,
+            expiration: None,
+            platform: platform.into(),
+            name: None,
+            source: None,
+            rating: None,
+            geohash: None,
+            bond: None,
+        }
+    }
+
+    /// Check if the order can be taken (it's `pending` and not expired)
+    #[inline]
+    pub fn is_active(&self, now: Timestamp) -> bool {
+        self.status == OrderStatus::Pending && self.expires_at > now
+    }
+
+    /// Update the status
+    ///
+    /// Returns an error if the transition isn't allowed (see [`OrderStatus::can_transition_to`]).
+    pub fn transition(&mut self, status: OrderStatus) -> Result<(), Error> {
+        if !self.status.can_transition_to(&status) {
+            return Err(Error::InvalidTransition {
+                from: self.status,
+                to: status,
+            });
+        }
+
+        self.status = status;
+        Ok(())
+    }
+
+    /// Parse order from an [`Event`] (kind `38383`)
+    pub fn from_event(event: &Event) -> Result<Self, Error> {
+        if event.kind != Kind::PeerToPeerOrder {
+            return Err(Error::WrongKind {
+                received: event.kind,
+                expected: Kind::PeerToPeerOrder,
+            });
+        }
+
+        let id: &str = event.tags.identifier().ok_or(Error::MissingTag("d"))?;
+
+        let mut order_type: Option<OrderType> = None;
+        let mut currency: Option<String> = None;
+        let mut status: Option<OrderStatus> = None;
+        let mut amount: Option<u64> = None;
+        let mut fiat_amount: Option<FiatAmount> = None;
+        let mut payment_methods: Option<Vec<String>> = None;
+        let mut premium: Option<i64> = None;
+        let mut network: Option<Network> = None;
+        let mut layer: Option<Layer> = None;
+        let mut expires_at: Option<Timestamp> = None;
+        let mut platform: Option<String> = None;
+        let mut expiration: Option<Timestamp> = None;
+        let mut name: Option<String> = None;
+        let mut source: Option<Url> = None;
+        let mut rating: Option<String> = None;
+        let mut geohash: Option<String> = None;
+        let mut bond: Option<u64> = None;
+
+        for tag in event.tags.iter() {
+            if let Some(TagStandard::Expiration(timestamp)) = tag.as_standardized() {
+                expiration = Some(*timestamp);
+                continue;
+            }
+
+            let (kind, values) = match tag.as_slice().split_first() {
+                Some((kind, values)) if !values.is_empty() => (kind.as_str(), values),
+                _ => continue,
+            };
+            let value: &String = &values[0];
+
+            match kind {
+                ORDER_TYPE => order_type = Some(OrderType::from_str(value)?),
+                CURRENCY => currency = Some(value.to_uppercase()),
+                STATUS => status = Some(OrderStatus::from_str(value)?),
+                AMOUNT => amount = Some(value.parse().map_err(|_| Error::InvalidTag(AMOUNT))?),
+                FIAT_AMOUNT => fiat_amount = Some(FiatAmount::parse(values)?),
+                PAYMENT_METHODS => payment_methods = Some(values.to_vec()),
+                PREMIUM => premium = Some(value.parse().map_err(|_| Error::InvalidTag(PREMIUM))?),
+                NETWORK => network = Some(Network::from_str(value)?),
+                LAYER => layer = Some(Layer::from_str(value)?),
+                EXPIRES_AT => {
+                    expires_at = Some(
+                        Timestamp::from_str(value).map_err(|_| Error::InvalidTag(EXPIRES_AT))?,
+                    )
+                }
+                PLATFORM => platform = Some(value.clone()),
+                NAME => name = Some(value.clone()),
+                SOURCE => source = Url::parse(value).ok(),
+                RATING => rating = Some(value.clone()),
+                GEOHASH => geohash = Some(value.clone()),
+                BOND => bond = value.parse().ok(),
+                _ => {}
+            }
+        }
+
+        Ok(Self {
+            id: id.to_string(),
+            order_type: order_type.ok_or(Error::MissingTag(ORDER_TYPE))?,
+            currency: currency.ok_or(Error::MissingTag(CURRENCY))?,
+            status: status.ok_or(Error::MissingTag(STATUS))?,
+            amount: amount.ok_or(Error::MissingTag(AMOUNT))?,
+            fiat_amount: fiat_amount.ok_or(Error::MissingTag(FIAT_AMOUNT))?,
+            payment_methods: payment_methods.ok_or(Error::MissingTag(PAYMENT_METHODS))?,
+            premium: premium.ok_or(Error::MissingTag(PREMIUM))?,
+            network: network.ok_or(Error::MissingTag(NETWORK))?,
+            layer: layer.ok_or(Error::MissingTag(LAYER))?,
+            expires_at: expires_at.ok_or(Error::MissingTag(EXPIRES_AT))?,
+            expiration,
+            platform: platform.ok_or(Error::MissingTag(PLATFORM))?,
+            name,
+            source,
+            rating,
+            geohash,
+            bond,
+        })
+    }
+
+    pub(crate) fn to_event_builder(self) -> EventBuilder {
+        let custom = |kind: &str, value: String| Tag::custom(TagKind::from(kind), [value]);
+
+        let mut tags: Vec<Tag> = vec![
+            Tag::identifier(self.id),
+            custom(ORDER_TYPE, self.order_type.to_string()),
+            custom(CURRENCY, self.currency.to_uppercase()),
+            custom(STATUS, self.status.to_string()),
+            custom(AMOUNT, self.amount.to_string()),
+            Tag::custom(TagKind::from(FIAT_AMOUNT), self.fiat_amount.to_values()),
+            Tag::custom(TagKind::from(PAYMENT_METHODS), self.payment_methods),
+            custom(PREMIUM, self.premium.to_string()),
+            custom(NETWORK, self.network.to_string()),
+            custom(LAYER, self.layer.to_string()),
+            custom(EXPIRES_AT, self.expires_at.to_string()),
+            custom(PLATFORM, self.platform),
+            custom(DOCUMENT, String::from("order")),
+        ];
+
+        if let Some(expiration) = self.expiration {
+            tags.push(Tag::expiration(expiration));
+        }
+
+        if let Some(name) = self.name {
+            tags.push(custom(NAME, name));
+        }
+
+        if let Some(source) = self.source {
+            tags.push(custom(SOURCE, source.to_string()));
+        }
+
+        if let Some(rating) = self.rating {
+            tags.push(custom(RATING, rating));
+        }
+
+        if let Some(geohash) = self.geohash {
+            tags.push(custom(GEOHASH, geohash));
+        }
+
+        if let Some(bond) = self.bond {
+            tags.push(custom(BOND, bond.to_string()));
+        }
+
+        EventBuilder::new(Kind::PeerToPeerOrder, "").tags(tags)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::filter::MatchEventOptions;
+    use crate::{Filter, Keys};
+
+    #[test]
+    fn test_status_transitions() {
+        assert!(OrderStatus::Pending.can_transition_to(&OrderStatus::InProgress));
+        assert!(OrderStatus::InProgress.can_transition_to(&OrderStatus::Success));
+        assert!(!OrderStatus::Success.can_transition_to(&OrderStatus::Pending));
+        assert!(!OrderStatus::Pending.can_transition_to(&OrderStatus::Success));
+        assert!(OrderStatus::Expired.is_final());
+
+        let mut order = PeerToPeerOrder::new(
+            "order",
+            OrderType::Buy,
+            "USD",
+            0,
+            FiatAmount::Fixed(100),
+            ["bank transfer"],
+            Timestamp::from(1_000),
+            "lnp2pbot",
+        );
+        assert_eq!(
+            order.transition(OrderStatus::Success),
+            Err(Error::InvalidTransition {
+                from: OrderStatus::Pending,
+                to: OrderStatus::Success
+            })
+        );
+        order.transition(OrderStatus::InProgress).unwrap();
+        assert_eq!(order.status, OrderStatus::InProgress);
+    }
+
+    #[test]
+    fn test_order() {
+        let keys = Keys::generate();
+
+        let mut order = PeerToPeerOrder::new(
+            "56242a5c-a3e6-4f4d-8c68-95c7f1c0b2a5",
+            OrderType::Sell,
+            "ves",
+            0,
+            FiatAmount::Range {
+                min: 100,
+                max: 1000,
+            },
+            ["face to face", "bank transfer"],
+            Timestamp::from(1_719_391_096),
+            "lnp2pbot",
+        );
+        order.premium = -1;
+        order.expiration = Some(Timestamp::from(1_719_995_096));
+        order.name = Some("Nakamoto".into());
+        order.bond = Some(0);
+
+        let event = EventBuilder::p2p_order(order.clone())
+            .sign_with_keys(&keys)
+            .unwrap();
+
+        order.currency = String::from("VES");
+        let parsed = PeerToPeerOrder::from_event(&event).unwrap();
+        assert_eq!(parsed, order);
+        assert!(parsed.is_active(Timestamp::from(1_719_000_000)));
+        assert!(!parsed.is_active(Timestamp::from(1_719_391_096)));
+        assert!(parsed.fiat_amount.contains(500));
+
+        let filter = Filter::new()
+            .kind(Kind::PeerToPeerOrder)
+            .p2p_order_type(OrderType::Sell)
+            .p2p_currency("VES")
+            .p2p_order_status(OrderStatus::Pending);
+        assert!(filter.match_event(&event, MatchEventOptions::new()));
+
+        let filter = filter.p2p_order_type(OrderType::Buy);
+        assert!(filter.match_event(&event, MatchEventOptions::new()));
+
+        let filter = Filter::new().p2p_currency("USD");
+        assert!(!filter.match_event(&event, MatchEventOptions::new()));
+
+        // Lowercase currency codes
+        let filter = Filter::new().p2p_currency("ves");
+        assert!(filter.match_event(&event, MatchEventOptions::new()));
+        let filter = Filter::new().p2p_currencies(["usd", "ves"]);
+        assert!(filter.match_event(&event, MatchEventOptions::new()));
+    }
+}
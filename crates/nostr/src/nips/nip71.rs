@@ -0,0 +1,264 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2025 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! NIP71: Video Events
+//!
+//! <https://github.com/nostr-protocol/nips/blob/master/71.md>
+
+#![allow(clippy::wrong_self_convention)]
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+
+use super::nip92::{self, MediaAttachment};
+use crate::{Event, EventBuilder, Kind, PublicKey, Tag, TagKind, TagStandard, Timestamp};
+
+const DURATION: &str = "duration";
+
+/// NIP71 error
+#[derive(Debug, PartialEq, Eq)]
+pub enum Error {
+    /// Wrong event kind
+    WrongKind {
+        /// The received kind
+        received: Kind,
+        /// The expected kind (kind `22` is also accepted, for short videos)
+        expected: Kind,
+    },
+    /// Title tag not found
+    TitleTagNotFound,
+    /// No valid `imeta` tag found
+    MediaNotFound,
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::WrongKind { received, expected } => {
+                write!(f, "wrong kind: received={received}, expected={expected}")
+            }
+            Self::TitleTagNotFound => f.write_str("title tag not found"),
+            Self::MediaNotFound => f.write_str("media not found"),
+        }
+    }
+}
+
+/// Video
+#[derive(Debug, Clone, PartialEq)]
+pub struct Video {
+    /// Short-form portrait video (kind `22`), otherwise normal video (kind `21`)
+    pub short: bool,
+    /// Title
+    pub title: String,
+    /// Summary or description
+    pub description: String,
+    /// Variants of the video (i.e. different resolutions)
+    pub variants: Vec<MediaAttachment>,
+    /// Publication timestamp
+    pub published_at: Option<Timestamp>,
+    /// Duration in seconds
+    pub duration: Option<f64>,
+    /// Description for accessibility
+    pub alt: Option<String>,
+    /// Content warning
+    ///
+    /// An empty reason marks the content as sensitive without specifying why.
+    pub content_warning: Option<String>,
+    /// Hashtags
+    pub hashtags: Vec<String>,
+    /// Participants
+    pub participants: Vec<PublicKey>,
+}
+
+impl Video {
+    /// New video
+    pub fn new<S>(title: S, variants: Vec<MediaAttachment>) -> Self
+    where
+        S: Into<String>,
+    {
+        Self {
+            short: false,
+            title: title.into(),
+            description: String::new(),
+            variants,
+            published_at: None,
+            duration: None,
+            alt: None,
+            content_warning: None,
+            hashtags: Vec::new(),
+            participants: Vec::new(),
+        }
+    }
+
+    /// Get the event kind
+    #[inline]
+    pub fn kind(&self) -> Kind {
+        if self.short {
+            Kind::ShortVideo
+        } else {
+            Kind::Video
+        }
+    }
+
+    /// Parse video from an [`Event`] (kind `21` or `22`)
+    pub fn from_event(event: &Event) -> Result<Self, Error> {
+        let short: bool = match event.kind {
+            Kind::Video => false,
+            Kind::ShortVideo => true,
+            kind => {
+                return Err(Error::WrongKind {
+                    received: kind,
+                    expected: Kind::Video,
+                })
+            }
+        };
+
+        let variants: Vec<MediaAttachment> =
+            nip92::extract_media_attachments(&event.tags).collect();
+
+        if variants.is_empty() {
+            return Err(Error::MediaNotFound);
+        }
+
+        let title: &String = match event.tags.find_standardized(TagKind::Title) {
+            Some(TagStandard::Title(title)) => title,
+            _ => return Err(Error::TitleTagNotFound),
+        };
+
+        let mut video: Self = Self::new(title.clone(), variants);
+        video.short = short;
+        video.description = event.content.clone();
+
+        for tag in event.tags.iter() {
+            match tag.as_standardized() {
+                Some(TagStandard::PublishedAt(timestamp)) => video.published_at = Some(*timestamp),
+                Some(TagStandard::Alt(alt)) => video.alt = Some(alt.clone()),
+                Some(TagStandard::ContentWarning { reason }) => {
+                    video.content_warning = Some(reason.clone().unwrap_or_default())
+                }
+                Some(TagStandard::Hashtag(hashtag)) => video.hashtags.push(hashtag.clone()),
+                Some(TagStandard::PublicKey {
+                    public_key,
+                    uppercase: false,
+                    ..
+                }) => video.participants.push(*public_key),
+                Some(..) => {}
+                None => {
+                    let values: &[String] = tag.as_slice();
+                    if values.first().map(|k| k.as_str()) == Some(DURATION) {
+                        video.duration = values.get(1).and_then(|d| d.parse().ok());
+                    }
+                }
+            }
+        }
+
+        Ok(video)
+    }
+
+    /// Get the best variant, with the highest resolution not exceeding `max_height` (if any)
+    ///
+    /// Variants without dimensions are considered only if none of the others is eligible.
+    pub fn best_variant(&self, max_height: Option<u64>) -> Option<&MediaAttachment> {
+        self.variants
+            .iter()
+            .filter(|v| match (v.dim, max_height) {
+                (Some(dim), Some(max)) => dim.height <= max,
+                _ => true,
+            })
+            .max_by_key(|v| v.dim.map(|d| d.height))
+    }
+
+    pub(crate) fn to_event_builder(self) -> Result<EventBuilder, Error> {
+        if self.variants.is_empty() {
+            return Err(Error::MediaNotFound);
+        }
+
+        let kind: Kind = self.kind();
+        let mut tags: Vec<Tag> = Vec::with_capacity(self.variants.len() + 4);
+
+        // Add title
+        tags.push(Tag::from_standardized_without_cell(TagStandard::Title(
+            self.title,
+        )));
+
+        // Add variants
+        tags.extend(self.variants.iter().map(MediaAttachment::to_tag));
+
+        // Add publication timestamp
+        if let Some(timestamp) = self.published_at {
+            tags.push(Tag::from_standardized_without_cell(
+                TagStandard::PublishedAt(timestamp),
+            ));
+        }
+
+        // Add duration
+        if let Some(duration) = self.duration {
+            tags.push(Tag::custom(TagKind::from(DURATION), [duration.to_string()]));
+        }
+
+        // Add alt
+        if let Some(alt) = self.alt {
+            tags.push(Tag::from_standardized_without_cell(TagStandard::Alt(alt)));
+        }
+
+        // Add content warning
+        if let Some(reason) = self.content_warning {
+            tags.push(Tag::from_standardized_without_cell(
+                TagStandard::ContentWarning {
+                    reason: (!reason.is_empty()).then_some(reason),
+                },
+            ));
+        }
+
+        // Add hashtags
+        tags.extend(self.hashtags.into_iter().map(Tag::hashtag));
+
+        // Add participants
+        tags.extend(self.participants.into_iter().map(Tag::public_key));
+
+        Ok(EventBuilder::new(kind, self.description).tags(tags))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ImageDimensions, Keys, Url};
+
+    #[test]
+    fn test_video() {
+        let keys = Keys::generate();
+
+        let variant = |height: u64| {
+            MediaAttachment::new(Url::parse(&format!("https://example.com/{height}.mp4")).unwrap())
+                .mime_type("video/mp4")
+                .dimensions(ImageDimensions::new(height * 16 / 9, height))
+                .image(Url::parse(&format!("https://example.com/{height}.jpg")).unwrap())
+        };
+
+        let mut video = Video::new("My video", vec![variant(1080), variant(720), variant(480)]);
+        video.short = true;
+        video.description = "A short video".into();
+        video.published_at = Some(Timestamp::from(1700000000));
+        video.duration = Some(29.223);
+        video.alt = Some("A cat".into());
+        video.hashtags = vec!["cats".into()];
+
+        let event = EventBuilder::video(video.clone())
+            .unwrap()
+            .sign_with_keys(&keys)
+            .unwrap();
+        assert_eq!(event.kind, Kind::ShortVideo);
+
+        let parsed = Video::from_event(&event).unwrap();
+        assert_eq!(parsed, video);
+        assert_eq!(parsed.best_variant(None), Some(&variant(1080)));
+        assert_eq!(parsed.best_variant(Some(800)), Some(&variant(720)));
+        assert_eq!(parsed.best_variant(Some(100)), None);
+    }
+}
@@ -0,0 +1,692 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2025 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! NIP72: Moderated Communities
+//!
+//! <https://github.com/nostr-protocol/nips/blob/master/72.md>
+
+#![allow(clippy::wrong_self_convention)]
+
+use alloc::collections::BTreeSet;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+use core::str::FromStr;
+
+use secp256k1::{Secp256k1, Verification};
+
+use crate::nips::nip01::Coordinate;
+use crate::types::{RelayUrl, Url};
+#[cfg(feature = "std")]
+use crate::SECP256K1;
+use crate::{
+    Alphabet, Event, EventBuilder, EventId, ImageDimensions, JsonUtil, Kind, PublicKey, Tag,
+    TagKind, TagStandard,
+};
+
+const MODERATOR: &str = "moderator";
+
+/// NIP72 error
+#[derive(Debug, PartialEq, Eq)]
+pub enum Error {
+    /// Wrong event kind
+    WrongKind {
+        /// The received kind
+        received: Kind,
+        /// The expected kind
+        expected: Kind,
+    },
+    /// Unknown relay marker
+    UnknownRelayMarker,
+    /// Identifier tag not found
+    IdentifierTagNotFound,
+    /// Community not found
+    CommunityNotFound,
+    /// Post not found
+    PostNotFound,
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::WrongKind { received, expected } => {
+                write!(f, "wrong kind: received={received}, expected={expected}")
+            }
+            Self::UnknownRelayMarker => f.write_str("unknown relay marker"),
+            Self::IdentifierTagNotFound => f.write_str("identifier tag not found"),
+            Self::CommunityNotFound => f.write_str("community not found"),
+            Self::PostNotFound => f.write_str("post not found"),
+        }
+    }
+}
+
+#[inline]
+fn check_kind(kind: Kind, expected: Kind) -> Result<(), Error> {
+    if kind != expected {
+        return Err(Error::WrongKind {
+            received: kind,
+            expected,
+        });
+    }
+
+    Ok(())
+}
+
+/// Community relay marker
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CommunityRelayMarker {
+    /// Relay hosting the author's kind `0`
+    Author,
+    /// Relay where to send and receive requests (posts)
+    Requests,
+    /// Relay where to send and receive approvals
+    Approvals,
+}
+
+impl fmt::Display for CommunityRelayMarker {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl CommunityRelayMarker {
+    /// Get as `&str`
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Author => "author",
+            Self::Requests => "requests",
+            Self::Approvals => "approvals",
+        }
+    }
+}
+
+impl FromStr for CommunityRelayMarker {
+    type Err = Error;
+
+    fn from_str(marker: &str) -> Result<Self, Self::Err> {
+        match marker {
+            "author" => Ok(Self::Author),
+            "requests" => Ok(Self::Requests),
+            "approvals" => Ok(Self::Approvals),
+            _ => Err(Error::UnknownRelayMarker),
+        }
+    }
+}
+
+/// Community relay
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CommunityRelay {
+    /// Relay URL
+    pub url: RelayUrl,
+    /// Optional marker
+    pub marker: Option<CommunityRelayMarker>,
+}
+
+/// Community moderator
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CommunityModerator {
+    /// Public key
+    pub public_key: PublicKey,
+    /// Relay hint
+    pub relay_url: Option<RelayUrl>,
+}
+
+/// Community definition
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CommunityDefinition {
+    /// Community identifier (`d` tag)
+    pub id: String,
+    /// Name
+    pub name: Option<String>,
+    /// Description
+    pub description: Option<String>,
+    /// Image
+    pub image: Option<(Url, Option<ImageDimensions>)>,
+    /// Moderators
+    pub moderators: Vec<CommunityModerator>,
+    /// Relays
+    pub relays: Vec<CommunityRelay>,
+}
+
+impl CommunityDefinition {
+    /// New community definition
+    pub fn new<S>(id: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self {
+            id: id.into(),
+            name: None,
+            description: None,
+            image: None,
+            moderators: Vec::new(),
+            relays: Vec::new(),
+        }
+    }
+
+    /// Parse community definition from an [`Event`] (kind `34550`)
+    pub fn from_event(event: &Event) -> Result<Self, Error> {
+        check_kind(event.kind, Kind::CommunityDefinition)?;
+
+        let id: &str = event
+            .tags
+            .identifier()
+            .ok_or(Error::IdentifierTagNotFound)?;
+
+        let mut definition: Self = Self::new(id);
+
+        for tag in event.tags.iter() {
+            match tag.as_standardized() {
+                Some(TagStandard::Name(name)) => definition.name = Some(name.clone()),
+                Some(TagStandard::Description(description)) => {
+                    definition.description = Some(description.clone())
+                }
+                Some(TagStandard::Image(url, dimensions)) => {
+                    definition.image = Some((url.clone(), *dimensions))
+                }
+                Some(TagStandard::PublicKey {
+                    public_key,
+                    relay_url,
+                    alias: Some(role),
+                    uppercase: false,
+                }) if role == MODERATOR => {
+                    definition.moderators.push(CommunityModerator {
+                        public_key: *public_key,
+                        relay_url: relay_url.clone(),
+                    });
+                }
+                _ => {
+                    // The `relay` tag with a marker isn't standardized: parse it manually.
+                    if tag.kind() == TagKind::Relay {
+                        if let Some(relay) = parse_relay_tag(tag.as_slice()) {
+                            definition.relays.push(relay);
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(definition)
+    }
+
+    /// Check if a public key is listed as moderator
+    #[inline]
+    pub fn is_moderator(&self, public_key: &PublicKey) -> bool {
+        self.moderators.iter().any(|m| &m.public_key == public_key)
+    }
+
+    pub(crate) fn to_event_builder(self) -> Result<EventBuilder, Error> {
+        if self.id.is_empty() {
+            return Err(Error::IdentifierTagNotFound);
+        }
+
+        let mut tags: Vec<Tag> = Vec::with_capacity(1 + self.moderators.len() + self.relays.len());
+
+        // Add identifier
+        tags.push(Tag::identifier(self.id));
+
+        // Add name
+        if let Some(name) = self.name {
+            tags.push(Tag::from_standardized_without_cell(TagStandard::Name(name)));
+        }
+
+        // Add description
+        if let Some(description) = self.description {
+            tags.push(Tag::from_standardized_without_cell(
+                TagStandard::Description(description),
+            ));
+        }
+
+        // Add image
+        if let Some((url, dimensions)) = self.image {
+            tags.push(Tag::image(url, dimensions));
+        }
+
+        // Add moderators
+        for moderator in self.moderators.into_iter() {
+            tags.push(Tag::from_standardized_without_cell(
+                TagStandard::PublicKey {
+                    public_key: moderator.public_key,
+                    relay_url: moderator.relay_url,
+                    alias: Some(MODERATOR.to_string()),
+                    uppercase: false,
+                },
+            ));
+        }
+
+        // Add relays
+        for relay in self.relays.into_iter() {
+            match relay.marker {
+                Some(marker) => tags.push(Tag::custom(
+                    TagKind::Relay,
+                    [relay.url.to_string(), marker.to_string()],
+                )),
+                None => tags.push(Tag::relay(relay.url)),
+            }
+        }
+
+        Ok(EventBuilder::new(Kind::CommunityDefinition, "").tags(tags))
+    }
+}
+
+fn parse_relay_tag(tag: &[String]) -> Option<CommunityRelay> {
+    let url: RelayUrl = RelayUrl::parse(tag.get(1)?).ok()?;
+    let marker: Option<CommunityRelayMarker> = match tag.get(2) {
+        Some(marker) if !marker.is_empty() => Some(CommunityRelayMarker::from_str(marker).ok()?),
+        _ => None,
+    };
+    Some(CommunityRelay { url, marker })
+}
+
+/// Community post approval
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommunityPostApproval {
+    /// Approved communities
+    pub communities: Vec<Coordinate>,
+    /// Approved post ID
+    ///
+    /// Approves a specific version of the post.
+    pub post_id: Option<EventId>,
+    /// Approved post coordinate
+    ///
+    /// Approves all the versions of an addressable post.
+    pub post_coordinate: Option<Coordinate>,
+    /// Post author
+    pub post_author: Option<PublicKey>,
+    /// Post kind
+    pub post_kind: Option<Kind>,
+    /// The approved post, if included in the approval content
+    pub post: Option<Event>,
+}
+
+impl CommunityPostApproval {
+    /// New approval for a post
+    pub fn new(community: Coordinate, post: &Event) -> Self {
+        Self {
+            communities: vec![community],
+            post_id: Some(post.id),
+            post_coordinate: post.coordinate().map(|c| c.into_owned()),
+            post_author: Some(post.pubkey),
+            post_kind: Some(post.kind),
+            post: Some(post.clone()),
+        }
+    }
+
+    /// Parse community post approval from an [`Event`] (kind `4550`)
+    pub fn from_event(event: &Event) -> Result<Self, Error> {
+        check_kind(event.kind, Kind::CommunityPostApproval)?;
+
+        let mut communities: Vec<Coordinate> = Vec::with_capacity(1);
+        let mut post_coordinate: Option<Coordinate> = None;
+
+        for coordinate in event.tags.coordinates() {
+            if coordinate.kind == Kind::CommunityDefinition {
+                communities.push(coordinate.clone());
+            } else if post_coordinate.is_none() {
+                post_coordinate = Some(coordinate.clone());
+            }
+        }
+
+        if communities.is_empty() {
+            return Err(Error::CommunityNotFound);
+        }
+
+        let post_id: Option<EventId> = event.tags.event_ids().next().copied();
+
+        if post_id.is_none() && post_coordinate.is_none() {
+            return Err(Error::PostNotFound);
+        }
+
+        let post_kind: Option<Kind> =
+            event
+                .tags
+                .filter_standardized(TagKind::k())
+                .find_map(|tag| match tag {
+                    TagStandard::Kind {
+                        kind,
+                        uppercase: false,
+                    } => Some(*kind),
+                    _ => None,
+                });
+
+        Ok(Self {
+            communities,
+            post_id,
+            post_coordinate,
+            post_author: event.tags.public_keys().next().copied(),
+            post_kind,
+            // The content is a stringified post: ignore it if not valid
+            post: Event::from_json(&event.content).ok(),
+        })
+    }
+
+    /// Check if the approval is for the given post
+    pub fn approves(&self, post: &Event) -> bool {
+        if self.post_id == Some(post.id) {
+            return true;
+        }
+
+        match (&self.post_coordinate, post.coordinate()) {
+            (Some(approved), Some(coordinate)) => {
+                approved.kind == *coordinate.kind
+                    && approved.public_key == *coordinate.public_key
+                    && approved.identifier == coordinate.identifier.unwrap_or_default()
+            }
+            _ => false,
+        }
+    }
+
+    pub(crate) fn to_event_builder(self) -> Result<EventBuilder, Error> {
+        if self.communities.is_empty() {
+            return Err(Error::CommunityNotFound);
+        }
+
+        if self.post_id.is_none() && self.post_coordinate.is_none() {
+            return Err(Error::PostNotFound);
+        }
+
+        let mut tags: Vec<Tag> = Vec::with_capacity(self.communities.len() + 4);
+
+        // Add communities
+        for community in self.communities.into_iter() {
+            check_kind(community.kind, Kind::CommunityDefinition)?;
+            tags.push(Tag::coordinate(community, None));
+        }
+
+        // Add post ID
+        if let Some(id) = self.post_id {
+            tags.push(Tag::event(id));
+        }
+
+        // Add post coordinate
+        if let Some(coordinate) = self.post_coordinate {
+            tags.push(Tag::coordinate(coordinate, None));
+        }
+
+        // Add post author
+        if let Some(author) = self.post_author {
+            tags.push(Tag::public_key(author));
+        }
+
+        // Add post kind
+        if let Some(kind) = self.post_kind {
+            tags.push(Tag::from_standardized_without_cell(TagStandard::Kind {
+                kind,
+                uppercase: false,
+            }));
+        }
+
+        let content: String = self.post.map(|post| post.as_json()).unwrap_or_default();
+
+        Ok(EventBuilder::new(Kind::CommunityPostApproval, content)
+            .tags(tags)
+            .allow_self_tagging())
+    }
+}
+
+/// Community moderation
+///
+/// Resolves which posts are approved in a community, given its **current** definition.
+///
+/// An approval is considered valid only if it's signed by the community owner or by one of the
+/// moderators listed in the current definition.
+/// When a moderator is removed, the posts approved only by them are no longer approved, until a
+/// current moderator (or the owner) re-approves them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommunityModeration {
+    /// Community coordinate
+    pub coordinate: Coordinate,
+    /// Community definition
+    pub definition: CommunityDefinition,
+}
+
+impl CommunityModeration {
+    /// Construct from the latest community definition [`Event`] (kind `34550`)
+    pub fn new(community: &Event) -> Result<Self, Error> {
+        let definition: CommunityDefinition = CommunityDefinition::from_event(community)?;
+        let coordinate: Coordinate = Coordinate::new(Kind::CommunityDefinition, community.pubkey)
+            .identifier(definition.id.clone());
+
+        Ok(Self {
+            coordinate,
+            definition,
+        })
+    }
+
+    /// Get the community owner
+    #[inline]
+    pub fn owner(&self) -> &PublicKey {
+        &self.coordinate.public_key
+    }
+
+    /// Check if a public key can approve posts (owner or current moderator)
+    #[inline]
+    pub fn can_approve(&self, public_key: &PublicKey) -> bool {
+        self.owner() == public_key || self.definition.is_moderator(public_key)
+    }
+
+    /// Check if a post targets this community
+    pub fn is_community_post(&self, post: &Event) -> bool {
+        post.tags
+            .filter_standardized(TagKind::a())
+            .chain(
+                post.tags
+                    .filter_standardized(TagKind::single_letter(Alphabet::A, true)),
+            )
+            .any(|tag| match tag {
+                TagStandard::Coordinate { coordinate, .. } => coordinate == &self.coordinate,
+                _ => false,
+            })
+    }
+
+    /// Parse the approval, returning it only if it's valid for this community
+    ///
+    /// The approval must reference this community and must be signed by the owner or by a current moderator.
+    pub fn validate_approval(&self, approval: &Event) -> Option<CommunityPostApproval> {
+        if !self.can_approve(&approval.pubkey) {
+            return None;
+        }
+
+        let parsed: CommunityPostApproval = CommunityPostApproval::from_event(approval).ok()?;
+
+        if !parsed.communities.contains(&self.coordinate) {
+            return None;
+        }
+
+        Some(parsed)
+    }
+
+    /// Get the approved posts
+    ///
+    /// Posts that don't target this community or that aren't approved by the owner or by a current moderator are discarded.
+    /// Posts included in the content of a valid approval are returned even if missing in `posts`.
+    ///
+    /// The `posts` are expected to be already verified (i.e., received from the relays through the pool):
+    /// only the embedded posts are verified, once and only if approved.
+    #[inline]
+    #[cfg(feature = "std")]
+    pub fn approved_posts<'a, I, A>(&self, posts: I, approvals: A) -> Vec<Event>
+    where
+        I: IntoIterator<Item = Event>,
+        A: IntoIterator<Item = &'a Event>,
+    {
+        self.approved_posts_with_ctx(SECP256K1, posts, approvals)
+    }
+
+    /// Get the approved posts
+    ///
+    /// Check [`CommunityModeration::approved_posts`] for more details.
+    pub fn approved_posts_with_ctx<'a, C, I, A>(
+        &self,
+        secp: &Secp256k1<C>,
+        posts: I,
+        approvals: A,
+    ) -> Vec<Event>
+    where
+        C: Verification,
+        I: IntoIterator<Item = Event>,
+        A: IntoIterator<Item = &'a Event>,
+    {
+        let approvals: Vec<CommunityPostApproval> = approvals
+            .into_iter()
+            .filter_map(|approval| self.validate_approval(approval))
+            .collect();
+
+        let mut ids: BTreeSet<EventId> = BTreeSet::new();
+        let mut approved: Vec<Event> = Vec::new();
+
+        let embedded = approvals
+            .iter()
+            .filter_map(|approval| approval.post.clone())
+            .map(|post| (post, true));
+
+        for (post, is_embedded) in posts.into_iter().map(|post| (post, false)).chain(embedded) {
+            // Check if already processed
+            if ids.contains(&post.id) {
+                continue;
+            }
+
+            if !self.is_community_post(&post)
+                || !approvals.iter().any(|approval| approval.approves(&post))
+            {
+                continue;
+            }
+
+            // Embedded posts may have been tampered: verify them
+            if is_embedded && post.verify_with_ctx(secp).is_err() {
+                continue;
+            }
+
+            ids.insert(post.id);
+            approved.push(post);
+        }
+
+        approved
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Keys;
+
+    fn community_event(owner: &Keys, moderators: &[&Keys]) -> Event {
+        let mut definition = CommunityDefinition::new("rust");
+        definition.name = Some("Rust".to_string());
+        definition.moderators = moderators
+            .iter()
+            .map(|keys| CommunityModerator {
+                public_key: keys.public_key(),
+                relay_url: None,
+            })
+            .collect();
+        definition.relays = vec![CommunityRelay {
+            url: RelayUrl::parse("wss://relay.example.com").unwrap(),
+            marker: Some(CommunityRelayMarker::Approvals),
+        }];
+
+        EventBuilder::community_definition(definition)
+            .unwrap()
+            .sign_with_keys(owner)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_community_definition() {
+        let owner = Keys::generate();
+        let moderator = Keys::generate();
+        let event = community_event(&owner, &[&moderator]);
+
+        let definition = CommunityDefinition::from_event(&event).unwrap();
+        assert_eq!(definition.id, "rust");
+        assert_eq!(definition.name.as_deref(), Some("Rust"));
+        assert!(definition.is_moderator(&moderator.public_key()));
+        assert_eq!(
+            definition.relays,
+            vec![CommunityRelay {
+                url: RelayUrl::parse("wss://relay.example.com").unwrap(),
+                marker: Some(CommunityRelayMarker::Approvals),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_approved_posts_with_moderator_changes() {
+        let owner = Keys::generate();
+        let old_moderator = Keys::generate();
+        let new_moderator = Keys::generate();
+        let author = Keys::generate();
+        let stranger = Keys::generate();
+
+        let old_community = community_event(&owner, &[&old_moderator]);
+        let coordinate =
+            Coordinate::new(Kind::CommunityDefinition, owner.public_key()).identifier("rust");
+
+        let post1 = EventBuilder::community_post(&coordinate, None, "post 1")
+            .unwrap()
+            .sign_with_keys(&author)
+            .unwrap();
+        let post2 = EventBuilder::community_post(&coordinate, None, "post 2")
+            .unwrap()
+            .sign_with_keys(&author)
+            .unwrap();
+        let post3 = EventBuilder::community_post(&coordinate, None, "post 3")
+            .unwrap()
+            .sign_with_keys(&author)
+            .unwrap();
+
+        let approve = |keys: &Keys, post: &Event| {
+            EventBuilder::community_post_approval(CommunityPostApproval::new(
+                coordinate.clone(),
+                post,
+            ))
+            .unwrap()
+            .sign_with_keys(keys)
+            .unwrap()
+        };
+
+        let approval1 = approve(&old_moderator, &post1);
+        let approval2 = approve(&old_moderator, &post2);
+        let reapproval2 = approve(&new_moderator, &post2);
+        let approval3 = approve(&stranger, &post3);
+        let owner_approval3 = approve(&owner, &post3);
+
+        let posts = vec![post1.clone(), post2.clone(), post3.clone()];
+
+        // With the old moderator set
+        let moderation = CommunityModeration::new(&old_community).unwrap();
+        let approved =
+            moderation.approved_posts(posts.clone(), [&approval1, &approval2, &approval3]);
+        assert_eq!(approved, vec![post1.clone(), post2.clone()]);
+
+        // Moderator set changed: only re-approved posts and owner approvals are kept
+        let new_community = community_event(&owner, &[&new_moderator]);
+        let moderation = CommunityModeration::new(&new_community).unwrap();
+        let approved = moderation.approved_posts(
+            posts,
+            [
+                &approval1,
+                &approval2,
+                &reapproval2,
+                &approval3,
+                &owner_approval3,
+            ],
+        );
+        assert_eq!(approved, vec![post2.clone(), post3]);
+
+        // Posts embedded in the approvals
+        let approved = moderation.approved_posts(Vec::new(), [&reapproval2]);
+        assert_eq!(approved, vec![post2.clone()]);
+
+        // Tampered embedded post
+        let mut tampered: Event = post2;
+        tampered.content = String::from("tampered");
+        let tampered_approval = approve(&new_moderator, &tampered);
+        let approved = moderation.approved_posts(Vec::new(), [&tampered_approval]);
+        assert!(approved.is_empty());
+    }
+}
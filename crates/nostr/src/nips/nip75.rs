@@ -0,0 +1,497 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2025 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! NIP75: Zap Goals
+//!
+//! <https://github.com/nostr-protocol/nips/blob/master/75.md>
+
+#![allow(clippy::wrong_self_convention)]
+
+use alloc::collections::BTreeSet;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+
+use secp256k1::{Secp256k1, Verification};
+
+use crate::types::{RelayUrl, Url};
+#[cfg(feature = "std")]
+use crate::SECP256K1;
+use crate::{
+    Event, EventBuilder, EventId, JsonUtil, Kind, PublicKey, Tag, TagKind, TagStandard, Timestamp,
+};
+
+const CLOSED_AT: &str = "closed_at";
+const ZAP: &str = "zap";
+
+/// NIP75 error
+#[derive(Debug, PartialEq, Eq)]
+pub enum Error {
+    /// Wrong event kind
+    WrongKind {
+        /// The received kind
+        received: Kind,
+        /// The expected kind
+        expected: Kind,
+    },
+    /// Amount tag not found
+    AmountTagNotFound,
+    /// Relays tag not found
+    RelaysTagNotFound,
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::WrongKind { received, expected } => {
+                write!(f, "wrong kind: received={received}, expected={expected}")
+            }
+            Self::AmountTagNotFound => f.write_str("amount tag not found"),
+            Self::RelaysTagNotFound => f.write_str("relays tag not found"),
+        }
+    }
+}
+
+/// Zap goal beneficiary (`zap` tag)
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ZapBeneficiary {
+    /// Public key
+    pub public_key: PublicKey,
+    /// Relay hint
+    pub relay_url: Option<RelayUrl>,
+    /// Weight
+    pub weight: Option<u64>,
+}
+
+impl ZapBeneficiary {
+    fn parse(tag: &[String]) -> Option<Self> {
+        if tag.first().map(|k| k.as_str()) != Some(ZAP) {
+            return None;
+        }
+
+        Some(Self {
+            public_key: PublicKey::parse(tag.get(1)?).ok()?,
+            relay_url: tag.get(2).and_then(|r| RelayUrl::parse(r).ok()),
+            weight: tag.get(3).and_then(|w| w.parse().ok()),
+        })
+    }
+
+    fn to_tag(self) -> Tag {
+        let mut values: Vec<String> = Vec::with_capacity(3);
+        values.push(self.public_key.to_hex());
+
+        match (self.relay_url, self.weight) {
+            (relay_url, Some(weight)) => {
+                values.push(relay_url.map(|r| r.to_string()).unwrap_or_default());
+                values.push(weight.to_string());
+            }
+            (Some(relay_url), None) => values.push(relay_url.to_string()),
+            (None, None) => {}
+        }
+
+        Tag::custom(TagKind::from(ZAP), values)
+    }
+}
+
+/// Zap goal
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ZapGoal {
+    /// Description of the goal
+    pub description: String,
+    /// Target amount in millisats
+    pub amount: u64,
+    /// Relays where the zaps to the goal will be sent and tallied from
+    pub relays: Vec<RelayUrl>,
+    /// Zaps published after this timestamp don't count towards the goal
+    pub closed_at: Option<Timestamp>,
+    /// Image
+    pub image: Option<Url>,
+    /// Summary
+    pub summary: Option<String>,
+    /// Beneficiaries
+    pub beneficiaries: Vec<ZapBeneficiary>,
+}
+
+impl ZapGoal {
+    /// New zap goal
+    pub fn new<S, I>(description: S, amount: u64, relays: I) -> Self
+    where
+        S: Into<String>,
+        I: IntoIterator<Item = RelayUrl>,
+    {
+        Self {
+            description: description.into(),
+            amount,
+            relays: relays.into_iter().collect(),
+            closed_at: None,
+            image: None,
+            summary: None,
+            beneficiaries: Vec::new(),
+        }
+    }
+
+    /// Parse zap goal from an [`Event`] (kind `9041`)
+    pub fn from_event(event: &Event) -> Result<Self, Error> {
+        if event.kind != Kind::ZapGoal {
+            return Err(Error::WrongKind {
+                received: event.kind,
+                expected: Kind::ZapGoal,
+            });
+        }
+
+        let mut amount: Option<u64> = None;
+        let mut relays: Option<Vec<RelayUrl>> = None;
+        let mut goal: Self = Self::new(event.content.clone(), 0, []);
+
+        for tag in event.tags.iter() {
+            match tag.as_standardized() {
+                Some(TagStandard::Amount { millisats, .. }) => amount = Some(*millisats),
+                Some(TagStandard::Relays(list)) => relays = Some(list.clone()),
+                Some(TagStandard::Image(url, ..)) => goal.image = Some(url.clone()),
+                Some(TagStandard::Summary(summary)) => goal.summary = Some(summary.clone()),
+                _ => {
+                    let values: &[String] = tag.as_slice();
+                    match values.first().map(|k| k.as_str()) {
+                        Some(CLOSED_AT) => {
+                            goal.closed_at = values
+                                .get(1)
+                                .and_then(|t| t.parse::<u64>().ok())
+                                .map(Timestamp::from)
+                        }
+                        Some(ZAP) => {
+                            if let Some(beneficiary) = ZapBeneficiary::parse(values) {
+                                goal.beneficiaries.push(beneficiary);
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+
+        goal.amount = amount.ok_or(Error::AmountTagNotFound)?;
+        goal.relays = relays.ok_or(Error::RelaysTagNotFound)?;
+
+        Ok(goal)
+    }
+
+    pub(crate) fn to_event_builder(self) -> Result<EventBuilder, Error> {
+        if self.relays.is_empty() {
+            return Err(Error::RelaysTagNotFound);
+        }
+
+        let mut tags: Vec<Tag> = Vec::with_capacity(2 + self.beneficiaries.len());
+
+        // Add amount
+        tags.push(Tag::from_standardized_without_cell(TagStandard::Amount {
+            millisats: self.amount,
+            bolt11: None,
+        }));
+
+        // Add relays
+        tags.push(Tag::from_standardized_without_cell(TagStandard::Relays(
+            self.relays,
+        )));
+
+        // Add closed at
+        if let Some(closed_at) = self.closed_at {
+            tags.push(Tag::custom(
+                TagKind::from(CLOSED_AT),
+                [closed_at.to_string()],
+            ));
+        }
+
+        // Add image
+        if let Some(image) = self.image {
+            tags.push(Tag::image(image, None));
+        }
+
+        // Add summary
+        if let Some(summary) = self.summary {
+            tags.push(Tag::from_standardized_without_cell(TagStandard::Summary(
+                summary,
+            )));
+        }
+
+        // Add beneficiaries
+        tags.extend(self.beneficiaries.into_iter().map(ZapBeneficiary::to_tag));
+
+        Ok(EventBuilder::new(Kind::ZapGoal, self.description).tags(tags))
+    }
+}
+
+/// Zap goal progress
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ZapGoalProgress {
+    /// Target amount in millisats
+    pub target: u64,
+    /// Raised amount in millisats
+    pub raised: u64,
+    /// Number of valid zaps
+    pub zaps: usize,
+}
+
+impl ZapGoalProgress {
+    /// Compute the progress of the goal from the zap receipts
+    ///
+    /// Check [`ZapGoalProgress::compute_with_ctx`] for more details.
+    #[inline]
+    #[cfg(feature = "std")]
+    pub fn compute<'a, I>(goal: &Event, receipts: I) -> Result<Self, Error>
+    where
+        I: IntoIterator<Item = &'a Event>,
+    {
+        Self::compute_with_ctx(SECP256K1, goal, receipts)
+    }
+
+    /// Compute the progress of the goal from the zap receipts
+    ///
+    /// A zap receipt (kind `9735`) is counted only if:
+    /// * its signature is valid;
+    /// * it's published before the goal `closed_at` (if any);
+    /// * its zap request (kind `9734`) is valid and references the goal;
+    /// * the `bolt11` invoice amount matches the zap request `amount` (if any).
+    ///
+    /// Receipts for the same invoice are counted once.
+    ///
+    /// The receipt signer is NOT checked against the `nostrPubkey` of the beneficiaries' LNURL servers,
+    /// since it requires fetching the LNURL pay endpoints: it's the caller's job to pass only the receipts
+    /// published by the expected zappers (i.e., using [`Filter::authors`](crate::Filter::authors) when fetching them).
+    pub fn compute_with_ctx<'a, C, I>(
+        secp: &Secp256k1<C>,
+        goal: &Event,
+        receipts: I,
+    ) -> Result<Self, Error>
+    where
+        C: Verification,
+        I: IntoIterator<Item = &'a Event>,
+    {
+        let zap_goal: ZapGoal = ZapGoal::from_event(goal)?;
+
+        let mut invoices: BTreeSet<&str> = BTreeSet::new();
+        let mut progress: Self = Self {
+            target: zap_goal.amount,
+            raised: 0,
+            zaps: 0,
+        };
+
+        for receipt in receipts.into_iter() {
+            // Check if closed
+            if let Some(closed_at) = zap_goal.closed_at {
+                if receipt.created_at > closed_at {
+                    continue;
+                }
+            }
+
+            if let Some((bolt11, amount)) = validate_zap_receipt(secp, &goal.id, receipt) {
+                if invoices.insert(bolt11) {
+                    progress.raised = progress.raised.saturating_add(amount);
+                    progress.zaps += 1;
+                }
+            }
+        }
+
+        Ok(progress)
+    }
+
+    /// Get the progress percentage (may exceed `100`)
+    pub fn percentage(&self) -> f64 {
+        if self.target == 0 {
+            return 100.0;
+        }
+
+        self.raised as f64 * 100.0 / self.target as f64
+    }
+
+    /// Check if the goal has been reached
+    #[inline]
+    pub fn is_reached(&self) -> bool {
+        self.raised >= self.target
+    }
+}
+
+/// Validate a zap receipt for the goal, returning the invoice and the amount in millisats
+fn validate_zap_receipt<'a, C>(
+    secp: &Secp256k1<C>,
+    goal_id: &EventId,
+    receipt: &'a Event,
+) -> Option<(&'a str, u64)>
+where
+    C: Verification,
+{
+    if receipt.kind != Kind::ZapReceipt || receipt.verify_with_ctx(secp).is_err() {
+        return None;
+    }
+
+    let bolt11: &str = match receipt.tags.find_standardized(TagKind::Bolt11) {
+        Some(TagStandard::Bolt11(bolt11)) => bolt11,
+        _ => return None,
+    };
+    let invoice_amount: u64 = bolt11_amount_msat(bolt11)?;
+
+    // Parse and verify the zap request
+    let zap_request: Event = match receipt.tags.find_standardized(TagKind::Description) {
+        Some(TagStandard::Description(description)) => Event::from_json(description).ok()?,
+        _ => return None,
+    };
+
+    if zap_request.kind != Kind::ZapRequest || zap_request.verify_with_ctx(secp).is_err() {
+        return None;
+    }
+
+    // Check that the zap request references the goal
+    if !zap_request.tags.event_ids().any(|id| id == goal_id) {
+        return None;
+    }
+
+    // Check that the amount matches the requested one
+    if let Some(TagStandard::Amount { millisats, .. }) =
+        zap_request.tags.find_standardized(TagKind::Amount)
+    {
+        if *millisats != invoice_amount {
+            return None;
+        }
+    }
+
+    Some((bolt11, invoice_amount))
+}
+
+/// Extract the amount in millisats from a BOLT11 invoice
+fn bolt11_amount_msat(invoice: &str) -> Option<u64> {
+    // The human-readable part ends at the last `1` separator: `ln` + currency + amount + multiplier
+    let invoice: String = invoice.to_lowercase();
+    let hrp: &str = &invoice[..invoice.rfind('1')?];
+    let hrp: &str = hrp.strip_prefix("ln")?;
+
+    // Skip the currency prefix
+    let amount: &str = hrp.trim_start_matches(|c: char| c.is_ascii_alphabetic());
+
+    let (digits, multiplier) = match amount.chars().last()? {
+        c if c.is_ascii_digit() => (amount, None),
+        c if c.is_ascii_alphabetic() => (amount.strip_suffix(c)?, Some(c)),
+        _ => return None,
+    };
+    let value: u64 = digits.parse().ok()?;
+
+    // 1 BTC = 100_000_000_000 msat
+    match multiplier {
+        None => value.checked_mul(100_000_000_000),
+        Some('m') => value.checked_mul(100_000_000),
+        Some('u') => value.checked_mul(100_000),
+        Some('n') => value.checked_mul(100),
+        Some('p') if value % 10 == 0 => Some(value / 10),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Keys;
+
+    #[test]
+    fn test_bolt11_amount() {
+        assert_eq!(
+            bolt11_amount_msat("lnbc2500u1pvjluezpp5qqqsyq"),
+            Some(250_000_000)
+        );
+        assert_eq!(
+            bolt11_amount_msat("lnbc20m1pvjluezhp58yjmdan79s"),
+            Some(2_000_000_000)
+        );
+        assert_eq!(bolt11_amount_msat("lntb10n1pvjluez"), Some(1_000));
+        assert_eq!(bolt11_amount_msat("lnbcrt1p1qqq"), None);
+        assert_eq!(bolt11_amount_msat("lnbc1pvjluezpp5qqqsyq"), None);
+        assert_eq!(bolt11_amount_msat("lnbcé1pvjluez"), None);
+        assert_eq!(bolt11_amount_msat("lnbc10é1pvjluez"), None);
+        assert_eq!(bolt11_amount_msat("lnbc10?1pvjluez"), None);
+    }
+
+    #[test]
+    fn test_zap_goal_progress() {
+        let creator = Keys::generate();
+        let zapper = Keys::generate();
+        let zapper_service = Keys::generate();
+        let relay = RelayUrl::parse("wss://relay.example.com").unwrap();
+
+        let mut goal = ZapGoal::new("Nostrasia travel expenses", 5_000_000, [relay.clone()]);
+        goal.closed_at = Some(Timestamp::from(1_800_000_000));
+        goal.summary = Some("Help me go to Nostrasia".into());
+        goal.beneficiaries = vec![ZapBeneficiary {
+            public_key: creator.public_key(),
+            relay_url: Some(relay.clone()),
+            weight: Some(1),
+        }];
+
+        let goal_event = EventBuilder::zap_goal(goal.clone())
+            .unwrap()
+            .sign_with_keys(&creator)
+            .unwrap();
+        assert_eq!(ZapGoal::from_event(&goal_event).unwrap(), goal);
+
+        let receipt = |bolt11: &str, amount: Option<u64>, created_at: u64| {
+            let mut tags = vec![
+                Tag::public_key(creator.public_key()),
+                Tag::event(goal_event.id),
+                Tag::from_standardized_without_cell(TagStandard::Relays(vec![relay.clone()])),
+            ];
+            if let Some(millisats) = amount {
+                tags.push(Tag::from_standardized_without_cell(TagStandard::Amount {
+                    millisats,
+                    bolt11: None,
+                }));
+            }
+            let zap_request = EventBuilder::new(Kind::ZapRequest, "")
+                .tags(tags)
+                .sign_with_keys(&zapper)
+                .unwrap();
+            EventBuilder::new(Kind::ZapReceipt, "")
+                .tags([
+                    Tag::public_key(creator.public_key()),
+                    Tag::event(goal_event.id),
+                    Tag::from_standardized_without_cell(TagStandard::Bolt11(bolt11.into())),
+                    Tag::from_standardized_without_cell(TagStandard::Description(
+                        zap_request.as_json(),
+                    )),
+                ])
+                .custom_created_at(Timestamp::from(created_at))
+                .sign_with_keys(&zapper_service)
+                .unwrap()
+        };
+
+        let valid = receipt("lnbc10u1valid", Some(1_000_000), 1_700_000_000);
+        let duplicated = valid.clone();
+        let no_amount = receipt("lnbc20u1noamount", None, 1_700_000_000);
+        let wrong_amount = receipt("lnbc30u1wrong", Some(1_000_000), 1_700_000_000);
+        let after_close = receipt("lnbc40u1late", Some(4_000_000), 1_900_000_000);
+        let mut tampered = receipt("lnbc50u1tampered", None, 1_700_000_000);
+        tampered.created_at = Timestamp::from(1_700_000_001);
+
+        let progress = ZapGoalProgress::compute(
+            &goal_event,
+            [
+                &valid,
+                &duplicated,
+                &no_amount,
+                &wrong_amount,
+                &after_close,
+                &tampered,
+            ],
+        )
+        .unwrap();
+        assert_eq!(
+            progress,
+            ZapGoalProgress {
+                target: 5_000_000,
+                raised: 3_000_000,
+                zaps: 2,
+            }
+        );
+        assert_eq!(progress.percentage(), 60.0);
+        assert!(!progress.is_reached());
+    }
+}
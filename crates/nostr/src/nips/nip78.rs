@@ -0,0 +1,276 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2025 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! NIP78: Arbitrary custom app data
+//!
+//! The app data is stored encrypted to self with NIP44.
+//!
+//! <https://github.com/nostr-protocol/nips/blob/master/78.md>
+
+use alloc::string::String;
+use core::fmt;
+
+use crate::signer::SignerError;
+use crate::{Event, EventBuilder, EventId, Kind, NostrSigner, PublicKey, Tag, Timestamp};
+
+/// NIP78 error
+#[derive(Debug, PartialEq)]
+pub enum Error {
+    /// Signer error
+    Signer(SignerError),
+    /// Wrong event kind
+    WrongKind {
+        /// The received kind
+        received: Kind,
+        /// The expected kind
+        expected: Kind,
+    },
+    /// Identifier tag not found
+    IdentifierTagNotFound,
+    /// The event is not authored by the signer
+    WrongAuthor,
+    /// Another version has been published since the base version
+    Conflict {
+        /// Event ID of the version the edit is based on
+        base: Option<EventId>,
+        /// Event ID of the latest version
+        latest: EventId,
+    },
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Signer(e) => e.fmt(f),
+            Self::WrongKind { received, expected } => {
+                write!(f, "wrong kind: received={received}, expected={expected}")
+            }
+            Self::IdentifierTagNotFound => f.write_str("identifier tag not found"),
+            Self::WrongAuthor => f.write_str("the event is not authored by the signer"),
+            Self::Conflict { base, latest } => match base {
+                Some(base) => write!(f, "conflict: base={base}, latest={latest}"),
+                None => write!(f, "conflict: base=none, latest={latest}"),
+            },
+        }
+    }
+}
+
+impl From<SignerError> for Error {
+    fn from(e: SignerError) -> Self {
+        Self::Signer(e)
+    }
+}
+
+/// App data
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct AppData {
+    /// Identifier (`d` tag)
+    pub identifier: String,
+    /// Plaintext content
+    pub content: String,
+}
+
+impl AppData {
+    /// New app data
+    pub fn new<I, S>(identifier: I, content: S) -> Self
+    where
+        I: Into<String>,
+        S: Into<String>,
+    {
+        Self {
+            identifier: identifier.into(),
+            content: content.into(),
+        }
+    }
+
+    /// Decrypt the app data from an [`Event`] (kind `30078`)
+    ///
+    /// The event must be authored by the signer.
+    pub async fn decrypt<T>(signer: &T, event: &Event) -> Result<Self, Error>
+    where
+        T: NostrSigner,
+    {
+        if event.kind != Kind::ApplicationSpecificData {
+            return Err(Error::WrongKind {
+                received: event.kind,
+                expected: Kind::ApplicationSpecificData,
+            });
+        }
+
+        let public_key: PublicKey = signer.get_public_key().await?;
+
+        if event.pubkey != public_key {
+            return Err(Error::WrongAuthor);
+        }
+
+        let identifier: &str = event
+            .tags
+            .identifier()
+            .ok_or(Error::IdentifierTagNotFound)?;
+        let content: String = signer.nip44_decrypt(&public_key, &event.content).await?;
+
+        Ok(Self::new(identifier, content))
+    }
+}
+
+/// A decrypted version of the app data
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct AppDataVersion {
+    /// App data
+    pub data: AppData,
+    /// Event ID of this version
+    pub id: EventId,
+    /// Timestamp of this version
+    pub created_at: Timestamp,
+}
+
+impl AppDataVersion {
+    /// Decrypt the app data version from an [`Event`] (kind `30078`)
+    ///
+    /// Check [`AppData::decrypt`] for more details.
+    pub async fn decrypt<T>(signer: &T, event: &Event) -> Result<Self, Error>
+    where
+        T: NostrSigner,
+    {
+        Ok(Self {
+            data: AppData::decrypt(signer, event).await?,
+            id: event.id,
+            created_at: event.created_at,
+        })
+    }
+}
+
+/// Get the latest version among the events
+///
+/// The newest event wins. In case of same timestamp, the one with the lowest ID wins (as for NIP01 replaceable events).
+pub fn latest_version<'a, I>(events: I) -> Option<&'a Event>
+where
+    I: IntoIterator<Item = &'a Event>,
+{
+    events.into_iter().min_by(|a, b| {
+        b.created_at
+            .cmp(&a.created_at)
+            .then_with(|| a.id.cmp(&b.id))
+    })
+}
+
+/// Check that the edit is based on the latest version
+///
+/// `base` is the event ID of the version the edit is based on ([`None`] if the app data didn't exist).
+/// Returns [`Error::Conflict`] if the latest version is not the base one,
+/// meaning that another version has been published in the meantime (even with the same timestamp).
+pub fn check_version(base: Option<&EventId>, latest: Option<&Event>) -> Result<(), Error> {
+    match (base, latest) {
+        (Some(base), Some(latest)) if &latest.id == base => Ok(()),
+        (_, Some(latest)) => Err(Error::Conflict {
+            base: base.copied(),
+            latest: latest.id,
+        }),
+        (_, None) => Ok(()),
+    }
+}
+
+/// Make app data event builder
+///
+/// The content is encrypted to self with NIP44.
+///
+/// If the `latest` version is passed, the `created_at` is set after it,
+/// so that the new version replaces it even if published in the same second.
+pub async fn make_app_data<T>(
+    signer: &T,
+    data: AppData,
+    latest: Option<&Event>,
+) -> Result<EventBuilder, Error>
+where
+    T: NostrSigner,
+{
+    let public_key: PublicKey = signer.get_public_key().await?;
+
+    // Encrypt content
+    let content: String = signer.nip44_encrypt(&public_key, &data.content).await?;
+
+    // Compose builder
+    let builder: EventBuilder = EventBuilder::new(Kind::ApplicationSpecificData, content)
+        .tag(Tag::identifier(data.identifier));
+
+    match latest {
+        Some(latest) => {
+            let created_at: Timestamp = latest.created_at + 1;
+
+            #[cfg(feature = "std")]
+            let created_at: Timestamp = created_at.max(Timestamp::now());
+
+            Ok(builder.custom_created_at(created_at))
+        }
+        None => Ok(builder),
+    }
+}
+
+#[cfg(all(feature = "std", feature = "nip44"))]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Keys;
+
+    #[tokio::test]
+    async fn test_app_data_sync() {
+        let keys = Keys::generate();
+
+        let data = AppData::new("my-app/settings", r#"{"theme":"dark"}"#);
+        let first: Event = EventBuilder::app_data(&keys, data.clone(), None)
+            .await
+            .unwrap()
+            .sign_with_keys(&keys)
+            .unwrap();
+        assert_ne!(first.content, data.content);
+        assert_eq!(first.tags.identifier(), Some("my-app/settings"));
+
+        let version = AppDataVersion::decrypt(&keys, &first).await.unwrap();
+        assert_eq!(version.data, data);
+        assert_eq!(version.created_at, first.created_at);
+
+        // Only the author can decrypt
+        let other = Keys::generate();
+        assert_eq!(
+            AppData::decrypt(&other, &first).await.unwrap_err(),
+            Error::WrongAuthor
+        );
+
+        // Edit based on the latest version
+        assert!(check_version(Some(&first.id), Some(&first)).is_ok());
+        let data = AppData::new("my-app/settings", r#"{"theme":"light"}"#);
+        let second: Event = EventBuilder::app_data(&keys, data.clone(), Some(&first))
+            .await
+            .unwrap()
+            .sign_with_keys(&keys)
+            .unwrap();
+        assert!(second.created_at > first.created_at);
+        assert_eq!(latest_version([&first, &second]), Some(&second));
+
+        // Stale edit
+        assert_eq!(
+            check_version(Some(&first.id), Some(&second)).unwrap_err(),
+            Error::Conflict {
+                base: Some(first.id),
+                latest: second.id
+            }
+        );
+
+        // Concurrent edit with the same timestamp
+        let concurrent: Event = EventBuilder::app_data(&keys, data.clone(), Some(&first))
+            .await
+            .unwrap()
+            .custom_created_at(second.created_at)
+            .sign_with_keys(&keys)
+            .unwrap();
+        assert_eq!(concurrent.created_at, second.created_at);
+        assert_ne!(concurrent.id, second.id);
+        assert!(check_version(Some(&concurrent.id), Some(&second)).is_err());
+        assert!(check_version(None, Some(&second)).is_err());
+        assert!(check_version(None, None).is_ok());
+    }
+}
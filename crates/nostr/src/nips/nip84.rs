@@ -0,0 +1,464 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2025 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! NIP84: Highlights
+//!
+//! <https://github.com/nostr-protocol/nips/blob/master/84.md>
+
+#![allow(clippy::wrong_self_convention)]
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+
+use super::nip01::Coordinate;
+use super::nip73::ExternalContentId;
+use crate::types::{RelayUrl, Url};
+use crate::{
+    Alphabet, Event, EventBuilder, EventId, Kind, PublicKey, SingleLetterTag, Tag, TagKind,
+    TagStandard,
+};
+
+const SOURCE: &str = "source";
+const MENTION: &str = "mention";
+const CONTEXT: &str = "context";
+const COMMENT: &str = "comment";
+
+/// NIP84 error
+#[derive(Debug, PartialEq, Eq)]
+pub enum Error {
+    /// Wrong event kind
+    WrongKind {
+        /// The received kind
+        received: Kind,
+        /// The expected kind
+        expected: Kind,
+    },
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::WrongKind { received, expected } => {
+                write!(f, "wrong kind: received={received}, expected={expected}")
+            }
+        }
+    }
+}
+
+/// Highlight source
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum HighlightSource {
+    /// Nostr event (`e` tag)
+    Event {
+        /// Event ID
+        id: EventId,
+        /// Relay hint
+        relay_url: Option<RelayUrl>,
+    },
+    /// Addressable nostr event (`a` tag), i.e. a long-form article
+    Coordinate {
+        /// Coordinate
+        coordinate: Coordinate,
+        /// Relay hint
+        relay_url: Option<RelayUrl>,
+    },
+    /// URL (`r` tag with `source` marker)
+    Url(Url),
+    /// External content (`i` tag)
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/73.md>
+    External {
+        /// External content ID
+        content: ExternalContentId,
+        /// URL hint
+        hint: Option<Url>,
+    },
+}
+
+impl HighlightSource {
+    fn to_tag(self) -> Tag {
+        match self {
+            Self::Event { id, relay_url } => {
+                Tag::from_standardized_without_cell(TagStandard::Event {
+                    event_id: id,
+                    relay_url,
+                    marker: None,
+                    public_key: None,
+                    uppercase: false,
+                })
+            }
+            Self::Coordinate {
+                coordinate,
+                relay_url,
+            } => Tag::coordinate(coordinate, relay_url),
+            Self::Url(url) => Tag::custom(
+                TagKind::SingleLetter(SingleLetterTag::lowercase(Alphabet::R)),
+                [url.to_string(), SOURCE.to_string()],
+            ),
+            Self::External { content, hint } => {
+                Tag::from_standardized_without_cell(TagStandard::ExternalContent {
+                    content,
+                    hint,
+                    uppercase: false,
+                })
+            }
+        }
+    }
+}
+
+/// Highlight author
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct HighlightAuthor {
+    /// Public key
+    pub public_key: PublicKey,
+    /// Relay hint
+    pub relay_url: Option<RelayUrl>,
+    /// Role (i.e. `author`, `editor`)
+    pub role: Option<String>,
+}
+
+impl HighlightAuthor {
+    /// New highlight author
+    #[inline]
+    pub fn new(public_key: PublicKey) -> Self {
+        Self {
+            public_key,
+            relay_url: None,
+            role: None,
+        }
+    }
+
+    /// Set role
+    #[inline]
+    pub fn role<S>(mut self, role: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.role = Some(role.into());
+        self
+    }
+}
+
+/// Highlight
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Highlight {
+    /// Highlighted text
+    pub content: String,
+    /// Sources
+    pub sources: Vec<HighlightSource>,
+    /// Authors of the highlighted material
+    pub authors: Vec<HighlightAuthor>,
+    /// Surrounding text of the highlight
+    ///
+    /// Check [`extract_context`].
+    pub context: Option<String>,
+    /// Comment, for quote highlights
+    pub comment: Option<String>,
+    /// Mentioned public keys (`p` tags with the `mention` marker)
+    pub mentioned_public_keys: Vec<PublicKey>,
+    /// Mentioned URLs (`r` tags with the `mention` marker)
+    pub mentioned_urls: Vec<Url>,
+}
+
+impl Highlight {
+    /// New highlight
+    pub fn new<S>(content: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self {
+            content: content.into(),
+            sources: Vec::new(),
+            authors: Vec::new(),
+            context: None,
+            comment: None,
+            mentioned_public_keys: Vec::new(),
+            mentioned_urls: Vec::new(),
+        }
+    }
+
+    /// Add a source
+    #[inline]
+    pub fn source(mut self, source: HighlightSource) -> Self {
+        self.sources.push(source);
+        self
+    }
+
+    /// Add an author
+    #[inline]
+    pub fn author(mut self, author: HighlightAuthor) -> Self {
+        self.authors.push(author);
+        self
+    }
+
+    /// Set context
+    #[inline]
+    pub fn context<S>(mut self, context: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.context = Some(context.into());
+        self
+    }
+
+    /// Set comment
+    #[inline]
+    pub fn comment<S>(mut self, comment: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.comment = Some(comment.into());
+        self
+    }
+
+    /// Parse highlight from an [`Event`] (kind `9802`)
+    pub fn from_event(event: &Event) -> Result<Self, Error> {
+        if event.kind != Kind::Highlight {
+            return Err(Error::WrongKind {
+                received: event.kind,
+                expected: Kind::Highlight,
+            });
+        }
+
+        let mut highlight: Self = Self::new(event.content.clone());
+
+        for tag in event.tags.iter() {
+            let values: &[String] = tag.as_slice();
+
+            match values.first().map(|k| k.as_str()) {
+                Some(CONTEXT) => {
+                    highlight.context = values.get(1).cloned();
+                    continue;
+                }
+                Some(COMMENT) => {
+                    highlight.comment = values.get(1).cloned();
+                    continue;
+                }
+                // The `r` tag with marker isn't standardized (or may be parsed as relay metadata): parse it manually
+                Some("r") => {
+                    if let Some(Ok(url)) = values.get(1).map(|u| Url::parse(u)) {
+                        match values.get(2).map(|m| m.as_str()) {
+                            Some(MENTION) => highlight.mentioned_urls.push(url),
+                            _ => highlight.sources.push(HighlightSource::Url(url)),
+                        }
+                    }
+                    continue;
+                }
+                _ => {}
+            }
+
+            match tag.as_standardized() {
+                Some(TagStandard::Event {
+                    event_id,
+                    relay_url,
+                    uppercase: false,
+                    ..
+                }) => highlight.sources.push(HighlightSource::Event {
+                    id: *event_id,
+                    relay_url: relay_url.clone(),
+                }),
+                Some(TagStandard::Coordinate {
+                    coordinate,
+                    relay_url,
+                    uppercase: false,
+                }) => highlight.sources.push(HighlightSource::Coordinate {
+                    coordinate: coordinate.clone(),
+                    relay_url: relay_url.clone(),
+                }),
+                Some(TagStandard::ExternalContent {
+                    content,
+                    hint,
+                    uppercase: false,
+                }) => highlight.sources.push(HighlightSource::External {
+                    content: content.clone(),
+                    hint: hint.clone(),
+                }),
+                Some(TagStandard::PublicKey {
+                    public_key,
+                    relay_url,
+                    alias,
+                    uppercase: false,
+                }) => match alias.as_deref() {
+                    Some(MENTION) => highlight.mentioned_public_keys.push(*public_key),
+                    _ => highlight.authors.push(HighlightAuthor {
+                        public_key: *public_key,
+                        relay_url: relay_url.clone(),
+                        role: alias.clone(),
+                    }),
+                },
+                _ => {}
+            }
+        }
+
+        Ok(highlight)
+    }
+
+    pub(crate) fn to_event_builder(self) -> EventBuilder {
+        let mut tags: Vec<Tag> = Vec::with_capacity(
+            self.sources.len()
+                + self.authors.len()
+                + self.mentioned_public_keys.len()
+                + self.mentioned_urls.len()
+                + 2,
+        );
+
+        // Add sources
+        tags.extend(self.sources.into_iter().map(HighlightSource::to_tag));
+
+        // Add authors
+        for author in self.authors.into_iter() {
+            tags.push(Tag::from_standardized_without_cell(
+                TagStandard::PublicKey {
+                    public_key: author.public_key,
+                    relay_url: author.relay_url,
+                    alias: author.role,
+                    uppercase: false,
+                },
+            ));
+        }
+
+        // Add context
+        if let Some(context) = self.context {
+            tags.push(Tag::custom(TagKind::from(CONTEXT), [context]));
+        }
+
+        // Add comment
+        if let Some(comment) = self.comment {
+            tags.push(Tag::custom(TagKind::from(COMMENT), [comment]));
+        }
+
+        // Add mentions
+        for public_key in self.mentioned_public_keys.into_iter() {
+            tags.push(Tag::from_standardized_without_cell(
+                TagStandard::PublicKey {
+                    public_key,
+                    relay_url: None,
+                    alias: Some(MENTION.to_string()),
+                    uppercase: false,
+                },
+            ));
+        }
+
+        for url in self.mentioned_urls.into_iter() {
+            tags.push(Tag::custom(
+                TagKind::SingleLetter(SingleLetterTag::lowercase(Alphabet::R)),
+                [url.to_string(), MENTION.to_string()],
+            ));
+        }
+
+        EventBuilder::new(Kind::Highlight, self.content).tags(tags)
+    }
+}
+
+/// Extract the context of a highlight from the source text
+///
+/// Returns the paragraph(s) containing the highlighted text, or [`None`] if the text isn't found
+/// or if the highlight already covers the whole paragraph(s).
+pub fn extract_context<'a>(source: &'a str, highlight: &str) -> Option<&'a str> {
+    let highlight: &str = highlight.trim();
+
+    if highlight.is_empty() {
+        return None;
+    }
+
+    let start: usize = source.find(highlight)?;
+    let end: usize = start + highlight.len();
+
+    // Expand to the paragraph boundaries
+    let paragraph_start: usize = source[..start].rfind("\n\n").map(|i| i + 2).unwrap_or(0);
+    let paragraph_end: usize = source[end..]
+        .find("\n\n")
+        .map(|i| end + i)
+        .unwrap_or(source.len());
+
+    let context: &str = source[paragraph_start..paragraph_end].trim();
+
+    if context == highlight {
+        return None;
+    }
+
+    Some(context)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Keys;
+
+    const ARTICLE: &str = "# Title\n\nThe first paragraph. It talks about nostr.\n\nThe second paragraph, with a highlight inside. Another sentence.\n\nThe end.";
+
+    #[test]
+    fn test_extract_context() {
+        assert_eq!(
+            extract_context(ARTICLE, "with a highlight inside"),
+            Some("The second paragraph, with a highlight inside. Another sentence.")
+        );
+        assert_eq!(
+            extract_context(ARTICLE, "It talks about nostr.\n\nThe second"),
+            Some("The first paragraph. It talks about nostr.\n\nThe second paragraph, with a highlight inside. Another sentence.")
+        );
+        assert_eq!(extract_context(ARTICLE, "The end."), None);
+        assert_eq!(extract_context(ARTICLE, "not found"), None);
+    }
+
+    #[test]
+    fn test_highlight() {
+        let keys = Keys::generate();
+        let author = Keys::generate().public_key();
+        let mentioned = Keys::generate().public_key();
+        let coordinate = Coordinate::new(Kind::LongFormTextNote, author).identifier("article");
+
+        let mut highlight = Highlight::new("with a highlight inside")
+            .source(HighlightSource::Coordinate {
+                coordinate,
+                relay_url: None,
+            })
+            .source(HighlightSource::Url(
+                Url::parse("https://example.com/article").unwrap(),
+            ))
+            .source(HighlightSource::External {
+                content: ExternalContentId::Book("9780765382030".to_string()),
+                hint: None,
+            })
+            .author(HighlightAuthor::new(author).role("author"))
+            .context(extract_context(ARTICLE, "with a highlight inside").unwrap())
+            .comment("Great point");
+        highlight.mentioned_public_keys.push(mentioned);
+        highlight
+            .mentioned_urls
+            .push(Url::parse("https://example.com/other").unwrap());
+
+        let event = EventBuilder::highlight(highlight.clone())
+            .sign_with_keys(&keys)
+            .unwrap();
+        assert_eq!(event.kind, Kind::Highlight);
+        assert_eq!(Highlight::from_event(&event).unwrap(), highlight);
+    }
+
+    #[test]
+    fn test_highlight_mentions_without_comment() {
+        let keys = Keys::generate();
+        let mentioned = Keys::generate().public_key();
+        let url = Url::parse("https://example.com/other").unwrap();
+
+        let mut highlight = Highlight::new("with a highlight inside");
+        highlight.mentioned_public_keys.push(mentioned);
+        highlight.mentioned_urls.push(url.clone());
+
+        let event = EventBuilder::highlight(highlight.clone())
+            .sign_with_keys(&keys)
+            .unwrap();
+        assert!(event.tags.public_keys().any(|p| p == &mentioned));
+
+        let parsed = Highlight::from_event(&event).unwrap();
+        assert_eq!(parsed.comment, None);
+        assert_eq!(parsed.mentioned_public_keys, vec![mentioned]);
+        assert_eq!(parsed.mentioned_urls, vec![url]);
+        assert_eq!(parsed, highlight);
+    }
+}
@@ -7,12 +7,15 @@
 //! <https://github.com/nostr-protocol/nips/blob/master/88.md>
 
 use alloc::borrow::Cow;
+use alloc::collections::{BTreeMap, BTreeSet};
 use alloc::string::{String, ToString};
 use alloc::vec::Vec;
 use core::fmt;
 use core::str::FromStr;
 
-use crate::{Event, EventBuilder, EventId, Kind, RelayUrl, Tag, TagKind, TagStandard, Timestamp};
+use crate::{
+    Event, EventBuilder, EventId, Kind, PublicKey, RelayUrl, Tag, TagKind, TagStandard, Timestamp,
+};
 
 pub(crate) const ENDS_AT_TAG_KIND_STR: &str = "endsAt";
 pub(crate) const ENDS_AT_TAG_KIND: TagKind = TagKind::Custom(Cow::Borrowed(ENDS_AT_TAG_KIND_STR));
@@ -221,10 +224,155 @@ impl PollResponse {
     }
 }
 
+/// Poll results
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PollResults {
+    /// Number of votes per option ID
+    ///
+    /// All the poll options are included, also the ones without votes.
+    pub votes: BTreeMap<String, usize>,
+    /// Number of counted voters
+    pub voters: usize,
+}
+
+impl PollResults {
+    /// Tally the poll responses
+    ///
+    /// Check [`PollResults::compute_restricted`] for more details.
+    #[inline]
+    pub fn compute<'a, I>(poll: &Event, responses: I) -> Result<Self, Error>
+    where
+        I: IntoIterator<Item = &'a Event>,
+    {
+        Self::tally(poll, responses, None)
+    }
+
+    /// Tally the poll responses, counting only the ones of the `voters` (i.e., follows or web of trust)
+    ///
+    /// A response (kind `1018`) is counted only if:
+    /// * it references the poll;
+    /// * it's published before the poll `endsAt` (if any);
+    /// * it's the latest response of its author (in case of same timestamp, the one with the lowest ID).
+    ///
+    /// For single choice polls only the first `response` tag is considered,
+    /// for multiple choice polls the first `response` tag for each option.
+    /// Responses to unknown options are ignored.
+    ///
+    /// The signatures of the responses are NOT verified: it's the caller's job to pass only verified events
+    /// (i.e., the ones received through the relay pool or loaded from the database).
+    #[inline]
+    pub fn compute_restricted<'a, I>(
+        poll: &Event,
+        responses: I,
+        voters: &BTreeSet<PublicKey>,
+    ) -> Result<Self, Error>
+    where
+        I: IntoIterator<Item = &'a Event>,
+    {
+        Self::tally(poll, responses, Some(voters))
+    }
+
+    fn tally<'a, I>(
+        poll_event: &Event,
+        responses: I,
+        voters: Option<&BTreeSet<PublicKey>>,
+    ) -> Result<Self, Error>
+    where
+        I: IntoIterator<Item = &'a Event>,
+    {
+        let poll: Poll = Poll::from_event(poll_event)?;
+
+        // Keep only the latest response of each author
+        let mut latest: BTreeMap<PublicKey, &Event> = BTreeMap::new();
+
+        for response in responses.into_iter() {
+            if response.kind != Kind::PollResponse {
+                continue;
+            }
+
+            // Check if the poll is ended
+            if let Some(ends_at) = poll.ends_at {
+                if response.created_at > ends_at {
+                    continue;
+                }
+            }
+
+            // Check if the author is allowed to vote
+            if let Some(voters) = voters {
+                if !voters.contains(&response.pubkey) {
+                    continue;
+                }
+            }
+
+            // Check if references the poll
+            if !response.tags.event_ids().any(|id| id == &poll_event.id) {
+                continue;
+            }
+
+            // Keep the newest one. In case of same timestamp, keep the one with the lowest ID (NIP01).
+            latest
+                .entry(response.pubkey)
+                .and_modify(|current| {
+                    if response.created_at > current.created_at
+                        || (response.created_at == current.created_at && response.id < current.id)
+                    {
+                        *current = response;
+                    }
+                })
+                .or_insert(response);
+        }
+
+        let mut results: Self = Self {
+            votes: poll
+                .options
+                .iter()
+                .map(|option| (option.id.clone(), 0))
+                .collect(),
+            voters: 0,
+        };
+
+        for response in latest.into_values() {
+            let mut choices = response
+                .tags
+                .filter_standardized(TagKind::Response)
+                .filter_map(|tag| match tag {
+                    TagStandard::PollResponse(id) => Some(id.as_str()),
+                    _ => None,
+                });
+
+            let choices: BTreeSet<&str> = match poll.r#type {
+                PollType::SingleChoice => choices.next().into_iter().collect(),
+                PollType::MultipleChoice => choices.collect(),
+            };
+
+            let mut counted: bool = false;
+
+            for choice in choices.into_iter() {
+                if let Some(votes) = results.votes.get_mut(choice) {
+                    *votes += 1;
+                    counted = true;
+                }
+            }
+
+            if counted {
+                results.voters += 1;
+            }
+        }
+
+        Ok(results)
+    }
+
+    /// Get the number of votes of an option
+    #[inline]
+    pub fn votes_for(&self, option_id: &str) -> usize {
+        self.votes.get(option_id).copied().unwrap_or_default()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::JsonUtil;
+    use crate::{JsonUtil, Keys};
 
     #[test]
     fn test_poll_type() {
@@ -374,4 +522,105 @@ mod tests {
         assert!(poll.relays.is_empty());
         assert!(poll.ends_at.is_none());
     }
+
+    #[test]
+    fn test_poll_results() {
+        let author = Keys::generate();
+        let alice = Keys::generate();
+        let bob = Keys::generate();
+        let carol = Keys::generate();
+        let dave = Keys::generate();
+
+        let option = |id: &str| PollOption {
+            id: id.to_string(),
+            text: id.to_uppercase(),
+        };
+
+        let poll = |r#type: PollType| {
+            EventBuilder::poll(Poll {
+                title: String::from("Best relay?"),
+                r#type,
+                options: vec![option("a"), option("b"), option("c")],
+                relays: Vec::new(),
+                ends_at: Some(Timestamp::from_secs(2_000)),
+            })
+            .sign_with_keys(&author)
+            .unwrap()
+        };
+
+        let response = |keys: &Keys, poll_id: EventId, responses: &[&str], created_at: u64| {
+            EventBuilder::poll_response(PollResponse::MultipleChoice {
+                poll_id,
+                responses: responses.iter().map(|r| r.to_string()).collect(),
+            })
+            .custom_created_at(Timestamp::from_secs(created_at))
+            .sign_with_keys(keys)
+            .unwrap()
+        };
+
+        // Single choice
+        let single = poll(PollType::SingleChoice);
+        let responses = vec![
+            // Only the first response is considered
+            response(&alice, single.id, &["a", "b"], 1_000),
+            // Superseded by the latest one
+            response(&bob, single.id, &["a"], 1_000),
+            response(&bob, single.id, &["b"], 1_500),
+            // After the end: the previous one is counted
+            response(&carol, single.id, &["b"], 1_000),
+            response(&carol, single.id, &["c"], 2_500),
+            // Unknown option
+            response(&dave, single.id, &["z"], 1_000),
+            // Another poll
+            response(&dave, EventId::all_zeros(), &["a"], 1_100),
+        ];
+
+        let results = PollResults::compute(&single, responses.iter()).unwrap();
+        assert_eq!(results.votes_for("a"), 1);
+        assert_eq!(results.votes_for("b"), 2);
+        assert_eq!(results.votes_for("c"), 0);
+        assert_eq!(results.votes.len(), 3);
+        assert_eq!(results.voters, 3);
+
+        // Multiple choice
+        let multiple = poll(PollType::MultipleChoice);
+        let responses = vec![
+            // Duplicated options are counted once
+            response(&alice, multiple.id, &["a", "b", "a"], 1_000),
+            response(&bob, multiple.id, &["c", "z"], 1_000),
+        ];
+
+        let results = PollResults::compute(&multiple, responses.iter()).unwrap();
+        assert_eq!(results.votes_for("a"), 1);
+        assert_eq!(results.votes_for("b"), 1);
+        assert_eq!(results.votes_for("c"), 1);
+        assert_eq!(results.voters, 2);
+
+        // Same timestamp: the response with the lowest ID wins
+        let first = response(&carol, multiple.id, &["a"], 1_200);
+        let second = response(&carol, multiple.id, &["b"], 1_200);
+        let (lowest, highest) = if first.id < second.id {
+            (first, second)
+        } else {
+            (second, first)
+        };
+        let expected: &str = if lowest.tags.iter().any(|t| t.content() == Some("a")) {
+            "a"
+        } else {
+            "b"
+        };
+        for responses in [[&lowest, &highest], [&highest, &lowest]] {
+            let results = PollResults::compute(&multiple, responses).unwrap();
+            assert_eq!(results.votes_for(expected), 1);
+            assert_eq!(results.voters, 1);
+        }
+
+        // Restricted to a set of voters
+        let voters: BTreeSet<PublicKey> = BTreeSet::from([bob.public_key()]);
+        let results =
+            PollResults::compute_restricted(&multiple, responses.iter(), &voters).unwrap();
+        assert_eq!(results.votes_for("a"), 0);
+        assert_eq!(results.votes_for("c"), 1);
+        assert_eq!(results.voters, 1);
+    }
 }
@@ -0,0 +1,546 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2025 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! NIP89: Recommended Application Handlers
+//!
+//! <https://github.com/nostr-protocol/nips/blob/master/89.md>
+
+#![allow(clippy::wrong_self_convention)]
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+use core::str::FromStr;
+
+use super::nip01::{self, Coordinate, Metadata};
+use super::nip19::{self, Nip19, ToBech32};
+use crate::{Event, EventBuilder, JsonUtil, Kind, RelayUrl, Tag, TagKind, TagStandard};
+
+/// Placeholder replaced with the bech32-encoded entity in handler URL templates
+pub const BECH32_PLACEHOLDER: &str = "<bech32>";
+
+/// NIP89 error
+#[derive(Debug, PartialEq)]
+pub enum Error {
+    /// NIP01 error
+    NIP01(nip01::Error),
+    /// NIP19 error
+    NIP19(nip19::Error),
+    /// Wrong event kind
+    WrongKind {
+        /// The received kind
+        received: Kind,
+        /// The expected kind
+        expected: Kind,
+    },
+    /// Identifier tag not found
+    IdentifierTagNotFound,
+    /// Invalid handler URL template
+    InvalidUrlTemplate,
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NIP01(e) => e.fmt(f),
+            Self::NIP19(e) => e.fmt(f),
+            Self::WrongKind { received, expected } => {
+                write!(f, "wrong kind: received={received}, expected={expected}")
+            }
+            Self::IdentifierTagNotFound => f.write_str("identifier tag not found"),
+            Self::InvalidUrlTemplate => f.write_str("invalid URL template"),
+        }
+    }
+}
+
+impl From<nip01::Error> for Error {
+    fn from(e: nip01::Error) -> Self {
+        Self::NIP01(e)
+    }
+}
+
+impl From<nip19::Error> for Error {
+    fn from(e: nip19::Error) -> Self {
+        Self::NIP19(e)
+    }
+}
+
+#[inline]
+fn check_kind(kind: Kind, expected: Kind) -> Result<(), Error> {
+    if kind != expected {
+        return Err(Error::WrongKind {
+            received: kind,
+            expected,
+        });
+    }
+
+    Ok(())
+}
+
+/// Get the bech32 prefix of a NIP19 entity
+fn nip19_prefix(entity: &Nip19) -> &'static str {
+    match entity {
+        Nip19::Secret(..) => nip19::PREFIX_BECH32_SECRET_KEY,
+        #[cfg(feature = "nip49")]
+        Nip19::EncryptedSecret(..) => nip19::PREFIX_BECH32_SECRET_KEY_ENCRYPTED,
+        Nip19::Pubkey(..) => nip19::PREFIX_BECH32_PUBLIC_KEY,
+        Nip19::Profile(..) => nip19::PREFIX_BECH32_PROFILE,
+        Nip19::EventId(..) => nip19::PREFIX_BECH32_NOTE_ID,
+        Nip19::Event(..) => nip19::PREFIX_BECH32_EVENT,
+        Nip19::Coordinate(..) => nip19::PREFIX_BECH32_COORDINATE,
+    }
+}
+
+/// Handler platform
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum HandlerPlatform {
+    /// Web
+    Web,
+    /// iOS
+    Ios,
+    /// Android
+    Android,
+    /// Custom
+    Custom(String),
+}
+
+impl fmt::Display for HandlerPlatform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl HandlerPlatform {
+    /// Get as `&str`
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Web => "web",
+            Self::Ios => "ios",
+            Self::Android => "android",
+            Self::Custom(platform) => platform.as_str(),
+        }
+    }
+}
+
+impl<S> From<S> for HandlerPlatform
+where
+    S: AsRef<str>,
+{
+    fn from(platform: S) -> Self {
+        match platform.as_ref() {
+            "web" => Self::Web,
+            "ios" => Self::Ios,
+            "android" => Self::Android,
+            platform => Self::Custom(platform.to_string()),
+        }
+    }
+}
+
+/// Handler URL
+///
+/// A URL template containing the [`BECH32_PLACEHOLDER`].
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct HandlerUrl {
+    /// Platform
+    pub platform: HandlerPlatform,
+    /// URL template (i.e. `https://example.com/e/<bech32>`)
+    pub template: String,
+    /// The NIP19 entity type handled by this URL (i.e. `nevent`, `naddr`, `nprofile`)
+    ///
+    /// If [`None`], the URL handles any entity type.
+    pub entity: Option<String>,
+}
+
+impl HandlerUrl {
+    /// New handler URL
+    pub fn new<T>(platform: HandlerPlatform, template: T) -> Result<Self, Error>
+    where
+        T: Into<String>,
+    {
+        let template: String = template.into();
+
+        if !template.contains(BECH32_PLACEHOLDER) {
+            return Err(Error::InvalidUrlTemplate);
+        }
+
+        Ok(Self {
+            platform,
+            template,
+            entity: None,
+        })
+    }
+
+    /// Set the handled NIP19 entity type (i.e. `nevent`)
+    #[inline]
+    pub fn entity<S>(mut self, entity: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.entity = Some(entity.into());
+        self
+    }
+
+    /// Check if the URL can handle the NIP19 entity
+    pub fn supports(&self, entity: &Nip19) -> bool {
+        match &self.entity {
+            Some(e) => e == nip19_prefix(entity),
+            None => true,
+        }
+    }
+
+    /// Build the URL for the NIP19 entity, replacing the [`BECH32_PLACEHOLDER`]
+    pub fn format(&self, entity: &Nip19) -> Result<String, Error> {
+        let bech32: String = entity.to_bech32()?;
+        Ok(self.template.replace(BECH32_PLACEHOLDER, &bech32))
+    }
+
+    fn parse(tag: &[String]) -> Option<Self> {
+        let template: &String = tag.get(1)?;
+
+        // Not a platform tag
+        if !template.contains(BECH32_PLACEHOLDER) {
+            return None;
+        }
+
+        Some(Self {
+            platform: HandlerPlatform::from(tag.first()?),
+            template: template.clone(),
+            entity: tag.get(2).filter(|e| !e.is_empty()).cloned(),
+        })
+    }
+
+    fn to_tag(self) -> Tag {
+        let mut values: Vec<String> = Vec::with_capacity(2);
+        values.push(self.template);
+
+        if let Some(entity) = self.entity {
+            values.push(entity);
+        }
+
+        Tag::custom(TagKind::from(self.platform.as_str()), values)
+    }
+}
+
+/// Handler information
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HandlerInformation {
+    /// Identifier (`d` tag)
+    pub identifier: String,
+    /// Optional handler metadata
+    ///
+    /// If [`None`], the kind `0` of the author should be used.
+    pub metadata: Option<Metadata>,
+    /// Supported kinds
+    pub kinds: Vec<Kind>,
+    /// Handler URLs
+    pub urls: Vec<HandlerUrl>,
+}
+
+impl HandlerInformation {
+    /// New handler information
+    pub fn new<S>(identifier: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self {
+            identifier: identifier.into(),
+            metadata: None,
+            kinds: Vec::new(),
+            urls: Vec::new(),
+        }
+    }
+
+    /// Parse handler information from an [`Event`] (kind `31990`)
+    pub fn from_event(event: &Event) -> Result<Self, Error> {
+        check_kind(event.kind, Kind::HandlerInformation)?;
+
+        let identifier: &str = event
+            .tags
+            .identifier()
+            .ok_or(Error::IdentifierTagNotFound)?;
+
+        let mut info: Self = Self::new(identifier);
+
+        // The content is optional: ignore it if empty or invalid
+        if !event.content.is_empty() {
+            info.metadata = Metadata::from_json(&event.content).ok();
+        }
+
+        for tag in event.tags.iter() {
+            match tag.as_standardized() {
+                Some(TagStandard::Kind {
+                    kind,
+                    uppercase: false,
+                }) => info.kinds.push(*kind),
+                // Platform tags may collide with standardized ones (i.e. `web`): parse them from the raw tag
+                _ => {
+                    if let Some(url) = HandlerUrl::parse(tag.as_slice()) {
+                        info.urls.push(url);
+                    }
+                }
+            }
+        }
+
+        Ok(info)
+    }
+
+    /// Check if the handler supports the kind
+    #[inline]
+    pub fn supports_kind(&self, kind: &Kind) -> bool {
+        self.kinds.contains(kind)
+    }
+
+    /// Get the URL to open the NIP19 entity on the given platform
+    ///
+    /// URLs handling the specific entity type take precedence over the generic ones.
+    pub fn url_for(&self, platform: &HandlerPlatform, entity: &Nip19) -> Option<String> {
+        let mut generic: Option<&HandlerUrl> = None;
+
+        for url in self
+            .urls
+            .iter()
+            .filter(|url| &url.platform == platform && url.supports(entity))
+        {
+            // Stop at the first URL handling the specific entity type
+            if url.entity.is_some() {
+                return url.format(entity).ok();
+            }
+
+            if generic.is_none() {
+                generic = Some(url);
+            }
+        }
+
+        generic?.format(entity).ok()
+    }
+
+    pub(crate) fn to_event_builder(self) -> Result<EventBuilder, Error> {
+        if self.identifier.is_empty() {
+            return Err(Error::IdentifierTagNotFound);
+        }
+
+        let mut tags: Vec<Tag> = Vec::with_capacity(1 + self.kinds.len() + self.urls.len());
+
+        // Add identifier
+        tags.push(Tag::identifier(self.identifier));
+
+        // Add kinds
+        for kind in self.kinds.into_iter() {
+            tags.push(Tag::from_standardized_without_cell(TagStandard::Kind {
+                kind,
+                uppercase: false,
+            }));
+        }
+
+        // Add URLs
+        tags.extend(self.urls.into_iter().map(HandlerUrl::to_tag));
+
+        let content: String = self.metadata.map(|m| m.as_json()).unwrap_or_default();
+
+        Ok(EventBuilder::new(Kind::HandlerInformation, content).tags(tags))
+    }
+}
+
+/// Recommended handler
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RecommendedHandler {
+    /// Handler information coordinate
+    pub coordinate: Coordinate,
+    /// Relay hint
+    pub relay_url: Option<RelayUrl>,
+    /// Platform
+    pub platform: Option<HandlerPlatform>,
+}
+
+impl RecommendedHandler {
+    /// New recommended handler
+    #[inline]
+    pub fn new(coordinate: Coordinate) -> Self {
+        Self {
+            coordinate,
+            relay_url: None,
+            platform: None,
+        }
+    }
+
+    fn parse(tag: &[String]) -> Option<Self> {
+        if tag.first().map(|k| k.as_str()) != Some("a") {
+            return None;
+        }
+
+        let coordinate: Coordinate = Coordinate::parse(tag.get(1)?).ok()?;
+
+        if coordinate.kind != Kind::HandlerInformation {
+            return None;
+        }
+
+        Some(Self {
+            coordinate,
+            relay_url: tag
+                .get(2)
+                .filter(|r| !r.is_empty())
+                .and_then(|r| RelayUrl::parse(r).ok()),
+            platform: tag
+                .get(3)
+                .filter(|p| !p.is_empty())
+                .map(HandlerPlatform::from),
+        })
+    }
+
+    fn to_tag(self) -> Tag {
+        let mut values: Vec<String> = Vec::with_capacity(3);
+        values.push(self.coordinate.to_string());
+
+        match (self.relay_url, self.platform) {
+            (relay_url, Some(platform)) => {
+                values.push(relay_url.map(|r| r.to_string()).unwrap_or_default());
+                values.push(platform.to_string());
+            }
+            (Some(relay_url), None) => values.push(relay_url.to_string()),
+            (None, None) => {}
+        }
+
+        Tag::custom(TagKind::a(), values)
+    }
+}
+
+/// Handler recommendation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HandlerRecommendation {
+    /// The recommended kind
+    pub kind: Kind,
+    /// Recommended handlers
+    pub handlers: Vec<RecommendedHandler>,
+}
+
+impl HandlerRecommendation {
+    /// New handler recommendation
+    #[inline]
+    pub fn new(kind: Kind) -> Self {
+        Self {
+            kind,
+            handlers: Vec::new(),
+        }
+    }
+
+    /// Parse handler recommendation from an [`Event`] (kind `31989`)
+    pub fn from_event(event: &Event) -> Result<Self, Error> {
+        check_kind(event.kind, Kind::HandlerRecommendation)?;
+
+        let identifier: &str = event
+            .tags
+            .identifier()
+            .ok_or(Error::IdentifierTagNotFound)?;
+        let kind: Kind = Kind::from_str(identifier).map_err(|_| Error::IdentifierTagNotFound)?;
+
+        Ok(Self {
+            kind,
+            handlers: event
+                .tags
+                .iter()
+                .filter_map(|tag| RecommendedHandler::parse(tag.as_slice()))
+                .collect(),
+        })
+    }
+
+    pub(crate) fn to_event_builder(self) -> EventBuilder {
+        let mut tags: Vec<Tag> = Vec::with_capacity(1 + self.handlers.len());
+
+        // Add identifier
+        tags.push(Tag::identifier(self.kind.to_string()));
+
+        // Add handlers
+        tags.extend(self.handlers.into_iter().map(RecommendedHandler::to_tag));
+
+        EventBuilder::new(Kind::HandlerRecommendation, "").tags(tags)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nips::nip19::{Nip19Coordinate, Nip19Event};
+    use crate::{EventId, Keys};
+
+    #[test]
+    fn test_handler_information() {
+        let keys = Keys::generate();
+
+        let mut info = HandlerInformation::new("my-app");
+        info.kinds = vec![Kind::TextNote, Kind::LongFormTextNote];
+        info.urls = vec![
+            HandlerUrl::new(HandlerPlatform::Web, "https://example.com/a/<bech32>")
+                .unwrap()
+                .entity("naddr"),
+            HandlerUrl::new(HandlerPlatform::Web, "https://example.com/e/<bech32>").unwrap(),
+            HandlerUrl::new(HandlerPlatform::Ios, "example:<bech32>").unwrap(),
+        ];
+
+        let event = EventBuilder::handler_information(info.clone())
+            .unwrap()
+            .sign_with_keys(&keys)
+            .unwrap();
+        let parsed = HandlerInformation::from_event(&event).unwrap();
+        assert_eq!(parsed, info);
+        assert!(parsed.supports_kind(&Kind::TextNote));
+        assert!(!parsed.supports_kind(&Kind::Reaction));
+
+        let event_id =
+            EventId::from_hex("b3e392b11f5d4f28321cedd09303a748acfd0487aea5a7450b3481c60b6e4f87")
+                .unwrap();
+        let entity = Nip19::Event(Nip19Event::new(event_id));
+        let bech32 = entity.to_bech32().unwrap();
+        assert_eq!(
+            parsed.url_for(&HandlerPlatform::Web, &entity),
+            Some(format!("https://example.com/e/{bech32}"))
+        );
+        assert_eq!(
+            parsed.url_for(&HandlerPlatform::Ios, &entity),
+            Some(format!("example:{bech32}"))
+        );
+        assert_eq!(parsed.url_for(&HandlerPlatform::Android, &entity), None);
+
+        let coordinate = Coordinate::new(Kind::LongFormTextNote, keys.public_key()).identifier("a");
+        let entity = Nip19::Coordinate(Nip19Coordinate::new(coordinate, []));
+        let bech32 = entity.to_bech32().unwrap();
+        assert_eq!(
+            parsed.url_for(&HandlerPlatform::Web, &entity),
+            Some(format!("https://example.com/a/{bech32}"))
+        );
+
+        assert_eq!(
+            HandlerUrl::new(HandlerPlatform::Web, "https://example.com"),
+            Err(Error::InvalidUrlTemplate)
+        );
+    }
+
+    #[test]
+    fn test_handler_recommendation() {
+        let keys = Keys::generate();
+        let coordinate =
+            Coordinate::new(Kind::HandlerInformation, keys.public_key()).identifier("my-app");
+
+        let mut recommendation = HandlerRecommendation::new(Kind::LongFormTextNote);
+        recommendation.handlers = vec![
+            RecommendedHandler {
+                coordinate: coordinate.clone(),
+                relay_url: Some(RelayUrl::parse("wss://relay.example.com").unwrap()),
+                platform: Some(HandlerPlatform::Web),
+            },
+            RecommendedHandler::new(coordinate),
+        ];
+
+        let event = EventBuilder::handler_recommendation(recommendation.clone())
+            .sign_with_keys(&keys)
+            .unwrap();
+        assert_eq!(event.tags.identifier(), Some("30023"));
+        assert_eq!(
+            HandlerRecommendation::from_event(&event).unwrap(),
+            recommendation
+        );
+    }
+}
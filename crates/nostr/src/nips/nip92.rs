@@ -0,0 +1,349 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2025 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! NIP92: Media Attachments
+//!
+//! <https://github.com/nostr-protocol/nips/blob/master/92.md>
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+use core::str::FromStr;
+
+use hashes::sha256::Hash as Sha256Hash;
+
+use super::nip94::FileMetadata;
+use crate::types::Url;
+use crate::{ImageDimensions, Tag, TagKind, Tags};
+
+/// `imeta` tag kind
+pub const IMETA: &str = "imeta";
+
+const URL: &str = "url";
+const MIME_TYPE: &str = "m";
+const SHA256: &str = "x";
+const SIZE: &str = "size";
+const DIM: &str = "dim";
+const BLURHASH: &str = "blurhash";
+const ALT: &str = "alt";
+const FALLBACK: &str = "fallback";
+const IMAGE: &str = "image";
+const MAGNET: &str = "magnet";
+
+/// NIP92 error
+#[derive(Debug, PartialEq, Eq)]
+pub enum Error {
+    /// Not an `imeta` tag
+    NotImeta,
+    /// The URL is missing or invalid
+    InvalidUrl,
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotImeta => f.write_str("not an imeta tag"),
+            Self::InvalidUrl => f.write_str("missing or invalid url"),
+        }
+    }
+}
+
+/// Media attachment (`imeta` tag)
+///
+/// The fields are the same of [`FileMetadata`], but only the URL is required.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MediaAttachment {
+    /// Url
+    pub url: Url,
+    /// MIME type
+    pub mime_type: Option<String>,
+    /// SHA256 of file
+    pub hash: Option<Sha256Hash>,
+    /// Size in bytes
+    pub size: Option<usize>,
+    /// Size in pixels
+    pub dim: Option<ImageDimensions>,
+    /// Magnet
+    pub magnet: Option<String>,
+    /// Blurhash
+    pub blurhash: Option<String>,
+    /// Description for accessibility
+    pub alt: Option<String>,
+    /// Fallback URLs
+    pub fallbacks: Vec<Url>,
+    /// Preview images (i.e. video thumbnails)
+    pub images: Vec<Url>,
+}
+
+impl MediaAttachment {
+    /// New media attachment
+    pub fn new(url: Url) -> Self {
+        Self {
+            url,
+            mime_type: None,
+            hash: None,
+            size: None,
+            dim: None,
+            magnet: None,
+            blurhash: None,
+            alt: None,
+            fallbacks: Vec::new(),
+            images: Vec::new(),
+        }
+    }
+
+    /// Set MIME type
+    pub fn mime_type<S>(self, mime_type: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self {
+            mime_type: Some(mime_type.into()),
+            ..self
+        }
+    }
+
+    /// Set SHA256 of file
+    pub fn hash(self, hash: Sha256Hash) -> Self {
+        Self {
+            hash: Some(hash),
+            ..self
+        }
+    }
+
+    /// Set file size (bytes)
+    pub fn size(self, size: usize) -> Self {
+        Self {
+            size: Some(size),
+            ..self
+        }
+    }
+
+    /// Set file size (pixels)
+    pub fn dimensions(self, dim: ImageDimensions) -> Self {
+        Self {
+            dim: Some(dim),
+            ..self
+        }
+    }
+
+    /// Set blurhash
+    pub fn blurhash<S>(self, blurhash: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self {
+            blurhash: Some(blurhash.into()),
+            ..self
+        }
+    }
+
+    /// Set alt
+    pub fn alt<S>(self, alt: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self {
+            alt: Some(alt.into()),
+            ..self
+        }
+    }
+
+    /// Add fallback URL
+    pub fn fallback(mut self, url: Url) -> Self {
+        self.fallbacks.push(url);
+        self
+    }
+
+    /// Add preview image
+    pub fn image(mut self, url: Url) -> Self {
+        self.images.push(url);
+        self
+    }
+
+    /// Parse from `imeta` tag
+    ///
+    /// Unknown and invalid fields are ignored.
+    pub fn from_tag(tag: &Tag) -> Result<Self, Error> {
+        let values: &[String] = tag.as_slice();
+
+        if values.first().map(|k| k.as_str()) != Some(IMETA) {
+            return Err(Error::NotImeta);
+        }
+
+        let entries = values
+            .iter()
+            .skip(1)
+            .filter_map(|value| value.split_once(' '))
+            .map(|(key, value)| (key, value.trim()));
+
+        let url: &str = entries
+            .clone()
+            .find_map(|(key, value)| (key == URL).then_some(value))
+            .ok_or(Error::InvalidUrl)?;
+        let mut attachment: Self = Self::new(Url::parse(url).map_err(|_| Error::InvalidUrl)?);
+
+        for (key, value) in entries {
+            match key {
+                MIME_TYPE => attachment.mime_type = Some(value.to_string()),
+                SHA256 => attachment.hash = Sha256Hash::from_str(value).ok(),
+                SIZE => attachment.size = value.parse().ok(),
+                DIM => attachment.dim = ImageDimensions::from_str(value).ok(),
+                MAGNET => attachment.magnet = Some(value.to_string()),
+                BLURHASH => attachment.blurhash = Some(value.to_string()),
+                ALT => attachment.alt = Some(value.to_string()),
+                FALLBACK => {
+                    if let Ok(url) = Url::parse(value) {
+                        attachment.fallbacks.push(url);
+                    }
+                }
+                IMAGE => {
+                    if let Ok(url) = Url::parse(value) {
+                        attachment.images.push(url);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Ok(attachment)
+    }
+
+    /// Convert to `imeta` tag
+    pub fn to_tag(&self) -> Tag {
+        let mut values: Vec<String> = Vec::with_capacity(8);
+
+        values.push(format!("{URL} {}", self.url));
+
+        if let Some(mime_type) = &self.mime_type {
+            values.push(format!("{MIME_TYPE} {mime_type}"));
+        }
+
+        if let Some(hash) = &self.hash {
+            values.push(format!("{SHA256} {hash}"));
+        }
+
+        if let Some(size) = &self.size {
+            values.push(format!("{SIZE} {size}"));
+        }
+
+        if let Some(dim) = &self.dim {
+            values.push(format!("{DIM} {dim}"));
+        }
+
+        if let Some(magnet) = &self.magnet {
+            values.push(format!("{MAGNET} {magnet}"));
+        }
+
+        if let Some(blurhash) = &self.blurhash {
+            values.push(format!("{BLURHASH} {blurhash}"));
+        }
+
+        if let Some(alt) = &self.alt {
+            values.push(format!("{ALT} {alt}"));
+        }
+
+        for fallback in self.fallbacks.iter() {
+            values.push(format!("{FALLBACK} {fallback}"));
+        }
+
+        for image in self.images.iter() {
+            values.push(format!("{IMAGE} {image}"));
+        }
+
+        Tag::custom(TagKind::from(IMETA), values)
+    }
+}
+
+impl From<FileMetadata> for MediaAttachment {
+    fn from(metadata: FileMetadata) -> Self {
+        Self {
+            url: metadata.url,
+            mime_type: Some(metadata.mime_type),
+            hash: Some(metadata.hash),
+            size: metadata.size,
+            dim: metadata.dim,
+            magnet: metadata.magnet,
+            blurhash: metadata.blurhash,
+            alt: None,
+            fallbacks: Vec::new(),
+            images: Vec::new(),
+        }
+    }
+}
+
+impl From<MediaAttachment> for Tag {
+    #[inline]
+    fn from(attachment: MediaAttachment) -> Self {
+        attachment.to_tag()
+    }
+}
+
+/// Extract the media attachments from the tags
+///
+/// Invalid `imeta` tags are skipped.
+pub fn extract_media_attachments(tags: &Tags) -> impl Iterator<Item = MediaAttachment> + '_ {
+    tags.iter()
+        .filter_map(|tag| MediaAttachment::from_tag(tag).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const IMAGE_URL: &str = "https://image.nostr.build/99a95fcb4b7a2591ad32467032c52a62d90a204d3b176bc2459ad7427a3f2b89.jpg";
+    const IMAGE_HASH: &str = "1aea8e98e0e5d969b7124f553b88dfae47d1f00472ea8c0dbf4ac4577d39ef02";
+
+    #[test]
+    fn test_imeta_tag() {
+        let tag = Tag::parse([
+            "imeta",
+            &format!("url {IMAGE_URL}"),
+            "m image/jpeg",
+            &format!("x {IMAGE_HASH}"),
+            "dim 3024x4032",
+            "blurhash eVF$^OI:${M{o#*0-nNFxakD-?xVM}WEWB%iNKxvR-oetmo#R-aen$",
+            "alt A scenic photo overlooking the coast of Costa Rica",
+            "fallback https://nostrcheck.me/alt1.jpg",
+            "fallback https://void.cat/alt1.jpg",
+            "unknown field",
+        ])
+        .unwrap();
+
+        let attachment = MediaAttachment::from_tag(&tag).unwrap();
+        let expected = MediaAttachment::new(Url::parse(IMAGE_URL).unwrap())
+            .mime_type("image/jpeg")
+            .hash(Sha256Hash::from_str(IMAGE_HASH).unwrap())
+            .dimensions(ImageDimensions::new(3024, 4032))
+            .blurhash("eVF$^OI:${M{o#*0-nNFxakD-?xVM}WEWB%iNKxvR-oetmo#R-aen$")
+            .alt("A scenic photo overlooking the coast of Costa Rica")
+            .fallback(Url::parse("https://nostrcheck.me/alt1.jpg").unwrap())
+            .fallback(Url::parse("https://void.cat/alt1.jpg").unwrap());
+        assert_eq!(attachment, expected);
+
+        // Round trip
+        assert_eq!(
+            MediaAttachment::from_tag(&attachment.to_tag()).unwrap(),
+            attachment
+        );
+
+        // Missing url
+        let tag = Tag::parse(["imeta", "m image/jpeg"]).unwrap();
+        assert_eq!(
+            MediaAttachment::from_tag(&tag).unwrap_err(),
+            Error::InvalidUrl
+        );
+
+        // Not imeta
+        let tag = Tag::parse(["t", "nostr"]).unwrap();
+        assert_eq!(
+            MediaAttachment::from_tag(&tag).unwrap_err(),
+            Error::NotImeta
+        );
+    }
+}
@@ -14,6 +14,7 @@ use hashes::sha256::Hash as Sha256Hash;
 use hashes::Hash;
 use serde::{Deserialize, Serialize};
 
+use crate::nips::nip92::MediaAttachment;
 use crate::nips::nip98;
 use crate::nips::nip98::{HttpData, HttpMethod};
 use crate::types::Url;
@@ -126,6 +127,38 @@ impl UploadResponse {
             _ => Err(Error::ResponseDecodeError),
         }
     }
+
+    /// Convert the upload response to a media attachment (`imeta` tag)
+    ///
+    /// Returns an error if the upload was unsuccessful or if the URL cannot be found
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/92.md>
+    pub fn media_attachment(&self) -> Result<MediaAttachment, Error> {
+        let url: &Url = self.download_url()?;
+        let mut attachment: MediaAttachment = MediaAttachment::new(url.clone());
+
+        // Already checked by `download_url`
+        if let Some(nip94_event) = &self.nip94_event {
+            for tag in nip94_event.tags.iter() {
+                match tag.as_standardized() {
+                    Some(TagStandard::MimeType(mime_type)) => {
+                        attachment.mime_type = Some(mime_type.clone())
+                    }
+                    Some(TagStandard::Sha256(hash)) => attachment.hash = Some(*hash),
+                    Some(TagStandard::Size(size)) => attachment.size = Some(*size),
+                    Some(TagStandard::Dim(dim)) => attachment.dim = Some(*dim),
+                    Some(TagStandard::Magnet(magnet)) => attachment.magnet = Some(magnet.clone()),
+                    Some(TagStandard::Blurhash(blurhash)) => {
+                        attachment.blurhash = Some(blurhash.clone())
+                    }
+                    Some(TagStandard::Alt(alt)) => attachment.alt = Some(alt.clone()),
+                    _ => {}
+                }
+            }
+        }
+
+        Ok(attachment)
+    }
 }
 
 impl JsonUtil for UploadResponse {
@@ -185,7 +218,10 @@ pub fn get_server_config_url(server_url: &Url) -> Result<Url, Error> {
 
 #[cfg(test)]
 mod tests {
+    use core::str::FromStr;
+
     use super::*;
+    use crate::ImageDimensions;
 
     #[test]
     fn test_get_server_config_url() {
@@ -238,4 +274,36 @@ mod tests {
             assert_eq!(msg, "File too large");
         }
     }
+
+    #[test]
+    fn test_upload_response_media_attachment() {
+        let json = r#"{
+            "status": "success",
+            "message": "Upload successful",
+            "nip94_event": {
+                "tags": [
+                    ["url", "https://nostr.media/file123.png"],
+                    ["ox", "1aea8e98e0e5d969b7124f553b88dfae47d1f00472ea8c0dbf4ac4577d39ef02"],
+                    ["x", "99a95fcb4b7a2591ad32467032c52a62d90a204d3b176bc2459ad7427a3f2b89"],
+                    ["m", "image/png"],
+                    ["dim", "800x600"]
+                ]
+            }
+        }"#;
+
+        let response = UploadResponse::from_json(json).unwrap();
+        let attachment = response.media_attachment().unwrap();
+        assert_eq!(
+            attachment,
+            MediaAttachment::new(Url::parse("https://nostr.media/file123.png").unwrap())
+                .hash(
+                    Sha256Hash::from_str(
+                        "99a95fcb4b7a2591ad32467032c52a62d90a204d3b176bc2459ad7427a3f2b89"
+                    )
+                    .unwrap()
+                )
+                .mime_type("image/png")
+                .dimensions(ImageDimensions::new(800, 600))
+        );
+    }
 }
@@ -30,6 +30,8 @@ pub use crate::message::{self, *};
 // NIPs
 pub use crate::nips::nip01::{self, *};
 pub use crate::nips::nip02::{self, *};
+#[cfg(feature = "nip03")]
+pub use crate::nips::nip03::{self, *};
 #[cfg(feature = "nip04")]
 pub use crate::nips::nip04;
 pub use crate::nips::nip05::{self, *};
@@ -45,6 +47,7 @@ pub use crate::nips::nip19::{self, *};
 pub use crate::nips::nip21::{self, *};
 pub use crate::nips::nip22::{self, *};
 pub use crate::nips::nip25::{self, *};
+pub use crate::nips::nip28::{self, *};
 pub use crate::nips::nip34::{self, *};
 pub use crate::nips::nip35::{self, *};
 pub use crate::nips::nip38::{self, *};
@@ -52,6 +55,7 @@ pub use crate::nips::nip39::{self, *};
 pub use crate::nips::nip42::{self, *};
 #[cfg(feature = "nip44")]
 pub use crate::nips::nip44::{self, *};
+pub use crate::nips::nip45::{self, *};
 #[cfg(all(feature = "std", feature = "nip46"))]
 pub use crate::nips::nip46::{self, *};
 #[cfg(feature = "nip47")]
@@ -59,8 +63,10 @@ pub use crate::nips::nip47::{self, *};
 pub use crate::nips::nip48::{self, *};
 #[cfg(feature = "nip49")]
 pub use crate::nips::nip49::{self, *};
+pub use crate::nips::nip50::{self, *};
 pub use crate::nips::nip51::{self, *};
 pub use crate::nips::nip53::{self, *};
+pub use crate::nips::nip54::{self, *};
 pub use crate::nips::nip56::{self, *};
 #[cfg(feature = "nip57")]
 pub use crate::nips::nip57::{self, *};
@@ -69,9 +75,18 @@ pub use crate::nips::nip58;
 pub use crate::nips::nip59::{self, *};
 pub use crate::nips::nip62::{self, *};
 pub use crate::nips::nip65::{self, *};
+pub use crate::nips::nip68::{self, *};
+pub use crate::nips::nip69::{self, *};
+pub use crate::nips::nip71::{self, *};
+pub use crate::nips::nip72::{self, *};
 pub use crate::nips::nip73::{self, *};
+pub use crate::nips::nip75::{self, *};
+pub use crate::nips::nip78::{self, *};
+pub use crate::nips::nip84::{self, *};
 pub use crate::nips::nip88::{self, *};
+pub use crate::nips::nip89::{self, *};
 pub use crate::nips::nip90::{self, *};
+pub use crate::nips::nip92::{self, *};
 pub use crate::nips::nip94::{self, *};
 #[cfg(all(feature = "std", feature = "nip96"))]
 pub use crate::nips::nip96::{self, *};
@@ -135,6 +135,113 @@ pub trait NostrDatabaseExt: NostrDatabase {
             Ok(map)
         })
     }
+
+    /// Get the wiki articles about a topic, ranked for the reader
+    ///
+    /// The topic is normalized and the articles are ranked by the reader's contact list and reactions
+    /// (see [`nip54::rank_articles`]). The reader counts as a contact.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/54.md>
+    fn wiki_articles<'a>(
+        &'a self,
+        reader: PublicKey,
+        topic: &'a str,
+    ) -> BoxedFuture<'a, Result<Vec<Event>, DatabaseError>> {
+        Box::pin(async move {
+            // Query articles
+            let topic: String = nip54::normalize_topic(topic);
+            let filter: Filter = Filter::new()
+                .kind(Kind::WikiArticle)
+                .identifier(topic.clone());
+            let articles: Events = self.query(filter).await?;
+
+            if articles.is_empty() {
+                return Ok(Vec::new());
+            }
+
+            // Query reactions, referencing the articles by event ID or by coordinate
+            let filter: Filter = Filter::new()
+                .kind(Kind::Reaction)
+                .events(articles.iter().map(|e| e.id));
+            let reactions: Events = self.query(filter).await?;
+
+            let coordinates: Vec<Coordinate> = articles
+                .iter()
+                .map(|e| Coordinate::new(Kind::WikiArticle, e.pubkey).identifier(topic.clone()))
+                .collect();
+            let filter: Filter = Filter::new()
+                .kind(Kind::Reaction)
+                .coordinates(coordinates.iter());
+            let reactions: Events = reactions.merge(self.query(filter).await?);
+
+            let mut contacts: BTreeSet<PublicKey> = self
+                .contacts_public_keys(reader)
+                .await?
+                .into_iter()
+                .collect();
+            contacts.insert(reader);
+
+            Ok(
+                nip54::rank_articles(articles.iter(), &contacts, reactions.iter())
+                    .into_iter()
+                    .cloned()
+                    .collect(),
+            )
+        })
+    }
 }
 
 impl<T: NostrDatabase + ?Sized> NostrDatabaseExt for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{MemoryDatabase, MemoryDatabaseOptions};
+
+    #[tokio::test]
+    async fn test_wiki_articles() {
+        let database = MemoryDatabase::with_opts(MemoryDatabaseOptions {
+            events: true,
+            max_events: None,
+        });
+
+        let reader = Keys::generate();
+        let voter = Keys::generate();
+        let alice = Keys::generate();
+        let bob = Keys::generate();
+        let carol = Keys::generate();
+
+        let article = |keys: &Keys, created_at: u64| {
+            EventBuilder::wiki_article(WikiArticle::new("Nostr", "Nostr"))
+                .custom_created_at(Timestamp::from(created_at))
+                .sign_with_keys(keys)
+                .unwrap()
+        };
+        let by_alice = article(&alice, 10);
+        let by_bob = article(&bob, 20);
+        let by_carol = article(&carol, 30);
+
+        // Reaction of the reader by event ID
+        let by_id = EventBuilder::new(Kind::Reaction, "+")
+            .tag(Tag::event(by_alice.id))
+            .sign_with_keys(&reader)
+            .unwrap();
+
+        // Reaction of a stranger by coordinate
+        let coordinate = Coordinate::new(Kind::WikiArticle, bob.public_key()).identifier("nostr");
+        let by_coordinate = EventBuilder::new(Kind::Reaction, "+")
+            .tag(Tag::coordinate(coordinate, None))
+            .sign_with_keys(&voter)
+            .unwrap();
+
+        for event in [&by_alice, &by_bob, &by_carol, &by_id, &by_coordinate] {
+            database.save_event(event).await.unwrap();
+        }
+
+        let ranked = database
+            .wiki_articles(reader.public_key(), "nostr")
+            .await
+            .unwrap();
+        assert_eq!(ranked, vec![by_alice, by_bob, by_carol]);
+    }
+}
@@ -158,6 +158,8 @@ struct InternalDatabaseHelper {
     param_replaceable_index: HashMap<(Kind, PublicKey, String), DatabaseEvent>,
     deleted_ids: HashSet<EventId>,
     deleted_coordinates: HashMap<Coordinate, Timestamp>,
+    /// Expiration index (NIP-40)
+    expirations: BTreeSet<(Timestamp, EventId)>,
 }
 
 impl InternalDatabaseHelper {
@@ -330,6 +332,11 @@ impl InternalDatabaseHelper {
 
             if inserted {
                 self.ids.insert(e.id, e.clone());
+
+                if let Some(expiration) = e.tags.expiration() {
+                    self.expirations.insert((*expiration, e.id));
+                }
+
                 self.author_index
                     .entry(author)
                     .or_default()
@@ -370,6 +377,10 @@ impl InternalDatabaseHelper {
             if let Some(ev) = self.ids.remove(id) {
                 self.events.remove(&ev);
 
+                if let Some(expiration) = ev.tags.expiration() {
+                    self.expirations.remove(&(*expiration, ev.id));
+                }
+
                 if let Some(set) = self.author_index.get_mut(&ev.pubkey) {
                     set.remove(&ev);
                 }
@@ -395,6 +406,10 @@ impl InternalDatabaseHelper {
     fn discard_event(&mut self, ev: DatabaseEvent) {
         self.ids.remove(&ev.id);
 
+        if let Some(expiration) = ev.tags.expiration() {
+            self.expirations.remove(&(*expiration, ev.id));
+        }
+
         if let Some(set) = self.author_index.get_mut(&ev.pubkey) {
             set.remove(&ev);
         }
@@ -411,6 +426,44 @@ impl InternalDatabaseHelper {
         }
     }
 
+    /// Remove the events expired at the `now` timestamp
+    ///
+    /// Unlike the deleted events, the IDs aren't tracked.
+    pub fn purge_expired(&mut self, now: &Timestamp) -> HashSet<EventId> {
+        let mut expired: HashSet<EventId> = HashSet::new();
+
+        // The index is sorted by expiration
+        while let Some((expiration, id)) = self.expirations.first().copied() {
+            if expiration >= *now {
+                break;
+            }
+
+            self.expirations.remove(&(expiration, id));
+
+            if let Some(ev) = self.ids.get(&id).cloned() {
+                self.events.remove(&ev);
+                self.discard_event(ev);
+                expired.insert(id);
+            }
+        }
+
+        expired
+    }
+
+    /// Get the IDs of the events expired at the `now` timestamp, not purged yet
+    fn expired_ids(&self, now: &Timestamp) -> HashSet<EventId> {
+        self.expirations
+            .iter()
+            .take_while(|(expiration, _)| expiration < now)
+            .map(|(_, id)| *id)
+            .collect()
+    }
+
+    #[cfg(test)]
+    fn has_expirations(&self) -> bool {
+        !self.expirations.is_empty()
+    }
+
     /// Import [Event]
     ///
     /// **This method assume that [`Event`] was already verified**
@@ -545,7 +598,10 @@ impl InternalDatabaseHelper {
     }
 
     fn internal_query(&self, filter: Filter) -> InternalQueryResult {
-        if filter.is_empty() {
+        // Expired events (NIP-40) not purged yet
+        let expired: HashSet<EventId> = self.expired_ids(&Timestamp::now());
+
+        if filter.is_empty() && expired.is_empty() {
             return InternalQueryResult::All;
         }
 
@@ -569,6 +625,7 @@ impl InternalDatabaseHelper {
             }
             QueryPattern::Generic(filter) => Box::new(self.internal_generic_query(*filter)),
         };
+        let evs = evs.filter(|ev| !expired.contains(&ev.id));
 
         if let Some(limit) = limit {
             matching_ids.extend(evs.take(limit))
@@ -718,6 +775,18 @@ impl DatabaseHelper {
         inner.index_event(event)
     }
 
+    /// Remove the expired events (NIP-40)
+    ///
+    /// The expired events are not removed automatically:
+    /// this must be called periodically and the returned IDs must be removed also from the underlying storage, if any.
+    ///
+    /// Return the IDs of the removed events.
+    pub async fn purge_expired(&self) -> HashSet<EventId> {
+        let mut inner = self.inner.write().await;
+        let now: Timestamp = Timestamp::now();
+        inner.purge_expired(&now)
+    }
+
     /// Get [Event] by ID
     pub async fn event_by_id(&self, id: &EventId) -> Option<Event> {
         let inner = self.inner.read().await;
@@ -792,7 +861,9 @@ impl DatabaseHelper {
 
 #[cfg(test)]
 mod tests {
-    use nostr::{FromBech32, JsonUtil, Keys, SecretKey};
+    use std::time::Duration;
+
+    use nostr::{EventBuilder, FromBech32, JsonUtil, Keys, SecretKey, Tag};
 
     use super::*;
 
@@ -1019,4 +1090,69 @@ mod tests {
             vec![ev]
         );
     }
+
+    #[test]
+    fn test_purge_expired() {
+        let keys = Keys::generate();
+        let now = Timestamp::now();
+
+        let mut helper = InternalDatabaseHelper::default();
+
+        let expiring = EventBuilder::text_note("Expiring")
+            .tag(Tag::expiration(now + Duration::from_secs(10)))
+            .sign_with_keys(&keys)
+            .unwrap();
+        let persistent = EventBuilder::text_note("Persistent")
+            .sign_with_keys(&keys)
+            .unwrap();
+
+        assert!(helper.index_event(&expiring).status.is_success());
+        assert!(helper.index_event(&persistent).status.is_success());
+
+        // Not expired yet
+        assert!(helper.purge_expired(&now).is_empty());
+        assert_eq!(helper.count(Filter::new()), 2);
+
+        // Expired
+        let purged = helper.purge_expired(&(now + Duration::from_secs(20)));
+        assert_eq!(purged.len(), 1);
+        assert!(purged.contains(&expiring.id));
+        assert!(!helper.has_event(&expiring.id));
+        assert!(helper.has_event(&persistent.id));
+        assert!(!helper.has_event_id_been_deleted(&expiring.id));
+        assert!(!helper.has_expirations());
+    }
+
+    #[test]
+    fn test_query_skips_expired_not_purged() {
+        let keys = Keys::generate();
+        let now = Timestamp::now();
+
+        let mut helper = InternalDatabaseHelper::default();
+
+        let persistent = EventBuilder::text_note("Persistent")
+            .custom_created_at(now - Duration::from_secs(10))
+            .sign_with_keys(&keys)
+            .unwrap();
+        let expired = EventBuilder::text_note("Expired")
+            .tag(Tag::expiration(now - Duration::from_secs(1)))
+            .sign_with_keys(&keys)
+            .unwrap();
+
+        assert!(helper.index_event(&persistent).status.is_success());
+        // Indexed before expiring, not purged yet
+        let before = now - Duration::from_secs(5);
+        assert!(helper
+            .internal_index_event(&expired, &before)
+            .status
+            .is_success());
+
+        // The expired event is excluded before applying the limit
+        let events: Vec<&Event> = helper
+            .query(Filter::new().kind(Kind::TextNote).limit(1))
+            .collect();
+        assert_eq!(events, vec![&persistent]);
+        assert_eq!(helper.count(Filter::new()), 1);
+        assert_eq!(helper.count(Filter::new().id(expired.id)), 0);
+    }
 }
@@ -5,8 +5,11 @@
 //! Memory (RAM) Storage backend for Nostr apps
 
 use std::num::NonZeroUsize;
-use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Weak};
+use std::time::Duration;
 
+use async_utility::{task, time};
 use lru::LruCache;
 use nostr::prelude::*;
 use tokio::sync::RwLock;
@@ -17,6 +20,8 @@ use crate::{
 };
 
 const MAX_EVENTS: usize = 35_000;
+/// Interval between the purges of the expired events
+const EXPIRATION_PURGE_INTERVAL: Duration = Duration::from_secs(60);
 
 /// Database options
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -52,13 +57,15 @@ enum InnerMemoryDatabase {
     /// Just an event ID tracker
     Tracker(Arc<RwLock<LruCache<EventId, ()>>>),
     /// A full in-memory events store
-    Full(DatabaseHelper),
+    Full(Arc<DatabaseHelper>),
 }
 
 /// Memory Database (RAM)
 #[derive(Debug, Clone)]
 pub struct MemoryDatabase {
     inner: InnerMemoryDatabase,
+    /// Whether the expiration purge task has been spawned
+    purge_task: Arc<AtomicBool>,
 }
 
 impl Default for MemoryDatabase {
@@ -86,7 +93,7 @@ impl MemoryDatabase {
                 Some(max) => DatabaseHelper::bounded(max),
                 None => DatabaseHelper::unbounded(),
             };
-            InnerMemoryDatabase::Full(helper)
+            InnerMemoryDatabase::Full(Arc::new(helper))
         } else {
             let cache: LruCache<EventId, ()> = match opts.max_events {
                 Some(max) if max > 0 => {
@@ -99,7 +106,35 @@ impl MemoryDatabase {
             InnerMemoryDatabase::Tracker(Arc::new(RwLock::new(cache)))
         };
 
-        Self { inner }
+        Self {
+            inner,
+            purge_task: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Spawn the task that periodically removes the expired events (NIP-40)
+    ///
+    /// The task is spawned only once and stops when the database is dropped.
+    fn spawn_expiration_purge_task(&self, helper: &Arc<DatabaseHelper>) {
+        // Check if already spawned
+        if self.purge_task.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        let helper: Weak<DatabaseHelper> = Arc::downgrade(helper);
+
+        task::spawn(async move {
+            loop {
+                time::sleep(EXPIRATION_PURGE_INTERVAL).await;
+
+                // Stop if the database has been dropped
+                let Some(helper) = helper.upgrade() else {
+                    break;
+                };
+
+                helper.purge_expired().await;
+            }
+        });
     }
 }
 
@@ -123,6 +158,11 @@ impl NostrDatabase for MemoryDatabase {
                 }
                 InnerMemoryDatabase::Full(helper) => {
                     let DatabaseEventResult { status, .. } = helper.index_event(event).await;
+
+                    if status.is_success() && event.tags.expiration().is_some() {
+                        self.spawn_expiration_purge_task(helper);
+                    }
+
                     Ok(status)
                 }
             }
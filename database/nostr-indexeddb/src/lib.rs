@@ -226,7 +226,33 @@ impl WebDatabase {
         Ok(())
     }
 
+    /// Remove the expired events (NIP-40) from the indexes and the store
+    async fn purge_expired(&self) -> Result<(), IndexedDBError> {
+        let expired: HashSet<EventId> = self.helper.purge_expired().await;
+
+        if expired.is_empty() {
+            return Ok(());
+        }
+
+        let tx = self
+            .db
+            .transaction_on_one_with_mode(EVENTS_CF, IdbTransactionMode::Readwrite)?;
+        let store = tx.object_store(EVENTS_CF)?;
+
+        for event_id in expired.into_iter() {
+            let key = JsValue::from(event_id.to_hex());
+            store.delete(&key)?;
+        }
+
+        tx.await.into_result()?;
+
+        Ok(())
+    }
+
     async fn _save_event(&self, event: &Event) -> Result<SaveEventStatus, IndexedDBError> {
+        // Remove the expired events, before indexing the new one
+        self.purge_expired().await?;
+
         // Index event
         let DatabaseEventResult { status, to_discard } = self.helper.index_event(event).await;
 
@@ -787,6 +787,174 @@ mod tests {
 
         let events = db.query(Filter::new().search("hola")).await.unwrap();
         assert_eq!(events.len(), 0);
+
+        // Extensions are ignored
+        let events = db
+            .query(Filter::new().search("text note include:spam"))
+            .await
+            .unwrap();
+        assert_eq!(events.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_full_text_search_ranking() {
+        let db = TempDatabase::new();
+
+        let now = Timestamp::now();
+
+        let (_, relevant) = db
+            .add_event(
+                EventBuilder::text_note("Nostr relay: a nostr relay for nostr")
+                    .custom_created_at(now - Duration::from_secs(100)),
+            )
+            .await;
+        let (_, other) = db
+            .add_event(
+                EventBuilder::text_note("Just a relay, also for nostr").custom_created_at(now),
+            )
+            .await;
+        db.add_event(EventBuilder::text_note("Only nostr here"))
+            .await;
+        db.add_event(EventBuilder::text_note("Bitcoin")).await;
+
+        // The most relevant event is returned first, even if older
+        let events = db
+            .query(Filter::new().search("nostr relay").limit(1))
+            .await
+            .unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events.first().unwrap().id, relevant.id);
+
+        let events = db.query(Filter::new().search("nostr")).await.unwrap();
+        assert_eq!(events.len(), 3);
+
+        // Search with other conditions
+        let events = db
+            .query(Filter::new().search("nostr relay").since(now))
+            .await
+            .unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events.first().unwrap().id, other.id);
+
+        // Deleted events are removed from the index
+        db.delete(Filter::new().id(relevant.id)).await.unwrap();
+        let events = db.query(Filter::new().search("nostr relay")).await.unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events.first().unwrap().id, other.id);
+    }
+
+    #[tokio::test]
+    async fn test_full_text_search_same_as_memory() {
+        let db = TempDatabase::new();
+        let memory = MemoryDatabase::with_opts(MemoryDatabaseOptions {
+            events: true,
+            max_events: None,
+        });
+
+        let keys = Keys::generate();
+        for content in [
+            "Kishimoto wrote nostr-sdk",
+            "Ki is short",
+            "Ünïcode nostr",
+            "Bitcoin",
+        ] {
+            let event = EventBuilder::text_note(content)
+                .sign_with_keys(&keys)
+                .unwrap();
+            db.save_event(&event).await.unwrap();
+            memory.save_event(&event).await.unwrap();
+        }
+
+        for query in [
+            "kishi",
+            "KISHIMOTO",
+            "imoto",
+            "ki",
+            "kis",
+            "no",
+            "nostr sdk",
+            "ünï",
+            "unicode",
+        ] {
+            let filter = Filter::new().search(query);
+            let lmdb_ids: HashSet<EventId> = db
+                .query(filter.clone())
+                .await
+                .unwrap()
+                .iter()
+                .map(|e| e.id)
+                .collect();
+            let memory_ids: HashSet<EventId> = memory
+                .query(filter.clone())
+                .await
+                .unwrap()
+                .iter()
+                .map(|e| e.id)
+                .collect();
+            assert_eq!(lmdb_ids, memory_ids, "query: {query}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_full_text_search_prefix_terms() {
+        let db = TempDatabase::new();
+
+        let long_token: String = "a".repeat(100);
+
+        let (_, note) = db
+            .add_event(EventBuilder::text_note("Kishimoto wrote nostr-sdk"))
+            .await;
+        let (_, long) = db
+            .add_event(EventBuilder::text_note(format!("{long_token}suffix")))
+            .await;
+        let (_, other) = db
+            .add_event(EventBuilder::text_note(format!("{long_token}other")))
+            .await;
+
+        // Terms match also as prefix of longer tokens
+        let events = db.query(Filter::new().search("kishi sdk")).await.unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events.first().unwrap().id, note.id);
+
+        let events = db.query(Filter::new().search("NOS")).await.unwrap();
+        assert_eq!(events.len(), 1);
+
+        // Not as part of the tokens
+        let events = db.query(Filter::new().search("imoto")).await.unwrap();
+        assert_eq!(events.len(), 0);
+
+        // Short terms match only the equal tokens
+        let events = db.query(Filter::new().search("no")).await.unwrap();
+        assert_eq!(events.len(), 0);
+
+        // Same results when the search is not the first filter condition
+        let filter = Filter::new().author(note.pubkey).search("kishi sdk");
+        let events = db.query(filter).await.unwrap();
+        assert_eq!(events.len(), 1);
+        let filter = Filter::new().author(note.pubkey).search("imoto");
+        let events = db.query(filter).await.unwrap();
+        assert_eq!(events.len(), 0);
+
+        // Long tokens are truncated in the index, but the events are checked again
+        let events = db.query(Filter::new().search("aaaa")).await.unwrap();
+        assert_eq!(events.len(), 2);
+
+        let events = db
+            .query(Filter::new().search(format!("{long_token}s")))
+            .await
+            .unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events.first().unwrap().id, long.id);
+
+        let events = db
+            .query(Filter::new().search(format!("{long_token}other")))
+            .await
+            .unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events.first().unwrap().id, other.id);
+
+        let events = db.query(Filter::new().search("suffix")).await.unwrap();
+        assert_eq!(events.len(), 0);
     }
 
     #[tokio::test]
@@ -9,12 +9,16 @@
 //!
 //! The ingester provides automatic batching of operations for optimal LMDB write performance.
 //! Events are collected from a channel and committed in batches using a single transaction.
+//!
+//! The ingester also periodically removes the expired events (NIP-40)
+//! and, between the batches, indexes the events stored before the full-text search and expiration indexes were introduced.
 
+use std::time::{Duration, Instant};
 use std::{iter, thread};
 
-use flume::{Receiver, Sender};
+use flume::{Receiver, RecvTimeoutError, Sender};
 use heed::RwTxn;
-use nostr::{Event, Filter};
+use nostr::{Event, Filter, Timestamp};
 use nostr_database::{FlatBufferBuilder, SaveEventStatus};
 use tokio::sync::oneshot;
 
@@ -27,6 +31,9 @@ use super::lmdb::Lmdb;
 /// Large events (with many tags or large content) may still trigger reallocation.
 const FLATBUFFER_CAPACITY: usize = 70_000;
 
+/// Interval between the purges of the expired events
+const EXPIRATION_PURGE_INTERVAL: Duration = Duration::from_secs(60);
+
 enum OperationResult {
     Save {
         result: Result<SaveEventStatus, Error>,
@@ -154,35 +161,83 @@ impl Ingester {
 
         let mut fbb: FlatBufferBuilder = FlatBufferBuilder::with_capacity(FLATBUFFER_CAPACITY);
         let mut results: Vec<OperationResult> = Vec::new();
+        let mut last_purge: Instant = Instant::now();
+        let mut backfilling: bool = true;
 
         loop {
-            // Recv the first item
-            let first_item: IngesterItem = match self.rx.recv() {
-                Ok(item) => item,
+            // Recv the first item, waiting at most until the next purge (not waiting while backfilling)
+            let timeout: Duration = if backfilling {
+                Duration::ZERO
+            } else {
+                EXPIRATION_PURGE_INTERVAL.saturating_sub(last_purge.elapsed())
+            };
+            match self.rx.recv_timeout(timeout) {
+                Ok(first_item) => {
+                    // Drain the rest of the channel into a batch
+                    let batch = iter::once(first_item).chain(self.rx.drain());
+
+                    // Process batch, reusing the "results" vector
+                    self.process_batch_in_transaction(batch, &mut fbb, &mut results);
+
+                    tracing::debug!("Processed batch of {} operations", results.len());
+
+                    // Drain the results and send them back through channels
+                    for result in results.drain(..) {
+                        result.send();
+                    }
+                }
+                // Time to purge
+                Err(RecvTimeoutError::Timeout) => {}
                 // All senders have been dropped, exit the loop
-                Err(flume::RecvError::Disconnected) => {
+                Err(RecvTimeoutError::Disconnected) => {
                     tracing::debug!("Ingester channel disconnected, exiting.");
                     break;
                 }
-            };
-
-            // Drain the rest of the channel into a batch
-            let batch = iter::once(first_item).chain(self.rx.drain());
-
-            // Process batch, reusing the "results" vector
-            self.process_batch_in_transaction(batch, &mut fbb, &mut results);
+            }
 
-            tracing::debug!("Processed batch of {} operations", results.len());
+            // Index the next batch of the events stored before the indexes were introduced
+            if backfilling {
+                backfilling = match self.db.backfill_next_batch() {
+                    Ok(more) => more,
+                    Err(e) => {
+                        tracing::error!(error = %e, "Failed to backfill the indexes");
+                        false
+                    }
+                };
+            }
 
-            // Drain the results and send them back through channels
-            for result in results.drain(..) {
-                result.send();
+            if last_purge.elapsed() >= EXPIRATION_PURGE_INTERVAL {
+                self.purge_expired();
+                last_purge = Instant::now();
             }
         }
 
         tracing::debug!("Ingester thread exited");
     }
 
+    fn purge_expired(&self) {
+        let mut txn: RwTxn = match self.db.write_txn() {
+            Ok(txn) => txn,
+            Err(e) => {
+                tracing::error!(error = %e, "Failed to create write transaction");
+                return;
+            }
+        };
+
+        let now: Timestamp = Timestamp::now();
+
+        match self.db.purge_expired(&mut txn, &now) {
+            Ok(purged) => match txn.commit() {
+                Ok(()) => tracing::debug!("Purged {purged} expired events"),
+                Err(e) => tracing::error!(error = %e, "Failed to commit purge transaction"),
+            },
+            Err(e) => {
+                tracing::error!(error = %e, "Failed to purge expired events");
+                txn.abort();
+            }
+        }
+    }
+
     fn process_batch_in_transaction<I>(
         &self,
         batch: I,
@@ -300,7 +355,7 @@ mod tests {
     use std::sync::Arc;
 
     use futures::future::join_all;
-    use nostr::{EventBuilder, Keys, Kind};
+    use nostr::{EventBuilder, Keys, Kind, Tag};
     use tempfile::TempDir;
 
     use super::*;
@@ -408,4 +463,83 @@ mod tests {
         // We had 10 events, deleted 5, added 2
         assert_eq!(remaining, 7);
     }
+
+    #[tokio::test]
+    async fn test_purge_expired() {
+        let (store, _temp_dir) = setup_test_store().await;
+        let keys = Keys::generate();
+        let now = Timestamp::now();
+
+        // Already expired
+        let expired = EventBuilder::text_note("Expired")
+            .tag(Tag::expiration(now - Duration::from_secs(10)))
+            .sign_with_keys(&keys)
+            .unwrap();
+        assert!(matches!(
+            store.save_event(&expired).await,
+            Ok(SaveEventStatus::Rejected(
+                nostr_database::RejectedReason::Expired
+            ))
+        ));
+
+        let expiring = EventBuilder::text_note("Expiring")
+            .tag(Tag::expiration(now + Duration::from_secs(10)))
+            .sign_with_keys(&keys)
+            .unwrap();
+        let persistent = EventBuilder::text_note("Persistent")
+            .sign_with_keys(&keys)
+            .unwrap();
+        store.save_event(&expiring).await.unwrap();
+        store.save_event(&persistent).await.unwrap();
+
+        // Nothing to purge yet
+        let mut txn = store.db.write_txn().unwrap();
+        assert_eq!(store.db.purge_expired(&mut txn, &now).unwrap(), 0);
+        txn.commit().unwrap();
+
+        // Purge
+        let mut txn = store.db.write_txn().unwrap();
+        let later = now + Duration::from_secs(20);
+        assert_eq!(store.db.purge_expired(&mut txn, &later).unwrap(), 1);
+        assert_eq!(store.db.purge_expired(&mut txn, &later).unwrap(), 0);
+        txn.commit().unwrap();
+
+        assert!(!store.has_event(&expiring.id).unwrap());
+        assert!(store.has_event(&persistent.id).unwrap());
+
+        // Expired events aren't marked as deleted
+        assert!(!store.event_is_deleted(&expiring.id).unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_query_skips_expired_not_purged() {
+        let (store, _temp_dir) = setup_test_store().await;
+        let keys = Keys::generate();
+        let now = Timestamp::now();
+
+        let persistent = EventBuilder::text_note("Persistent")
+            .custom_created_at(now - Duration::from_secs(10))
+            .sign_with_keys(&keys)
+            .unwrap();
+        store.save_event(&persistent).await.unwrap();
+
+        // Expired, but not purged yet: store it bypassing the checks
+        let expired = EventBuilder::text_note("Expired")
+            .tag(Tag::expiration(now - Duration::from_secs(1)))
+            .sign_with_keys(&keys)
+            .unwrap();
+        let mut fbb = FlatBufferBuilder::new();
+        let mut txn = store.db.write_txn().unwrap();
+        store.db.store(&mut txn, &mut fbb, &expired).unwrap();
+        txn.commit().unwrap();
+
+        // The expired event is excluded before applying the limit
+        let events = store
+            .query(Filter::new().kind(Kind::TextNote).limit(1))
+            .unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events.first().unwrap().id, persistent.id);
+        assert_eq!(store.count(Filter::new()).unwrap(), 1);
+        assert_eq!(store.count(Filter::new().id(expired.id)).unwrap(), 0);
+    }
 }
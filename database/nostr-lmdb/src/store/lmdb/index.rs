@@ -11,6 +11,9 @@ use nostr::{EventId, PublicKey, SingleLetterTag, Timestamp};
 const CREATED_AT_BE: usize = 8;
 const KIND_BE: usize = 2;
 const TAG_VALUE_PAD_LEN: usize = 182;
+/// Fixed len of the token in the full-text search index keys: `token(63)` + `truncated(1)`
+pub const TOKEN_LEN: usize = 64;
+const TOKEN_PAD_LEN: usize = TOKEN_LEN - 1;
 
 /// Reverse created_at and convert `u64` to big-endian byte order
 #[inline]
@@ -33,6 +36,46 @@ fn extend_key_with_tag_value(key: &mut Vec<u8>, len: usize, tag_value: &str) {
     }
 }
 
+/// Encode the search `token` (fixed len of 64 bytes)
+///
+/// The token is truncated to 63 bytes (at char boundary) and padded with zeros.
+/// The last byte is set to `1` if the token has been truncated.
+pub fn make_fts_token(token: &str) -> [u8; TOKEN_LEN] {
+    let mut len: usize = cmp::min(token.len(), TOKEN_PAD_LEN);
+    while !token.is_char_boundary(len) {
+        len -= 1;
+    }
+
+    let mut encoded: [u8; TOKEN_LEN] = [0; TOKEN_LEN];
+    encoded[..len].copy_from_slice(&token.as_bytes()[..len]);
+    encoded[TOKEN_PAD_LEN] = u8::from(len < token.len());
+    encoded
+}
+
+/// Make the range of the full-text search index keys of the tokens starting with the search `term`
+///
+/// The `term` is truncated as the indexed tokens (see [`make_fts_token`]).
+/// Returns the start (included) and the end (excluded) keys: the end is `None` if unbounded.
+pub fn make_fts_prefix_range(term: &str) -> (Vec<u8>, Option<Vec<u8>>) {
+    let token: [u8; TOKEN_LEN] = make_fts_token(term);
+    let len: usize = token[..TOKEN_PAD_LEN]
+        .iter()
+        .position(|b| *b == 0)
+        .unwrap_or(TOKEN_PAD_LEN);
+    let start: Vec<u8> = token[..len].to_vec();
+
+    // Increment the last byte that can be incremented, removing the following ones
+    let mut end: Vec<u8> = start.clone();
+    while let Some(last) = end.pop() {
+        if last < u8::MAX {
+            end.push(last + 1);
+            return (start, Some(end));
+        }
+    }
+
+    (start, None)
+}
+
 /// Make CreatedAt + ID index key
 ///
 /// ## Structure
@@ -180,3 +223,54 @@ pub fn make_coordinate_index_key(coordinate: &CoordinateBorrow<'_>) -> Vec<u8> {
 
     key
 }
+
+/// Make full-text search index key (for looking up event by search `token`)
+///
+/// ## Structure
+///
+/// `token(64)` + `reverse_created_at(8)` + `event_id(32)`
+///
+/// Check [`make_fts_token`] for the token encoding.
+pub fn make_fts_index_key(
+    token: &[u8; TOKEN_LEN],
+    created_at: &Timestamp,
+    event_id: &[u8; EventId::LEN],
+) -> Vec<u8> {
+    let mut key: Vec<u8> = Vec::with_capacity(TOKEN_LEN + CREATED_AT_BE + EventId::LEN);
+    key.extend(token);
+    key.extend(reverse_and_conv_to_be64(created_at));
+    key.extend(event_id);
+    key
+}
+
+/// Make the key after all the full-text search index keys of the encoded `token`
+pub fn make_fts_token_end_key(token: &[u8; TOKEN_LEN]) -> Vec<u8> {
+    let mut key: Vec<u8> = Vec::with_capacity(TOKEN_LEN + CREATED_AT_BE + EventId::LEN);
+    key.extend(token);
+    key.extend(iter::repeat(u8::MAX).take(CREATED_AT_BE + EventId::LEN));
+    key
+}
+
+/// Extract the encoded token from a full-text search index key
+#[inline]
+pub fn fts_index_key_token(key: &[u8]) -> Option<&[u8; TOKEN_LEN]> {
+    key.get(..TOKEN_LEN)?.try_into().ok()
+}
+
+/// Extract the event ID from a full-text search index key
+#[inline]
+pub fn fts_index_key_event_id(key: &[u8]) -> Option<&[u8]> {
+    key.get(TOKEN_LEN + CREATED_AT_BE..)
+}
+
+/// Make expiration index key
+///
+/// ## Structure
+///
+/// `expiration(8)` + `event_id(32)`
+pub fn make_expiration_index_key(expiration: &Timestamp, event_id: &[u8; EventId::LEN]) -> Vec<u8> {
+    let mut key: Vec<u8> = Vec::with_capacity(CREATED_AT_BE + EventId::LEN);
+    key.extend(expiration.as_u64().to_be_bytes());
+    key.extend(event_id);
+    key
+}
@@ -3,14 +3,14 @@
 // Copyright (c) 2023-2025 Rust Nostr Developers
 // Distributed under the MIT software license
 
-use std::cmp::Ordering;
-use std::collections::BTreeSet;
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BTreeMap, BTreeSet, BinaryHeap, HashMap, HashSet};
 use std::iter;
 use std::ops::Bound;
 use std::path::Path;
 
 use heed::byteorder::NativeEndian;
-use heed::types::{Bytes, Unit, U64};
+use heed::types::{Bytes, Unit, U32, U64};
 use heed::{Database, Env, EnvFlags, EnvOpenOptions, RoRange, RoTxn, RwTxn};
 use nostr::prelude::*;
 use nostr_database::flatbuffers::FlatBufferDecodeBorrowed;
@@ -23,6 +23,7 @@ use super::types::DatabaseFilter;
 
 const EVENT_ID_ALL_ZEROS: [u8; 32] = [0; 32];
 const EVENT_ID_ALL_255: [u8; 32] = [255; 32];
+const BACKFILL_BATCH_SIZE: usize = 10_000;
 
 /// Information needed to delete an event from all indexes.
 ///
@@ -76,6 +77,8 @@ struct DeletionInfo {
     created_at: Timestamp,
     kind: u16,
     tags: Vec<(SingleLetterTag, String)>,
+    tokens: BTreeSet<[u8; index::TOKEN_LEN]>,
+    expiration: Option<Timestamp>,
 }
 
 impl From<&EventBorrow<'_>> for DeletionInfo {
@@ -91,10 +94,66 @@ impl From<&EventBorrow<'_>> for DeletionInfo {
                 .filter_map(|tag| tag.extract())
                 .map(|(name, value)| (name, value.to_string()))
                 .collect(),
+            tokens: nip50::tokenize(event.content)
+                .map(|token| index::make_fts_token(&token))
+                .collect(),
+            expiration: extract_expiration(event),
+        }
+    }
+}
+
+/// Index built from the already stored events
+#[derive(Debug, Clone, Copy)]
+enum BackfillIndex {
+    /// Full-text search index
+    Fts,
+    /// Expiration index
+    Expiration,
+}
+
+impl BackfillIndex {
+    fn as_key(&self) -> &'static [u8] {
+        match self {
+            Self::Fts => b"fts",
+            Self::Expiration => b"expiration",
         }
     }
 }
 
+/// Full-text search hit
+///
+/// Ordered by rank: higher score first, then by the event order (most recent first).
+#[derive(Debug, Clone, Copy)]
+struct SearchHit {
+    score: f64,
+    created_at: Timestamp,
+    id: [u8; 32],
+}
+
+impl PartialEq for SearchHit {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for SearchHit {}
+
+impl PartialOrd for SearchHit {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for SearchHit {
+    /// The greater is the better ranked
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.score
+            .total_cmp(&other.score)
+            .then_with(|| self.created_at.cmp(&other.created_at))
+            .then_with(|| other.id.cmp(&self.id))
+    }
+}
+
 #[derive(Debug, Clone)]
 pub(crate) struct Lmdb {
     /// LMDB env
@@ -117,6 +176,12 @@ pub(crate) struct Lmdb {
     deleted_ids: Database<Bytes, Unit>, // Event ID
     /// Deleted coordinates
     deleted_coordinates: Database<Bytes, U64<NativeEndian>>, // Coordinate, UNIX timestamp
+    /// Full-text search index
+    fts_index: Database<Bytes, U32<NativeEndian>>, // <Index>, Term frequency
+    /// Expiration + ID index
+    expiration_index: Database<Bytes, Unit>, // <Index>
+    /// Index backfill progress
+    backfill: Database<Bytes, Bytes>, // Index name, Last indexed key of the created_at index
 }
 
 impl Lmdb {
@@ -133,7 +198,7 @@ impl Lmdb {
         let env: Env = unsafe {
             EnvOpenOptions::new()
                 .flags(EnvFlags::NO_TLS)
-                .max_dbs(9 + additional_dbs)
+                .max_dbs(12 + additional_dbs)
                 .max_readers(max_readers)
                 .map_size(map_size)
                 .open(path)?
@@ -188,11 +253,31 @@ impl Lmdb {
             .name("deleted-coordinates")
             .create(&mut txn)?;
 
-        // Commit changes
-        txn.commit()?;
+        // Check if the full-text search and expiration indexes already exist, before creating them
+        let fts_index_exists: bool = env
+            .open_database::<Bytes, U32<NativeEndian>>(&txn, Some("fts"))?
+            .is_some();
+        let fts_index = env
+            .database_options()
+            .types::<Bytes, U32<NativeEndian>>()
+            .name("fts")
+            .create(&mut txn)?;
+        let expiration_index_exists: bool = env
+            .open_database::<Bytes, Unit>(&txn, Some("expiration"))?
+            .is_some();
+        let expiration_index = env
+            .database_options()
+            .types::<Bytes, Unit>()
+            .name("expiration")
+            .create(&mut txn)?;
+        let backfill = env
+            .database_options()
+            .types::<Bytes, Bytes>()
+            .name("backfill")
+            .create(&mut txn)?;
 
-        Ok(Self {
-            env,
+        let db = Self {
+            env: env.clone(),
             events,
             ci_index,
             tc_index,
@@ -202,7 +287,127 @@ impl Lmdb {
             ktc_index,
             deleted_ids,
             deleted_coordinates,
-        })
+            fts_index,
+            expiration_index,
+            backfill,
+        };
+
+        // Schedule the indexing of the events stored before the indexes were introduced
+        // (see `Lmdb::backfill_next_batch`)
+        let has_events: bool = !db.ci_index.is_empty(&txn)?;
+
+        if has_events && !fts_index_exists {
+            db.backfill
+                .put(&mut txn, BackfillIndex::Fts.as_key(), &[])?;
+        }
+
+        if has_events && !expiration_index_exists {
+            db.backfill
+                .put(&mut txn, BackfillIndex::Expiration.as_key(), &[])?;
+        }
+
+        // Commit changes
+        txn.commit()?;
+
+        Ok(db)
+    }
+
+    /// Index a batch of the events stored before the full-text search and expiration indexes were introduced
+    ///
+    /// Each batch is committed in its own transaction, together with the last indexed key,
+    /// so an interrupted backfill is resumed on the next open.
+    ///
+    /// Returns `false` if there was nothing to index.
+    pub(crate) fn backfill_next_batch(&self) -> Result<bool, Error> {
+        for target in [BackfillIndex::Fts, BackfillIndex::Expiration] {
+            let txn = self.read_txn()?;
+            let pending: bool = self.is_backfill_pending(&txn, target)?;
+            txn.commit()?;
+
+            if pending {
+                self.backfill_batch(target)?;
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+
+    /// Check if the events stored before the index was introduced are still being indexed
+    fn is_backfill_pending(&self, txn: &RoTxn, target: BackfillIndex) -> Result<bool, Error> {
+        Ok(self.backfill.get(txn, target.as_key())?.is_some())
+    }
+
+    /// Index a batch of events
+    fn backfill_batch(&self, target: BackfillIndex) -> Result<(), Error> {
+        let mut txn = self.env.write_txn()?;
+
+        // Last indexed key of the created_at index (empty if not started yet)
+        let last_key: Vec<u8> = match self.backfill.get(&txn, target.as_key())? {
+            Some(key) => key.to_vec(),
+            None => return Ok(()),
+        };
+
+        let range = if last_key.is_empty() {
+            (Bound::Unbounded, Bound::Unbounded)
+        } else {
+            (Bound::Excluded(last_key.as_slice()), Bound::Unbounded)
+        };
+
+        let mut fts_entries: Vec<(Vec<u8>, u32)> = Vec::new();
+        let mut expiration_entries: Vec<Vec<u8>> = Vec::new();
+        let mut next_key: Option<Vec<u8>> = None;
+        let mut count: usize = 0;
+
+        // NOTE: iterate the created_at index, since the events are stored in the unnamed database,
+        // which also contains the names of the other databases.
+        for result in self.ci_index.range(&txn, &range)? {
+            let (key, id) = result?;
+            let event = self.get_event_by_id(&txn, id)?.ok_or(Error::NotFound)?;
+
+            match target {
+                BackfillIndex::Fts => {
+                    for (token, frequency) in term_frequencies(event.content) {
+                        let key: Vec<u8> =
+                            index::make_fts_index_key(&token, &event.created_at, event.id);
+                        fts_entries.push((key, frequency));
+                    }
+                }
+                BackfillIndex::Expiration => {
+                    if let Some(expiration) = extract_expiration(&event) {
+                        expiration_entries
+                            .push(index::make_expiration_index_key(&expiration, event.id));
+                    }
+                }
+            }
+
+            count += 1;
+
+            if count >= BACKFILL_BATCH_SIZE {
+                next_key = Some(key.to_vec());
+                break;
+            }
+        }
+
+        for (key, frequency) in fts_entries {
+            self.fts_index.put(&mut txn, &key, &frequency)?;
+        }
+
+        for key in expiration_entries {
+            self.expiration_index.put(&mut txn, &key, &())?;
+        }
+
+        // Save the progress or, if there are no more events, mark the backfill as completed
+        match next_key {
+            Some(key) => self.backfill.put(&mut txn, target.as_key(), &key)?,
+            None => {
+                self.backfill.delete(&mut txn, target.as_key())?;
+            }
+        }
+
+        txn.commit()?;
+
+        Ok(())
     }
 
     /// Get a read transaction
@@ -288,6 +493,20 @@ impl Lmdb {
             }
         }
 
+        // Index by search token (with created_at and id)
+        for (token, frequency) in term_frequencies(&event.content) {
+            let fts_index_key: Vec<u8> =
+                index::make_fts_index_key(&token, &event.created_at, event.id.as_bytes());
+            self.fts_index.put(txn, &fts_index_key, &frequency)?;
+        }
+
+        // Index by expiration (with id)
+        if let Some(expiration) = event.tags.expiration() {
+            let expiration_index_key: Vec<u8> =
+                index::make_expiration_index_key(expiration, event.id.as_bytes());
+            self.expiration_index.put(txn, &expiration_index_key, &())?;
+        }
+
         Ok(())
     }
 
@@ -354,9 +573,74 @@ impl Lmdb {
             self.tc_index.delete(txn, &tc_index_key)?;
         }
 
+        // Delete search tokens
+        for token in info.tokens.iter() {
+            let fts_index_key: Vec<u8> =
+                index::make_fts_index_key(token, &info.created_at, &info.id);
+            self.fts_index.delete(txn, &fts_index_key)?;
+        }
+
+        // Delete from expiration index
+        if let Some(expiration) = &info.expiration {
+            let expiration_index_key: Vec<u8> =
+                index::make_expiration_index_key(expiration, &info.id);
+            self.expiration_index.delete(txn, &expiration_index_key)?;
+        }
+
         Ok(())
     }
 
+    /// Remove the events expired at the `now` timestamp (NIP-40)
+    ///
+    /// Return the number of removed events.
+    pub(crate) fn purge_expired(&self, txn: &mut RwTxn, now: &Timestamp) -> Result<usize, Error> {
+        // The index is sorted by expiration: get all the keys before `now`
+        let end_prefix: Vec<u8> = index::make_expiration_index_key(now, &EVENT_ID_ALL_ZEROS);
+        let range = (Bound::Unbounded, Bound::Excluded(end_prefix.as_slice()));
+
+        // Collect DeletionInfo for all events first to avoid iterator lifetime issues
+        let mut deletion_infos: Vec<DeletionInfo> = Vec::new();
+        let mut orphans: Vec<Vec<u8>> = Vec::new();
+
+        for result in self.expiration_index.range(txn, &range)? {
+            let (key, ()) = result?;
+            let id: &[u8] = &key[key.len() - EventId::LEN..];
+
+            match self.get_event_by_id(txn, id)? {
+                Some(event) => deletion_infos.push(DeletionInfo::from(&event)),
+                None => orphans.push(key.to_vec()),
+            }
+        }
+
+        // Now perform deletions
+        for info in deletion_infos.iter() {
+            self.remove(txn, info)?;
+        }
+
+        for key in orphans {
+            self.expiration_index.delete(txn, &key)?;
+        }
+
+        Ok(deletion_infos.len())
+    }
+
+    /// Get the IDs of the events expired at the `now` timestamp, not purged yet (NIP-40)
+    fn expired_ids(&self, txn: &RoTxn, now: &Timestamp) -> Result<HashSet<[u8; 32]>, Error> {
+        let end_prefix: Vec<u8> = index::make_expiration_index_key(now, &EVENT_ID_ALL_ZEROS);
+        let range = (Bound::Unbounded, Bound::Excluded(end_prefix.as_slice()));
+
+        let mut ids: HashSet<[u8; 32]> = HashSet::new();
+
+        for result in self.expiration_index.range(txn, &range)? {
+            let (key, ()) = result?;
+            let mut id: [u8; 32] = [0; 32];
+            id.copy_from_slice(&key[key.len() - EventId::LEN..]);
+            ids.insert(id);
+        }
+
+        Ok(ids)
+    }
+
     pub(crate) fn wipe(&self, txn: &mut RwTxn) -> Result<(), Error> {
         self.events.clear(txn)?;
         self.ci_index.clear(txn)?;
@@ -367,6 +651,9 @@ impl Lmdb {
         self.ktc_index.clear(txn)?;
         self.deleted_ids.clear(txn)?;
         self.deleted_coordinates.clear(txn)?;
+        self.fts_index.clear(txn)?;
+        self.expiration_index.clear(txn)?;
+        self.backfill.clear(txn)?;
         Ok(())
     }
 
@@ -386,6 +673,10 @@ impl Lmdb {
             return Ok(SaveEventStatus::Rejected(RejectedReason::Ephemeral));
         }
 
+        if event.is_expired() {
+            return Ok(SaveEventStatus::Rejected(RejectedReason::Expired));
+        }
+
         // Already exists
         if self.has_event(txn, event.id.as_bytes())? {
             return Ok(SaveEventStatus::Rejected(RejectedReason::Duplicate));
@@ -496,7 +787,10 @@ impl Lmdb {
         let since = filter.since.unwrap_or_else(Timestamp::min);
         let until = filter.until.unwrap_or_else(Timestamp::max);
 
-        let filter: DatabaseFilter = filter.into();
+        let mut filter: DatabaseFilter = filter.into();
+        filter.expired = self.expired_ids(txn, &Timestamp::now())?;
+
+        let fts_backfill_pending: bool = self.is_backfill_pending(txn, BackfillIndex::Fts)?;
 
         if !filter.ids.is_empty() {
             // Fetch by id
@@ -515,6 +809,17 @@ impl Lmdb {
                     }
                 }
             }
+        } else if let Some(query) = filter
+            .search
+            .as_ref()
+            .filter(|q| q.has_terms())
+            .filter(|_| !fts_backfill_pending)
+        {
+            // Full-text search: results are ordered by relevance.
+            // While the stored events are still being indexed, the other indexes are scanned instead.
+            let events: Vec<EventBorrow<'a>> =
+                self.search(txn, &filter, query.terms(), &since, &until, limit)?;
+            return Ok(Box::new(events.into_iter()));
         } else if !filter.authors.is_empty() && !filter.kinds.is_empty() {
             // We may bring since forward if we hit the limit without going back that
             // far, so we use a mutable since:
@@ -676,6 +981,96 @@ impl Lmdb {
         })
     }
 
+    /// Search events by terms, using the full-text search index
+    ///
+    /// The events must contain all the terms, also as prefix of a longer token (see [`Self::fts_postings`]).
+    /// The events are ranked by TF-IDF, the most recent first in case of equal score.
+    fn search<'a>(
+        &self,
+        txn: &'a RoTxn,
+        filter: &DatabaseFilter,
+        terms: &[String],
+        since: &Timestamp,
+        until: &Timestamp,
+        limit: Option<usize>,
+    ) -> Result<Vec<EventBorrow<'a>>, Error> {
+        let total: f64 = self.ci_index.len(txn)? as f64;
+
+        // Event ID, score
+        let mut candidates: Option<HashMap<[u8; 32], f64>> = None;
+
+        // Deduplicate terms
+        let terms: BTreeSet<&String> = terms.iter().collect();
+
+        for term in terms.into_iter() {
+            // Collect term frequencies
+            let postings: HashMap<[u8; 32], u32> = self.fts_postings(txn, term, since, until)?;
+
+            // Inverse document frequency
+            let idf: f64 = (1.0 + total / (postings.len() as f64 + 1.0)).ln();
+
+            let scores: HashMap<[u8; 32], f64> = match candidates {
+                // Keep only the events that contain also this term
+                Some(candidates) => candidates
+                    .into_iter()
+                    .filter_map(|(id, score)| {
+                        let frequency: u32 = *postings.get(&id)?;
+                        Some((id, score + term_score(frequency, idf)))
+                    })
+                    .collect(),
+                None => postings
+                    .into_iter()
+                    .map(|(id, frequency)| (id, term_score(frequency, idf)))
+                    .collect(),
+            };
+
+            // Stop early if no event contains all the terms
+            if scores.is_empty() {
+                return Ok(Vec::new());
+            }
+
+            candidates = Some(scores);
+        }
+
+        // Keep only the best hits: with a limit, the worst one is at the top of the heap and is popped when exceeding it
+        let mut ranked: BinaryHeap<Reverse<SearchHit>> = BinaryHeap::new();
+
+        for (id, score) in candidates.unwrap_or_default().into_iter() {
+            // Skip stale postings
+            let Some(event) = self.get_event_by_id(txn, &id)? else {
+                continue;
+            };
+
+            // Check against the rest of the filter
+            if !filter.match_event(&event) {
+                continue;
+            }
+
+            ranked.push(Reverse(SearchHit {
+                score,
+                created_at: event.created_at,
+                id,
+            }));
+
+            if let Some(limit) = limit {
+                if ranked.len() > limit {
+                    ranked.pop();
+                }
+            }
+        }
+
+        // Sort by score (descending), then by the event order (most recent first),
+        // and load the winning events
+        let mut events: Vec<EventBorrow<'a>> = Vec::with_capacity(ranked.len());
+        for Reverse(hit) in ranked.into_sorted_vec().into_iter() {
+            if let Some(event) = self.get_event_by_id(txn, &hit.id)? {
+                events.push(event);
+            }
+        }
+
+        Ok(events)
+    }
+
     fn iterate_filter_until_limit<'a>(
         &self,
         txn: &'a RoTxn,
@@ -988,6 +1383,95 @@ impl Lmdb {
         Ok(self.atc_index.range(txn, &range)?)
     }
 
+    /// Collect the frequencies of the `term` in the events, summed over all the tokens that start with it
+    ///
+    /// The terms shorter than [`nip50::MIN_PREFIX_CHARS`] match only the equal tokens.
+    /// Otherwise, the tokens in the prefix range are visited by seeking to the next distinct token,
+    /// so the events of the token are read only once.
+    fn fts_postings(
+        &self,
+        txn: &RoTxn,
+        term: &str,
+        since: &Timestamp,
+        until: &Timestamp,
+    ) -> Result<HashMap<[u8; 32], u32>, Error> {
+        let mut postings: HashMap<[u8; 32], u32> = HashMap::new();
+
+        // Short term: equal token only
+        if term.chars().count() < nip50::MIN_PREFIX_CHARS {
+            let token: [u8; index::TOKEN_LEN] = index::make_fts_token(term);
+            self.fts_collect_postings(txn, &token, since, until, &mut postings)?;
+            return Ok(postings);
+        }
+
+        let (start, end) = index::make_fts_prefix_range(term);
+        let end: Bound<&[u8]> = match &end {
+            Some(end) => Bound::Excluded(end.as_slice()),
+            None => Bound::Unbounded,
+        };
+
+        // Key after the last visited token
+        let mut next: Option<Vec<u8>> = None;
+
+        loop {
+            let range = match &next {
+                Some(key) => (Bound::Excluded(key.as_slice()), end),
+                None => (Bound::Included(start.as_slice()), end),
+            };
+
+            let token: [u8; index::TOKEN_LEN] = match self.fts_index.range(txn, &range)?.next() {
+                Some(result) => {
+                    let (key, _) = result?;
+                    *index::fts_index_key_token(key).ok_or(Error::NotFound)?
+                }
+                None => return Ok(postings),
+            };
+
+            self.fts_collect_postings(txn, &token, since, until, &mut postings)?;
+
+            next = Some(index::make_fts_token_end_key(&token));
+        }
+    }
+
+    fn fts_collect_postings(
+        &self,
+        txn: &RoTxn,
+        token: &[u8; index::TOKEN_LEN],
+        since: &Timestamp,
+        until: &Timestamp,
+        postings: &mut HashMap<[u8; 32], u32>,
+    ) -> Result<(), Error> {
+        for result in self.fts_iter(txn, token, since, until)? {
+            let (key, frequency) = result?;
+            if let Some(id) = index::fts_index_key_event_id(key) {
+                let id: [u8; 32] = id.try_into().map_err(|_| Error::NotFound)?;
+                *postings.entry(id).or_default() += frequency;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn fts_iter<'a>(
+        &'a self,
+        txn: &'a RoTxn,
+        token: &[u8; index::TOKEN_LEN],
+        since: &Timestamp,
+        until: &Timestamp,
+    ) -> Result<RoRange<'a, Bytes, U32<NativeEndian>>, Error> {
+        let start_prefix: Vec<u8> = index::make_fts_index_key(
+            token,
+            until, // scan goes backwards in time
+            &EVENT_ID_ALL_ZEROS,
+        );
+        let end_prefix: Vec<u8> = index::make_fts_index_key(token, since, &EVENT_ID_ALL_255);
+        let range = (
+            Bound::Included(start_prefix.as_slice()),
+            Bound::Excluded(end_prefix.as_slice()),
+        );
+        Ok(self.fts_index.range(txn, &range)?)
+    }
+
     fn handle_deletion_event(&self, txn: &mut RwTxn, event: &Event) -> Result<bool, Error> {
         // Collect DeletionInfo and EventIds for all valid targets first
         let mut deletions_to_process = Vec::new();
@@ -1058,6 +1542,31 @@ impl Lmdb {
     }
 }
 
+/// Extract the NIP-40 expiration
+fn extract_expiration(event: &EventBorrow) -> Option<Timestamp> {
+    event.tags.iter().find_map(|tag| match tag.as_slice() {
+        [kind, value, ..] if kind == "expiration" => value.parse().ok().map(Timestamp::from_secs),
+        _ => None,
+    })
+}
+
+/// Count the occurrences of each search token (encoded for the index) in the content
+fn term_frequencies(content: &str) -> BTreeMap<[u8; index::TOKEN_LEN], u32> {
+    let mut frequencies: BTreeMap<[u8; index::TOKEN_LEN], u32> = BTreeMap::new();
+    for token in nip50::tokenize(content) {
+        *frequencies
+            .entry(index::make_fts_token(&token))
+            .or_default() += 1;
+    }
+    frequencies
+}
+
+/// Score of a term in an event (TF-IDF)
+#[inline]
+fn term_score(frequency: u32, idf: f64) -> f64 {
+    (1.0 + (frequency as f64).ln()) * idf
+}
+
 /// Check if the new event should replace the stored one.
 fn has_event_been_replaced(stored: &EventBorrow, event: &Event) -> bool {
     match stored.created_at.cmp(&event.created_at) {
@@ -1070,3 +1579,96 @@ fn has_event_been_replaced(stored: &EventBorrow, event: &Event) -> bool {
         Ordering::Less => false,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+
+    fn search(db: &Lmdb, query: &str) -> usize {
+        let txn = db.read_txn().unwrap();
+        db.query(&txn, Filter::new().search(query)).unwrap().count()
+    }
+
+    #[test]
+    fn test_resume_backfill() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Lmdb::new(temp_dir.path(), 1024 * 1024 * 32, 126, 0).unwrap();
+
+        let keys = Keys::generate();
+        let mut fbb = FlatBufferBuilder::new();
+        let mut txn = db.write_txn().unwrap();
+        let mut ci_keys: Vec<Vec<u8>> = Vec::new();
+        for (i, content) in ["alpha", "beta", "gamma"].into_iter().enumerate() {
+            let event = EventBuilder::text_note(content)
+                .custom_created_at(Timestamp::from(i as u64 + 1))
+                .sign_with_keys(&keys)
+                .unwrap();
+            db.save_event_with_txn(&mut txn, &mut fbb, &event).unwrap();
+            ci_keys.push(index::make_ci_index_key(
+                &event.created_at,
+                event.id.as_bytes(),
+            ));
+        }
+
+        // Simulate a backfill interrupted after the first event (the created_at index is sorted from the newest)
+        db.fts_index.clear(&mut txn).unwrap();
+        db.backfill
+            .put(&mut txn, BackfillIndex::Fts.as_key(), &ci_keys[2])
+            .unwrap();
+        txn.commit().unwrap();
+        drop(db);
+
+        // Until the backfill is completed, the search scans the events
+        let db = Lmdb::new(temp_dir.path(), 1024 * 1024 * 32, 126, 0).unwrap();
+        assert_eq!(search(&db, "alpha"), 1);
+        assert_eq!(search(&db, "gamma"), 1);
+
+        // The backfill is resumed after the last indexed event
+        assert!(db.backfill_next_batch().unwrap());
+        assert!(!db.backfill_next_batch().unwrap());
+        assert_eq!(search(&db, "alpha"), 1);
+        assert_eq!(search(&db, "beta"), 1);
+        assert_eq!(search(&db, "gamma"), 0);
+
+        let txn = db.read_txn().unwrap();
+        assert!(db.backfill.is_empty(&txn).unwrap());
+    }
+
+    #[test]
+    fn test_search_limit_and_stale_postings() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Lmdb::new(temp_dir.path(), 1024 * 1024 * 32, 126, 0).unwrap();
+
+        let keys = Keys::generate();
+        let mut fbb = FlatBufferBuilder::new();
+        let mut txn = db.write_txn().unwrap();
+        let mut events: Vec<Event> = Vec::new();
+        for (i, content) in ["nostr", "nostr nostr nostr", "nostr nostr", "nostr"]
+            .into_iter()
+            .enumerate()
+        {
+            let event = EventBuilder::text_note(content)
+                .custom_created_at(Timestamp::from(i as u64 + 1))
+                .sign_with_keys(&keys)
+                .unwrap();
+            db.save_event_with_txn(&mut txn, &mut fbb, &event).unwrap();
+            events.push(event);
+        }
+
+        // Stale posting: the event is missing
+        db.events.delete(&mut txn, events[1].id.as_bytes()).unwrap();
+        txn.commit().unwrap();
+
+        let txn = db.read_txn().unwrap();
+        let ids: Vec<EventId> = db
+            .query(&txn, Filter::new().search("nostr").limit(2))
+            .unwrap()
+            .map(|e| EventId::from_byte_array(*e.id))
+            .collect();
+
+        // Best score first, then the most recent
+        assert_eq!(ids, vec![events[2].id, events[3].id]);
+    }
+}
@@ -5,17 +5,23 @@
 use std::collections::{BTreeMap, BTreeSet, HashSet};
 
 use nostr::event::borrow::EventBorrow;
+use nostr::nips::nip50::{self, SearchQuery};
 use nostr::{Filter, SingleLetterTag, Timestamp};
 
 pub struct DatabaseFilter {
     pub ids: HashSet<[u8; 32]>,
     pub authors: HashSet<[u8; 32]>,
     pub kinds: HashSet<u16>,
-    /// Lowercase query
-    pub search: Option<String>,
+    /// Search query
+    ///
+    /// Only the terms are matched, as the full-text search index does (see [`nip50::match_word`]):
+    /// the NIP-50 extensions are ignored.
+    pub search: Option<SearchQuery>,
     pub since: Option<Timestamp>,
     pub until: Option<Timestamp>,
     pub generic_tags: BTreeMap<SingleLetterTag, BTreeSet<String>>,
+    /// IDs of the expired events not purged yet (NIP-40)
+    pub expired: HashSet<[u8; 32]>,
 }
 
 impl DatabaseFilter {
@@ -63,22 +69,21 @@ impl DatabaseFilter {
     #[inline]
     fn search_match(&self, event: &EventBorrow) -> bool {
         match &self.search {
-            Some(query) => {
-                // NOTE: `query` was already converted to lowercase
-                let query: &[u8] = query.as_bytes();
-                event
-                    .content
-                    .as_bytes()
-                    .windows(query.len())
-                    .any(|window| window.eq_ignore_ascii_case(query))
+            Some(query) if query.has_terms() => {
+                let tokens: Vec<String> = nip50::tokenize(event.content).collect();
+                query
+                    .terms()
+                    .iter()
+                    .all(|term| tokens.iter().any(|token| nip50::match_word(token, term)))
             }
-            None => true,
+            _ => true,
         }
     }
 
     #[inline]
     pub fn match_event(&self, event: &EventBorrow) -> bool {
-        self.ids_match(event)
+        !self.expired.contains(event.id)
+            && self.ids_match(event)
             && self.authors_match(event)
             && self.kind_match(event)
             && self.since.map_or(true, |t| event.created_at >= t)
@@ -108,14 +113,11 @@ impl From<Filter> for DatabaseFilter {
                 .kinds
                 .map(|kinds| kinds.into_iter().map(|id| id.as_u16()).collect())
                 .unwrap_or_default(),
-            search: filter.search.map(|mut s| {
-                // Convert to lowercase
-                s.make_ascii_lowercase();
-                s
-            }),
+            search: filter.search,
             since: filter.since,
             until: filter.until,
             generic_tags: filter.generic_tags,
+            expired: HashSet::new(),
         }
     }
 }
@@ -1,6 +1,7 @@
 //! Implements data structures specific to BUD-02
 
 use nostr::hashes::sha256::Hash as Sha256Hash;
+use nostr::nips::nip92::MediaAttachment;
 use nostr::{Timestamp, Url};
 use serde::{Deserialize, Serialize};
 
@@ -19,3 +20,43 @@ pub struct BlobDescriptor {
     /// The date at which the blob was uploaded, as a UNIX timestamp (in seconds)
     pub uploaded: Timestamp,
 }
+
+impl From<BlobDescriptor> for MediaAttachment {
+    fn from(blob: BlobDescriptor) -> Self {
+        Self {
+            mime_type: blob.mime_type,
+            hash: Some(blob.sha256),
+            size: usize::try_from(blob.size).ok(),
+            ..Self::new(blob.url)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::str::FromStr;
+
+    use super::*;
+
+    #[test]
+    fn test_blob_descriptor_to_media_attachment() {
+        let blob = BlobDescriptor {
+            url: Url::parse("https://cdn.example.com/b1674191a88ec5cdd733e4240a81803105dc412d6c6708d53ab94fc248f4f553.png").unwrap(),
+            sha256: Sha256Hash::from_str(
+                "b1674191a88ec5cdd733e4240a81803105dc412d6c6708d53ab94fc248f4f553",
+            )
+            .unwrap(),
+            size: 184292,
+            mime_type: Some(String::from("image/png")),
+            uploaded: Timestamp::from_secs(1725105921),
+        };
+
+        let attachment = MediaAttachment::from(blob.clone());
+        assert_eq!(attachment.url, blob.url);
+        assert_eq!(attachment.mime_type.as_deref(), Some("image/png"));
+        assert_eq!(attachment.hash, Some(blob.sha256));
+        assert_eq!(attachment.size, Some(184292));
+        assert_eq!(attachment.dim, None);
+        assert!(attachment.fallbacks.is_empty());
+    }
+}
@@ -25,7 +25,7 @@ async fn main() -> Result<()> {
     // let signer = NostrConnectRemoteSigner::from_uri(uri, keys, None, None)?;
 
     // Print bunker URI
-    let uri = signer.bunker_uri();
+    let uri = signer.bunker_uri().await;
     println!("\n{uri}\n");
 
     // Serve signer
@@ -13,7 +13,7 @@ use async_utility::time;
 use nostr::nips::nip46::ResponseResult;
 use nostr_relay_pool::prelude::*;
 use tokio::sync::broadcast::Receiver;
-use tokio::sync::OnceCell;
+use tokio::sync::{OnceCell, RwLock};
 
 use crate::error::Error;
 
@@ -23,6 +23,7 @@ use crate::error::Error;
 #[derive(Debug, Clone)]
 pub struct NostrConnect {
     uri: NostrConnectURI,
+    relays: Arc<RwLock<Vec<RelayUrl>>>,
     app_keys: Keys,
     remote_signer_public_key: OnceCell<PublicKey>,
     user_public_key: OnceCell<PublicKey>,
@@ -58,6 +59,7 @@ impl NostrConnect {
             timeout,
             opts: opts.unwrap_or_default(),
             secret: uri.secret().map(|secret| secret.to_string()),
+            relays: Arc::new(RwLock::new(uri.relays().to_vec())),
             uri,
             auth_url_handler: None,
         })
@@ -114,7 +116,7 @@ impl NostrConnect {
 
     async fn bootstrap(&self) -> Result<PublicKey, Error> {
         // Add relays
-        for url in self.uri.relays().iter() {
+        for url in self.relays().await.iter() {
             self.pool.add_relay(url, self.opts.clone()).await?;
         }
 
@@ -190,16 +192,15 @@ impl NostrConnect {
     }
 
     /// Get signer relays
-    #[inline]
-    pub fn relays(&self) -> &[RelayUrl] {
-        self.uri.relays()
+    pub async fn relays(&self) -> Vec<RelayUrl> {
+        self.relays.read().await.clone()
     }
 
     /// Get `bunker` URI
     pub async fn bunker_uri(&self) -> Result<NostrConnectURI, Error> {
         Ok(NostrConnectURI::Bunker {
             remote_signer_public_key: *self.remote_signer_public_key().await?,
-            relays: self.relays().to_vec(),
+            relays: self.relays().await,
             secret: self.secret.clone(),
         })
     }
@@ -311,6 +312,7 @@ impl NostrConnect {
         let req = NostrConnectRequest::Connect {
             public_key: remote_signer_public_key,
             secret: self.secret.clone(),
+            perms: Vec::new(),
         };
         let res = self
             .send_request_with_pk(req, remote_signer_public_key)
@@ -386,6 +388,44 @@ impl NostrConnect {
         Ok(res.to_nip44_decrypt()?)
     }
 
+    /// Ask the signer for its current relays and switch to them
+    ///
+    /// Returns `true` if the relays changed.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/46.md>
+    pub async fn switch_relays(&self) -> Result<bool, Error> {
+        let res = self.send_request(NostrConnectRequest::SwitchRelays).await?;
+
+        let new_relays: Vec<RelayUrl> = match res.to_switch_relays()? {
+            Some(relays) if !relays.is_empty() => relays,
+            _ => return Ok(false),
+        };
+
+        let mut relays = self.relays.write().await;
+
+        if *relays == new_relays {
+            return Ok(false);
+        }
+
+        // Add the new relays (they inherit the pool subscription)
+        for url in new_relays.iter() {
+            if self.pool.add_relay(url, self.opts.clone()).await? {
+                self.pool.connect_relay(url).await?;
+            }
+        }
+
+        // Remove the old ones
+        for url in relays.iter() {
+            if !new_relays.contains(url) {
+                self.pool.remove_relay(url).await?;
+            }
+        }
+
+        *relays = new_relays;
+
+        Ok(true)
+    }
+
     /// Completely shutdown
     pub async fn shutdown(self) {
         self.pool.shutdown().await
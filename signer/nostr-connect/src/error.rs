@@ -12,6 +12,8 @@ use nostr::PublicKey;
 use nostr_relay_pool::pool;
 use tokio::sync::SetError;
 
+use crate::permission::PermissionStoreError;
+
 /// Nostr Connect error
 #[derive(Debug)]
 pub enum Error {
@@ -25,6 +27,8 @@ pub enum Error {
     NIP46(nip46::Error),
     /// Pool
     Pool(pool::Error),
+    /// Permission store error
+    PermissionStore(PermissionStoreError),
     /// Set user public key error
     SetUserPublicKey(SetError<PublicKey>),
     /// NIP46 response error
@@ -57,6 +61,7 @@ impl fmt::Display for Error {
             Self::NIP44(e) => e.fmt(f),
             Self::NIP46(e) => e.fmt(f),
             Self::Pool(e) => e.fmt(f),
+            Self::PermissionStore(e) => e.fmt(f),
             Self::SetUserPublicKey(e) => e.fmt(f),
             Self::Response(e) => e.fmt(f),
             Self::SignerPublicKeyNotFound => f.write_str("signer public key not found"),
@@ -77,6 +82,12 @@ impl From<builder::Error> for Error {
     }
 }
 
+impl From<PermissionStoreError> for Error {
+    fn from(e: PermissionStoreError) -> Self {
+        Self::PermissionStore(e)
+    }
+}
+
 impl From<nip04::Error> for Error {
     fn from(e: nip04::Error) -> Self {
         Self::NIP04(e)
@@ -13,5 +13,6 @@
 
 pub mod client;
 pub mod error;
+pub mod permission;
 pub mod prelude;
 pub mod signer;
@@ -0,0 +1,528 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2025 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Nostr Connect permissions
+//!
+//! Per-client grants for the [`NostrConnectRemoteSigner`](crate::signer::NostrConnectRemoteSigner).
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use nostr::nips::nip46::{NostrConnectPermission, NostrConnectRequest};
+use nostr::util::BoxedFuture;
+use nostr::{PublicKey, Timestamp};
+use tokio::sync::RwLock;
+
+/// Permission store error
+#[derive(Debug)]
+pub enum PermissionStoreError {
+    /// An error happened in the underlying backend.
+    Backend(Box<dyn std::error::Error + Send + Sync>),
+}
+
+impl std::error::Error for PermissionStoreError {}
+
+impl fmt::Display for PermissionStoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Backend(e) => e.fmt(f),
+        }
+    }
+}
+
+impl PermissionStoreError {
+    /// Create a new backend error
+    ///
+    /// Shorthand for `Error::Backend(Box::new(error))`.
+    #[inline]
+    pub fn backend<E>(error: E) -> Self
+    where
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        Self::Backend(Box::new(error))
+    }
+}
+
+/// Permission granted to a client
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NostrConnectGrant {
+    /// Permission
+    pub permission: NostrConnectPermission,
+    /// Optional expiration
+    pub expires_at: Option<Timestamp>,
+}
+
+impl NostrConnectGrant {
+    /// New grant
+    #[inline]
+    pub fn new(permission: NostrConnectPermission, expires_at: Option<Timestamp>) -> Self {
+        Self {
+            permission,
+            expires_at,
+        }
+    }
+
+    /// Check if the grant is expired
+    #[inline]
+    pub fn is_expired_at(&self, now: &Timestamp) -> bool {
+        match &self.expires_at {
+            Some(expires_at) => expires_at <= now,
+            None => false,
+        }
+    }
+}
+
+/// Store of the grants, by client public key
+pub trait NostrConnectPermissionStore: fmt::Debug + Send + Sync {
+    /// Get the grants of a client
+    fn grants<'a>(
+        &'a self,
+        client: &'a PublicKey,
+    ) -> BoxedFuture<'a, Result<Vec<NostrConnectGrant>, PermissionStoreError>>;
+
+    /// Save grants for a client
+    ///
+    /// An existing grant for the same permission must be replaced.
+    fn grant<'a>(
+        &'a self,
+        client: &'a PublicKey,
+        grants: Vec<NostrConnectGrant>,
+    ) -> BoxedFuture<'a, Result<(), PermissionStoreError>>;
+
+    /// Revoke all the grants of a client
+    fn revoke<'a>(
+        &'a self,
+        client: &'a PublicKey,
+    ) -> BoxedFuture<'a, Result<(), PermissionStoreError>>;
+}
+
+/// In-memory permission store
+#[derive(Debug, Default)]
+pub struct MemoryPermissionStore {
+    grants: RwLock<HashMap<PublicKey, HashMap<NostrConnectPermission, Option<Timestamp>>>>,
+}
+
+impl MemoryPermissionStore {
+    /// New in-memory store
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl NostrConnectPermissionStore for MemoryPermissionStore {
+    fn grants<'a>(
+        &'a self,
+        client: &'a PublicKey,
+    ) -> BoxedFuture<'a, Result<Vec<NostrConnectGrant>, PermissionStoreError>> {
+        Box::pin(async move {
+            let grants = self.grants.read().await;
+            Ok(grants
+                .get(client)
+                .map(|grants| {
+                    grants
+                        .iter()
+                        .map(|(permission, expires_at)| {
+                            NostrConnectGrant::new(*permission, *expires_at)
+                        })
+                        .collect()
+                })
+                .unwrap_or_default())
+        })
+    }
+
+    fn grant<'a>(
+        &'a self,
+        client: &'a PublicKey,
+        new_grants: Vec<NostrConnectGrant>,
+    ) -> BoxedFuture<'a, Result<(), PermissionStoreError>> {
+        Box::pin(async move {
+            let mut grants = self.grants.write().await;
+            grants.entry(*client).or_default().extend(
+                new_grants
+                    .into_iter()
+                    .map(|grant| (grant.permission, grant.expires_at)),
+            );
+            Ok(())
+        })
+    }
+
+    fn revoke<'a>(
+        &'a self,
+        client: &'a PublicKey,
+    ) -> BoxedFuture<'a, Result<(), PermissionStoreError>> {
+        Box::pin(async move {
+            let mut grants = self.grants.write().await;
+            grants.remove(client);
+            Ok(())
+        })
+    }
+}
+
+/// Decision for a request outside the granted scope
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum NostrConnectDecision {
+    /// Reject the request
+    Reject,
+    /// Approve only this request
+    ///
+    /// For `connect` requests, the requested permissions are granted
+    /// for the duration passed to [`NostrConnectPermissions::new`].
+    Approve,
+    /// Approve and remember the permission of the request
+    ///
+    /// For `connect` requests, the requested permissions are granted.
+    ApproveAndRemember {
+        /// Optional expiration
+        expires_at: Option<Timestamp>,
+    },
+}
+
+/// Prompt the user for requests outside the granted scope
+pub trait NostrConnectPrompt: fmt::Debug + Send + Sync {
+    /// Ask the user
+    fn prompt<'a>(
+        &'a self,
+        client: &'a PublicKey,
+        req: &'a NostrConnectRequest,
+    ) -> BoxedFuture<'a, NostrConnectDecision>;
+}
+
+/// Outcome of [`NostrConnectPermissions::authorize`]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum NostrConnectAuthorization {
+    /// The request is rejected
+    Rejected,
+    /// The request is approved
+    Approved,
+    /// The `connect` request is approved
+    ///
+    /// The grants must be saved with [`NostrConnectPermissions::commit`]
+    /// only after the signer has accepted the connection (i.e., the secret matches).
+    Connect(Vec<NostrConnectGrant>),
+}
+
+impl NostrConnectAuthorization {
+    /// Check if the request is approved
+    #[inline]
+    pub fn is_approved(&self) -> bool {
+        !matches!(self, Self::Rejected)
+    }
+}
+
+/// Nostr Connect permission engine
+///
+/// * `connect` requests are always prompted: if approved, the permissions requested by the client are returned,
+///   to be granted once the connection is accepted. The secret must be checked before the authorization,
+///   to not prompt the user for connections that would be refused anyway
+///   (done by [`NostrConnectRemoteSigner`](crate::signer::NostrConnectRemoteSigner));
+/// * requests within the granted (and not expired) permissions are auto-approved;
+/// * `get_public_key` is auto-approved for clients with at least a valid grant;
+/// * `ping` is always approved;
+/// * everything else is prompted.
+#[derive(Debug, Clone)]
+pub struct NostrConnectPermissions {
+    store: Arc<dyn NostrConnectPermissionStore>,
+    prompt: Arc<dyn NostrConnectPrompt>,
+    grant_duration: Option<Duration>,
+}
+
+impl NostrConnectPermissions {
+    /// New permission engine
+    ///
+    /// The `grant_duration` is the duration of the permissions granted with
+    /// an approved `connect` request (see [`NostrConnectDecision::Approve`]):
+    /// if `None`, these permissions never expire, until revoked.
+    pub fn new<S, P>(store: S, prompt: P, grant_duration: Option<Duration>) -> Self
+    where
+        S: NostrConnectPermissionStore + 'static,
+        P: NostrConnectPrompt + 'static,
+    {
+        Self {
+            store: Arc::new(store),
+            prompt: Arc::new(prompt),
+            grant_duration,
+        }
+    }
+
+    /// Get the store
+    #[inline]
+    pub fn store(&self) -> &Arc<dyn NostrConnectPermissionStore> {
+        &self.store
+    }
+
+    /// Check if the client can execute the request, prompting the user if needed
+    ///
+    /// The permissions requested with `connect` are not saved:
+    /// check [`NostrConnectAuthorization::Connect`].
+    pub async fn authorize(
+        &self,
+        client: &PublicKey,
+        req: &NostrConnectRequest,
+    ) -> Result<NostrConnectAuthorization, PermissionStoreError> {
+        let now: Timestamp = Timestamp::now();
+
+        // Requested permissions
+        if let NostrConnectRequest::Connect { perms, .. } = req {
+            let expires_at: Option<Timestamp> = match self.prompt.prompt(client, req).await {
+                NostrConnectDecision::Reject => return Ok(NostrConnectAuthorization::Rejected),
+                NostrConnectDecision::Approve => self.grant_duration.map(|d| now + d),
+                NostrConnectDecision::ApproveAndRemember { expires_at } => expires_at,
+            };
+
+            let grants: Vec<NostrConnectGrant> = perms
+                .iter()
+                .map(|perm| NostrConnectGrant::new(*perm, expires_at))
+                .collect();
+
+            return Ok(NostrConnectAuthorization::Connect(grants));
+        }
+
+        if let NostrConnectRequest::Ping = req {
+            return Ok(NostrConnectAuthorization::Approved);
+        }
+
+        let grants: Vec<NostrConnectGrant> = self
+            .store
+            .grants(client)
+            .await?
+            .into_iter()
+            .filter(|grant| !grant.is_expired_at(&now))
+            .collect();
+
+        // Within the granted scope
+        if grants.iter().any(|grant| grant.permission.allows(req)) {
+            return Ok(NostrConnectAuthorization::Approved);
+        }
+
+        // Session already established
+        if let (NostrConnectRequest::GetPublicKey, false) = (req, grants.is_empty()) {
+            return Ok(NostrConnectAuthorization::Approved);
+        }
+
+        match self.prompt.prompt(client, req).await {
+            NostrConnectDecision::Reject => Ok(NostrConnectAuthorization::Rejected),
+            NostrConnectDecision::Approve => Ok(NostrConnectAuthorization::Approved),
+            NostrConnectDecision::ApproveAndRemember { expires_at } => {
+                let grant = NostrConnectGrant::new(request_permission(req), expires_at);
+                self.store.grant(client, vec![grant]).await?;
+                Ok(NostrConnectAuthorization::Approved)
+            }
+        }
+    }
+
+    /// Save the grants of an accepted `connect` request (see [`NostrConnectAuthorization::Connect`])
+    #[inline]
+    pub async fn commit(
+        &self,
+        client: &PublicKey,
+        grants: Vec<NostrConnectGrant>,
+    ) -> Result<(), PermissionStoreError> {
+        self.store.grant(client, grants).await
+    }
+
+    /// Revoke all the grants of a client
+    #[inline]
+    pub async fn revoke(&self, client: &PublicKey) -> Result<(), PermissionStoreError> {
+        self.store.revoke(client).await
+    }
+}
+
+/// Get the narrowest permission that allows the request
+fn request_permission(req: &NostrConnectRequest) -> NostrConnectPermission {
+    match req {
+        NostrConnectRequest::SignEvent(unsigned) => {
+            NostrConnectPermission::sign_event(unsigned.kind)
+        }
+        req => NostrConnectPermission::new(req.method()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use nostr::nips::nip46::NostrConnectMethod;
+    use nostr::{EventBuilder, Keys, Kind, UnsignedEvent};
+
+    use super::*;
+
+    #[derive(Debug)]
+    struct MockPrompt {
+        decision: NostrConnectDecision,
+        prompts: Arc<AtomicUsize>,
+    }
+
+    impl NostrConnectPrompt for MockPrompt {
+        fn prompt<'a>(
+            &'a self,
+            _client: &'a PublicKey,
+            _req: &'a NostrConnectRequest,
+        ) -> BoxedFuture<'a, NostrConnectDecision> {
+            Box::pin(async move {
+                self.prompts.fetch_add(1, Ordering::SeqCst);
+                self.decision
+            })
+        }
+    }
+
+    fn sign_event(keys: &Keys, kind: Kind) -> NostrConnectRequest {
+        let unsigned: UnsignedEvent = EventBuilder::new(kind, "").build(keys.public_key());
+        NostrConnectRequest::SignEvent(unsigned)
+    }
+
+    #[tokio::test]
+    async fn test_authorize() {
+        let keys = Keys::generate();
+        let client = Keys::generate().public_key();
+        let other_client = Keys::generate().public_key();
+
+        let prompts = Arc::new(AtomicUsize::new(0));
+        let permissions = NostrConnectPermissions::new(
+            MemoryPermissionStore::new(),
+            MockPrompt {
+                decision: NostrConnectDecision::Approve,
+                prompts: prompts.clone(),
+            },
+            None,
+        );
+
+        // Connect
+        let connect = NostrConnectRequest::Connect {
+            public_key: keys.public_key(),
+            secret: None,
+            perms: NostrConnectPermission::parse_list("sign_event:1,nip44_encrypt"),
+        };
+        let grants = match permissions.authorize(&client, &connect).await.unwrap() {
+            NostrConnectAuthorization::Connect(grants) => grants,
+            res => panic!("Unexpected authorization: {res:?}"),
+        };
+        assert_eq!(grants.len(), 2);
+        assert_eq!(prompts.load(Ordering::SeqCst), 1);
+
+        // Not saved until the connection is accepted
+        assert!(permissions
+            .store()
+            .grants(&client)
+            .await
+            .unwrap()
+            .is_empty());
+        permissions.commit(&client, grants).await.unwrap();
+
+        // Within the scope
+        let text_note = sign_event(&keys, Kind::TextNote);
+        assert!(permissions
+            .authorize(&client, &text_note)
+            .await
+            .unwrap()
+            .is_approved());
+        assert!(permissions
+            .authorize(&client, &NostrConnectRequest::GetPublicKey)
+            .await
+            .unwrap()
+            .is_approved());
+        assert!(permissions
+            .authorize(&client, &NostrConnectRequest::Ping)
+            .await
+            .unwrap()
+            .is_approved());
+        assert_eq!(prompts.load(Ordering::SeqCst), 1);
+
+        // Outside the scope
+        let metadata = sign_event(&keys, Kind::Metadata);
+        assert!(permissions
+            .authorize(&client, &metadata)
+            .await
+            .unwrap()
+            .is_approved());
+        assert_eq!(prompts.load(Ordering::SeqCst), 2);
+
+        // Grants are per client
+        assert!(permissions
+            .authorize(&other_client, &text_note)
+            .await
+            .unwrap()
+            .is_approved());
+        assert_eq!(prompts.load(Ordering::SeqCst), 3);
+
+        // Revoke
+        permissions.revoke(&client).await.unwrap();
+        assert!(permissions
+            .authorize(&client, &text_note)
+            .await
+            .unwrap()
+            .is_approved());
+        assert_eq!(prompts.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn test_remember_and_expiration() {
+        let keys = Keys::generate();
+        let client = Keys::generate().public_key();
+
+        let store = MemoryPermissionStore::new();
+        store
+            .grant(
+                &client,
+                vec![NostrConnectGrant::new(
+                    NostrConnectPermission::new(NostrConnectMethod::Nip44Decrypt),
+                    Some(Timestamp::from_secs(1)),
+                )],
+            )
+            .await
+            .unwrap();
+
+        let prompts = Arc::new(AtomicUsize::new(0));
+        let permissions = NostrConnectPermissions::new(
+            store,
+            MockPrompt {
+                decision: NostrConnectDecision::ApproveAndRemember { expires_at: None },
+                prompts: prompts.clone(),
+            },
+            None,
+        );
+
+        // Expired grant: prompt and remember
+        let decrypt = NostrConnectRequest::Nip44Decrypt {
+            public_key: keys.public_key(),
+            ciphertext: String::from("ciphertext"),
+        };
+        assert!(permissions
+            .authorize(&client, &decrypt)
+            .await
+            .unwrap()
+            .is_approved());
+        assert!(permissions
+            .authorize(&client, &decrypt)
+            .await
+            .unwrap()
+            .is_approved());
+        assert_eq!(prompts.load(Ordering::SeqCst), 1);
+
+        let grants = permissions.store().grants(&client).await.unwrap();
+        assert_eq!(
+            grants,
+            vec![NostrConnectGrant::new(
+                NostrConnectPermission::new(NostrConnectMethod::Nip44Decrypt),
+                None
+            )]
+        );
+
+        // Only the kind of the request is remembered
+        let text_note = sign_event(&keys, Kind::TextNote);
+        assert!(permissions
+            .authorize(&client, &text_note)
+            .await
+            .unwrap()
+            .is_approved());
+        assert!(permissions
+            .authorize(&client, &sign_event(&keys, Kind::Metadata))
+            .await
+            .unwrap()
+            .is_approved());
+        assert_eq!(prompts.load(Ordering::SeqCst), 3);
+    }
+}
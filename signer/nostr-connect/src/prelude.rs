@@ -12,4 +12,5 @@ pub use nostr::prelude::*;
 
 pub use crate::client::*;
 pub use crate::error::*;
+pub use crate::permission::*;
 pub use crate::signer::*;
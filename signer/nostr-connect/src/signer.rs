@@ -4,13 +4,18 @@
 
 //! Nostr Connect signer
 
+use std::collections::{HashMap, HashSet};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 
+use async_utility::task;
 use nostr::nips::nip46::ResponseResult;
+use nostr::secp256k1::rand;
 use nostr_relay_pool::prelude::*;
+use tokio::sync::RwLock;
 
 use crate::error::Error;
+use crate::permission::{NostrConnectAuthorization, NostrConnectPermissions};
 
 /// Nostr Connect Keys
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -23,6 +28,21 @@ pub struct NostrConnectKeys {
     pub user: Keys,
 }
 
+/// Nostr Connect client session
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NostrConnectSession {
+    /// Client public key
+    pub public_key: PublicKey,
+    /// Secret used to connect
+    pub secret: Option<String>,
+    /// Relays used to communicate with the client
+    pub relays: Vec<RelayUrl>,
+    /// When the session was established
+    pub connected_at: Timestamp,
+    /// Last request from the client
+    pub last_seen: Timestamp,
+}
+
 /// Nostr Connect Signer
 ///
 /// Signer that listen for requests from a client, handle them and send the response.
@@ -31,10 +51,16 @@ pub struct NostrConnectKeys {
 #[derive(Debug, Clone)]
 pub struct NostrConnectRemoteSigner {
     keys: NostrConnectKeys,
-    relays: Vec<RelayUrl>,
+    relays: Arc<RwLock<Vec<RelayUrl>>>,
     pool: RelayPool,
     opts: RelayOptions,
     secret: Option<String>,
+    /// Single-use secrets
+    secrets: Arc<RwLock<HashSet<String>>>,
+    sessions: Arc<RwLock<HashMap<PublicKey, NostrConnectSession>>>,
+    revoked: Arc<RwLock<HashSet<PublicKey>>>,
+    /// Permissions engine used by [`NostrConnectRemoteSigner::serve_with_permissions`]
+    permissions: Arc<RwLock<Option<NostrConnectPermissions>>>,
     nostr_connect_client_public_key: Option<PublicKey>,
     bootstrapped: Arc<AtomicBool>,
 }
@@ -63,10 +89,14 @@ impl NostrConnectRemoteSigner {
 
         Ok(Self {
             keys,
-            relays,
+            relays: Arc::new(RwLock::new(relays)),
             pool: RelayPool::default(),
             opts: opts.unwrap_or_default(),
             secret,
+            secrets: Arc::new(RwLock::new(HashSet::new())),
+            sessions: Arc::new(RwLock::new(HashMap::new())),
+            revoked: Arc::new(RwLock::new(HashSet::new())),
+            permissions: Arc::new(RwLock::new(None)),
             nostr_connect_client_public_key: None,
             bootstrapped: Arc::new(AtomicBool::new(false)),
         })
@@ -92,23 +122,171 @@ impl NostrConnectRemoteSigner {
     }
 
     /// Get signer relays
-    pub fn relays(&self) -> &[RelayUrl] {
-        &self.relays
+    pub async fn relays(&self) -> Vec<RelayUrl> {
+        self.relays.read().await.clone()
     }
 
     /// Get `bunker` URI
-    pub fn bunker_uri(&self) -> NostrConnectURI {
+    pub async fn bunker_uri(&self) -> NostrConnectURI {
         NostrConnectURI::Bunker {
             remote_signer_public_key: self.keys.signer.public_key(),
-            relays: self.relays().to_vec(),
+            relays: self.relays().await,
             secret: self.secret.clone(),
         }
     }
 
+    /// Get a `bunker` URI with a new single-use secret
+    ///
+    /// Useful to connect a new client without sharing the main secret.
+    pub async fn new_bunker_uri(&self) -> NostrConnectURI {
+        let secret: String = format!("{:032x}", rand::random::<u128>());
+
+        let mut secrets = self.secrets.write().await;
+        secrets.insert(secret.clone());
+
+        NostrConnectURI::Bunker {
+            remote_signer_public_key: self.keys.signer.public_key(),
+            relays: self.relays().await,
+            secret: Some(secret),
+        }
+    }
+
+    /// Switch the signer relays
+    ///
+    /// The previous relays are kept connected, so the clients can still reach the signer
+    /// until they update their relays with a `switch_relays` request.
+    pub async fn switch_relays<I, U>(&self, urls: I) -> Result<(), Error>
+    where
+        I: IntoIterator<Item = U>,
+        U: TryIntoUrl,
+        pool::Error: From<<U as TryIntoUrl>::Err>,
+    {
+        let mut relays: Vec<RelayUrl> = Vec::new();
+        for url in urls.into_iter() {
+            relays.push(
+                url.try_into_url()
+                    .map_err(|e| Error::Pool(pool::Error::from(e)))?,
+            );
+        }
+
+        // Add the new relays, if already bootstrapped (they inherit the pool subscription)
+        if self.bootstrapped.load(Ordering::SeqCst) {
+            for url in relays.iter() {
+                if self
+                    .pool
+                    .add_relay::<&RelayUrl>(url, self.opts.clone())
+                    .await?
+                {
+                    self.pool.connect_relay::<&RelayUrl>(url).await?;
+                }
+            }
+        }
+
+        let mut current = self.relays.write().await;
+        *current = relays;
+
+        Ok(())
+    }
+
+    /// Get the client sessions
+    pub async fn sessions(&self) -> Vec<NostrConnectSession> {
+        let sessions = self.sessions.read().await;
+        sessions.values().cloned().collect()
+    }
+
+    /// Get the session of a client
+    pub async fn session(&self, public_key: &PublicKey) -> Option<NostrConnectSession> {
+        let sessions = self.sessions.read().await;
+        sessions.get(public_key).cloned()
+    }
+
+    /// Revoke the session of a client
+    ///
+    /// All the following requests of the client, `connect` included, are rejected
+    /// until it's allowed again with [`NostrConnectRemoteSigner::allow_client`].
+    ///
+    /// The grants saved in the [`NostrConnectPermissions`] engine are revoked too,
+    /// so the client is prompted again after being allowed.
+    ///
+    /// Returns `false` if the session doesn't exist.
+    pub async fn revoke_session(&self, public_key: &PublicKey) -> Result<bool, Error> {
+        let mut revoked = self.revoked.write().await;
+        revoked.insert(*public_key);
+
+        let removed: bool = {
+            let mut sessions = self.sessions.write().await;
+            sessions.remove(public_key).is_some()
+        };
+
+        // If the signer isn't served with a permissions engine yet,
+        // the grants are revoked when it starts (see `serve_with_permissions`).
+        let permissions = self.permissions.read().await;
+        if let Some(permissions) = permissions.as_ref() {
+            permissions.revoke(public_key).await?;
+        }
+
+        Ok(removed)
+    }
+
+    /// Allow a revoked client to connect again
+    ///
+    /// Returns `false` if the client wasn't revoked.
+    pub async fn allow_client(&self, public_key: &PublicKey) -> bool {
+        let mut revoked = self.revoked.write().await;
+        revoked.remove(public_key)
+    }
+
+    async fn open_session(&self, public_key: PublicKey, secret: Option<String>) {
+        let now: Timestamp = Timestamp::now();
+        let relays: Vec<RelayUrl> = self.relays().await;
+
+        let mut sessions = self.sessions.write().await;
+        sessions.insert(
+            public_key,
+            NostrConnectSession {
+                public_key,
+                secret,
+                relays,
+                connected_at: now,
+                last_seen: now,
+            },
+        );
+    }
+
+    /// Check the session of the client and update its last seen timestamp
+    ///
+    /// The requests of the revoked clients are rejected.
+    /// If a secret is set, also the requests of the clients without a session are rejected, except `connect`.
+    async fn touch_session(
+        &self,
+        public_key: &PublicKey,
+        req: &NostrConnectRequest,
+    ) -> Result<(), NostrConnectResponse> {
+        let revoked = self.revoked.read().await;
+        if revoked.contains(public_key) {
+            return Err(NostrConnectResponse::with_error("Session revoked"));
+        }
+
+        let mut sessions = self.sessions.write().await;
+        match sessions.get_mut(public_key) {
+            Some(session) => {
+                session.last_seen = Timestamp::now();
+                Ok(())
+            }
+            None if self.secret.is_some()
+                && !matches!(req, NostrConnectRequest::Connect { .. }) =>
+            {
+                Err(NostrConnectResponse::with_error("Not connected"))
+            }
+            None => Ok(()),
+        }
+    }
+
     async fn send_connect_ack(&self, public_key: PublicKey) -> Result<(), Error> {
         let req: NostrConnectRequest = NostrConnectRequest::Connect {
             public_key: self.keys.user.public_key(),
             secret: self.secret.clone(),
+            perms: Vec::new(),
         };
 
         let msg: NostrConnectMessage = NostrConnectMessage::request(&req);
@@ -125,7 +303,7 @@ impl NostrConnectRemoteSigner {
         }
 
         // Add relays to pool
-        for url in self.relays.iter() {
+        for url in self.relays().await.iter() {
             self.pool.add_relay(url, self.opts.clone()).await?;
         }
 
@@ -148,10 +326,36 @@ impl NostrConnectRemoteSigner {
         Ok(())
     }
 
-    fn match_secret(&self, secret: Option<String>) -> bool {
+    /// Check if the secret is valid, without consuming it (if single-use)
+    async fn is_valid_secret(&self, secret: Option<&String>) -> bool {
+        // Single-use secrets
+        if let Some(secret) = secret {
+            let secrets = self.secrets.read().await;
+            if secrets.contains(secret) {
+                return true;
+            }
+        }
+
+        self.match_main_secret(secret)
+    }
+
+    /// Check if the secret is valid, consuming it (if single-use)
+    async fn match_secret(&self, secret: Option<&String>) -> bool {
+        // Single-use secrets
+        if let Some(secret) = secret {
+            let mut secrets = self.secrets.write().await;
+            if secrets.remove(secret) {
+                return true;
+            }
+        }
+
+        self.match_main_secret(secret)
+    }
+
+    fn match_main_secret(&self, secret: Option<&String>) -> bool {
         match (&self.secret, secret) {
             // Both secrets are set, check if values are equal.
-            (Some(s1), Some(s2)) => s1 == &s2,
+            (Some(s1), Some(s2)) => s1 == s2,
             // Only the secret on our side is set, must return `false`.
             (Some(..), None) => false,
             // Only the secret on their side is set, can continue, return `true`.
@@ -165,12 +369,69 @@ impl NostrConnectRemoteSigner {
     pub async fn serve<T>(&self, actions: T) -> Result<(), Error>
     where
         T: NostrConnectSignerActions,
+    {
+        self.serve_with(None, |public_key, req| {
+            let authorization: NostrConnectAuthorization = if actions.approve(&public_key, &req) {
+                NostrConnectAuthorization::Approved
+            } else {
+                NostrConnectAuthorization::Rejected
+            };
+            Box::pin(async move { authorization })
+        })
+        .await
+    }
+
+    /// Serve signer, approving the requests with the [`NostrConnectPermissions`] engine
+    ///
+    /// Requests within the permissions granted to a client are approved automatically,
+    /// the others are prompted (see [`NostrConnectPrompt`](crate::permission::NostrConnectPrompt)).
+    /// Each request is handled in its own task, so a pending prompt doesn't delay the requests of the other clients.
+    ///
+    /// The `connect` requests are prompted, and their permissions granted, only if the secret matches.
+    pub async fn serve_with_permissions(
+        &self,
+        permissions: NostrConnectPermissions,
+    ) -> Result<(), Error> {
+        {
+            // Revoke the grants of the clients revoked before serving
+            let revoked = self.revoked.read().await;
+            for public_key in revoked.iter() {
+                permissions.revoke(public_key).await?;
+            }
+
+            let mut engine = self.permissions.write().await;
+            *engine = Some(permissions.clone());
+        }
+
+        self.serve_with(Some(&permissions), |public_key, req| {
+            let permissions = permissions.clone();
+            Box::pin(async move {
+                match permissions.authorize(&public_key, &req).await {
+                    Ok(authorization) => authorization,
+                    Err(e) => {
+                        tracing::error!(error = %e, "Impossible to check permissions.");
+                        NostrConnectAuthorization::Rejected
+                    }
+                }
+            })
+        })
+        .await
+    }
+
+    async fn serve_with<F>(
+        &self,
+        permissions: Option<&NostrConnectPermissions>,
+        approve: F,
+    ) -> Result<(), Error>
+    where
+        F: Fn(PublicKey, NostrConnectRequest) -> BoxedFuture<'static, NostrConnectAuthorization>,
     {
         self.bootstrap().await?;
 
         // TODO: move into bootstrap method?
         if let Some(public_key) = self.nostr_connect_client_public_key {
             self.send_connect_ack(public_key).await?;
+            self.open_session(public_key, self.secret.clone()).await;
         }
 
         self.pool
@@ -189,146 +450,40 @@ impl NostrConnectRemoteSigner {
                                 let id: String = msg.id().to_string();
 
                                 if let Ok(req) = msg.to_request() {
-                                    // Generate response
-                                    let response: NostrConnectResponse = if actions
-                                        .approve(&event.pubkey, &req)
-                                    {
-                                        match req {
-                                            NostrConnectRequest::Connect { secret, .. } => {
-                                                if self.match_secret(secret) {
-                                                    NostrConnectResponse::with_result(
-                                                        ResponseResult::Ack,
-                                                    )
-                                                } else {
-                                                    NostrConnectResponse::with_error(
-                                                        "Secret not match",
+                                    let public_key: PublicKey = event.pubkey;
+
+                                    // Ask for the approval only if the request passes the checks
+                                    let authorization = match self.check_request(&public_key, &req).await {
+                                        Ok(()) => Ok(approve(public_key, req.clone())),
+                                        Err(response) => Err(response),
+                                    };
+
+                                    // Wait for the approval, handle the request and send the response in a new task,
+                                    // to not block the requests of the other clients while the user is prompted
+                                    let signer: Self = self.clone();
+                                    let permissions: Option<NostrConnectPermissions> =
+                                        permissions.cloned();
+                                    task::spawn(async move {
+                                        let response: NostrConnectResponse = match authorization {
+                                            Ok(authorization) => {
+                                                signer
+                                                    .handle_authorized_request(
+                                                        permissions.as_ref(),
+                                                        public_key,
+                                                        req,
+                                                        authorization.await,
                                                     )
-                                                }
-                                            }
-                                            NostrConnectRequest::GetPublicKey => {
-                                                NostrConnectResponse::with_result(
-                                                    ResponseResult::GetPublicKey(
-                                                        self.keys.user.public_key(),
-                                                    ),
-                                                )
+                                                    .await
                                             }
-                                            NostrConnectRequest::Nip04Encrypt {
-                                                public_key,
-                                                text,
-                                            } => {
-                                                match nip04::encrypt(
-                                                    self.keys.user.secret_key(),
-                                                    &public_key,
-                                                    text,
-                                                ) {
-                                                    Ok(ciphertext) => {
-                                                        NostrConnectResponse::with_result(
-                                                            ResponseResult::Nip04Encrypt {
-                                                                ciphertext,
-                                                            },
-                                                        )
-                                                    }
-                                                    Err(e) => NostrConnectResponse::with_error(
-                                                        e.to_string(),
-                                                    ),
-                                                }
-                                            }
-                                            NostrConnectRequest::Nip04Decrypt {
-                                                public_key,
-                                                ciphertext,
-                                            } => {
-                                                match nip04::decrypt(
-                                                    self.keys.user.secret_key(),
-                                                    &public_key,
-                                                    ciphertext,
-                                                ) {
-                                                    Ok(plaintext) => {
-                                                        NostrConnectResponse::with_result(
-                                                            ResponseResult::Nip04Decrypt {
-                                                                plaintext,
-                                                            },
-                                                        )
-                                                    }
-                                                    Err(e) => NostrConnectResponse::with_error(
-                                                        e.to_string(),
-                                                    ),
-                                                }
-                                            }
-                                            NostrConnectRequest::Nip44Encrypt {
-                                                public_key,
-                                                text,
-                                            } => {
-                                                match nip44::encrypt(
-                                                    self.keys.user.secret_key(),
-                                                    &public_key,
-                                                    text,
-                                                    nip44::Version::default(),
-                                                ) {
-                                                    Ok(ciphertext) => {
-                                                        NostrConnectResponse::with_result(
-                                                            ResponseResult::Nip44Encrypt {
-                                                                ciphertext,
-                                                            },
-                                                        )
-                                                    }
-                                                    Err(e) => NostrConnectResponse::with_error(
-                                                        e.to_string(),
-                                                    ),
-                                                }
-                                            }
-                                            NostrConnectRequest::Nip44Decrypt {
-                                                public_key,
-                                                ciphertext,
-                                            } => {
-                                                match nip44::decrypt(
-                                                    self.keys.user.secret_key(),
-                                                    &public_key,
-                                                    ciphertext,
-                                                ) {
-                                                    Ok(plaintext) => {
-                                                        NostrConnectResponse::with_result(
-                                                            ResponseResult::Nip44Decrypt {
-                                                                plaintext,
-                                                            },
-                                                        )
-                                                    }
-                                                    Err(e) => NostrConnectResponse::with_error(
-                                                        e.to_string(),
-                                                    ),
-                                                }
-                                            }
-                                            NostrConnectRequest::SignEvent(unsigned) => {
-                                                match unsigned.sign_with_keys(&self.keys.user) {
-                                                    Ok(event) => NostrConnectResponse::with_result(
-                                                        ResponseResult::SignEvent(Box::new(event)),
-                                                    ),
-                                                    Err(e) => NostrConnectResponse::with_error(
-                                                        e.to_string(),
-                                                    ),
-                                                }
-                                            }
-                                            NostrConnectRequest::Ping => {
-                                                NostrConnectResponse::with_result(
-                                                    ResponseResult::Pong,
-                                                )
-                                            }
-                                        }
-                                    } else {
-                                        NostrConnectResponse::with_error("Rejected")
-                                    };
+                                            Err(response) => response,
+                                        };
 
-                                    // Compose message
-                                    let msg: NostrConnectMessage =
-                                        NostrConnectMessage::response(id, response);
-
-                                    // Compose and publish event
-                                    let event = EventBuilder::nostr_connect(
-                                        &self.keys.signer,
-                                        event.pubkey,
-                                        msg,
-                                    )?
-                                    .sign_with_keys(&self.keys.signer)?;
-                                    self.pool.send_event(&event).await?;
+                                        if let Err(e) =
+                                            signer.send_response(public_key, id, response).await
+                                        {
+                                            tracing::error!(error = %e, "Impossible to send response.");
+                                        }
+                                    });
                                 }
                             }
                             Err(e) => {
@@ -343,6 +498,154 @@ impl NostrConnectRemoteSigner {
 
         Ok(())
     }
+
+    /// Check a request before asking for the approval
+    ///
+    /// The session of the client is checked (see [`NostrConnectRemoteSigner::touch_session`]).
+    /// The secret of a `connect` request is checked before asking for the approval,
+    /// to not prompt the user for connections that would be refused anyway.
+    async fn check_request(
+        &self,
+        public_key: &PublicKey,
+        req: &NostrConnectRequest,
+    ) -> Result<(), NostrConnectResponse> {
+        self.touch_session(public_key, req).await?;
+
+        if let NostrConnectRequest::Connect { secret, .. } = req {
+            if !self.is_valid_secret(secret.as_ref()).await {
+                return Err(NostrConnectResponse::with_error("Secret not match"));
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn send_response(
+        &self,
+        public_key: PublicKey,
+        id: String,
+        response: NostrConnectResponse,
+    ) -> Result<(), Error> {
+        let msg: NostrConnectMessage = NostrConnectMessage::response(id, response);
+        let event: Event = EventBuilder::nostr_connect(&self.keys.signer, public_key, msg)?
+            .sign_with_keys(&self.keys.signer)?;
+        self.pool.send_event(&event).await?;
+        Ok(())
+    }
+
+    async fn handle_authorized_request(
+        &self,
+        permissions: Option<&NostrConnectPermissions>,
+        public_key: PublicKey,
+        req: NostrConnectRequest,
+        authorization: NostrConnectAuthorization,
+    ) -> NostrConnectResponse {
+        match authorization {
+            NostrConnectAuthorization::Rejected => NostrConnectResponse::with_error("Rejected"),
+            NostrConnectAuthorization::Approved => self.handle_request(public_key, req).await,
+            NostrConnectAuthorization::Connect(grants) => {
+                let response: NostrConnectResponse = self.handle_request(public_key, req).await;
+
+                // Grant the permissions only if the connection has been accepted
+                if let (Some(permissions), None) = (permissions, &response.error) {
+                    if let Err(e) = permissions.commit(&public_key, grants).await {
+                        tracing::error!(error = %e, "Impossible to save permissions.");
+                    }
+                }
+
+                response
+            }
+        }
+    }
+
+    async fn handle_request(
+        &self,
+        public_key: PublicKey,
+        req: NostrConnectRequest,
+    ) -> NostrConnectResponse {
+        match req {
+            NostrConnectRequest::Connect { secret, .. } => {
+                if self.match_secret(secret.as_ref()).await {
+                    self.open_session(public_key, secret).await;
+                    NostrConnectResponse::with_result(ResponseResult::Ack)
+                } else {
+                    NostrConnectResponse::with_error("Secret not match")
+                }
+            }
+            NostrConnectRequest::GetPublicKey => NostrConnectResponse::with_result(
+                ResponseResult::GetPublicKey(self.keys.user.public_key()),
+            ),
+            NostrConnectRequest::Nip04Encrypt { public_key, text } => {
+                match nip04::encrypt(self.keys.user.secret_key(), &public_key, text) {
+                    Ok(ciphertext) => {
+                        NostrConnectResponse::with_result(ResponseResult::Nip04Encrypt {
+                            ciphertext,
+                        })
+                    }
+                    Err(e) => NostrConnectResponse::with_error(e.to_string()),
+                }
+            }
+            NostrConnectRequest::Nip04Decrypt {
+                public_key,
+                ciphertext,
+            } => match nip04::decrypt(self.keys.user.secret_key(), &public_key, ciphertext) {
+                Ok(plaintext) => {
+                    NostrConnectResponse::with_result(ResponseResult::Nip04Decrypt { plaintext })
+                }
+                Err(e) => NostrConnectResponse::with_error(e.to_string()),
+            },
+            NostrConnectRequest::Nip44Encrypt { public_key, text } => {
+                match nip44::encrypt(
+                    self.keys.user.secret_key(),
+                    &public_key,
+                    text,
+                    nip44::Version::default(),
+                ) {
+                    Ok(ciphertext) => {
+                        NostrConnectResponse::with_result(ResponseResult::Nip44Encrypt {
+                            ciphertext,
+                        })
+                    }
+                    Err(e) => NostrConnectResponse::with_error(e.to_string()),
+                }
+            }
+            NostrConnectRequest::Nip44Decrypt {
+                public_key,
+                ciphertext,
+            } => match nip44::decrypt(self.keys.user.secret_key(), &public_key, ciphertext) {
+                Ok(plaintext) => {
+                    NostrConnectResponse::with_result(ResponseResult::Nip44Decrypt { plaintext })
+                }
+                Err(e) => NostrConnectResponse::with_error(e.to_string()),
+            },
+            NostrConnectRequest::SignEvent(unsigned) => {
+                match unsigned.sign_with_keys(&self.keys.user) {
+                    Ok(event) => NostrConnectResponse::with_result(ResponseResult::SignEvent(
+                        Box::new(event),
+                    )),
+                    Err(e) => NostrConnectResponse::with_error(e.to_string()),
+                }
+            }
+            NostrConnectRequest::Ping => NostrConnectResponse::with_result(ResponseResult::Pong),
+            NostrConnectRequest::SwitchRelays => {
+                let relays: Vec<RelayUrl> = self.relays().await;
+
+                let mut sessions = self.sessions.write().await;
+                let changed: bool = match sessions.get_mut(&public_key) {
+                    Some(session) if session.relays == relays => false,
+                    Some(session) => {
+                        session.relays = relays.clone();
+                        true
+                    }
+                    None => true,
+                };
+
+                NostrConnectResponse::with_result(ResponseResult::SwitchRelays(
+                    changed.then_some(relays),
+                ))
+            }
+        }
+    }
 }
 
 /// Nostr Connect signer actions
@@ -350,3 +653,437 @@ pub trait NostrConnectSignerActions {
     /// Approve
     fn approve(&self, public_key: &PublicKey, req: &NostrConnectRequest) -> bool;
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+    use std::time::Duration;
+
+    use async_utility::time;
+    use nostr_relay_builder::prelude::*;
+
+    use super::*;
+    use crate::client::NostrConnect;
+    use crate::permission::{MemoryPermissionStore, NostrConnectDecision, NostrConnectPrompt};
+
+    struct ApproveAll;
+
+    impl NostrConnectSignerActions for ApproveAll {
+        fn approve(&self, _public_key: &PublicKey, _req: &NostrConnectRequest) -> bool {
+            true
+        }
+    }
+
+    #[derive(Debug, Default)]
+    struct ApprovePrompt {
+        prompts: Arc<AtomicUsize>,
+    }
+
+    impl NostrConnectPrompt for ApprovePrompt {
+        fn prompt<'a>(
+            &'a self,
+            _client: &'a PublicKey,
+            _req: &'a NostrConnectRequest,
+        ) -> BoxedFuture<'a, NostrConnectDecision> {
+            Box::pin(async move {
+                self.prompts.fetch_add(1, Ordering::SeqCst);
+                NostrConnectDecision::Approve
+            })
+        }
+    }
+
+    /// Check, authorize and handle a request, as the served signer does
+    async fn process_request(
+        signer: &NostrConnectRemoteSigner,
+        permissions: &NostrConnectPermissions,
+        public_key: PublicKey,
+        req: NostrConnectRequest,
+    ) -> NostrConnectResponse {
+        if let Err(response) = signer.check_request(&public_key, &req).await {
+            return response;
+        }
+
+        let authorization = permissions.authorize(&public_key, &req).await.unwrap();
+        signer
+            .handle_authorized_request(Some(permissions), public_key, req, authorization)
+            .await
+    }
+
+    #[tokio::test]
+    async fn test_connect_grants_require_secret() {
+        let keys = NostrConnectKeys {
+            signer: Keys::generate(),
+            user: Keys::generate(),
+        };
+        let signer = NostrConnectRemoteSigner::new(
+            keys.clone(),
+            Vec::<RelayUrl>::new(),
+            Some(String::from("main-secret")),
+            None,
+        )
+        .unwrap();
+        let permissions = NostrConnectPermissions::new(
+            MemoryPermissionStore::new(),
+            ApprovePrompt::default(),
+            None,
+        );
+
+        let client: PublicKey = Keys::generate().public_key();
+        let connect = |secret: &str| NostrConnectRequest::Connect {
+            public_key: keys.user.public_key(),
+            secret: Some(secret.to_string()),
+            perms: NostrConnectPermission::parse_list("sign_event:1"),
+        };
+
+        // Wrong secret: nothing is granted
+        let req = connect("wrong-secret");
+        let authorization = permissions.authorize(&client, &req).await.unwrap();
+        assert!(authorization.is_approved());
+        let response = signer
+            .handle_authorized_request(Some(&permissions), client, req, authorization)
+            .await;
+        assert!(response.error.is_some());
+        assert!(signer.session(&client).await.is_none());
+        assert!(permissions
+            .store()
+            .grants(&client)
+            .await
+            .unwrap()
+            .is_empty());
+
+        // Right secret
+        let req = connect("main-secret");
+        let authorization = permissions.authorize(&client, &req).await.unwrap();
+        let response = signer
+            .handle_authorized_request(Some(&permissions), client, req, authorization)
+            .await;
+        assert!(response.error.is_none());
+        assert!(signer.session(&client).await.is_some());
+        assert_eq!(permissions.store().grants(&client).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_connect_secret_checked_before_prompt() {
+        let keys = NostrConnectKeys {
+            signer: Keys::generate(),
+            user: Keys::generate(),
+        };
+        let signer = NostrConnectRemoteSigner::new(
+            keys.clone(),
+            Vec::<RelayUrl>::new(),
+            Some(String::from("main-secret")),
+            None,
+        )
+        .unwrap();
+        let prompts = Arc::new(AtomicUsize::new(0));
+        let permissions = NostrConnectPermissions::new(
+            MemoryPermissionStore::new(),
+            ApprovePrompt {
+                prompts: prompts.clone(),
+            },
+            None,
+        );
+
+        let client: PublicKey = Keys::generate().public_key();
+        let connect = |secret: &str| NostrConnectRequest::Connect {
+            public_key: keys.user.public_key(),
+            secret: Some(secret.to_string()),
+            perms: NostrConnectPermission::parse_list("sign_event:1"),
+        };
+
+        // Wrong secret: the user is not prompted
+        let response =
+            process_request(&signer, &permissions, client, connect("wrong-secret")).await;
+        assert_eq!(response.error.as_deref(), Some("Secret not match"));
+        assert_eq!(prompts.load(Ordering::SeqCst), 0);
+        assert!(signer.session(&client).await.is_none());
+
+        // Single-use secret: prompted, then consumed
+        let uri = signer.new_bunker_uri().await;
+        let secret: String = uri.secret().unwrap().to_string();
+        let response = process_request(&signer, &permissions, client, connect(&secret)).await;
+        assert!(response.error.is_none());
+        assert_eq!(prompts.load(Ordering::SeqCst), 1);
+        assert!(signer.session(&client).await.is_some());
+        assert_eq!(permissions.store().grants(&client).await.unwrap().len(), 1);
+
+        let other: PublicKey = Keys::generate().public_key();
+        let response = process_request(&signer, &permissions, other, connect(&secret)).await;
+        assert!(response.error.is_some());
+        assert_eq!(prompts.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_revoke_session_revokes_grants() {
+        let keys = NostrConnectKeys {
+            signer: Keys::generate(),
+            user: Keys::generate(),
+        };
+        let signer = NostrConnectRemoteSigner::new(
+            keys.clone(),
+            Vec::<RelayUrl>::new(),
+            Some(String::from("main-secret")),
+            None,
+        )
+        .unwrap();
+        let prompts = Arc::new(AtomicUsize::new(0));
+        let permissions = NostrConnectPermissions::new(
+            MemoryPermissionStore::new(),
+            ApprovePrompt {
+                prompts: prompts.clone(),
+            },
+            None,
+        );
+        *signer.permissions.write().await = Some(permissions.clone());
+
+        let client: PublicKey = Keys::generate().public_key();
+        let connect = NostrConnectRequest::Connect {
+            public_key: keys.user.public_key(),
+            secret: Some(String::from("main-secret")),
+            perms: NostrConnectPermission::parse_list("sign_event:1"),
+        };
+        let sign = || {
+            NostrConnectRequest::SignEvent(
+                EventBuilder::text_note("hello").build(keys.user.public_key()),
+            )
+        };
+
+        // Connect: prompted once, then the granted requests are approved automatically
+        let response = process_request(&signer, &permissions, client, connect).await;
+        assert!(response.error.is_none());
+        let response = process_request(&signer, &permissions, client, sign()).await;
+        assert!(response.error.is_none());
+        assert_eq!(prompts.load(Ordering::SeqCst), 1);
+
+        // Revoke: the grants are removed
+        assert!(signer.revoke_session(&client).await.unwrap());
+        assert!(permissions
+            .store()
+            .grants(&client)
+            .await
+            .unwrap()
+            .is_empty());
+
+        // Allowed again: the client must connect again and doesn't get the old permissions back
+        assert!(signer.allow_client(&client).await);
+        let response = process_request(&signer, &permissions, client, sign()).await;
+        assert_eq!(response.error.as_deref(), Some("Not connected"));
+        let connect = NostrConnectRequest::Connect {
+            public_key: keys.user.public_key(),
+            secret: Some(String::from("main-secret")),
+            perms: Vec::new(),
+        };
+        let response = process_request(&signer, &permissions, client, connect).await;
+        assert!(response.error.is_none());
+        assert_eq!(prompts.load(Ordering::SeqCst), 2);
+        let response = process_request(&signer, &permissions, client, sign()).await;
+        assert!(response.error.is_none());
+        assert_eq!(prompts.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_sessions_and_switch_relays() {
+        let mock1 = MockRelay::run().await.unwrap();
+        let mock2 = MockRelay::run().await.unwrap();
+        let url1 = RelayUrl::parse(&mock1.url()).unwrap();
+        let url2 = RelayUrl::parse(&mock2.url()).unwrap();
+
+        let keys = NostrConnectKeys {
+            signer: Keys::generate(),
+            user: Keys::generate(),
+        };
+        let signer = NostrConnectRemoteSigner::new(
+            keys.clone(),
+            [url1.clone()],
+            Some(String::from("main-secret")),
+            None,
+        )
+        .unwrap();
+
+        // Client connected with a single-use secret
+        let uri = signer.new_bunker_uri().await;
+        let secret: String = uri.secret().unwrap().to_string();
+        assert_ne!(secret, "main-secret");
+
+        let s = signer.clone();
+        tokio::spawn(async move { s.serve(ApproveAll).await });
+        time::sleep(Duration::from_millis(500)).await;
+
+        let client_keys = Keys::generate();
+        let client =
+            NostrConnect::new(uri, client_keys.clone(), Duration::from_secs(10), None).unwrap();
+        let client_public_key: PublicKey = client_keys.public_key();
+        assert_eq!(
+            client.get_public_key().await.unwrap(),
+            keys.user.public_key()
+        );
+
+        let session = signer.session(&client_public_key).await.unwrap();
+        assert_eq!(session.secret, Some(secret));
+        assert_eq!(session.relays, vec![url1.clone()]);
+        assert_eq!(signer.sessions().await.len(), 1);
+
+        // Nothing to switch
+        assert!(!client.switch_relays().await.unwrap());
+
+        // Switch relays
+        signer.switch_relays([url2.clone()]).await.unwrap();
+        assert!(client.switch_relays().await.unwrap());
+        assert_eq!(client.relays().await, vec![url2.clone()]);
+        assert_eq!(
+            signer.session(&client_public_key).await.unwrap().relays,
+            vec![url2]
+        );
+
+        // Requests go through the new relay
+        let unsigned = EventBuilder::text_note("hello").build(keys.user.public_key());
+        assert!(client.sign_event(unsigned.clone()).await.is_ok());
+
+        // Revoke
+        assert!(signer.revoke_session(&client_public_key).await.unwrap());
+        assert!(signer.session(&client_public_key).await.is_none());
+        let err = client.sign_event(unsigned).await.unwrap_err();
+        assert!(err.to_string().contains("Session revoked"));
+
+        // Can't connect again, also with a new secret
+        let uri = signer.new_bunker_uri().await;
+        let client =
+            NostrConnect::new(uri, client_keys.clone(), Duration::from_secs(10), None).unwrap();
+        let err = client.get_public_key().await.unwrap_err();
+        assert!(err.to_string().contains("Session revoked"));
+        assert!(signer.session(&client_public_key).await.is_none());
+
+        // Allow the client again
+        assert!(signer.allow_client(&client_public_key).await);
+        assert!(!signer.allow_client(&client_public_key).await);
+        let uri = signer.new_bunker_uri().await;
+        let client = NostrConnect::new(uri, client_keys, Duration::from_secs(10), None).unwrap();
+        assert_eq!(
+            client.get_public_key().await.unwrap(),
+            keys.user.public_key()
+        );
+        assert!(signer.session(&client_public_key).await.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_requests_require_connect() {
+        let keys = NostrConnectKeys {
+            signer: Keys::generate(),
+            user: Keys::generate(),
+        };
+        let signer = NostrConnectRemoteSigner::new(
+            keys.clone(),
+            Vec::<RelayUrl>::new(),
+            Some(String::from("main-secret")),
+            None,
+        )
+        .unwrap();
+        let prompts = Arc::new(AtomicUsize::new(0));
+        let store = MemoryPermissionStore::new();
+        let permissions = NostrConnectPermissions::new(
+            store,
+            ApprovePrompt {
+                prompts: prompts.clone(),
+            },
+            None,
+        );
+
+        // Requests sent without connecting first are rejected, without prompting or granting anything
+        let client: PublicKey = Keys::generate().public_key();
+        let sign = NostrConnectRequest::SignEvent(
+            EventBuilder::text_note("hello").build(keys.user.public_key()),
+        );
+        let response = process_request(&signer, &permissions, client, sign).await;
+        assert_eq!(response.error.as_deref(), Some("Not connected"));
+        let decrypt = NostrConnectRequest::Nip44Decrypt {
+            public_key: Keys::generate().public_key(),
+            ciphertext: String::from("ciphertext"),
+        };
+        let response = process_request(&signer, &permissions, client, decrypt).await;
+        assert_eq!(response.error.as_deref(), Some("Not connected"));
+        assert_eq!(prompts.load(Ordering::SeqCst), 0);
+        assert!(permissions
+            .store()
+            .grants(&client)
+            .await
+            .unwrap()
+            .is_empty());
+
+        // Without a secret, the clients don't need to connect
+        let signer =
+            NostrConnectRemoteSigner::new(keys.clone(), Vec::<RelayUrl>::new(), None, None)
+                .unwrap();
+        let response =
+            process_request(&signer, &permissions, client, NostrConnectRequest::Ping).await;
+        assert!(response.error.is_none());
+    }
+
+    /// Prompt that never answers to the sign requests of a client
+    #[derive(Debug)]
+    struct BlockingPrompt {
+        blocked: PublicKey,
+    }
+
+    impl NostrConnectPrompt for BlockingPrompt {
+        fn prompt<'a>(
+            &'a self,
+            client: &'a PublicKey,
+            req: &'a NostrConnectRequest,
+        ) -> BoxedFuture<'a, NostrConnectDecision> {
+            Box::pin(async move {
+                if client == &self.blocked && matches!(req, NostrConnectRequest::SignEvent(..)) {
+                    std::future::pending::<()>().await;
+                }
+
+                NostrConnectDecision::Approve
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_pending_prompt_does_not_block_other_clients() {
+        let mock = MockRelay::run().await.unwrap();
+        let url = RelayUrl::parse(&mock.url()).unwrap();
+
+        let keys = NostrConnectKeys {
+            signer: Keys::generate(),
+            user: Keys::generate(),
+        };
+        let signer = NostrConnectRemoteSigner::new(
+            keys.clone(),
+            [url],
+            Some(String::from("main-secret")),
+            None,
+        )
+        .unwrap();
+
+        let blocked_keys = Keys::generate();
+        let permissions = NostrConnectPermissions::new(
+            MemoryPermissionStore::new(),
+            BlockingPrompt {
+                blocked: blocked_keys.public_key(),
+            },
+            None,
+        );
+
+        let s = signer.clone();
+        tokio::spawn(async move { s.serve_with_permissions(permissions).await });
+        time::sleep(Duration::from_millis(500)).await;
+
+        let uri = signer.bunker_uri().await;
+        let blocked =
+            NostrConnect::new(uri.clone(), blocked_keys, Duration::from_secs(10), None).unwrap();
+        let other = NostrConnect::new(uri, Keys::generate(), Duration::from_secs(5), None).unwrap();
+        blocked.get_public_key().await.unwrap();
+        other.get_public_key().await.unwrap();
+
+        // The sign request of the first client waits for the user
+        let unsigned = EventBuilder::text_note("hello").build(keys.user.public_key());
+        let u = unsigned.clone();
+        tokio::spawn(async move { blocked.sign_event(u).await });
+        time::sleep(Duration::from_millis(500)).await;
+
+        // The other client is served in the meantime
+        assert!(other.sign_event(unsigned).await.is_ok());
+    }
+}
@@ -0,0 +1,109 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2025 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! FROST coordinator
+
+use std::collections::BTreeMap;
+
+use nostr::secp256k1::rand;
+use nostr::secp256k1::schnorr::Signature;
+use nostr::UnsignedEvent;
+
+use crate::error::Error;
+use crate::keys::{Identifier, PublicKeyPackage};
+use crate::signing::{self, SignatureShare, SigningCommitments, SigningPackage};
+use crate::transport::FrostTransport;
+
+/// FROST coordinator
+///
+/// Drives the signing rounds, talking with the participants through a [`FrostTransport`].
+#[derive(Debug, Clone)]
+pub struct FrostCoordinator<T> {
+    public_key_package: PublicKeyPackage,
+    transport: T,
+}
+
+impl<T> FrostCoordinator<T>
+where
+    T: FrostTransport,
+{
+    /// Construct new coordinator
+    #[inline]
+    pub fn new(public_key_package: PublicKeyPackage, transport: T) -> Self {
+        Self {
+            public_key_package,
+            transport,
+        }
+    }
+
+    /// Get the public key package
+    #[inline]
+    pub fn public_key_package(&self) -> &PublicKeyPackage {
+        &self.public_key_package
+    }
+
+    /// Get the transport
+    #[inline]
+    pub fn transport(&self) -> &T {
+        &self.transport
+    }
+
+    /// Sign an event
+    ///
+    /// The participants are asked for their commitments in order of identifier,
+    /// until the threshold is reached: the unreachable ones are skipped.
+    ///
+    /// The event is sent to the participants, which check its ID before signing.
+    pub async fn sign(&self, unsigned: &UnsignedEvent) -> Result<Signature, Error> {
+        unsigned.verify_id()?;
+
+        let message: [u8; 32] = signing::event_id(unsigned).to_bytes();
+        let session: String = format!("{:032x}", rand::random::<u128>());
+        let threshold: u16 = self.public_key_package.threshold();
+
+        // Round 1: collect the commitments
+        let mut commitments: Vec<SigningCommitments> = Vec::with_capacity(threshold as usize);
+        for identifier in self.public_key_package.identifiers() {
+            if commitments.len() >= threshold as usize {
+                break;
+            }
+
+            match self.transport.commit(*identifier, &session).await {
+                Ok(c) if c.identifier == *identifier => commitments.push(c),
+                Ok(..) => {
+                    tracing::warn!(participant = %identifier, "Received commitments of another participant.");
+                }
+                Err(e) => {
+                    tracing::warn!(participant = %identifier, error = %e, "Can't get commitments.");
+                }
+            }
+        }
+
+        if commitments.len() < threshold as usize {
+            return Err(Error::NotEnoughParticipants {
+                threshold,
+                available: commitments.len(),
+            });
+        }
+
+        let package: SigningPackage = SigningPackage::new(message, commitments);
+
+        // Round 2: collect the signature shares
+        let mut shares: BTreeMap<Identifier, SignatureShare> = BTreeMap::new();
+        for identifier in package.commitments().keys() {
+            let share: SignatureShare = self
+                .transport
+                .sign(*identifier, &session, unsigned, &package)
+                .await?;
+
+            if share.identifier != *identifier {
+                return Err(Error::InvalidSignatureShare(*identifier));
+            }
+
+            shares.insert(*identifier, share);
+        }
+
+        signing::aggregate(&package, &shares, &self.public_key_package)
+    }
+}
@@ -0,0 +1,362 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2025 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Distributed key generation (DKG)
+//!
+//! Generate the key packages without a trusted dealer: the secret key is never known by anyone.
+//!
+//! 1. Every participant calls [`part1`] and broadcasts the [`Round1Package`] to the others;
+//! 2. Every participant calls [`part2`] with the others round 1 packages and sends each [`Round2Package`] **privately** to its receiver;
+//! 3. Every participant calls [`part3`] with the round 2 packages received from the others.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use nostr::secp256k1::{PublicKey as Point, SecretKey};
+use serde::{Deserialize, Serialize};
+
+use crate::error::Error;
+use crate::keys::{self, Identifier, KeyPackage, PublicKeyPackage};
+use crate::scalar;
+
+const PROOF_OF_KNOWLEDGE_TAG: &str = "nostr-frost/dkg";
+
+/// Round 1 secret package
+///
+/// Must be kept private by the participant until [`part2`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Round1SecretPackage {
+    identifier: Identifier,
+    coefficients: Vec<SecretKey>,
+    commitments: Vec<Point>,
+    threshold: u16,
+    max_signers: u16,
+}
+
+/// Round 1 package
+///
+/// Must be broadcast to all the other participants.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Round1Package {
+    /// Sender
+    pub identifier: Identifier,
+    /// Polynomial commitments
+    pub commitments: Vec<Point>,
+    /// Proof of knowledge of the secret: nonce commitment
+    pub proof_r: Point,
+    /// Proof of knowledge of the secret: response
+    pub proof_z: SecretKey,
+}
+
+/// Round 2 secret package
+///
+/// Must be kept private by the participant until [`part3`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Round2SecretPackage {
+    identifier: Identifier,
+    own_share: SecretKey,
+    commitments: BTreeMap<Identifier, Vec<Point>>,
+    threshold: u16,
+}
+
+/// Round 2 package
+///
+/// Contains a secret share: must be sent **privately** to the receiver.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Round2Package {
+    /// Sender
+    pub sender: Identifier,
+    /// Receiver
+    pub receiver: Identifier,
+    /// Secret share for the receiver
+    pub secret_share: SecretKey,
+}
+
+fn proof_of_knowledge_challenge(
+    identifier: &Identifier,
+    commitment: &Point,
+    r: &Point,
+) -> Result<SecretKey, Error> {
+    scalar::hash_to_scalar(
+        PROOF_OF_KNOWLEDGE_TAG,
+        &[
+            &identifier.as_u16().to_be_bytes(),
+            &commitment.serialize(),
+            &r.serialize(),
+        ],
+    )
+}
+
+/// DKG round 1
+pub fn part1(
+    identifier: Identifier,
+    threshold: u16,
+    max_signers: u16,
+) -> Result<(Round1SecretPackage, Round1Package), Error> {
+    keys::check_threshold(threshold, max_signers)?;
+
+    let coefficients: Vec<SecretKey> = (0..threshold).map(|_| scalar::random()).collect();
+    let commitments: Vec<Point> = coefficients.iter().map(scalar::point).collect();
+
+    // Prove the knowledge of the constant term
+    let k: SecretKey = scalar::random();
+    let proof_r: Point = scalar::point(&k);
+    let c: SecretKey = proof_of_knowledge_challenge(&identifier, &commitments[0], &proof_r)?;
+    let proof_z: SecretKey = scalar::add(&k, &scalar::mul(&coefficients[0], &c)?)?;
+
+    let package: Round1Package = Round1Package {
+        identifier,
+        commitments: commitments.clone(),
+        proof_r,
+        proof_z,
+    };
+    let secret: Round1SecretPackage = Round1SecretPackage {
+        identifier,
+        coefficients,
+        commitments,
+        threshold,
+        max_signers,
+    };
+
+    Ok((secret, package))
+}
+
+/// DKG round 2
+///
+/// Verify the round 1 packages of the other participants and compute their secret shares.
+pub fn part2(
+    secret: Round1SecretPackage,
+    round1_packages: &[Round1Package],
+) -> Result<(Round2SecretPackage, Vec<Round2Package>), Error> {
+    let others: usize = secret.max_signers as usize - 1;
+    if round1_packages.len() != others {
+        return Err(Error::NotEnoughParticipants {
+            threshold: secret.max_signers,
+            available: round1_packages.len() + 1,
+        });
+    }
+
+    let mut commitments: BTreeMap<Identifier, Vec<Point>> = BTreeMap::new();
+    commitments.insert(secret.identifier, secret.commitments.clone());
+
+    let mut packages: Vec<Round2Package> = Vec::with_capacity(others);
+
+    for package in round1_packages.iter() {
+        if package.commitments.len() != secret.threshold as usize {
+            return Err(Error::InvalidProofOfKnowledge(package.identifier));
+        }
+
+        // Verify proof of knowledge: z * G == R + c * C0
+        let c: SecretKey = proof_of_knowledge_challenge(
+            &package.identifier,
+            &package.commitments[0],
+            &package.proof_r,
+        )?;
+        let expected: Point = scalar::point_add(
+            &package.proof_r,
+            &scalar::point_mul(&package.commitments[0], &c)?,
+        )?;
+        if scalar::point(&package.proof_z) != expected {
+            return Err(Error::InvalidProofOfKnowledge(package.identifier));
+        }
+
+        if commitments
+            .insert(package.identifier, package.commitments.clone())
+            .is_some()
+        {
+            return Err(Error::DuplicatedIdentifier(package.identifier));
+        }
+
+        packages.push(Round2Package {
+            sender: secret.identifier,
+            receiver: package.identifier,
+            secret_share: scalar::polynomial_evaluate(
+                &secret.coefficients,
+                &package.identifier.to_scalar()?,
+            )?,
+        });
+    }
+
+    let own_share: SecretKey =
+        scalar::polynomial_evaluate(&secret.coefficients, &secret.identifier.to_scalar()?)?;
+
+    Ok((
+        Round2SecretPackage {
+            identifier: secret.identifier,
+            own_share,
+            commitments,
+            threshold: secret.threshold,
+        },
+        packages,
+    ))
+}
+
+/// DKG round 3
+///
+/// Verify the secret shares received from the other participants and compute the key packages.
+pub fn part3(
+    secret: &Round2SecretPackage,
+    round2_packages: &[Round2Package],
+) -> Result<(KeyPackage, PublicKeyPackage), Error> {
+    let x: SecretKey = secret.identifier.to_scalar()?;
+
+    let mut secret_share: SecretKey = secret.own_share;
+    let mut senders: BTreeSet<Identifier> = BTreeSet::new();
+
+    for package in round2_packages.iter() {
+        if package.receiver != secret.identifier {
+            return Err(Error::UnexpectedMessage);
+        }
+
+        let commitments: &Vec<Point> = match secret.commitments.get(&package.sender) {
+            Some(commitments) if package.sender != secret.identifier => commitments,
+            _ => return Err(Error::UnknownParticipant(package.sender)),
+        };
+
+        if !senders.insert(package.sender) {
+            return Err(Error::DuplicatedIdentifier(package.sender));
+        }
+
+        // Verify the share against the sender commitments
+        if scalar::point(&package.secret_share) != scalar::commitment_evaluate(commitments, &x)? {
+            return Err(Error::InvalidSecretShare(package.sender));
+        }
+
+        secret_share = scalar::add(&secret_share, &package.secret_share)?;
+    }
+
+    if senders.len() + 1 != secret.commitments.len() {
+        return Err(Error::NotEnoughParticipants {
+            threshold: secret.commitments.len() as u16,
+            available: senders.len() + 1,
+        });
+    }
+
+    // Group public key: sum of the constant term commitments
+    let constant_terms: Vec<&Point> = secret.commitments.values().map(|c| &c[0]).collect();
+    let group_public_key: Point = Point::combine_keys(&constant_terms)?;
+
+    // Public keys of the shares of all the participants
+    let mut verifying_shares: BTreeMap<Identifier, Point> = BTreeMap::new();
+    for identifier in secret.commitments.keys() {
+        let x: SecretKey = identifier.to_scalar()?;
+        let points: Vec<Point> = secret
+            .commitments
+            .values()
+            .map(|commitments| scalar::commitment_evaluate(commitments, &x))
+            .collect::<Result<_, _>>()?;
+        let points: Vec<&Point> = points.iter().collect();
+        verifying_shares.insert(*identifier, Point::combine_keys(&points)?);
+    }
+
+    let key_package: KeyPackage = KeyPackage::new(
+        secret.identifier,
+        secret_share,
+        group_public_key,
+        secret.threshold,
+    );
+
+    if verifying_shares.get(&secret.identifier) != Some(key_package.verifying_share()) {
+        return Err(Error::InvalidSecretShare(secret.identifier));
+    }
+
+    Ok((
+        key_package,
+        PublicKeyPackage::new(group_public_key, verifying_shares, secret.threshold),
+    ))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    pub(crate) fn run_dkg(
+        threshold: u16,
+        max_signers: u16,
+    ) -> (BTreeMap<Identifier, KeyPackage>, PublicKeyPackage) {
+        let mut secrets1 = BTreeMap::new();
+        let mut packages1 = Vec::new();
+        for id in 1..=max_signers {
+            let identifier = Identifier::new(id).unwrap();
+            let (secret, package) = part1(identifier, threshold, max_signers).unwrap();
+            secrets1.insert(identifier, secret);
+            packages1.push(package);
+        }
+
+        let mut secrets2 = BTreeMap::new();
+        let mut packages2 = Vec::new();
+        for (identifier, secret) in secrets1.into_iter() {
+            let others: Vec<Round1Package> = packages1
+                .iter()
+                .filter(|p| p.identifier != identifier)
+                .cloned()
+                .collect();
+            let (secret, packages) = part2(secret, &others).unwrap();
+            secrets2.insert(identifier, secret);
+            packages2.extend(packages);
+        }
+
+        let mut key_packages = BTreeMap::new();
+        let mut public_key_package = None;
+        for (identifier, secret) in secrets2.iter() {
+            let received: Vec<Round2Package> = packages2
+                .iter()
+                .filter(|p| &p.receiver == identifier)
+                .cloned()
+                .collect();
+            let (key_package, pkp) = part3(secret, &received).unwrap();
+
+            // All the participants must agree on the public key package
+            if let Some(public_key_package) = &public_key_package {
+                assert_eq!(public_key_package, &pkp);
+            }
+
+            public_key_package = Some(pkp);
+            key_packages.insert(*identifier, key_package);
+        }
+
+        (key_packages, public_key_package.unwrap())
+    }
+
+    #[test]
+    fn test_dkg() {
+        let (key_packages, public_key_package) = run_dkg(2, 3);
+        assert_eq!(key_packages.len(), 3);
+
+        for (identifier, key_package) in key_packages.iter() {
+            assert_eq!(
+                public_key_package.verifying_share(identifier),
+                Some(key_package.verifying_share())
+            );
+            assert_eq!(key_package.public_key(), public_key_package.public_key());
+        }
+    }
+
+    #[test]
+    fn test_dkg_invalid_share() {
+        let id1 = Identifier::new(1).unwrap();
+        let id2 = Identifier::new(2).unwrap();
+
+        let (secret1, package1) = part1(id1, 2, 2).unwrap();
+        let (secret2, package2) = part1(id2, 2, 2).unwrap();
+
+        let (_, mut packages1) = part2(secret1, &[package2]).unwrap();
+        let (secret2, _) = part2(secret2, &[package1.clone()]).unwrap();
+
+        // Tamper the share
+        packages1[0].secret_share = scalar::random();
+        assert!(matches!(
+            part3(&secret2, &packages1),
+            Err(Error::InvalidSecretShare(id)) if id == id1
+        ));
+
+        // Tamper the proof of knowledge
+        let (secret2, _) = part1(id2, 2, 2).unwrap();
+        let mut package1 = package1;
+        package1.proof_z = scalar::random();
+        assert!(matches!(
+            part2(secret2, &[package1]),
+            Err(Error::InvalidProofOfKnowledge(id)) if id == id1
+        ));
+    }
+}
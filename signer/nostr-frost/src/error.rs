@@ -0,0 +1,165 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2025 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! FROST error
+
+use std::fmt;
+
+use nostr::event::builder;
+use nostr::nips::nip44;
+use nostr::{event, secp256k1, serde_json};
+use nostr_relay_pool::pool;
+
+use crate::keys::Identifier;
+
+/// FROST error
+#[derive(Debug)]
+pub enum Error {
+    /// Secp256k1 error
+    Secp256k1(secp256k1::Error),
+    /// Event error
+    Event(event::Error),
+    /// Event builder error
+    Builder(builder::Error),
+    /// NIP44 error
+    NIP44(nip44::Error),
+    /// Json error
+    Json(serde_json::Error),
+    /// Pool
+    Pool(pool::Error),
+    /// Invalid identifier (must be greater than zero)
+    InvalidIdentifier,
+    /// Invalid threshold
+    InvalidThreshold {
+        /// Threshold
+        threshold: u16,
+        /// Max number of signers
+        max_signers: u16,
+    },
+    /// Hash out of the scalar range
+    ScalarOutOfRange,
+    /// Duplicated identifier
+    DuplicatedIdentifier(Identifier),
+    /// Unknown participant
+    UnknownParticipant(Identifier),
+    /// Invalid DKG proof of knowledge
+    InvalidProofOfKnowledge(Identifier),
+    /// Invalid DKG secret share
+    InvalidSecretShare(Identifier),
+    /// Invalid signature share
+    InvalidSignatureShare(Identifier),
+    /// Commitments not found in signing package
+    MissingCommitments(Identifier),
+    /// Commitments indexed by another identifier in signing package
+    InvalidCommitments(Identifier),
+    /// Signing nonces not found
+    NoncesNotFound,
+    /// Not enough participants
+    NotEnoughParticipants {
+        /// Threshold
+        threshold: u16,
+        /// Available participants
+        available: usize,
+    },
+    /// Invalid aggregated signature
+    InvalidSignature,
+    /// The event isn't authored by the group public key
+    PublicKeyMismatch,
+    /// The message of the signing package isn't the event ID
+    MessageMismatch,
+    /// Signing request rejected
+    Rejected,
+    /// Participant error
+    Participant(String),
+    /// Unexpected message
+    UnexpectedMessage,
+    /// Request timeout
+    Timeout,
+}
+
+impl std::error::Error for Error {}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Secp256k1(e) => e.fmt(f),
+            Self::Event(e) => e.fmt(f),
+            Self::Builder(e) => e.fmt(f),
+            Self::NIP44(e) => e.fmt(f),
+            Self::Json(e) => e.fmt(f),
+            Self::Pool(e) => e.fmt(f),
+            Self::InvalidIdentifier => f.write_str("invalid identifier"),
+            Self::InvalidThreshold {
+                threshold,
+                max_signers,
+            } => write!(
+                f,
+                "invalid threshold: threshold={threshold}, max_signers={max_signers}"
+            ),
+            Self::ScalarOutOfRange => f.write_str("scalar out of range"),
+            Self::DuplicatedIdentifier(id) => write!(f, "duplicated identifier: {id}"),
+            Self::UnknownParticipant(id) => write!(f, "unknown participant: {id}"),
+            Self::InvalidProofOfKnowledge(id) => {
+                write!(f, "invalid proof of knowledge: participant={id}")
+            }
+            Self::InvalidSecretShare(id) => write!(f, "invalid secret share: participant={id}"),
+            Self::InvalidSignatureShare(id) => {
+                write!(f, "invalid signature share: participant={id}")
+            }
+            Self::MissingCommitments(id) => write!(f, "missing commitments: participant={id}"),
+            Self::InvalidCommitments(id) => write!(f, "invalid commitments: participant={id}"),
+            Self::NoncesNotFound => f.write_str("signing nonces not found"),
+            Self::NotEnoughParticipants {
+                threshold,
+                available,
+            } => write!(
+                f,
+                "not enough participants: threshold={threshold}, available={available}"
+            ),
+            Self::InvalidSignature => f.write_str("invalid signature"),
+            Self::PublicKeyMismatch => f.write_str("event public key not match group public key"),
+            Self::MessageMismatch => f.write_str("signing package message not match event ID"),
+            Self::Rejected => f.write_str("signing request rejected"),
+            Self::Participant(e) => write!(f, "participant error: {e}"),
+            Self::UnexpectedMessage => f.write_str("unexpected message"),
+            Self::Timeout => f.write_str("timeout"),
+        }
+    }
+}
+
+impl From<secp256k1::Error> for Error {
+    fn from(e: secp256k1::Error) -> Self {
+        Self::Secp256k1(e)
+    }
+}
+
+impl From<event::Error> for Error {
+    fn from(e: event::Error) -> Self {
+        Self::Event(e)
+    }
+}
+
+impl From<builder::Error> for Error {
+    fn from(e: builder::Error) -> Self {
+        Self::Builder(e)
+    }
+}
+
+impl From<nip44::Error> for Error {
+    fn from(e: nip44::Error) -> Self {
+        Self::NIP44(e)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Self::Json(e)
+    }
+}
+
+impl From<pool::Error> for Error {
+    fn from(e: pool::Error) -> Self {
+        Self::Pool(e)
+    }
+}
@@ -0,0 +1,373 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2025 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! FROST keys
+//!
+//! Key packages and trusted dealer key generation.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+use nostr::secp256k1::{PublicKey as Point, SecretKey};
+use nostr::{Keys, PublicKey};
+use serde::de::{self, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::error::Error;
+use crate::scalar;
+
+/// Participant identifier
+///
+/// Must be greater than zero.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Identifier(u16);
+
+impl Identifier {
+    /// Construct new identifier
+    pub fn new(id: u16) -> Result<Self, Error> {
+        if id == 0 {
+            return Err(Error::InvalidIdentifier);
+        }
+
+        Ok(Self(id))
+    }
+
+    /// Get as `u16`
+    #[inline]
+    pub fn as_u16(&self) -> u16 {
+        self.0
+    }
+
+    #[inline]
+    pub(crate) fn to_scalar(self) -> Result<SecretKey, Error> {
+        scalar::from_u16(self.0)
+    }
+
+    /// Lagrange coefficient, at zero, of this identifier in the given set
+    pub(crate) fn lagrange_coefficient(&self, set: &BTreeSet<Self>) -> Result<SecretKey, Error> {
+        let x_i: SecretKey = self.to_scalar()?;
+
+        let mut num: Option<SecretKey> = None;
+        let mut den: Option<SecretKey> = None;
+
+        for id in set.iter().filter(|id| *id != self) {
+            let x_j: SecretKey = id.to_scalar()?;
+            let diff: SecretKey = scalar::sub(&x_j, &x_i)?;
+
+            num = Some(match num {
+                Some(num) => scalar::mul(&num, &x_j)?,
+                None => x_j,
+            });
+            den = Some(match den {
+                Some(den) => scalar::mul(&den, &diff)?,
+                None => diff,
+            });
+        }
+
+        match (num, den) {
+            (Some(num), Some(den)) => scalar::mul(&num, &scalar::invert(&den)?),
+            // Single signer
+            _ => scalar::from_u16(1),
+        }
+    }
+}
+
+impl fmt::Display for Identifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl TryFrom<u16> for Identifier {
+    type Error = Error;
+
+    fn try_from(id: u16) -> Result<Self, Self::Error> {
+        Self::new(id)
+    }
+}
+
+impl From<Identifier> for u16 {
+    fn from(id: Identifier) -> Self {
+        id.0
+    }
+}
+
+impl Serialize for Identifier {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_u16(self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for Identifier {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        // JSON map keys are strings
+        deserializer.deserialize_any(IdentifierVisitor)
+    }
+}
+
+struct IdentifierVisitor;
+
+impl Visitor<'_> for IdentifierVisitor {
+    type Value = Identifier;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a participant identifier")
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        let id: u16 = u16::try_from(v).map_err(de::Error::custom)?;
+        Identifier::new(id).map_err(de::Error::custom)
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        let id: u16 = v.parse().map_err(de::Error::custom)?;
+        Identifier::new(id).map_err(de::Error::custom)
+    }
+}
+
+/// Key package of a participant
+///
+/// Contains the secret share: must be kept private by the participant.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyPackage {
+    identifier: Identifier,
+    secret_share: SecretKey,
+    verifying_share: Point,
+    group_public_key: Point,
+    threshold: u16,
+}
+
+impl KeyPackage {
+    pub(crate) fn new(
+        identifier: Identifier,
+        secret_share: SecretKey,
+        group_public_key: Point,
+        threshold: u16,
+    ) -> Self {
+        Self {
+            identifier,
+            secret_share,
+            verifying_share: scalar::point(&secret_share),
+            group_public_key,
+            threshold,
+        }
+    }
+
+    /// Participant identifier
+    #[inline]
+    pub fn identifier(&self) -> Identifier {
+        self.identifier
+    }
+
+    /// Secret share
+    #[inline]
+    pub fn secret_share(&self) -> &SecretKey {
+        &self.secret_share
+    }
+
+    /// Public key of the secret share
+    #[inline]
+    pub fn verifying_share(&self) -> &Point {
+        &self.verifying_share
+    }
+
+    /// Group public key
+    #[inline]
+    pub fn group_public_key(&self) -> &Point {
+        &self.group_public_key
+    }
+
+    /// Get the nostr public key of the group
+    #[inline]
+    pub fn public_key(&self) -> PublicKey {
+        PublicKey::from(self.group_public_key.x_only_public_key().0)
+    }
+
+    /// Min number of signers
+    #[inline]
+    pub fn threshold(&self) -> u16 {
+        self.threshold
+    }
+}
+
+/// Public key package
+///
+/// Contains the group public key and the public keys of the participants shares.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PublicKeyPackage {
+    group_public_key: Point,
+    verifying_shares: BTreeMap<Identifier, Point>,
+    threshold: u16,
+}
+
+impl PublicKeyPackage {
+    pub(crate) fn new(
+        group_public_key: Point,
+        verifying_shares: BTreeMap<Identifier, Point>,
+        threshold: u16,
+    ) -> Self {
+        Self {
+            group_public_key,
+            verifying_shares,
+            threshold,
+        }
+    }
+
+    /// Group public key
+    #[inline]
+    pub fn group_public_key(&self) -> &Point {
+        &self.group_public_key
+    }
+
+    /// Get the nostr public key of the group
+    #[inline]
+    pub fn public_key(&self) -> PublicKey {
+        PublicKey::from(self.group_public_key.x_only_public_key().0)
+    }
+
+    /// Public keys of the participants shares
+    #[inline]
+    pub fn verifying_shares(&self) -> &BTreeMap<Identifier, Point> {
+        &self.verifying_shares
+    }
+
+    /// Public key of a participant share
+    #[inline]
+    pub fn verifying_share(&self, identifier: &Identifier) -> Option<&Point> {
+        self.verifying_shares.get(identifier)
+    }
+
+    /// Participants identifiers
+    #[inline]
+    pub fn identifiers(&self) -> impl Iterator<Item = &Identifier> + '_ {
+        self.verifying_shares.keys()
+    }
+
+    /// Min number of signers
+    #[inline]
+    pub fn threshold(&self) -> u16 {
+        self.threshold
+    }
+}
+
+pub(crate) fn check_threshold(threshold: u16, max_signers: u16) -> Result<(), Error> {
+    if threshold == 0 || threshold > max_signers {
+        return Err(Error::InvalidThreshold {
+            threshold,
+            max_signers,
+        });
+    }
+
+    Ok(())
+}
+
+/// Split existing keys into `max_signers` shares, `threshold` of which are needed to sign (trusted dealer)
+///
+/// The participants get the identifiers from `1` to `max_signers`.
+/// The dealer knows the secret key: after distributing the key packages, the original keys should be destroyed.
+pub fn split(
+    keys: &Keys,
+    threshold: u16,
+    max_signers: u16,
+) -> Result<(BTreeMap<Identifier, KeyPackage>, PublicKeyPackage), Error> {
+    check_threshold(threshold, max_signers)?;
+
+    // Random polynomial, with the secret key as constant term
+    let mut coefficients: Vec<SecretKey> = Vec::with_capacity(threshold as usize);
+    coefficients.push(**keys.secret_key());
+    coefficients.extend((1..threshold).map(|_| scalar::random()));
+
+    let group_public_key: Point = scalar::point(&coefficients[0]);
+
+    let mut key_packages: BTreeMap<Identifier, KeyPackage> = BTreeMap::new();
+    let mut verifying_shares: BTreeMap<Identifier, Point> = BTreeMap::new();
+
+    for id in 1..=max_signers {
+        let identifier: Identifier = Identifier::new(id)?;
+        let secret_share: SecretKey =
+            scalar::polynomial_evaluate(&coefficients, &identifier.to_scalar()?)?;
+        let key_package: KeyPackage =
+            KeyPackage::new(identifier, secret_share, group_public_key, threshold);
+
+        verifying_shares.insert(identifier, key_package.verifying_share);
+        key_packages.insert(identifier, key_package);
+    }
+
+    Ok((
+        key_packages,
+        PublicKeyPackage::new(group_public_key, verifying_shares, threshold),
+    ))
+}
+
+/// Generate new keys and split them into `max_signers` shares (trusted dealer)
+///
+/// See [`split`].
+#[inline]
+pub fn generate_with_dealer(
+    threshold: u16,
+    max_signers: u16,
+) -> Result<(BTreeMap<Identifier, KeyPackage>, PublicKeyPackage), Error> {
+    split(&Keys::generate(), threshold, max_signers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split() {
+        let keys = Keys::generate();
+        let (key_packages, public_key_package) = split(&keys, 2, 3).unwrap();
+
+        assert_eq!(key_packages.len(), 3);
+        assert_eq!(public_key_package.public_key(), keys.public_key());
+
+        // Reconstruct the secret key with any 2 shares
+        let set: BTreeSet<Identifier> = [Identifier(1), Identifier(3)].into_iter().collect();
+        let mut secret: Option<SecretKey> = None;
+        for id in set.iter() {
+            let lambda = id.lagrange_coefficient(&set).unwrap();
+            let term = scalar::mul(key_packages[id].secret_share(), &lambda).unwrap();
+            secret = Some(match secret {
+                Some(s) => scalar::add(&s, &term).unwrap(),
+                None => term,
+            });
+        }
+        assert_eq!(secret.unwrap(), **keys.secret_key());
+
+        assert!(split(&keys, 0, 3).is_err());
+        assert!(split(&keys, 4, 3).is_err());
+        assert!(Identifier::new(0).is_err());
+    }
+
+    #[test]
+    fn test_public_key_package_serialization() {
+        let (key_packages, public_key_package) = generate_with_dealer(2, 3).unwrap();
+
+        let json = nostr::serde_json::to_string(&public_key_package).unwrap();
+        assert_eq!(
+            nostr::serde_json::from_str::<PublicKeyPackage>(&json).unwrap(),
+            public_key_package
+        );
+
+        let key_package = &key_packages[&Identifier(2)];
+        let json = nostr::serde_json::to_string(key_package).unwrap();
+        assert_eq!(
+            &nostr::serde_json::from_str::<KeyPackage>(&json).unwrap(),
+            key_package
+        );
+    }
+}
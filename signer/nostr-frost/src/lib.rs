@@ -0,0 +1,25 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2025 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! FROST threshold signer
+//!
+//! `t`-of-`n` Schnorr threshold signatures over secp256k1, producing BIP-340 signatures.
+//!
+//! <https://datatracker.ietf.org/doc/rfc9591/>
+
+#![forbid(unsafe_code)]
+#![warn(missing_docs)]
+#![warn(rustdoc::bare_urls)]
+#![warn(clippy::large_futures)]
+
+pub mod coordinator;
+pub mod dkg;
+pub mod error;
+pub mod keys;
+pub mod participant;
+pub mod prelude;
+mod scalar;
+pub mod signer;
+pub mod signing;
+pub mod transport;
@@ -0,0 +1,246 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2025 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! FROST participant
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use nostr::{EventId, Timestamp, UnsignedEvent};
+use tokio::sync::Mutex;
+
+use crate::error::Error;
+use crate::keys::{Identifier, KeyPackage};
+use crate::signing::{self, SignatureShare, SigningCommitments, SigningNonces, SigningPackage};
+
+/// Max number of pending signing sessions
+///
+/// When reached, the nonces of the oldest session are discarded.
+const MAX_PENDING_SESSIONS: usize = 100;
+/// Time after which the nonces of a pending signing session are discarded
+const NONCES_TTL: Duration = Duration::from_secs(300);
+
+#[derive(Debug)]
+struct PendingNonces {
+    nonces: SigningNonces,
+    created_at: Timestamp,
+}
+
+/// FROST participant
+///
+/// Holds the key package and the nonces of the pending signing sessions.
+#[derive(Debug, Clone)]
+pub struct FrostParticipant {
+    key_package: Arc<KeyPackage>,
+    /// Pending nonces, by session
+    nonces: Arc<Mutex<HashMap<String, PendingNonces>>>,
+}
+
+impl FrostParticipant {
+    /// Construct new participant
+    pub fn new(key_package: KeyPackage) -> Self {
+        Self {
+            key_package: Arc::new(key_package),
+            nonces: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Get the key package
+    #[inline]
+    pub fn key_package(&self) -> &KeyPackage {
+        &self.key_package
+    }
+
+    /// Get the participant identifier
+    #[inline]
+    pub fn identifier(&self) -> Identifier {
+        self.key_package.identifier()
+    }
+
+    /// Generate the nonces for a signing session and return their commitments
+    ///
+    /// If the session already exists, the previous nonces are discarded.
+    ///
+    /// The nonces of the sessions older than 5 minutes are discarded and,
+    /// if there are too many pending sessions, the oldest one is discarded too.
+    pub async fn commit(&self, session: &str) -> SigningCommitments {
+        self.commit_at(session, Timestamp::now()).await
+    }
+
+    async fn commit_at(&self, session: &str, now: Timestamp) -> SigningCommitments {
+        let nonces: SigningNonces = signing::commit(&self.key_package);
+        let commitments: SigningCommitments = *nonces.commitments();
+
+        let mut sessions = self.nonces.lock().await;
+
+        // Remove the expired nonces
+        sessions.retain(|_, pending| pending.created_at + NONCES_TTL > now);
+
+        // Remove the oldest nonces if the limit is reached
+        if !sessions.contains_key(session) && sessions.len() >= MAX_PENDING_SESSIONS {
+            let oldest: Option<String> = sessions
+                .iter()
+                .min_by_key(|(_, pending)| pending.created_at)
+                .map(|(session, _)| session.clone());
+            if let Some(oldest) = oldest {
+                sessions.remove(&oldest);
+            }
+        }
+
+        sessions.insert(
+            session.to_string(),
+            PendingNonces {
+                nonces,
+                created_at: now,
+            },
+        );
+
+        commitments
+    }
+
+    async fn take_nonces(&self, session: &str) -> Result<SigningNonces, Error> {
+        let mut sessions = self.nonces.lock().await;
+        let pending: PendingNonces = sessions.remove(session).ok_or(Error::NoncesNotFound)?;
+
+        if pending.created_at + NONCES_TTL <= Timestamp::now() {
+            return Err(Error::NoncesNotFound);
+        }
+
+        Ok(pending.nonces)
+    }
+
+    /// Discard the nonces of a signing session
+    ///
+    /// Returns `false` if the session doesn't exist.
+    pub async fn discard(&self, session: &str) -> bool {
+        let mut sessions = self.nonces.lock().await;
+        sessions.remove(session).is_some()
+    }
+
+    /// Compute the signature share for a signing session
+    ///
+    /// The message of the package is signed as is: use [`FrostParticipant::sign_event`] to sign events.
+    ///
+    /// The nonces of the session are removed, also if the signing fails, so they are never reused.
+    pub async fn sign(
+        &self,
+        session: &str,
+        package: &SigningPackage,
+    ) -> Result<SignatureShare, Error> {
+        let nonces: SigningNonces = self.take_nonces(session).await?;
+        signing::sign(package, nonces, &self.key_package)
+    }
+
+    /// Compute the signature share of an event for a signing session
+    ///
+    /// The event must be authored by the group public key,
+    /// and the message of the package must be its ID (computed locally).
+    ///
+    /// The nonces of the session are removed, also if the signing fails, so they are never reused.
+    pub async fn sign_event(
+        &self,
+        session: &str,
+        unsigned: &UnsignedEvent,
+        package: &SigningPackage,
+    ) -> Result<SignatureShare, Error> {
+        let nonces: SigningNonces = self.take_nonces(session).await?;
+
+        if unsigned.pubkey != self.key_package.public_key() {
+            return Err(Error::PublicKeyMismatch);
+        }
+
+        let id: EventId = signing::event_id(unsigned);
+        if id.as_bytes() != package.message() {
+            return Err(Error::MessageMismatch);
+        }
+
+        signing::sign(package, nonces, &self.key_package)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nostr::EventBuilder;
+
+    use super::*;
+    use crate::keys;
+
+    #[tokio::test]
+    async fn test_sign_event_checks_message() {
+        let (key_packages, public_key_package) = keys::generate_with_dealer(1, 1).unwrap();
+        let participant = FrostParticipant::new(key_packages.into_values().next().unwrap());
+        let public_key = public_key_package.public_key();
+
+        let mut unsigned = EventBuilder::text_note("FROST").build(public_key);
+        let id: EventId = unsigned.id();
+
+        // The coordinator asks to sign another message
+        let commitments = participant.commit("a").await;
+        let package = SigningPackage::new([1u8; 32], [commitments]);
+        assert!(matches!(
+            participant.sign_event("a", &unsigned, &package).await,
+            Err(Error::MessageMismatch)
+        ));
+
+        // The nonces have been removed
+        assert!(matches!(
+            participant.sign("a", &package).await,
+            Err(Error::NoncesNotFound)
+        ));
+
+        // The cached ID is ignored
+        let commitments = participant.commit("b").await;
+        let package = SigningPackage::new([1u8; 32], [commitments]);
+        let mut tampered = unsigned.clone();
+        tampered.id = Some(EventId::from_byte_array([1u8; 32]));
+        assert!(matches!(
+            participant.sign_event("b", &tampered, &package).await,
+            Err(Error::MessageMismatch)
+        ));
+
+        // Another author
+        let commitments = participant.commit("c").await;
+        let package = SigningPackage::new(id.to_bytes(), [commitments]);
+        let mut other = unsigned.clone();
+        other.pubkey = nostr::Keys::generate().public_key();
+        assert!(matches!(
+            participant.sign_event("c", &other, &package).await,
+            Err(Error::PublicKeyMismatch)
+        ));
+
+        let commitments = participant.commit("d").await;
+        let package = SigningPackage::new(id.to_bytes(), [commitments]);
+        assert!(participant
+            .sign_event("d", &unsigned, &package)
+            .await
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_pending_nonces_are_bounded() {
+        let (key_packages, _) = keys::generate_with_dealer(1, 1).unwrap();
+        let participant = FrostParticipant::new(key_packages.into_values().next().unwrap());
+        let now = Timestamp::now();
+
+        // Too many sessions: the oldest is discarded
+        participant
+            .commit_at("oldest", now - Duration::from_secs(60))
+            .await;
+        for i in 0..MAX_PENDING_SESSIONS {
+            participant.commit_at(&i.to_string(), now).await;
+        }
+        {
+            let sessions = participant.nonces.lock().await;
+            assert_eq!(sessions.len(), MAX_PENDING_SESSIONS);
+            assert!(!sessions.contains_key("oldest"));
+        }
+
+        // Expired sessions are discarded
+        participant.commit_at("new", now + NONCES_TTL).await;
+        let sessions = participant.nonces.lock().await;
+        assert_eq!(sessions.len(), 1);
+        assert!(sessions.contains_key("new"));
+    }
+}
@@ -0,0 +1,21 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2025 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Prelude
+
+#![allow(unknown_lints)]
+#![allow(ambiguous_glob_reexports)]
+#![doc(hidden)]
+
+pub use nostr::prelude::*;
+
+pub use crate::coordinator::*;
+pub use crate::dkg;
+pub use crate::error::*;
+pub use crate::keys::*;
+pub use crate::participant::*;
+pub use crate::signer::*;
+pub use crate::signing::*;
+pub use crate::transport::relay::*;
+pub use crate::transport::*;
@@ -0,0 +1,177 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2025 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Scalar and point arithmetic
+//!
+//! Scalars are represented with [`SecretKey`], so they can never be zero:
+//! the operations that would produce a zero scalar (or the point at infinity) return an error,
+//! but with random inputs this happens with negligible probability.
+
+use nostr::hashes::sha256::Hash as Sha256Hash;
+use nostr::hashes::{Hash, HashEngine};
+use nostr::secp256k1::rand::rngs::OsRng;
+use nostr::secp256k1::{PublicKey as Point, Scalar, SecretKey};
+use nostr::SECP256K1;
+
+use crate::error::Error;
+
+/// Curve order minus 2 (big endian), used to invert scalars (Fermat's little theorem)
+const ORDER_MINUS_TWO: [u8; 32] = [
+    0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFE,
+    0xBA, 0xAE, 0xDC, 0xE6, 0xAF, 0x48, 0xA0, 0x3B, 0xBF, 0xD2, 0x5E, 0x8C, 0xD0, 0x36, 0x41, 0x3F,
+];
+
+#[inline]
+pub(crate) fn random() -> SecretKey {
+    SecretKey::new(&mut OsRng)
+}
+
+pub(crate) fn from_u16(value: u16) -> Result<SecretKey, Error> {
+    let mut bytes: [u8; 32] = [0u8; 32];
+    bytes[30..].copy_from_slice(&value.to_be_bytes());
+    Ok(SecretKey::from_slice(&bytes)?)
+}
+
+#[inline]
+pub(crate) fn add(a: &SecretKey, b: &SecretKey) -> Result<SecretKey, Error> {
+    Ok(a.add_tweak(&Scalar::from(*b))?)
+}
+
+#[inline]
+pub(crate) fn sub(a: &SecretKey, b: &SecretKey) -> Result<SecretKey, Error> {
+    add(a, &b.negate())
+}
+
+#[inline]
+pub(crate) fn mul(a: &SecretKey, b: &SecretKey) -> Result<SecretKey, Error> {
+    Ok(a.mul_tweak(&Scalar::from(*b))?)
+}
+
+/// Compute `a^(n-2)`, that is the inverse of `a`
+pub(crate) fn invert(a: &SecretKey) -> Result<SecretKey, Error> {
+    let mut result: Option<SecretKey> = None;
+
+    for byte in ORDER_MINUS_TWO.iter() {
+        for bit in (0..8).rev() {
+            if let Some(r) = &result {
+                result = Some(mul(r, r)?);
+            }
+
+            if (byte >> bit) & 1 == 1 {
+                result = Some(match &result {
+                    Some(r) => mul(r, a)?,
+                    None => *a,
+                });
+            }
+        }
+    }
+
+    result.ok_or(Error::ScalarOutOfRange)
+}
+
+/// BIP-340 tagged hash
+pub(crate) fn tagged_hash(tag: &str, data: &[&[u8]]) -> [u8; 32] {
+    let tag: Sha256Hash = Sha256Hash::hash(tag.as_bytes());
+
+    let mut engine = Sha256Hash::engine();
+    engine.input(tag.as_byte_array());
+    engine.input(tag.as_byte_array());
+
+    for data in data.iter() {
+        engine.input(data);
+    }
+
+    Sha256Hash::from_engine(engine).to_byte_array()
+}
+
+/// Tagged hash converted to scalar
+///
+/// Returns an error if the hash is zero or not lower than the curve order (negligible probability).
+pub(crate) fn hash_to_scalar(tag: &str, data: &[&[u8]]) -> Result<SecretKey, Error> {
+    let hash: [u8; 32] = tagged_hash(tag, data);
+    SecretKey::from_slice(&hash).map_err(|_| Error::ScalarOutOfRange)
+}
+
+#[inline]
+pub(crate) fn point(s: &SecretKey) -> Point {
+    Point::from_secret_key(SECP256K1, s)
+}
+
+#[inline]
+pub(crate) fn point_add(a: &Point, b: &Point) -> Result<Point, Error> {
+    Ok(a.combine(b)?)
+}
+
+#[inline]
+pub(crate) fn point_mul(p: &Point, s: &SecretKey) -> Result<Point, Error> {
+    Ok(p.mul_tweak(SECP256K1, &Scalar::from(*s))?)
+}
+
+/// Evaluate the polynomial at `x` (Horner's method)
+///
+/// The coefficients are ordered from the constant term.
+pub(crate) fn polynomial_evaluate(
+    coefficients: &[SecretKey],
+    x: &SecretKey,
+) -> Result<SecretKey, Error> {
+    let mut iter = coefficients.iter().rev();
+    let mut value: SecretKey = *iter.next().ok_or(Error::ScalarOutOfRange)?;
+
+    for coefficient in iter {
+        value = add(&mul(&value, x)?, coefficient)?;
+    }
+
+    Ok(value)
+}
+
+/// Evaluate the polynomial commitment at `x` (Horner's method)
+pub(crate) fn commitment_evaluate(commitments: &[Point], x: &SecretKey) -> Result<Point, Error> {
+    let mut iter = commitments.iter().rev();
+    let mut value: Point = *iter.next().ok_or(Error::ScalarOutOfRange)?;
+
+    for commitment in iter {
+        value = point_add(&point_mul(&value, x)?, commitment)?;
+    }
+
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_invert() {
+        let a = random();
+        let inv = invert(&a).unwrap();
+        assert_eq!(mul(&a, &inv).unwrap(), from_u16(1).unwrap());
+
+        let two = from_u16(2).unwrap();
+        assert_eq!(
+            mul(&two, &invert(&two).unwrap()).unwrap(),
+            from_u16(1).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_polynomial() {
+        // f(x) = 1 + 2x + 3x^2
+        let coefficients = [
+            from_u16(1).unwrap(),
+            from_u16(2).unwrap(),
+            from_u16(3).unwrap(),
+        ];
+        let x = from_u16(2).unwrap();
+        assert_eq!(
+            polynomial_evaluate(&coefficients, &x).unwrap(),
+            from_u16(17).unwrap()
+        );
+
+        let commitments: Vec<Point> = coefficients.iter().map(point).collect();
+        assert_eq!(
+            commitment_evaluate(&commitments, &x).unwrap(),
+            point(&from_u16(17).unwrap())
+        );
+    }
+}
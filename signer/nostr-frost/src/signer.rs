@@ -0,0 +1,104 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2025 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! FROST signer
+
+use std::borrow::Cow;
+
+use nostr::prelude::*;
+
+use crate::coordinator::FrostCoordinator;
+use crate::error::Error;
+use crate::transport::FrostTransport;
+
+/// FROST signer
+///
+/// Sign the events with a threshold of participants, through a [`FrostCoordinator`].
+///
+/// Encryption and decryption aren't supported.
+#[derive(Debug, Clone)]
+pub struct FrostSigner<T> {
+    coordinator: FrostCoordinator<T>,
+}
+
+impl<T> FrostSigner<T>
+where
+    T: FrostTransport,
+{
+    /// Construct new signer
+    #[inline]
+    pub fn new(coordinator: FrostCoordinator<T>) -> Self {
+        Self { coordinator }
+    }
+
+    /// Get the coordinator
+    #[inline]
+    pub fn coordinator(&self) -> &FrostCoordinator<T> {
+        &self.coordinator
+    }
+
+    /// Get the group public key
+    #[inline]
+    pub fn public_key(&self) -> PublicKey {
+        self.coordinator.public_key_package().public_key()
+    }
+
+    async fn _sign_event(&self, unsigned: UnsignedEvent) -> Result<Event, Error> {
+        let sig: Signature = self.coordinator.sign(&unsigned).await?;
+        Ok(unsigned.add_signature(sig)?)
+    }
+}
+
+impl<T> NostrSigner for FrostSigner<T>
+where
+    T: FrostTransport + 'static,
+{
+    fn backend(&self) -> SignerBackend {
+        SignerBackend::Custom(Cow::Borrowed("FROST"))
+    }
+
+    fn get_public_key(&self) -> BoxedFuture<Result<PublicKey, SignerError>> {
+        Box::pin(async move { Ok(self.public_key()) })
+    }
+
+    fn sign_event(&self, unsigned: UnsignedEvent) -> BoxedFuture<Result<Event, SignerError>> {
+        Box::pin(async move {
+            self._sign_event(unsigned)
+                .await
+                .map_err(SignerError::backend)
+        })
+    }
+
+    fn nip04_encrypt<'a>(
+        &'a self,
+        _public_key: &'a PublicKey,
+        _content: &'a str,
+    ) -> BoxedFuture<'a, Result<String, SignerError>> {
+        Box::pin(async move { Err(SignerError::from("NIP04 not supported by FROST signer")) })
+    }
+
+    fn nip04_decrypt<'a>(
+        &'a self,
+        _public_key: &'a PublicKey,
+        _encrypted_content: &'a str,
+    ) -> BoxedFuture<'a, Result<String, SignerError>> {
+        Box::pin(async move { Err(SignerError::from("NIP04 not supported by FROST signer")) })
+    }
+
+    fn nip44_encrypt<'a>(
+        &'a self,
+        _public_key: &'a PublicKey,
+        _content: &'a str,
+    ) -> BoxedFuture<'a, Result<String, SignerError>> {
+        Box::pin(async move { Err(SignerError::from("NIP44 not supported by FROST signer")) })
+    }
+
+    fn nip44_decrypt<'a>(
+        &'a self,
+        _public_key: &'a PublicKey,
+        _payload: &'a str,
+    ) -> BoxedFuture<'a, Result<String, SignerError>> {
+        Box::pin(async move { Err(SignerError::from("NIP44 not supported by FROST signer")) })
+    }
+}
@@ -0,0 +1,485 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2025 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! FROST signing rounds
+//!
+//! 1. Every signer calls [`commit`] and sends the [`SigningCommitments`] to the coordinator;
+//! 2. The coordinator builds the [`SigningPackage`] and sends it to the signers;
+//! 3. Every signer calls [`sign`] and sends the [`SignatureShare`] to the coordinator;
+//! 4. The coordinator calls [`aggregate`] to get the BIP-340 signature.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use nostr::secp256k1::schnorr::Signature;
+use nostr::secp256k1::{Message, Parity, PublicKey as Point, SecretKey, XOnlyPublicKey};
+use nostr::{EventId, UnsignedEvent, SECP256K1};
+use serde::{Deserialize, Serialize};
+
+use crate::error::Error;
+use crate::keys::{Identifier, KeyPackage, PublicKeyPackage};
+use crate::scalar;
+
+const BINDING_FACTOR_TAG: &str = "nostr-frost/rho";
+const CHALLENGE_TAG: &str = "BIP0340/challenge";
+
+/// Signing nonces
+///
+/// Must be kept private and used **only once**.
+#[derive(Debug, PartialEq, Eq)]
+pub struct SigningNonces {
+    hiding: SecretKey,
+    binding: SecretKey,
+    commitments: SigningCommitments,
+}
+
+impl SigningNonces {
+    /// Get the commitments
+    #[inline]
+    pub fn commitments(&self) -> &SigningCommitments {
+        &self.commitments
+    }
+}
+
+/// Signing commitments
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SigningCommitments {
+    /// Signer
+    pub identifier: Identifier,
+    /// Hiding nonce commitment
+    pub hiding: Point,
+    /// Binding nonce commitment
+    pub binding: Point,
+}
+
+/// Signing package
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "RawSigningPackage")]
+pub struct SigningPackage {
+    message: [u8; 32],
+    commitments: BTreeMap<Identifier, SigningCommitments>,
+}
+
+#[derive(Deserialize)]
+struct RawSigningPackage {
+    message: [u8; 32],
+    commitments: BTreeMap<Identifier, SigningCommitments>,
+}
+
+impl TryFrom<RawSigningPackage> for SigningPackage {
+    type Error = Error;
+
+    fn try_from(raw: RawSigningPackage) -> Result<Self, Self::Error> {
+        // The commitments must be indexed by their own identifier
+        for (identifier, commitments) in raw.commitments.iter() {
+            if *identifier != commitments.identifier {
+                return Err(Error::InvalidCommitments(*identifier));
+            }
+        }
+
+        Ok(Self {
+            message: raw.message,
+            commitments: raw.commitments,
+        })
+    }
+}
+
+impl SigningPackage {
+    /// Construct new signing package
+    pub fn new<I>(message: [u8; 32], commitments: I) -> Self
+    where
+        I: IntoIterator<Item = SigningCommitments>,
+    {
+        Self {
+            message,
+            commitments: commitments.into_iter().map(|c| (c.identifier, c)).collect(),
+        }
+    }
+
+    /// Message to sign
+    #[inline]
+    pub fn message(&self) -> &[u8; 32] {
+        &self.message
+    }
+
+    /// Signers commitments
+    #[inline]
+    pub fn commitments(&self) -> &BTreeMap<Identifier, SigningCommitments> {
+        &self.commitments
+    }
+
+    /// Signers identifiers
+    #[inline]
+    pub fn identifiers(&self) -> BTreeSet<Identifier> {
+        self.commitments.keys().copied().collect()
+    }
+}
+
+/// Signature share
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignatureShare {
+    /// Signer
+    pub identifier: Identifier,
+    /// Share
+    pub share: SecretKey,
+}
+
+/// Values shared by the signers and the coordinator
+struct Context {
+    signers: BTreeSet<Identifier>,
+    binding_factors: BTreeMap<Identifier, SecretKey>,
+    /// Commitment of each signer (`D + rho * E`)
+    commitments: BTreeMap<Identifier, Point>,
+    /// Group commitment
+    r: Point,
+    /// If the group commitment has an odd Y (the nonces must be negated)
+    r_odd: bool,
+    /// If the group public key has an odd Y (the secret shares must be negated)
+    y_odd: bool,
+    challenge: SecretKey,
+}
+
+impl Context {
+    fn new(package: &SigningPackage, group_public_key: &Point) -> Result<Self, Error> {
+        let (y, y_parity): (XOnlyPublicKey, Parity) = group_public_key.x_only_public_key();
+
+        // Encode the commitments list
+        let mut encoded: Vec<u8> = Vec::with_capacity(package.commitments.len() * 68);
+        for c in package.commitments.values() {
+            encoded.extend_from_slice(&c.identifier.as_u16().to_be_bytes());
+            encoded.extend_from_slice(&c.hiding.serialize());
+            encoded.extend_from_slice(&c.binding.serialize());
+        }
+
+        let mut binding_factors: BTreeMap<Identifier, SecretKey> = BTreeMap::new();
+        let mut commitments: BTreeMap<Identifier, Point> = BTreeMap::new();
+
+        for (identifier, c) in package.commitments.iter() {
+            let rho: SecretKey = scalar::hash_to_scalar(
+                BINDING_FACTOR_TAG,
+                &[
+                    &y.serialize(),
+                    &package.message,
+                    &encoded,
+                    &identifier.as_u16().to_be_bytes(),
+                ],
+            )?;
+            let commitment: Point =
+                scalar::point_add(&c.hiding, &scalar::point_mul(&c.binding, &rho)?)?;
+
+            binding_factors.insert(*identifier, rho);
+            commitments.insert(*identifier, commitment);
+        }
+
+        let points: Vec<&Point> = commitments.values().collect();
+        let r: Point = Point::combine_keys(&points)?;
+        let (r_x, r_parity): (XOnlyPublicKey, Parity) = r.x_only_public_key();
+
+        let challenge: SecretKey = scalar::hash_to_scalar(
+            CHALLENGE_TAG,
+            &[&r_x.serialize(), &y.serialize(), &package.message],
+        )?;
+
+        Ok(Self {
+            signers: package.identifiers(),
+            binding_factors,
+            commitments,
+            r,
+            r_odd: r_parity == Parity::Odd,
+            y_odd: y_parity == Parity::Odd,
+            challenge,
+        })
+    }
+
+    fn binding_factor(&self, identifier: &Identifier) -> Result<&SecretKey, Error> {
+        self.binding_factors
+            .get(identifier)
+            .ok_or(Error::MissingCommitments(*identifier))
+    }
+
+    /// Verify the signature share: `z * G == R_i + lambda * c * Y_i`
+    fn verify_share(&self, share: &SignatureShare, verifying_share: &Point) -> Result<(), Error> {
+        let mut commitment: Point = *self
+            .commitments
+            .get(&share.identifier)
+            .ok_or(Error::MissingCommitments(share.identifier))?;
+        if self.r_odd {
+            commitment = commitment.negate(SECP256K1);
+        }
+
+        let mut verifying_share: Point = *verifying_share;
+        if self.y_odd {
+            verifying_share = verifying_share.negate(SECP256K1);
+        }
+
+        let lambda: SecretKey = share.identifier.lagrange_coefficient(&self.signers)?;
+        let expected: Point = scalar::point_add(
+            &commitment,
+            &scalar::point_mul(&verifying_share, &scalar::mul(&lambda, &self.challenge)?)?,
+        )?;
+
+        if scalar::point(&share.share) != expected {
+            return Err(Error::InvalidSignatureShare(share.identifier));
+        }
+
+        Ok(())
+    }
+}
+
+/// Compute the ID of an unsigned event, ignoring the one already set
+pub(crate) fn event_id(unsigned: &UnsignedEvent) -> EventId {
+    EventId::new(
+        &unsigned.pubkey,
+        &unsigned.created_at,
+        &unsigned.kind,
+        &unsigned.tags,
+        &unsigned.content,
+    )
+}
+
+/// Signing round 1: generate the nonces and their commitments
+pub fn commit(key_package: &KeyPackage) -> SigningNonces {
+    let hiding: SecretKey = scalar::random();
+    let binding: SecretKey = scalar::random();
+
+    SigningNonces {
+        hiding,
+        binding,
+        commitments: SigningCommitments {
+            identifier: key_package.identifier(),
+            hiding: scalar::point(&hiding),
+            binding: scalar::point(&binding),
+        },
+    }
+}
+
+/// Signing round 2: compute the signature share
+///
+/// The nonces are consumed, so they can't be reused.
+pub fn sign(
+    package: &SigningPackage,
+    nonces: SigningNonces,
+    key_package: &KeyPackage,
+) -> Result<SignatureShare, Error> {
+    let identifier: Identifier = key_package.identifier();
+
+    // Check that the commitments in the package are the ones of the nonces
+    match package.commitments.get(&identifier) {
+        Some(commitments) if commitments == &nonces.commitments => {}
+        _ => return Err(Error::MissingCommitments(identifier)),
+    }
+
+    if package.commitments.len() < key_package.threshold() as usize {
+        return Err(Error::NotEnoughParticipants {
+            threshold: key_package.threshold(),
+            available: package.commitments.len(),
+        });
+    }
+
+    let ctx: Context = Context::new(package, key_package.group_public_key())?;
+
+    let (mut hiding, mut binding) = (nonces.hiding, nonces.binding);
+    if ctx.r_odd {
+        hiding = hiding.negate();
+        binding = binding.negate();
+    }
+
+    let mut secret_share: SecretKey = *key_package.secret_share();
+    if ctx.y_odd {
+        secret_share = secret_share.negate();
+    }
+
+    // z = d + e * rho + lambda * s * c
+    let lambda: SecretKey = identifier.lagrange_coefficient(&ctx.signers)?;
+    let nonce: SecretKey = scalar::add(
+        &hiding,
+        &scalar::mul(&binding, ctx.binding_factor(&identifier)?)?,
+    )?;
+    let share: SecretKey = scalar::add(
+        &nonce,
+        &scalar::mul(&scalar::mul(&lambda, &secret_share)?, &ctx.challenge)?,
+    )?;
+
+    Ok(SignatureShare { identifier, share })
+}
+
+/// Aggregate the signature shares into a BIP-340 signature
+///
+/// If the signature is invalid, the shares are verified to identify the misbehaving signer.
+pub fn aggregate(
+    package: &SigningPackage,
+    shares: &BTreeMap<Identifier, SignatureShare>,
+    public_key_package: &PublicKeyPackage,
+) -> Result<Signature, Error> {
+    if package.commitments.len() < public_key_package.threshold() as usize {
+        return Err(Error::NotEnoughParticipants {
+            threshold: public_key_package.threshold(),
+            available: package.commitments.len(),
+        });
+    }
+
+    // Check that there is a share for each signer
+    for identifier in package.commitments.keys() {
+        if !shares.contains_key(identifier) {
+            return Err(Error::MissingCommitments(*identifier));
+        }
+    }
+
+    let ctx: Context = Context::new(package, public_key_package.group_public_key())?;
+
+    let mut z: Option<SecretKey> = None;
+    for share in shares.values() {
+        if !ctx.signers.contains(&share.identifier) {
+            return Err(Error::UnknownParticipant(share.identifier));
+        }
+
+        z = Some(match z {
+            Some(z) => scalar::add(&z, &share.share)?,
+            None => share.share,
+        });
+    }
+    let z: SecretKey = z.ok_or(Error::InvalidSignature)?;
+
+    let mut bytes: [u8; 64] = [0u8; 64];
+    bytes[..32].copy_from_slice(&ctx.r.x_only_public_key().0.serialize());
+    bytes[32..].copy_from_slice(&z.secret_bytes());
+    let signature: Signature = Signature::from_slice(&bytes)?;
+
+    let message: Message = Message::from_digest(package.message);
+    let (group, _) = public_key_package.group_public_key().x_only_public_key();
+
+    if SECP256K1
+        .verify_schnorr(&signature, &message, &group)
+        .is_err()
+    {
+        // Find the misbehaving signer
+        for share in shares.values() {
+            let verifying_share: &Point = public_key_package
+                .verifying_share(&share.identifier)
+                .ok_or(Error::UnknownParticipant(share.identifier))?;
+            ctx.verify_share(share, verifying_share)?;
+        }
+
+        return Err(Error::InvalidSignature);
+    }
+
+    Ok(signature)
+}
+
+#[cfg(test)]
+mod tests {
+    use nostr::prelude::*;
+
+    use super::*;
+    use crate::dkg;
+    use crate::keys;
+
+    fn sign_with(
+        key_packages: &BTreeMap<Identifier, KeyPackage>,
+        public_key_package: &PublicKeyPackage,
+        signers: &[u16],
+        message: [u8; 32],
+    ) -> Result<Signature, Error> {
+        let mut nonces = BTreeMap::new();
+        for id in signers.iter() {
+            let identifier = Identifier::new(*id).unwrap();
+            nonces.insert(identifier, commit(&key_packages[&identifier]));
+        }
+
+        let package = SigningPackage::new(message, nonces.values().map(|n| *n.commitments()));
+
+        let mut shares = BTreeMap::new();
+        for (identifier, nonces) in nonces.into_iter() {
+            let share = sign(&package, nonces, &key_packages[&identifier])?;
+            shares.insert(identifier, share);
+        }
+
+        aggregate(&package, &shares, public_key_package)
+    }
+
+    #[test]
+    fn test_sign_event() {
+        let (key_packages, public_key_package) = keys::generate_with_dealer(2, 3).unwrap();
+        let public_key = public_key_package.public_key();
+
+        for signers in [[1, 2], [1, 3], [3, 2]] {
+            let mut unsigned = EventBuilder::text_note("FROST").build(public_key);
+            let id = unsigned.id();
+
+            let sig =
+                sign_with(&key_packages, &public_key_package, &signers, id.to_bytes()).unwrap();
+            let event = unsigned.add_signature(sig).unwrap();
+            assert!(event.verify().is_ok());
+        }
+
+        // All the participants
+        let sig = sign_with(&key_packages, &public_key_package, &[1, 2, 3], [7u8; 32]).unwrap();
+        let message = Message::from_digest([7u8; 32]);
+        let (xonly, _) = public_key_package.group_public_key().x_only_public_key();
+        assert!(SECP256K1.verify_schnorr(&sig, &message, &xonly).is_ok());
+
+        // Not enough signers
+        assert!(matches!(
+            sign_with(&key_packages, &public_key_package, &[1], [0u8; 32]),
+            Err(Error::NotEnoughParticipants { .. })
+        ));
+    }
+
+    #[test]
+    fn test_sign_with_dkg() {
+        let (key_packages, public_key_package) = dkg::tests::run_dkg(3, 5);
+
+        for signers in [[1, 2, 3], [2, 4, 5], [5, 1, 3]] {
+            let mut unsigned =
+                EventBuilder::text_note("FROST DKG").build(public_key_package.public_key());
+            let id = unsigned.id();
+
+            let sig =
+                sign_with(&key_packages, &public_key_package, &signers, id.to_bytes()).unwrap();
+            let event = unsigned.add_signature(sig).unwrap();
+            assert!(event.verify().is_ok());
+        }
+    }
+
+    #[test]
+    fn test_signing_package_deserialization() {
+        let (key_packages, _) = keys::generate_with_dealer(2, 2).unwrap();
+        let id1 = Identifier::new(1).unwrap();
+        let id2 = Identifier::new(2).unwrap();
+
+        let c1 = *commit(&key_packages[&id1]).commitments();
+        let c2 = *commit(&key_packages[&id2]).commitments();
+        let package = SigningPackage::new([1u8; 32], [c1, c2]);
+
+        let json: String = nostr::serde_json::to_string(&package).unwrap();
+        let decoded: SigningPackage = nostr::serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded, package);
+
+        // Commitments of participant 1 under the identifier 2
+        let mut value: nostr::serde_json::Value = nostr::serde_json::to_value(&package).unwrap();
+        value["commitments"]["2"] = nostr::serde_json::to_value(c1).unwrap();
+        assert!(nostr::serde_json::from_value::<SigningPackage>(value).is_err());
+    }
+
+    #[test]
+    fn test_invalid_share() {
+        let (key_packages, public_key_package) = keys::generate_with_dealer(2, 2).unwrap();
+        let id1 = Identifier::new(1).unwrap();
+        let id2 = Identifier::new(2).unwrap();
+
+        let nonces1 = commit(&key_packages[&id1]);
+        let nonces2 = commit(&key_packages[&id2]);
+        let package =
+            SigningPackage::new([1u8; 32], [*nonces1.commitments(), *nonces2.commitments()]);
+
+        let share1 = sign(&package, nonces1, &key_packages[&id1]).unwrap();
+        let mut share2 = sign(&package, nonces2, &key_packages[&id2]).unwrap();
+        share2.share = scalar::random();
+
+        let shares = [(id1, share1), (id2, share2)].into_iter().collect();
+        assert!(matches!(
+            aggregate(&package, &shares, &public_key_package),
+            Err(Error::InvalidSignatureShare(id)) if id == id2
+        ));
+    }
+}
@@ -0,0 +1,94 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2025 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! FROST transport
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use nostr::util::BoxedFuture;
+use nostr::UnsignedEvent;
+
+use crate::error::Error;
+use crate::keys::Identifier;
+use crate::participant::FrostParticipant;
+use crate::signing::{SignatureShare, SigningCommitments, SigningPackage};
+
+pub mod relay;
+
+/// FROST transport
+///
+/// Used by the [`FrostCoordinator`](crate::coordinator::FrostCoordinator) to exchange the round messages with the participants.
+pub trait FrostTransport: fmt::Debug + Send + Sync {
+    /// Ask a participant for the commitments of a new signing session
+    fn commit<'a>(
+        &'a self,
+        participant: Identifier,
+        session: &'a str,
+    ) -> BoxedFuture<'a, Result<SigningCommitments, Error>>;
+
+    /// Ask a participant for the signature share of an event for a signing session
+    ///
+    /// The event is sent along with the signing package, so the participant can check what it's signing.
+    fn sign<'a>(
+        &'a self,
+        participant: Identifier,
+        session: &'a str,
+        unsigned: &'a UnsignedEvent,
+        package: &'a SigningPackage,
+    ) -> BoxedFuture<'a, Result<SignatureShare, Error>>;
+}
+
+/// Local transport
+///
+/// All the participants are in the same process (i.e., for testing or for multiple shares on the same machine).
+#[derive(Debug, Clone, Default)]
+pub struct LocalTransport {
+    participants: BTreeMap<Identifier, FrostParticipant>,
+}
+
+impl LocalTransport {
+    /// Construct new local transport
+    pub fn new<I>(participants: I) -> Self
+    where
+        I: IntoIterator<Item = FrostParticipant>,
+    {
+        Self {
+            participants: participants
+                .into_iter()
+                .map(|p| (p.identifier(), p))
+                .collect(),
+        }
+    }
+
+    fn participant(&self, identifier: &Identifier) -> Result<&FrostParticipant, Error> {
+        self.participants
+            .get(identifier)
+            .ok_or(Error::UnknownParticipant(*identifier))
+    }
+}
+
+impl FrostTransport for LocalTransport {
+    fn commit<'a>(
+        &'a self,
+        participant: Identifier,
+        session: &'a str,
+    ) -> BoxedFuture<'a, Result<SigningCommitments, Error>> {
+        Box::pin(async move { Ok(self.participant(&participant)?.commit(session).await) })
+    }
+
+    fn sign<'a>(
+        &'a self,
+        participant: Identifier,
+        session: &'a str,
+        unsigned: &'a UnsignedEvent,
+        package: &'a SigningPackage,
+    ) -> BoxedFuture<'a, Result<SignatureShare, Error>> {
+        Box::pin(async move {
+            self.participant(&participant)?
+                .sign_event(session, unsigned, package)
+                .await
+        })
+    }
+}
@@ -0,0 +1,601 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2025 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Relay transport
+//!
+//! The round messages are exchanged as NIP-44 encrypted ephemeral events.
+
+use std::collections::{BTreeMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_utility::time;
+use nostr_relay_pool::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::FrostTransport;
+use crate::error::Error;
+use crate::keys::Identifier;
+use crate::participant::FrostParticipant;
+use crate::signing::{self, SignatureShare, SigningCommitments, SigningPackage};
+
+/// Kind of the FROST round messages (ephemeral)
+pub const FROST_KIND: Kind = Kind::Custom(24140);
+
+/// FROST round message
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum FrostMessage {
+    /// Ask the commitments for a new signing session
+    CommitRequest {
+        /// Session ID
+        session: String,
+    },
+    /// Commitments
+    Commitments {
+        /// Session ID
+        session: String,
+        /// Commitments
+        commitments: SigningCommitments,
+    },
+    /// Ask the signature share of an event
+    SignRequest {
+        /// Session ID
+        session: String,
+        /// Event to sign
+        event: UnsignedEvent,
+        /// Signing package
+        package: SigningPackage,
+    },
+    /// Signature share
+    SignatureShare {
+        /// Session ID
+        session: String,
+        /// Signature share
+        share: SignatureShare,
+    },
+    /// Error
+    Error {
+        /// Session ID
+        session: String,
+        /// Error message
+        message: String,
+    },
+}
+
+impl FrostMessage {
+    /// Get the session ID
+    pub fn session(&self) -> &str {
+        match self {
+            Self::CommitRequest { session }
+            | Self::Commitments { session, .. }
+            | Self::SignRequest { session, .. }
+            | Self::SignatureShare { session, .. }
+            | Self::Error { session, .. } => session,
+        }
+    }
+
+    /// Check if it's a response
+    #[inline]
+    pub fn is_response(&self) -> bool {
+        !matches!(self, Self::CommitRequest { .. } | Self::SignRequest { .. })
+    }
+}
+
+impl JsonUtil for FrostMessage {
+    type Err = Error;
+}
+
+/// Relay pool shared by the coordinator and the participants
+#[derive(Debug, Clone)]
+struct Channel {
+    keys: Keys,
+    relays: Vec<RelayUrl>,
+    pool: RelayPool,
+    opts: RelayOptions,
+    bootstrapped: Arc<AtomicBool>,
+}
+
+impl Channel {
+    fn new<I, U>(keys: Keys, urls: I, opts: Option<RelayOptions>) -> Result<Self, Error>
+    where
+        I: IntoIterator<Item = U>,
+        U: TryIntoUrl,
+        pool::Error: From<<U as TryIntoUrl>::Err>,
+    {
+        let mut relays = Vec::new();
+        for url in urls.into_iter() {
+            relays.push(
+                url.try_into_url()
+                    .map_err(|e| Error::Pool(pool::Error::from(e)))?,
+            );
+        }
+
+        Ok(Self {
+            keys,
+            relays,
+            pool: RelayPool::default(),
+            opts: opts.unwrap_or_default(),
+            bootstrapped: Arc::new(AtomicBool::new(false)),
+        })
+    }
+
+    async fn bootstrap(&self) -> Result<(), Error> {
+        // Check if already bootstrapped
+        if self.bootstrapped.load(Ordering::SeqCst) {
+            return Ok(());
+        }
+
+        // Add relays to pool
+        for url in self.relays.iter() {
+            self.pool.add_relay(url, self.opts.clone()).await?;
+        }
+
+        // Connect
+        self.pool.connect().await;
+
+        let filter = Filter::new()
+            .pubkey(self.keys.public_key())
+            .kind(FROST_KIND)
+            .since(Timestamp::now());
+
+        // Subscribe
+        self.pool
+            .subscribe(filter, SubscribeOptions::default())
+            .await?;
+
+        // Mark as bootstrapped
+        self.bootstrapped.store(true, Ordering::SeqCst);
+
+        Ok(())
+    }
+
+    async fn send(&self, receiver: PublicKey, msg: &FrostMessage) -> Result<(), Error> {
+        let content: String = nip44::encrypt(
+            self.keys.secret_key(),
+            &receiver,
+            msg.try_as_json()?,
+            nip44::Version::default(),
+        )?;
+        let event: Event = EventBuilder::new(FROST_KIND, content)
+            .tag(Tag::public_key(receiver))
+            .sign_with_keys(&self.keys)?;
+        self.pool.send_event(&event).await?;
+        Ok(())
+    }
+
+    fn decrypt(&self, event: &Event) -> Result<FrostMessage, Error> {
+        let msg: String = nip44::decrypt(self.keys.secret_key(), &event.pubkey, &event.content)?;
+        FrostMessage::from_json(msg)
+    }
+}
+
+/// Nostr transport
+///
+/// Coordinator side of the nostr transport: the participants must run a [`NostrFrostParticipant`].
+#[derive(Debug, Clone)]
+pub struct NostrTransport {
+    channel: Channel,
+    participants: BTreeMap<Identifier, PublicKey>,
+    timeout: Duration,
+}
+
+impl NostrTransport {
+    /// Construct new nostr transport
+    ///
+    /// The `keys` are used only to communicate with the participants, which are identified by their nostr public key.
+    pub fn new<P, I, U>(
+        keys: Keys,
+        participants: P,
+        urls: I,
+        timeout: Duration,
+        opts: Option<RelayOptions>,
+    ) -> Result<Self, Error>
+    where
+        P: IntoIterator<Item = (Identifier, PublicKey)>,
+        I: IntoIterator<Item = U>,
+        U: TryIntoUrl,
+        pool::Error: From<<U as TryIntoUrl>::Err>,
+    {
+        Ok(Self {
+            channel: Channel::new(keys, urls, opts)?,
+            participants: participants.into_iter().collect(),
+            timeout,
+        })
+    }
+
+    /// Get the participants public keys
+    #[inline]
+    pub fn participants(&self) -> &BTreeMap<Identifier, PublicKey> {
+        &self.participants
+    }
+
+    async fn request(
+        &self,
+        participant: Identifier,
+        msg: FrostMessage,
+    ) -> Result<FrostMessage, Error> {
+        let public_key: PublicKey = *self
+            .participants
+            .get(&participant)
+            .ok_or(Error::UnknownParticipant(participant))?;
+
+        self.channel.bootstrap().await?;
+
+        let mut notifications = self.channel.pool.notifications();
+
+        // Send request
+        self.channel.send(public_key, &msg).await?;
+
+        time::timeout(Some(self.timeout), async {
+            while let Ok(notification) = notifications.recv().await {
+                if let RelayPoolNotification::Event { event, .. } = notification {
+                    if event.kind == FROST_KIND && event.pubkey == public_key {
+                        match self.channel.decrypt(&event) {
+                            Ok(res) => {
+                                if res.session() == msg.session() && res.is_response() {
+                                    return Ok(res);
+                                }
+                            }
+                            Err(e) => {
+                                tracing::error!(error = %e, "Impossible to decrypt message.")
+                            }
+                        }
+                    }
+                }
+            }
+
+            Err(Error::Timeout)
+        })
+        .await
+        .ok_or(Error::Timeout)?
+    }
+
+    /// Completely shutdown
+    pub async fn shutdown(self) {
+        self.channel.pool.shutdown().await
+    }
+}
+
+impl FrostTransport for NostrTransport {
+    fn commit<'a>(
+        &'a self,
+        participant: Identifier,
+        session: &'a str,
+    ) -> BoxedFuture<'a, Result<SigningCommitments, Error>> {
+        Box::pin(async move {
+            let req: FrostMessage = FrostMessage::CommitRequest {
+                session: session.to_string(),
+            };
+
+            match self.request(participant, req).await? {
+                FrostMessage::Commitments { commitments, .. } => Ok(commitments),
+                FrostMessage::Error { message, .. } => Err(Error::Participant(message)),
+                _ => Err(Error::UnexpectedMessage),
+            }
+        })
+    }
+
+    fn sign<'a>(
+        &'a self,
+        participant: Identifier,
+        session: &'a str,
+        unsigned: &'a UnsignedEvent,
+        package: &'a SigningPackage,
+    ) -> BoxedFuture<'a, Result<SignatureShare, Error>> {
+        Box::pin(async move {
+            let req: FrostMessage = FrostMessage::SignRequest {
+                session: session.to_string(),
+                event: unsigned.clone(),
+                package: package.clone(),
+            };
+
+            match self.request(participant, req).await? {
+                FrostMessage::SignatureShare { share, .. } => Ok(share),
+                FrostMessage::Error { message, .. } => Err(Error::Participant(message)),
+                _ => Err(Error::UnexpectedMessage),
+            }
+        })
+    }
+}
+
+/// Nostr FROST participant
+///
+/// Participant side of the nostr transport: listen for requests from the coordinators and send the responses.
+///
+/// Only the requests of the authorized coordinators are handled.
+/// The coordinators send the events to sign along with the signing packages:
+/// the participant checks that the package message is the event ID and asks [`FrostParticipantActions`] for the approval.
+#[derive(Debug, Clone)]
+pub struct NostrFrostParticipant {
+    channel: Channel,
+    participant: FrostParticipant,
+    coordinators: HashSet<PublicKey>,
+}
+
+impl NostrFrostParticipant {
+    /// Construct new nostr participant
+    ///
+    /// The `keys` are used only to communicate with the coordinators.
+    pub fn new<C, I, U>(
+        participant: FrostParticipant,
+        keys: Keys,
+        coordinators: C,
+        urls: I,
+        opts: Option<RelayOptions>,
+    ) -> Result<Self, Error>
+    where
+        C: IntoIterator<Item = PublicKey>,
+        I: IntoIterator<Item = U>,
+        U: TryIntoUrl,
+        pool::Error: From<<U as TryIntoUrl>::Err>,
+    {
+        Ok(Self {
+            channel: Channel::new(keys, urls, opts)?,
+            participant,
+            coordinators: coordinators.into_iter().collect(),
+        })
+    }
+
+    /// Get the participant
+    #[inline]
+    pub fn participant(&self) -> &FrostParticipant {
+        &self.participant
+    }
+
+    /// Serve participant
+    pub async fn serve<T>(&self, actions: T) -> Result<(), Error>
+    where
+        T: FrostParticipantActions,
+    {
+        self.channel.bootstrap().await?;
+
+        self.channel
+            .pool
+            .handle_notifications(|notification| async {
+                if let RelayPoolNotification::Event { event, .. } = notification {
+                    if event.kind == FROST_KIND && self.coordinators.contains(&event.pubkey) {
+                        match self.channel.decrypt(&event) {
+                            Ok(req) => {
+                                if let Some(res) =
+                                    self.handle_request(&event.pubkey, req, &actions).await
+                                {
+                                    self.channel.send(event.pubkey, &res).await?;
+                                }
+                            }
+                            Err(e) => {
+                                tracing::error!(error = %e, "Impossible to decrypt message.")
+                            }
+                        }
+                    }
+                }
+                Ok(false) // Set to true to exit from the loop
+            })
+            .await?;
+
+        Ok(())
+    }
+
+    async fn handle_request<T>(
+        &self,
+        coordinator: &PublicKey,
+        req: FrostMessage,
+        actions: &T,
+    ) -> Option<FrostMessage>
+    where
+        T: FrostParticipantActions,
+    {
+        match req {
+            FrostMessage::CommitRequest { session } => {
+                let commitments: SigningCommitments = self.participant.commit(&session).await;
+                Some(FrostMessage::Commitments {
+                    session,
+                    commitments,
+                })
+            }
+            FrostMessage::SignRequest {
+                session,
+                event,
+                package,
+            } => {
+                // Check the event ID before asking for the approval
+                let res = if signing::event_id(&event).as_bytes() != package.message() {
+                    Err(Error::MessageMismatch)
+                } else if !actions.approve(coordinator, &event) {
+                    Err(Error::Rejected)
+                } else {
+                    Ok(())
+                };
+
+                let res = match res {
+                    Ok(()) => {
+                        self.participant
+                            .sign_event(&session, &event, &package)
+                            .await
+                    }
+                    Err(e) => {
+                        self.participant.discard(&session).await;
+                        Err(e)
+                    }
+                };
+
+                match res {
+                    Ok(share) => Some(FrostMessage::SignatureShare { session, share }),
+                    Err(e) => Some(FrostMessage::Error {
+                        session,
+                        message: e.to_string(),
+                    }),
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Completely shutdown
+    pub async fn shutdown(self) {
+        self.channel.pool.shutdown().await
+    }
+}
+
+/// Nostr FROST participant actions
+pub trait FrostParticipantActions {
+    /// Approve the signing of an event, requested by a coordinator
+    ///
+    /// The event ID has already been checked against the signing package.
+    fn approve(&self, coordinator: &PublicKey, unsigned: &UnsignedEvent) -> bool;
+}
+
+#[cfg(test)]
+mod tests {
+    use nostr_relay_builder::prelude::*;
+
+    use super::*;
+    use crate::coordinator::FrostCoordinator;
+    use crate::error::Error;
+    use crate::keys;
+    use crate::signer::FrostSigner;
+
+    #[derive(Debug)]
+    struct ApproveTextNotes;
+
+    impl FrostParticipantActions for ApproveTextNotes {
+        fn approve(&self, _coordinator: &PublicKey, unsigned: &UnsignedEvent) -> bool {
+            unsigned.kind == Kind::TextNote
+        }
+    }
+
+    #[test]
+    fn test_message_serialization() {
+        let msg = FrostMessage::CommitRequest {
+            session: String::from("abcd"),
+        };
+        assert_eq!(
+            msg.as_json(),
+            r#"{"type":"commit_request","session":"abcd"}"#
+        );
+        assert_eq!(FrostMessage::from_json(msg.as_json()).unwrap(), msg);
+        assert!(!msg.is_response());
+    }
+
+    #[tokio::test]
+    async fn test_nostr_transport() {
+        let mock = MockRelay::run().await.unwrap();
+        let url = RelayUrl::parse(&mock.url()).unwrap();
+
+        let org = Keys::generate();
+        let (key_packages, public_key_package) = keys::split(&org, 2, 3).unwrap();
+
+        let coordinator_keys = Keys::generate();
+        let mut participants = BTreeMap::new();
+
+        // Participant 3 is offline
+        for (identifier, key_package) in key_packages.into_iter() {
+            let keys = Keys::generate();
+            participants.insert(identifier, keys.public_key());
+
+            if identifier.as_u16() == 3 {
+                continue;
+            }
+
+            let participant = NostrFrostParticipant::new(
+                FrostParticipant::new(key_package),
+                keys,
+                [coordinator_keys.public_key()],
+                [&url],
+                None,
+            )
+            .unwrap();
+            tokio::spawn(async move { participant.serve(ApproveTextNotes).await });
+        }
+
+        tokio::time::sleep(Duration::from_millis(500)).await;
+
+        let transport = NostrTransport::new(
+            coordinator_keys,
+            participants,
+            [&url],
+            Duration::from_secs(10),
+            None,
+        )
+        .unwrap();
+        let signer = FrostSigner::new(FrostCoordinator::new(public_key_package, transport));
+
+        assert_eq!(signer.get_public_key().await.unwrap(), org.public_key());
+
+        let unsigned = EventBuilder::text_note("Signed by FROST").build(org.public_key());
+        let event = signer.sign_event(unsigned).await.unwrap();
+        assert!(event.verify().is_ok());
+        assert_eq!(event.pubkey, org.public_key());
+    }
+
+    #[tokio::test]
+    async fn test_participant_checks_sign_request() {
+        let org = Keys::generate();
+        let (key_packages, _) = keys::split(&org, 1, 1).unwrap();
+        let coordinator = Keys::generate().public_key();
+        let participant = NostrFrostParticipant::new(
+            FrostParticipant::new(key_packages.into_values().next().unwrap()),
+            Keys::generate(),
+            [coordinator],
+            Vec::<RelayUrl>::new(),
+            None,
+        )
+        .unwrap();
+
+        let sign = |session: &str, unsigned: UnsignedEvent, message: [u8; 32]| {
+            let participant = participant.clone();
+            let session = session.to_string();
+            async move {
+                let commitments = match participant
+                    .handle_request(
+                        &coordinator,
+                        FrostMessage::CommitRequest {
+                            session: session.clone(),
+                        },
+                        &ApproveTextNotes,
+                    )
+                    .await
+                {
+                    Some(FrostMessage::Commitments { commitments, .. }) => commitments,
+                    res => panic!("unexpected response: {res:?}"),
+                };
+                let req = FrostMessage::SignRequest {
+                    session,
+                    event: unsigned,
+                    package: SigningPackage::new(message, [commitments]),
+                };
+                participant
+                    .handle_request(&coordinator, req, &ApproveTextNotes)
+                    .await
+                    .unwrap()
+            }
+        };
+
+        // The package message isn't the event ID
+        let mut unsigned = EventBuilder::text_note("FROST").build(org.public_key());
+        let id: EventId = unsigned.id();
+        let res = sign("a", unsigned.clone(), [1u8; 32]).await;
+        assert!(
+            matches!(res, FrostMessage::Error { message, .. } if message == Error::MessageMismatch.to_string())
+        );
+
+        // Not approved
+        let mut metadata =
+            EventBuilder::metadata(&Metadata::new().name("FROST")).build(org.public_key());
+        let metadata_id: EventId = metadata.id();
+        let res = sign("b", metadata, metadata_id.to_bytes()).await;
+        assert!(
+            matches!(res, FrostMessage::Error { message, .. } if message == Error::Rejected.to_string())
+        );
+
+        // The nonces of the rejected sessions are discarded
+        assert!(!participant.participant().discard("a").await);
+        assert!(!participant.participant().discard("b").await);
+
+        // Approved
+        let res = sign("c", unsigned, id.to_bytes()).await;
+        assert!(matches!(res, FrostMessage::SignatureShare { .. }));
+    }
+}